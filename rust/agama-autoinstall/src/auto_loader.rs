@@ -18,8 +18,14 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use crate::{ConfigLoader, UserQuestions};
-use agama_lib::http::BaseHTTPClient;
+use std::{fs, path::Path};
+
+use crate::{ConfigLoader, Facts, KernelCmdline, RulesProfileBuilder, UserQuestions, VerifiedUrl};
+use agama_lib::{
+    http::BaseHTTPClient,
+    install_settings::SettingsMerger,
+    utils::{Transfer, Verification},
+};
 use anyhow::anyhow;
 
 /// List of pre-defined locations for profiles.
@@ -32,6 +38,9 @@ const PREDEFINED_LOCATIONS: [&str; 6] = [
     "file:///autoinst.xml",
 ];
 
+/// Where to write the profile built from the rules.
+const RULES_PROFILE_PATH: &str = "/run/agama/rules/profile.json";
+
 /// Loads the configuration for the unattended installation.
 ///
 /// This struct is responsible for finding and loading the configuration
@@ -41,6 +50,7 @@ const PREDEFINED_LOCATIONS: [&str; 6] = [
 pub struct ConfigAutoLoader {
     questions: UserQuestions,
     insecure: bool,
+    transfer: Option<Transfer>,
 }
 
 impl ConfigAutoLoader {
@@ -52,9 +62,18 @@ impl ConfigAutoLoader {
         Ok(Self {
             questions: UserQuestions::new(http),
             insecure,
+            transfer: None,
         })
     }
 
+    /// Sets the transfer used to fetch the rules and their fragments.
+    ///
+    /// * `transfer`: transfer with the options and the progress reporting to use.
+    pub fn with_transfer(mut self, transfer: Transfer) -> Self {
+        self.transfer = Some(transfer);
+        self
+    }
+
     /// Loads the configuration for the unattended installation.
     ///
     /// * If a set of URLs are given, it processes them in interactive mode (asking
@@ -63,34 +82,108 @@ impl ConfigAutoLoader {
     ///   locations. It does not report problems for these locations.
     ///
     /// See [Self::load] for further information.
-    pub async fn load(&self, urls: &Vec<String>) -> anyhow::Result<()> {
+    pub async fn load(&self, urls: &[VerifiedUrl]) -> anyhow::Result<()> {
         let loader = ConfigLoader::new(self.insecure);
         if urls.is_empty() {
             self.load_predefined_config(loader).await
         } else {
-            self.load_user_config(loader, &urls).await
+            self.load_user_config(loader, urls).await
+        }
+    }
+
+    /// Loads the configuration built from a rules document.
+    ///
+    /// It evaluates the rules against the system facts, reports which rules matched
+    /// and loads the profile resulting from merging the selected classes.
+    ///
+    /// * `url`: rules document URL.
+    /// * `cmdline`: kernel command-line arguments, exposed as facts to the rules.
+    pub async fn load_rules(&self, url: &str, cmdline: &KernelCmdline) -> anyhow::Result<()> {
+        println!("Evaluating the rules from {url}");
+        let facts = Facts::read(cmdline)?.to_json();
+        let mut builder = RulesProfileBuilder::new(self.insecure);
+        if let Some(transfer) = &self.transfer {
+            builder = builder.with_transfer(transfer.clone());
+        }
+        let outcome = loop {
+            match builder.build(url, &facts) {
+                Ok(outcome) => break outcome,
+                Err(error) => {
+                    eprintln!("Could not build the configuration from {url}: {error:#}");
+                    if !self.should_retry(url, &format!("{error:#}")).await? {
+                        return Err(error);
+                    }
+                }
+            }
+        };
+        println!("{}", outcome.report);
+
+        let path = Path::new(RULES_PROFILE_PATH);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        fs::write(path, serde_json::to_string_pretty(&outcome.profile)?)?;
+
+        let loader = ConfigLoader::new(self.insecure);
+        loader
+            .load(
+                &format!("file://{RULES_PROFILE_PATH}"),
+                &Verification::default(),
+            )
+            .await?;
+        println!("Configuration loaded from {url}");
+        Ok(())
     }
 
     /// Loads configuration files specified by the user.
-    async fn load_user_config(&self, loader: ConfigLoader, urls: &[String]) -> anyhow::Result<()> {
-        for url in urls {
+    ///
+    /// When several files are given, they are merged (see [SettingsMerger]) and the result
+    /// is loaded at once.
+    async fn load_user_config(
+        &self,
+        loader: ConfigLoader,
+        urls: &[VerifiedUrl],
+    ) -> anyhow::Result<()> {
+        if let [VerifiedUrl { url, verification }] = urls {
             println!("Loading configuration from {url}");
-            while let Err(error) = loader.load(url).await {
+            while let Err(error) = loader.load(url, verification).await {
                 eprintln!("Could not load configuration from {url}: {error}");
                 if !self.should_retry(url, &error.to_string()).await? {
                     return Err(error);
                 }
             }
             println!("Configuration loaded from {url}");
+            return Ok(());
         }
+
+        let mut profiles = vec![];
+        for VerifiedUrl { url, verification } in urls {
+            println!("Reading configuration from {url}");
+            let profile = loop {
+                match loader.generate(url, verification).await {
+                    Ok(profile) => break profile,
+                    Err(error) => {
+                        eprintln!("Could not read configuration from {url}: {error}");
+                        if !self.should_retry(url, &error.to_string()).await? {
+                            return Err(error);
+                        }
+                    }
+                }
+            };
+            profiles.push(serde_json::from_str(&profile)?);
+        }
+
+        let profile = SettingsMerger::default().merge_all(profiles);
+        loader.load_json(&profile.to_string()).await?;
+        let urls: Vec<_> = urls.iter().map(|u| u.url.as_str()).collect();
+        println!("Configuration loaded from {}", urls.join(", "));
         Ok(())
     }
 
     /// Loads configuration files from pre-defined locations.
     async fn load_predefined_config(&self, loader: ConfigLoader) -> anyhow::Result<()> {
         for url in PREDEFINED_LOCATIONS {
-            match loader.load(&url).await {
+            match loader.load(url, &Verification::default()).await {
                 Ok(()) => {
                     println!("Configuration loaded from {url}");
                     return Ok(());
@@ -20,7 +20,16 @@
 
 use std::{collections::HashMap, path::Path};
 
+use agama_lib::utils::Verification;
 use anyhow::Context;
+use url::Url;
+
+/// URL and the integrity checks to apply when downloading it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerifiedUrl {
+    pub url: String,
+    pub verification: Verification,
+}
 
 /// Implements a mechanism to read the kernel's command-line arguments.
 ///
@@ -69,11 +78,52 @@ impl KernelCmdline {
         let values = self.0.get(name)?;
         values.last().cloned()
     }
+
+    /// Returns the URLs for the argument, including their integrity checks.
+    ///
+    /// The checksums and signatures are read from the `<name>_checksum` and `<name>_signature`
+    /// arguments. They are matched by position, so the first checksum applies to the first
+    /// URL and so on. Relative signature URLs are resolved against the URL of the file.
+    ///
+    /// * `name`: argument name (e.g., "inst.auto").
+    pub fn get_verified_urls(&self, name: &str) -> anyhow::Result<Vec<VerifiedUrl>> {
+        let checksums = self.get(&format!("{name}_checksum"));
+        let signatures = self.get(&format!("{name}_signature"));
+
+        let mut urls = vec![];
+        for (index, url) in self.get(name).into_iter().enumerate() {
+            let checksum = checksums
+                .get(index)
+                .map(|c| c.parse())
+                .transpose()
+                .context(format!("Invalid checksum for {url}"))?;
+            let signature = signatures
+                .get(index)
+                .map(|s| Url::parse(&url).and_then(|u| u.join(s)))
+                .transpose()
+                .context(format!("Invalid signature URL for {url}"))?
+                .map(|s| s.to_string());
+            urls.push(VerifiedUrl {
+                url,
+                verification: Verification {
+                    checksum,
+                    signature,
+                },
+            });
+        }
+        Ok(urls)
+    }
+
+    /// Returns an iterator over the arguments and their values.
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.0.iter()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::KernelCmdline;
+    use agama_lib::utils::{ChecksumAlgorithm, Verification};
 
     #[test]
     fn test_cmdline_args() {
@@ -95,4 +145,31 @@ mod tests {
 
         assert_eq!(args.get_last("inst.auto_insecure"), Some("0".to_string()));
     }
+
+    #[test]
+    fn test_cmdline_verified_urls() {
+        let checksum = format!("sha256:{}", "a".repeat(64));
+        let args_str = format!(
+            "inst.auto=http://example.net/a.json inst.auto=http://example.net/b.json \
+             inst.auto_checksum={checksum} inst.auto_signature=a.json.asc"
+        );
+        let args = KernelCmdline::parse_str(&args_str);
+
+        let urls = args.get_verified_urls("inst.auto").unwrap();
+        assert_eq!(urls.len(), 2);
+        let verification = &urls[0].verification;
+        assert_eq!(
+            verification.checksum.as_ref().map(|c| c.algorithm),
+            Some(ChecksumAlgorithm::Sha256)
+        );
+        assert_eq!(
+            verification.signature,
+            Some("http://example.net/a.json.asc".to_string())
+        );
+        assert_eq!(urls[1].url, "http://example.net/b.json");
+        assert_eq!(urls[1].verification, Verification::default());
+
+        let args = KernelCmdline::parse_str("inst.auto=file:///a.json inst.auto_checksum=md5:1");
+        assert!(args.get_verified_urls("inst.auto").is_err());
+    }
 }
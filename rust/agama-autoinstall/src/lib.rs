@@ -19,7 +19,7 @@
 // find current contact information at www.suse.com.
 
 mod kernel_cmdline;
-pub use kernel_cmdline::KernelCmdline;
+pub use kernel_cmdline::{KernelCmdline, VerifiedUrl};
 
 mod loader;
 pub use loader::ConfigLoader;
@@ -32,3 +32,6 @@ pub use scripts::ScriptsRunner;
 
 mod questions;
 pub use questions::UserQuestions;
+
+pub mod rules;
+pub use rules::{Facts, RulesEngine, RulesProfileBuilder, RulesReport};
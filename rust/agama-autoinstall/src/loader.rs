@@ -20,6 +20,7 @@
 
 use std::{io::Write, process::Stdio};
 
+use agama_lib::utils::Verification;
 use anyhow::anyhow;
 
 /// It loads the an Agama configuration.
@@ -39,10 +40,32 @@ impl ConfigLoader {
     }
 
     /// Loads the configuration from the given URL.
-    pub async fn load(&self, url: &str) -> anyhow::Result<()> {
-        let mut generate_args = vec!["config", "generate", url];
+    ///
+    /// * `url`: configuration URL.
+    /// * `verification`: integrity checks for the configuration.
+    pub async fn load(&self, url: &str, verification: &Verification) -> anyhow::Result<()> {
+        let profile = self.generate(url, verification).await?;
+        self.load_json(&profile).await
+    }
+
+    /// Generates the configuration from the given URL and returns it as JSON.
+    ///
+    /// * `url`: configuration URL.
+    /// * `verification`: integrity checks for the configuration.
+    pub async fn generate(&self, url: &str, verification: &Verification) -> anyhow::Result<String> {
+        let mut generate_args = vec![
+            "config".to_string(),
+            "generate".to_string(),
+            url.to_string(),
+        ];
         if self.insecure {
-            generate_args.insert(0, "--insecure");
+            generate_args.insert(0, "--insecure".to_string());
+        }
+        if let Some(checksum) = &verification.checksum {
+            generate_args.extend(["--checksum".to_string(), checksum.to_string()]);
+        }
+        if let Some(signature) = &verification.signature {
+            generate_args.extend(["--signature".to_string(), signature.clone()]);
         }
 
         let generate_cmd = std::process::Command::new("agama")
@@ -56,6 +79,11 @@ impl ConfigLoader {
             return Err(anyhow!("Could not generate the configuration: {}", message));
         }
 
+        Ok(String::from_utf8(generate_cmd.stdout)?)
+    }
+
+    /// Loads the given JSON configuration.
+    pub async fn load_json(&self, profile: &str) -> anyhow::Result<()> {
         let mut load_args = vec!["config", "load"];
         if self.insecure {
             load_args.insert(0, "--insecure");
@@ -72,7 +100,7 @@ impl ConfigLoader {
             .stdin
             .take()
             .ok_or(anyhow!("Could not write to \"config load\" stdin"))?;
-        stdin.write_all(&generate_cmd.stdout)?;
+        stdin.write_all(profile.as_bytes())?;
         drop(stdin);
 
         let config_cmd = child.wait_with_output()?;
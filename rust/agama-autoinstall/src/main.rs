@@ -18,22 +18,30 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
-use agama_autoinstall::{ConfigAutoLoader, KernelCmdline, ScriptsRunner};
+use agama_autoinstall::{ConfigAutoLoader, KernelCmdline, ScriptsRunner, VerifiedUrl};
 use agama_lib::{
     auth::AuthToken,
     http::BaseHTTPClient,
     manager::{FinishMethod, ManagerHTTPClient},
+    utils::{DBusProgressReporter, Transfer, TransferOptions},
 };
 use anyhow::anyhow;
 
 const CMDLINE_FILE: &str = "/run/agama/cmdline.d/agama.conf";
-const API_URL: &str = "http://localhost/api";
+const API_URL: &str = "unix:///run/agama/api.sock";
 
+/// Builds a client which connects to the API through the Unix socket.
+///
+/// The server authorizes root through the peer credentials, so the master token is used only if
+/// it is available.
 pub fn build_base_client() -> anyhow::Result<BaseHTTPClient> {
-    let token = AuthToken::master().ok_or(anyhow!("Could not find the master token"))?;
-    Ok(BaseHTTPClient::new(API_URL)?.authenticated(&token)?)
+    let client = BaseHTTPClient::new(API_URL)?;
+    match AuthToken::master() {
+        Some(token) => Ok(client.authenticated(&token)?),
+        None => Ok(client),
+    }
 }
 
 pub fn insecure_from(cmdline: &KernelCmdline, key: &str) -> bool {
@@ -41,26 +49,85 @@ pub fn insecure_from(cmdline: &KernelCmdline, key: &str) -> bool {
     Some("1".to_string()) == value
 }
 
+/// Reads the transfer options from the kernel command-line.
+///
+/// * `inst.transfer_retries`: number of retries after a transient error.
+/// * `inst.transfer_timeout`: maximum time, in seconds, to download a file.
+/// * `inst.transfer_no_cache`: ignore the cached copies (if set to "1").
+pub fn transfer_options_from(cmdline: &KernelCmdline) -> anyhow::Result<TransferOptions> {
+    let mut options = TransferOptions::default();
+    if let Some(retries) = cmdline.get_last("inst.transfer_retries") {
+        options.retries = retries
+            .parse()
+            .map_err(|_| anyhow!("Invalid inst.transfer_retries value: {retries}"))?;
+    }
+    if let Some(timeout) = cmdline.get_last("inst.transfer_timeout") {
+        let seconds = timeout
+            .parse()
+            .map_err(|_| anyhow!("Invalid inst.transfer_timeout value: {timeout}"))?;
+        options.timeout = Some(Duration::from_secs(seconds));
+    }
+    options.bypass_cache = cmdline
+        .get_last("inst.transfer_no_cache")
+        .is_some_and(|v| v == "1");
+    Ok(options)
+}
+
+/// Builds a transfer which reports the progress, if possible.
+pub fn build_transfer(
+    options: &TransferOptions,
+    insecure: bool,
+    reporter: Option<&DBusProgressReporter>,
+) -> Transfer {
+    let transfer = Transfer::new(TransferOptions {
+        insecure,
+        ..options.clone()
+    });
+    match reporter {
+        Some(reporter) => transfer.with_progress(reporter.callback()),
+        None => transfer,
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = KernelCmdline::parse_file(CMDLINE_FILE)?;
     let http = build_base_client()?;
     let manager_client = ManagerHTTPClient::new(http.clone());
+    let transfer_options = transfer_options_from(&args)?;
+    let reporter = match agama_lib::connection().await {
+        Ok(connection) => Some(DBusProgressReporter::new(connection)),
+        Err(error) => {
+            eprintln!("Downloads progress will not be reported: {error}");
+            None
+        }
+    };
 
-    let scripts = args.get("inst.script");
+    let scripts = args.get_verified_urls("inst.script")?;
     let script_insecure = insecure_from(&args, "inst.script_insecure");
-    let mut runner = ScriptsRunner::new(http.clone(), "/run/agama/inst-scripts", script_insecure);
-    for url in scripts {
+    let mut runner =
+        ScriptsRunner::new(http.clone(), "/run/agama/inst-scripts", script_insecure).with_transfer(
+            build_transfer(&transfer_options, script_insecure, reporter.as_ref()),
+        );
+    for VerifiedUrl { url, verification } in scripts {
         println!("Running script from {}", &url);
-        if let Err(error) = runner.run(&url).await {
+        if let Err(error) = runner.run(&url, &verification).await {
             eprintln!("Error running the script from {url}: {}", error);
         }
     }
 
     let auto_insecure = insecure_from(&args, "inst.auto_insecure");
-    let loader = ConfigAutoLoader::new(http.clone(), auto_insecure)?;
-    let urls = args.get("inst.auto");
-    if let Err(error) = loader.load(&urls).await {
+    let loader = ConfigAutoLoader::new(http.clone(), auto_insecure)?.with_transfer(build_transfer(
+        &transfer_options,
+        auto_insecure,
+        reporter.as_ref(),
+    ));
+    let urls = args.get_verified_urls("inst.auto")?;
+    let result = match args.get_last("inst.rules") {
+        Some(rules_url) => loader.load_rules(&rules_url, &args).await,
+        None => loader.load(&urls).await,
+    };
+    if let Err(error) = result {
         eprintln!("Skipping the auto-installation: {error}");
         return Ok(());
     }
@@ -0,0 +1,660 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements a rules engine to build the profile from a set of fragments.
+//!
+//! Instead of writing a profile for each type of machine, it is possible to write a rules
+//! document which selects and merges several profile fragments (_classes_) depending on the
+//! system facts (see [Facts]).
+//!
+//! ```json
+//! {
+//!   "classes": {
+//!     "base": "base.json",
+//!     "server": ["server.json", "raid.jsonnet"],
+//!     "laptop": "laptop.json"
+//!   },
+//!   "rules": [
+//!     {
+//!       "name": "big-servers",
+//!       "conditions": [
+//!         { "fact": "hardware.memory.size", "op": "gte", "value": "32 GiB" },
+//!         { "fact": "hardware.disks.*.transport", "op": "equals", "value": "nvme" }
+//!       ],
+//!       "classes": ["base", "server"]
+//!     },
+//!     {
+//!       "name": "fallback",
+//!       "classes": ["base", "laptop"]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The rules are evaluated in order and, by default, the evaluation stops at the first matching
+//! rule. Set `"continue": true` to keep evaluating the following rules. The fragments of the
+//! selected classes are merged in order using the [SettingsMerger].
+
+pub mod facts;
+mod model;
+
+pub use facts::Facts;
+pub use model::{ClassFragments, Condition, LogicalOperator, Operator, Rule, RulesDocument};
+
+use std::fmt;
+
+use agama_lib::{
+    install_settings::{InstallSettings, SettingsMerger},
+    profile::{ProfileEvaluator, StaticHardwareProvider},
+    utils::{FileFormat, Transfer, TransferOptions},
+};
+use anyhow::{anyhow, Context};
+use regex::Regex;
+use serde_json::Value;
+use url::Url;
+
+/// Evaluates the rules against a set of facts.
+pub struct RulesEngine {
+    document: RulesDocument,
+}
+
+impl RulesEngine {
+    /// Builds an engine for the given document.
+    pub fn new(document: RulesDocument) -> Self {
+        Self { document }
+    }
+
+    /// Builds an engine from a JSON rules document.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let document = serde_json::from_str(json).context("Invalid rules document")?;
+        Ok(Self::new(document))
+    }
+
+    /// Returns the rules document.
+    pub fn document(&self) -> &RulesDocument {
+        &self.document
+    }
+
+    /// Evaluates the rules and returns a report with the selected classes.
+    ///
+    /// * `facts`: system facts as a JSON document.
+    pub fn evaluate(&self, facts: &Value) -> anyhow::Result<RulesReport> {
+        let mut report = RulesReport::default();
+
+        for rule in &self.document.rules {
+            let mut results = vec![];
+            for condition in &rule.conditions {
+                results.push(evaluate_condition(condition, facts)?);
+            }
+
+            let matched = match rule.operator {
+                LogicalOperator::And => results.iter().all(|(m, _)| *m),
+                LogicalOperator::Or => results.is_empty() || results.iter().any(|(m, _)| *m),
+            };
+            let mut reasons: Vec<String> = results.into_iter().map(|(_, r)| r).collect();
+            if reasons.is_empty() {
+                reasons.push("no conditions".to_string());
+            }
+
+            report.rules.push(RuleReport {
+                name: rule.name.clone(),
+                matched,
+                reasons,
+            });
+
+            if matched {
+                for class in &rule.classes {
+                    if !report.classes.contains(class) {
+                        report.classes.push(class.clone());
+                    }
+                }
+                if !rule.continue_matching {
+                    break;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Result of evaluating the rules.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RulesReport {
+    /// Evaluated rules, in order.
+    pub rules: Vec<RuleReport>,
+    /// Selected classes, in the order they should be applied.
+    pub classes: Vec<String>,
+}
+
+impl RulesReport {
+    /// Returns the rules that matched.
+    pub fn matched(&self) -> impl Iterator<Item = &RuleReport> {
+        self.rules.iter().filter(|r| r.matched)
+    }
+}
+
+impl fmt::Display for RulesReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for rule in &self.rules {
+            let status = if rule.matched {
+                "matched"
+            } else {
+                "not matched"
+            };
+            writeln!(f, "Rule \"{}\": {}", rule.name, status)?;
+            for reason in &rule.reasons {
+                writeln!(f, "  - {reason}")?;
+            }
+        }
+        write!(f, "Selected classes: {}", self.classes.join(", "))
+    }
+}
+
+/// Result of evaluating a single rule.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RuleReport {
+    pub name: String,
+    pub matched: bool,
+    /// Human-readable explanation of each condition result.
+    pub reasons: Vec<String>,
+}
+
+/// Evaluates a condition and returns whether it matched and why.
+fn evaluate_condition(condition: &Condition, facts: &Value) -> anyhow::Result<(bool, String)> {
+    let values = find_facts(facts, &condition.fact);
+    let expected = &condition.value;
+
+    if condition.op == Operator::Exists {
+        let should_exist = expected.as_bool().unwrap_or(true);
+        let exists = !values.is_empty();
+        let reason = if exists {
+            format!("{} exists", condition.fact)
+        } else {
+            format!("{} does not exist", condition.fact)
+        };
+        return Ok((exists == should_exist, reason));
+    }
+
+    if values.is_empty() {
+        let matched = condition.op == Operator::NotEquals;
+        return Ok((matched, format!("{} does not exist", condition.fact)));
+    }
+
+    let regex = match (condition.op, expected) {
+        (Operator::Matches, Value::String(pattern)) => Some(
+            Regex::new(pattern)
+                .with_context(|| format!("Invalid regular expression in {}", condition.fact))?,
+        ),
+        (Operator::Matches, _) => {
+            return Err(anyhow!(
+                "The value of the \"matches\" condition on {} must be a string",
+                condition.fact
+            ))
+        }
+        _ => None,
+    };
+
+    let check = |value: &Value| -> bool {
+        match condition.op {
+            Operator::Equals | Operator::NotEquals => loose_eq(value, expected),
+            Operator::Gt => compare(value, expected).is_some_and(|o| o.is_gt()),
+            Operator::Gte => compare(value, expected).is_some_and(|o| o.is_ge()),
+            Operator::Lt => compare(value, expected).is_some_and(|o| o.is_lt()),
+            Operator::Lte => compare(value, expected).is_some_and(|o| o.is_le()),
+            Operator::Contains => contains(value, expected),
+            Operator::Matches => regex.as_ref().is_some_and(|r| r.is_match(&to_text(value))),
+            Operator::OneOf => expected
+                .as_array()
+                .is_some_and(|options| options.iter().any(|o| loose_eq(value, o))),
+            Operator::Exists => true,
+        }
+    };
+
+    let found = values.iter().find(|v| check(v));
+    let matched = if condition.op == Operator::NotEquals {
+        found.is_none()
+    } else {
+        found.is_some()
+    };
+
+    let actual = found.copied().unwrap_or(values[0]);
+    let negation = if matched { "" } else { "not " };
+    let reason = format!(
+        "{} is {}, which is {}{} {}",
+        condition.fact, actual, negation, condition.op, expected
+    );
+    Ok((matched, reason))
+}
+
+/// Finds the values of a fact.
+///
+/// The path is a dot-separated list of keys. Array elements can be referenced by index or
+/// using `*` (any element). Keys containing dots (e.g., "cmdline.inst.role") are supported too.
+fn find_facts<'a>(facts: &'a Value, path: &str) -> Vec<&'a Value> {
+    let segments: Vec<&str> = path.split('.').collect();
+    let mut found = vec![];
+    find_values(facts, &segments, &mut found);
+    found.into_iter().filter(|v| !v.is_null()).collect()
+}
+
+fn find_values<'a>(value: &'a Value, segments: &[&str], found: &mut Vec<&'a Value>) {
+    let Some(segment) = segments.first() else {
+        found.push(value);
+        return;
+    };
+
+    match value {
+        Value::Object(map) => {
+            // Try the longest key first, so "inst.role" wins over "inst".
+            for len in (1..=segments.len()).rev() {
+                if let Some(child) = map.get(&segments[..len].join(".")) {
+                    find_values(child, &segments[len..], found);
+                    return;
+                }
+            }
+        }
+        Value::Array(items) if *segment == "*" => {
+            for item in items {
+                find_values(item, &segments[1..], found);
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item) = segment.parse::<usize>().ok().and_then(|i| items.get(i)) {
+                find_values(item, &segments[1..], found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Compares two values, converting them to numbers when possible.
+///
+/// The kernel command-line values are always strings, so "1" is considered equal to 1.
+fn loose_eq(value: &Value, expected: &Value) -> bool {
+    if value == expected {
+        return true;
+    }
+    match (to_number(value), to_number(expected)) {
+        (Some(a), Some(b)) => a == b,
+        _ => to_text(value) == to_text(expected),
+    }
+}
+
+fn compare(value: &Value, expected: &Value) -> Option<std::cmp::Ordering> {
+    to_number(value)?.partial_cmp(&to_number(expected)?)
+}
+
+fn contains(value: &Value, expected: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(|i| loose_eq(i, expected)),
+        Value::String(text) => text.contains(&to_text(expected)),
+        _ => false,
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Converts a value to a number.
+///
+/// Strings can include a size unit (e.g., "512 MiB", "8GB").
+fn to_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => parse_size(text),
+        _ => None,
+    }
+}
+
+fn parse_size(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0_f64.powi(2),
+        "gib" => 1024.0_f64.powi(3),
+        "tib" => 1024.0_f64.powi(4),
+        _ => return None,
+    };
+    Some(number * multiplier)
+}
+
+/// Profile built from a rules document.
+pub struct RulesOutcome {
+    pub report: RulesReport,
+    /// Result of merging the fragments of the selected classes.
+    pub profile: Value,
+}
+
+/// Builds a profile from a rules document.
+///
+/// It fetches the rules, evaluates them and merges the fragments of the selected classes.
+pub struct RulesProfileBuilder {
+    transfer: Transfer,
+}
+
+impl RulesProfileBuilder {
+    /// Creates a new builder.
+    ///
+    /// * `insecure`: whether to skip SSL cert checks when fetching the rules and fragments.
+    pub fn new(insecure: bool) -> Self {
+        Self {
+            transfer: Transfer::new(TransferOptions {
+                insecure,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Sets the transfer used to fetch the rules and the fragments.
+    ///
+    /// * `transfer`: transfer with the options and the progress reporting to use.
+    pub fn with_transfer(mut self, transfer: Transfer) -> Self {
+        self.transfer = transfer;
+        self
+    }
+
+    /// Builds the profile.
+    ///
+    /// * `url`: rules document URL, supporting agama-specific schemes.
+    /// * `facts`: system facts as a JSON document. The `hardware` and `lshw` sections are
+    ///   passed to the Jsonnet fragments.
+    pub fn build(&self, url: &str, facts: &Value) -> anyhow::Result<RulesOutcome> {
+        let base = Url::parse(url).with_context(|| format!("Invalid rules URL {url}"))?;
+        let engine = RulesEngine::from_json(&self.fetch(url)?)?;
+        let report = engine.evaluate(facts)?;
+
+        let hardware = facts.get("hardware").cloned().unwrap_or_default();
+        let mut provider = StaticHardwareProvider::new(hardware);
+        if let Some(lshw) = facts.get("lshw") {
+            provider = provider.with_lshw(lshw.clone());
+        }
+        let evaluator = ProfileEvaluator::new(provider);
+        let mut profiles = vec![];
+        for class in &report.classes {
+            let fragments = engine
+                .document()
+                .classes
+                .get(class)
+                .ok_or(anyhow!("Unknown class {class}"))?;
+            for fragment in fragments.urls() {
+                let fragment_url = resolve_url(&base, &fragment)?;
+                let content = self.fetch(&fragment_url)?;
+                let value = read_fragment(&evaluator, &content)
+                    .with_context(|| format!("Could not read the fragment {fragment_url}"))?;
+                profiles.push(value);
+            }
+        }
+        let profile = SettingsMerger::default().merge_all(profiles);
+
+        serde_json::from_value::<InstallSettings>(profile.clone())
+            .context("The merged profile is not valid")?;
+        Ok(RulesOutcome { report, profile })
+    }
+
+    fn fetch(&self, url: &str) -> anyhow::Result<String> {
+        let mut content: Vec<u8> = vec![];
+        self.transfer
+            .fetch(url, &mut content)
+            .with_context(|| format!("Could not fetch {url}"))?;
+        Ok(String::from_utf8(content)?)
+    }
+}
+
+/// Resolves a fragment URL relative to the rules document.
+fn resolve_url(base: &Url, fragment: &str) -> anyhow::Result<String> {
+    match Url::parse(fragment) {
+        Ok(url) => Ok(url.to_string()),
+        Err(url::ParseError::RelativeUrlWithoutBase) => Ok(base.join(fragment)?.to_string()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn read_fragment(evaluator: &ProfileEvaluator, content: &str) -> anyhow::Result<Value> {
+    match FileFormat::from_string(content) {
+        FileFormat::Json => Ok(serde_json::from_str(content)?),
+        FileFormat::Jsonnet => Ok(serde_json::from_str(&evaluator.evaluate_string(content)?)?),
+        FileFormat::Unknown => Err(anyhow!("Unsupported fragment format")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_size, RulesEngine, RulesProfileBuilder};
+    use serde_json::{json, Value};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn facts() -> Value {
+        json!({
+            "hardware": {
+                "memory": { "size": 34359738368u64 },
+                "disks": [
+                    { "name": "sda", "transport": "sata", "rotational": true },
+                    { "name": "nvme0n1", "transport": "nvme", "rotational": false }
+                ],
+                "dmi": { "sysVendor": "Dell Inc.", "productName": "PowerEdge R650" }
+            },
+            "cmdline": { "inst.role": "db", "inst.debug": "1" },
+            "network": {
+                "hostname": "node01",
+                "interfaces": [{ "name": "eth0", "addresses": ["10.0.0.5/24"] }]
+            }
+        })
+    }
+
+    fn evaluate(rules: Value) -> super::RulesReport {
+        RulesEngine::from_json(&rules.to_string())
+            .unwrap()
+            .evaluate(&facts())
+            .unwrap()
+    }
+
+    fn matches(condition: Value) -> bool {
+        let report = evaluate(json!({
+            "rules": [{ "name": "test", "conditions": [condition], "classes": ["test"] }]
+        }));
+        report.rules[0].matched
+    }
+
+    #[test]
+    fn test_operators() {
+        assert!(matches(
+            json!({ "fact": "hardware.memory.size", "op": "gte", "value": "32 GiB" })
+        ));
+        assert!(!matches(
+            json!({ "fact": "hardware.memory.size", "op": "gt", "value": "32 GiB" })
+        ));
+        assert!(matches(
+            json!({ "fact": "hardware.memory.size", "op": "lt", "value": 64e9 })
+        ));
+        assert!(matches(
+            json!({ "fact": "hardware.disks.*.transport", "value": "nvme" })
+        ));
+        assert!(matches(
+            json!({ "fact": "hardware.disks.0.name", "op": "notEquals", "value": "vda" })
+        ));
+        assert!(matches(
+            json!({ "fact": "hardware.dmi.productName", "op": "matches", "value": "^PowerEdge" })
+        ));
+        assert!(matches(
+            json!({ "fact": "hardware.dmi.sysVendor", "op": "contains", "value": "Dell" })
+        ));
+        assert!(matches(
+            json!({ "fact": "network.interfaces.*.addresses", "op": "contains", "value": "10.0.0.5/24" })
+        ));
+        assert!(matches(
+            json!({ "fact": "cmdline.inst.role", "op": "oneOf", "value": ["db", "web"] })
+        ));
+        assert!(matches(json!({ "fact": "cmdline.inst.debug", "value": 1 })));
+        assert!(matches(
+            json!({ "fact": "network.hostname", "op": "exists" })
+        ));
+        assert!(matches(
+            json!({ "fact": "hardware.gpu", "op": "exists", "value": false })
+        ));
+    }
+
+    #[test]
+    fn test_dotted_keys() {
+        // "cmdline.inst.role" must find the "inst.role" key.
+        assert!(matches(
+            json!({ "fact": "cmdline.inst.role", "value": "db" })
+        ));
+    }
+
+    #[test]
+    fn test_first_match() {
+        let report = evaluate(json!({
+            "rules": [
+                {
+                    "name": "laptops",
+                    "conditions": [{ "fact": "hardware.memory.size", "op": "lt", "value": "16 GiB" }],
+                    "classes": ["laptop"]
+                },
+                {
+                    "name": "servers",
+                    "conditions": [{ "fact": "hardware.dmi.sysVendor", "value": "Dell Inc." }],
+                    "classes": ["base", "server"]
+                },
+                { "name": "fallback", "classes": ["base"] }
+            ]
+        }));
+
+        assert_eq!(report.rules.len(), 2);
+        assert!(!report.rules[0].matched);
+        assert_eq!(
+            report.rules[0].reasons,
+            vec!["hardware.memory.size is 34359738368, which is not lt \"16 GiB\""]
+        );
+        assert!(report.rules[1].matched);
+        assert_eq!(report.classes, vec!["base", "server"]);
+    }
+
+    #[test]
+    fn test_continue_and_or() {
+        let report = evaluate(json!({
+            "rules": [
+                { "name": "common", "classes": ["base"], "continue": true },
+                {
+                    "name": "db-or-web",
+                    "operator": "or",
+                    "conditions": [
+                        { "fact": "cmdline.inst.role", "value": "web" },
+                        { "fact": "cmdline.inst.role", "value": "db" }
+                    ],
+                    "classes": ["base", "db"]
+                }
+            ]
+        }));
+
+        let matched: Vec<_> = report.matched().map(|r| r.name.as_str()).collect();
+        assert_eq!(matched, vec!["common", "db-or-web"]);
+        assert_eq!(report.classes, vec!["base", "db"]);
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let engine = RulesEngine::from_json(
+            &json!({
+                "rules": [{
+                    "name": "test",
+                    "conditions": [{ "fact": "network.hostname", "op": "matches", "value": "(" }]
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert!(engine.evaluate(&facts()).is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Some(1024.0));
+        assert_eq!(parse_size("2 KiB"), Some(2048.0));
+        assert_eq!(parse_size("1.5GB"), Some(1.5e9));
+        assert_eq!(parse_size("many"), None);
+    }
+
+    #[test]
+    fn test_build_profile() {
+        let tmp_dir = TempDir::with_prefix("rules").unwrap();
+        let rules = json!({
+            "classes": {
+                "base": "base.json",
+                "server": ["server.jsonnet", "memory.jsonnet"]
+            },
+            "rules": [
+                {
+                    "name": "servers",
+                    "conditions": [{ "fact": "hardware.dmi.sysVendor", "value": "Dell Inc." }],
+                    "classes": ["base", "server"]
+                }
+            ]
+        });
+        fs::write(tmp_dir.path().join("rules.json"), rules.to_string()).unwrap();
+        fs::write(
+            tmp_dir.path().join("base.json"),
+            r#"{ "product": { "id": "Tumbleweed" }, "localization": { "language": "en_US.UTF-8" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            tmp_dir.path().join("server.jsonnet"),
+            "local hw = std.extVar('hw'); { product: { id: 'SLES' }, hostname: { static: std.asciiLower(hw.dmi.productName) } }",
+        )
+        .unwrap();
+        fs::write(
+            tmp_dir.path().join("memory.jsonnet"),
+            "local agama = import 'hw.libsonnet'; { software: { patterns: if agama.findByID(agama.lshw, 'memory').size > 16e9 then ['kvm_server'] else [] } }",
+        )
+        .unwrap();
+
+        let mut facts = facts();
+        facts["lshw"] = json!({
+            "id": "computer",
+            "children": [{ "id": "memory", "class": "memory", "size": 34359738368u64 }]
+        });
+        let url = format!("file://{}/rules.json", tmp_dir.path().display());
+        let outcome = RulesProfileBuilder::new(false).build(&url, &facts).unwrap();
+        assert_eq!(
+            outcome.profile,
+            json!({
+                "product": { "id": "SLES" },
+                "localization": { "language": "en_US.UTF-8" },
+                "hostname": { "static": "poweredge r650" },
+                "software": { "patterns": ["kvm_server"] }
+            })
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Facts about the system which can be used in the rules conditions.
+
+use std::{collections::BTreeMap, process::Command};
+
+use agama_lib::hardware::{HardwareInfo, HardwareReader};
+use serde::{Deserialize, Serialize};
+
+use crate::KernelCmdline;
+
+/// System facts.
+///
+/// They are exposed to the rules as a JSON document with these sections:
+///
+/// * `hardware`: hardware information (see [HardwareInfo]).
+/// * `cmdline`: kernel command-line arguments. Only the last value of each argument is kept.
+/// * `network`: hostname and network interfaces with their IP addresses.
+/// * `lshw`: output of `lshw -json`, if it is available. The Jsonnet fragments get it through
+///   the `hw.libsonnet` file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Facts {
+    pub hardware: HardwareInfo,
+    pub cmdline: BTreeMap<String, String>,
+    pub network: NetworkFacts,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lshw: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFacts {
+    pub hostname: Option<String>,
+    pub interfaces: Vec<InterfaceFacts>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceFacts {
+    pub name: String,
+    pub mac: Option<String>,
+    /// IP addresses, including the prefix (e.g., "192.168.122.10/24").
+    pub addresses: Vec<String>,
+}
+
+impl Facts {
+    /// Reads the facts from the running system.
+    ///
+    /// * `cmdline`: kernel command-line arguments.
+    pub fn read(cmdline: &KernelCmdline) -> anyhow::Result<Self> {
+        Ok(Self {
+            hardware: HardwareReader::from_system().read()?,
+            cmdline: cmdline_facts(cmdline),
+            network: NetworkFacts::read(),
+            lshw: HardwareReader::from_system().lshw().ok(),
+        })
+    }
+
+    /// Returns the facts as a JSON document.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+fn cmdline_facts(cmdline: &KernelCmdline) -> BTreeMap<String, String> {
+    cmdline
+        .entries()
+        .filter_map(|(key, values)| Some((key.clone(), values.last()?.clone())))
+        .collect()
+}
+
+/// Interface as reported by `ip -json address`.
+#[derive(Deserialize)]
+struct IpInterface {
+    ifname: String,
+    address: Option<String>,
+    #[serde(default)]
+    addr_info: Vec<IpAddress>,
+}
+
+#[derive(Deserialize)]
+struct IpAddress {
+    local: String,
+    prefixlen: u8,
+}
+
+impl NetworkFacts {
+    /// Reads the network facts from the running system.
+    ///
+    /// The network is not essential for the rules, so it does not fail. If the information is
+    /// not available, it returns empty facts.
+    pub fn read() -> Self {
+        let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .map(|h| h.trim().to_string())
+            .filter(|h| !h.is_empty());
+
+        let interfaces = Command::new("ip")
+            .args(["-json", "address", "show"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .and_then(|o| Self::interfaces_from_ip(&String::from_utf8_lossy(&o.stdout)).ok())
+            .unwrap_or_default();
+
+        Self {
+            hostname,
+            interfaces,
+        }
+    }
+
+    /// Builds the list of interfaces from the output of `ip -json address`.
+    fn interfaces_from_ip(output: &str) -> serde_json::Result<Vec<InterfaceFacts>> {
+        let interfaces: Vec<IpInterface> = serde_json::from_str(output)?;
+        Ok(interfaces
+            .into_iter()
+            .filter(|i| i.ifname != "lo")
+            .map(|i| InterfaceFacts {
+                name: i.ifname,
+                mac: i.address,
+                addresses: i
+                    .addr_info
+                    .iter()
+                    .map(|a| format!("{}/{}", a.local, a.prefixlen))
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cmdline_facts, NetworkFacts};
+    use crate::KernelCmdline;
+
+    #[test]
+    fn test_cmdline_facts() {
+        let cmdline = KernelCmdline::parse_str("rd.neednet inst.role=db inst.role=web");
+        let facts = cmdline_facts(&cmdline);
+        assert_eq!(facts.get("inst.role"), Some(&"web".to_string()));
+        assert_eq!(facts.get("rd.neednet"), Some(&"1".to_string()));
+    }
+
+    #[test]
+    fn test_interfaces_from_ip() {
+        let output = r#"[
+          {"ifindex": 1, "ifname": "lo", "address": "00:00:00:00:00:00",
+           "addr_info": [{"family": "inet", "local": "127.0.0.1", "prefixlen": 8}]},
+          {"ifindex": 2, "ifname": "enp1s0", "address": "52:54:00:12:34:56",
+           "addr_info": [
+             {"family": "inet", "local": "192.168.122.10", "prefixlen": 24},
+             {"family": "inet6", "local": "fe80::5054:ff:fe12:3456", "prefixlen": 64}
+           ]}
+        ]"#;
+        let interfaces = NetworkFacts::interfaces_from_ip(output).unwrap();
+        assert_eq!(interfaces.len(), 1);
+        let enp1s0 = &interfaces[0];
+        assert_eq!(enp1s0.name, "enp1s0");
+        assert_eq!(enp1s0.mac, Some("52:54:00:12:34:56".to_string()));
+        assert_eq!(
+            enp1s0.addresses,
+            vec!["192.168.122.10/24", "fe80::5054:ff:fe12:3456/64"]
+        );
+    }
+}
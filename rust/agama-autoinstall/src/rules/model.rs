@@ -0,0 +1,136 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Data model of the rules document.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Rules document.
+///
+/// It defines a set of classes (named lists of profile fragments) and a list of rules which
+/// select the classes to apply depending on the system facts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesDocument {
+    /// Classes indexed by name.
+    #[serde(default)]
+    pub classes: BTreeMap<String, ClassFragments>,
+    /// Rules, evaluated in the given order.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// Fragments of a class.
+///
+/// It can be a single URL or a list of them. Relative URLs are resolved from the location of
+/// the rules document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ClassFragments {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl ClassFragments {
+    /// Returns the list of fragments.
+    pub fn urls(&self) -> Vec<String> {
+        match self {
+            Self::One(url) => vec![url.clone()],
+            Self::Many(urls) => urls.clone(),
+        }
+    }
+}
+
+/// Rule which selects a set of classes when its conditions are met.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub name: String,
+    /// Conditions to check. A rule without conditions always matches.
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    /// How to combine the conditions.
+    #[serde(default)]
+    pub operator: LogicalOperator,
+    /// Classes to apply when the rule matches.
+    #[serde(default)]
+    pub classes: Vec<String>,
+    /// Whether to keep evaluating the following rules after this one matches.
+    #[serde(default, rename = "continue")]
+    pub continue_matching: bool,
+}
+
+/// Condition over a fact.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    /// Dotted path to the fact (e.g., "hardware.memory.size" or "hardware.disks.*.transport").
+    pub fact: String,
+    #[serde(default)]
+    pub op: Operator,
+    /// Value to compare the fact with. Sizes can be written with units (e.g., "8 GiB").
+    #[serde(default)]
+    pub value: Value,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Operator {
+    #[default]
+    Equals,
+    NotEquals,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+    Matches,
+    Exists,
+    OneOf,
+}
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Equals => "equals",
+            Self::NotEquals => "notEquals",
+            Self::Gt => "gt",
+            Self::Gte => "gte",
+            Self::Lt => "lt",
+            Self::Lte => "lte",
+            Self::Contains => "contains",
+            Self::Matches => "matches",
+            Self::Exists => "exists",
+            Self::OneOf => "oneOf",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogicalOperator {
+    #[default]
+    And,
+    Or,
+}
@@ -26,7 +26,10 @@ use std::{
     process::Output,
 };
 
-use agama_lib::{http::BaseHTTPClient, utils::Transfer};
+use agama_lib::{
+    http::BaseHTTPClient,
+    utils::{Transfer, TransferOptions, Verification},
+};
 use anyhow::anyhow;
 use url::Url;
 
@@ -36,7 +39,7 @@ use crate::UserQuestions;
 pub struct ScriptsRunner {
     pub path: PathBuf,
     questions: UserQuestions,
-    insecure: bool,
+    transfer: Transfer,
     idx: usize,
 }
 
@@ -50,24 +53,38 @@ impl ScriptsRunner {
         Self {
             path: path.as_ref().to_path_buf(),
             questions: UserQuestions::new(http),
-            insecure,
+            transfer: Transfer::new(TransferOptions {
+                insecure,
+                ..Default::default()
+            }),
             idx: 0,
         }
     }
 
+    /// Sets the transfer used to download the scripts.
+    ///
+    /// It replaces the default one, including the `insecure` setting.
+    ///
+    /// * `transfer`: transfer with the options and the progress reporting to use.
+    pub fn with_transfer(mut self, transfer: Transfer) -> Self {
+        self.transfer = transfer;
+        self
+    }
+
     /// Downloads and runs the script from the given URL.
     ///
     /// It downloads the script from the given URL to the runner directory.
     /// It saves the stdout, stderr and exit code to separate files.
     ///
     /// * url: script URL, supporting agama-specific schemes.
-    pub async fn run(&mut self, url: &str) -> anyhow::Result<()> {
+    /// * verification: integrity checks for the script.
+    pub async fn run(&mut self, url: &str, verification: &Verification) -> anyhow::Result<()> {
         create_dir_all(&self.path)?;
 
         let file_name = self.file_name_for(&url)?;
 
         let path = self.path.join(&file_name);
-        self.save_script(url, &path).await?;
+        self.save_script(url, &path, verification).await?;
 
         let output = std::process::Command::new(&path).output()?;
         self.save_logs(&path, output)?;
@@ -95,9 +112,14 @@ impl ScriptsRunner {
             .unwrap_or(unnamed))
     }
 
-    async fn save_script(&self, url: &str, path: &PathBuf) -> anyhow::Result<()> {
+    async fn save_script(
+        &self,
+        url: &str,
+        path: &PathBuf,
+        verification: &Verification,
+    ) -> anyhow::Result<()> {
         let mut file = Self::create_file(&path, 0o700)?;
-        while let Err(error) = Transfer::get(url, &mut file, self.insecure) {
+        while let Err(error) = self.transfer.fetch_verified(url, &mut file, verification) {
             eprintln!("Could not load configuration from {url}: {error}");
             if !self.should_retry(&url, &error.to_string()).await? {
                 return Err(anyhow!(error));
@@ -147,7 +169,7 @@ impl ScriptsRunner {
 #[cfg(test)]
 mod tests {
     use super::ScriptsRunner;
-    use agama_lib::http::BaseHTTPClient;
+    use agama_lib::{http::BaseHTTPClient, utils::Verification};
     use tokio::test;
 
     fn script_path(name: &str) -> String {
@@ -165,7 +187,7 @@ mod tests {
     async fn test_run_script() {
         let url = script_path("success.sh");
         let mut runner = script_runner();
-        runner.run(&url).await.unwrap();
+        runner.run(&url, &Verification::default()).await.unwrap();
 
         let contents = std::fs::read_to_string(runner.path().join("1-success.stdout")).unwrap();
         assert_eq!(&contents, "SUCCESS\n");
@@ -177,7 +199,7 @@ mod tests {
     async fn test_run_script_failed() {
         let url = script_path("error.sh");
         let mut runner = script_runner();
-        runner.run(&url).await.unwrap();
+        runner.run(&url, &Verification::default()).await.unwrap();
 
         let contents = std::fs::read_to_string(runner.path().join("1-error.stderr")).unwrap();
         assert_eq!(&contents, "ERROR\n");
@@ -0,0 +1,226 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the `agama api` command, a generic client for the HTTP API.
+
+use std::io::{self, Read};
+
+use agama_lib::{api_spec::ApiSpec, http::BaseHTTPClient};
+use anyhow::{anyhow, Context};
+use clap::{builder::PossibleValuesParser, Args, Command, Subcommand};
+use reqwest::Method;
+use serde_json::Value;
+
+/// Path of the OpenAPI specification, relative to the API.
+const SPEC_PATH: &str = "/openapi.json";
+
+#[derive(Subcommand, Debug)]
+pub enum ApiCommands {
+    /// Send a GET request and print the response.
+    Get(RequestArgs),
+    /// Send a PUT request and print the response.
+    Put(RequestArgs),
+    /// Send a POST request and print the response.
+    Post(RequestArgs),
+    /// Send a PATCH request and print the response.
+    Patch(RequestArgs),
+    /// Send a DELETE request and print the response.
+    Delete(RequestArgs),
+    /// List the paths of the HTTP API.
+    ///
+    /// The paths are read from the OpenAPI specification published by the server. Parameters
+    /// are written between curly braces or prefixed by a colon (e.g., /software/licenses/:id).
+    Paths {
+        /// Display only the paths starting with the given prefix.
+        prefix: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct RequestArgs {
+    /// Path relative to the API (e.g., /software/config).
+    path: String,
+    /// File containing the JSON request body. Use "-" to read it from the standard input.
+    #[arg(long, short)]
+    data: Option<String>,
+    /// Send the request even if it does not match the OpenAPI specification (e.g., the path is
+    /// unknown or the body is not valid).
+    #[arg(long)]
+    force: bool,
+}
+
+/// Request subcommands and the HTTP method of each one.
+const REQUEST_COMMANDS: [(&str, Method); 5] = [
+    ("get", Method::GET),
+    ("put", Method::PUT),
+    ("post", Method::POST),
+    ("patch", Method::PATCH),
+    ("delete", Method::DELETE),
+];
+
+/// Adds the paths of the HTTP API to the `api` request subcommands, so the shell completion
+/// can offer them.
+///
+/// The paths are not enforced when parsing the arguments, as the completion scripts are
+/// generated from the returned command only.
+///
+/// * `cmd`: Agama CLI command.
+/// * `spec`: OpenAPI specification of the HTTP API.
+pub fn complete_api_paths(cmd: Command, spec: &ApiSpec) -> Command {
+    let paths = spec.paths();
+    cmd.mut_subcommand("api", |mut api| {
+        for (name, method) in REQUEST_COMMANDS {
+            let candidates: Vec<_> = paths
+                .iter()
+                .filter(|p| p.methods.iter().any(|m| m == method.as_str()))
+                .map(|p| p.path.clone())
+                .collect();
+            api = api.mut_subcommand(name, |request| {
+                request.mut_arg("path", |arg| {
+                    arg.value_parser(PossibleValuesParser::new(candidates))
+                })
+            });
+        }
+        api
+    })
+}
+
+async fn read_spec(client: &BaseHTTPClient) -> anyhow::Result<ApiSpec> {
+    let spec: Value = client
+        .get(SPEC_PATH)
+        .await
+        .context("Could not read the OpenAPI specification")?;
+    Ok(ApiSpec::new(spec))
+}
+
+fn read_body(data: &str) -> anyhow::Result<Value> {
+    let contents = if data == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        std::fs::read_to_string(data).with_context(|| format!("Could not read {data}"))?
+    };
+    serde_json::from_str(&contents).context("The request body is not valid JSON")
+}
+
+async fn send_request(
+    client: BaseHTTPClient,
+    method: Method,
+    args: RequestArgs,
+) -> anyhow::Result<()> {
+    let path = if args.path.starts_with('/') {
+        args.path
+    } else {
+        format!("/{}", args.path)
+    };
+    let body = args.data.as_deref().map(read_body).transpose()?;
+
+    let validation = match read_spec(&client).await {
+        Ok(spec) => spec
+            .validate_request(method.as_str(), &path, body.as_ref())
+            .map_err(anyhow::Error::from),
+        Err(error) => Err(error),
+    };
+    if let Err(error) = validation {
+        if !args.force {
+            return Err(anyhow!(
+                "{error:#}\nUse --force to send the request anyway."
+            ));
+        }
+        eprintln!("Sending the request anyway: {error:#}");
+    }
+
+    let response = client.send_raw(method, &path, body.as_ref()).await?;
+    let status = response.status();
+    let text = response.text().await?;
+    let output = match serde_json::from_str::<Value>(&text) {
+        Ok(json) => serde_json::to_string_pretty(&json)?,
+        Err(_) => text,
+    };
+
+    if status.is_success() {
+        if !output.is_empty() {
+            println!("{output}");
+        }
+        Ok(())
+    } else {
+        if !output.is_empty() {
+            eprintln!("{output}");
+        }
+        Err(anyhow!("The server answered with {status}"))
+    }
+}
+
+/// Main entry point called from Agama CLI main loop
+pub async fn run(client: BaseHTTPClient, subcommand: ApiCommands) -> anyhow::Result<()> {
+    match subcommand {
+        ApiCommands::Get(args) => send_request(client, Method::GET, args).await,
+        ApiCommands::Put(args) => send_request(client, Method::PUT, args).await,
+        ApiCommands::Post(args) => send_request(client, Method::POST, args).await,
+        ApiCommands::Patch(args) => send_request(client, Method::PATCH, args).await,
+        ApiCommands::Delete(args) => send_request(client, Method::DELETE, args).await,
+        ApiCommands::Paths { prefix } => {
+            let spec = read_spec(&client).await?;
+            for path in spec.complete(prefix.as_deref().unwrap_or("/")) {
+                println!("{path}");
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agama_lib::api_spec::ApiSpec;
+    use clap::CommandFactory;
+    use serde_json::json;
+
+    use super::complete_api_paths;
+    use crate::Cli;
+
+    #[test]
+    fn test_complete_api_paths() {
+        let spec = ApiSpec::new(json!({
+            "paths": {
+                "/api/software/config": { "get": {}, "put": {} },
+                "/api/storage/devices/system": { "get": {} }
+            }
+        }));
+        let mut cmd = complete_api_paths(Cli::command(), &spec);
+        let api = cmd.find_subcommand_mut("api").unwrap();
+
+        let values = |api: &mut clap::Command, name: &str| -> Vec<String> {
+            let request = api.find_subcommand_mut(name).unwrap();
+            let path = request.get_arguments().find(|a| a.get_id() == "path");
+            path.unwrap()
+                .get_possible_values()
+                .iter()
+                .map(|v| v.get_name().to_string())
+                .collect()
+        };
+        assert_eq!(
+            values(api, "get"),
+            vec!["/software/config", "/storage/devices/system"]
+        );
+        assert_eq!(values(api, "put"), vec!["/software/config"]);
+        assert!(values(api, "delete").is_empty());
+    }
+}
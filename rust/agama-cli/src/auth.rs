@@ -18,7 +18,10 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use agama_lib::{auth::AuthToken, error::ServiceError};
+use agama_lib::{
+    auth::{AuthToken, CreateTokenRequest, CreateTokenResponse, TokenScope},
+    error::ServiceError,
+};
 use clap::Subcommand;
 use url::Url;
 
@@ -28,6 +31,8 @@ use agama_lib::http::BaseHTTPClient;
 use inquire::Password;
 use std::collections::HashMap;
 use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 /// HTTP client to handle authentication
 struct AuthHTTPClient {
@@ -55,6 +60,69 @@ impl AuthHTTPClient {
             None => Err(anyhow::anyhow!("Failed to get authentication token")),
         }
     }
+
+    /// Query web server for JWT using an SSH key
+    ///
+    /// * `key`: path to the private key. The public one is expected to be at `<key>.pub`.
+    pub async fn authenticate_with_key(&self, key: &Path) -> anyhow::Result<String> {
+        let response = self
+            .api
+            .post::<HashMap<String, String>>("/auth/challenge", &HashMap::<String, String>::new())
+            .await?;
+        let Some(challenge) = response.get("challenge") else {
+            return Err(anyhow::anyhow!(
+                "Failed to get the authentication challenge"
+            ));
+        };
+
+        let mut public_key_path = key.as_os_str().to_owned();
+        public_key_path.push(".pub");
+        let public_key = std::fs::read_to_string(&public_key_path)?;
+        let signature = sign_challenge(key, challenge)?;
+
+        let mut auth_body = HashMap::new();
+        auth_body.insert("publicKey", public_key.trim().to_string());
+        auth_body.insert("challenge", challenge.clone());
+        auth_body.insert("signature", signature);
+
+        let response = self
+            .api
+            .post::<HashMap<String, String>>("/auth/key", &auth_body)
+            .await?;
+
+        match response.get("token") {
+            Some(token) => Ok(token.clone()),
+            None => Err(anyhow::anyhow!("Failed to get authentication token")),
+        }
+    }
+
+    /// Revokes the current token.
+    pub async fn revoke_current(&self) -> anyhow::Result<()> {
+        Ok(self.api.delete_void("/auth").await?)
+    }
+
+    /// Creates a new token.
+    ///
+    /// * `scopes`: scopes of the new token (the ones of the current token if empty).
+    /// * `ttl`: lifetime in seconds (the server default if `None`).
+    async fn create_token(
+        &self,
+        scopes: Vec<TokenScope>,
+        ttl: Option<u64>,
+    ) -> anyhow::Result<CreateTokenResponse> {
+        let request = CreateTokenRequest {
+            scopes: (!scopes.is_empty()).then(|| scopes.into_iter().collect()),
+            ttl,
+        };
+        Ok(self.api.post("/auth/tokens", &request).await?)
+    }
+
+    /// Revokes the token with the given identifier.
+    ///
+    /// * `id`: token identifier.
+    async fn revoke_token(&self, id: &str) -> anyhow::Result<()> {
+        Ok(self.api.delete_void(&format!("/auth/tokens/{id}")).await?)
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -64,11 +132,43 @@ pub enum AuthCommands {
     /// This command tries to get the password from the standard input. If it is not there, it asks
     /// the user interactively. Upon successful login, it stores the token in .agama/agama-jwt. The
     /// token will be automatically sent to authenticate the following requests.
-    Login,
+    ///
+    /// Alternatively, it can use an SSH key which is listed in the authorized keys of the server.
+    Login {
+        /// Authenticate using the given SSH private key instead of the root password.
+        #[arg(long, value_name = "PATH")]
+        ssh_key: Option<PathBuf>,
+    },
     /// Deauthenticate by removing the token.
     Logout,
     /// Print the used token to the standard output.
     Show,
+    /// Manage additional tokens (e.g., read-only tokens for monitoring).
+    #[command(subcommand)]
+    Token(TokenCommands),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TokenCommands {
+    /// Create a new token and print it to the standard output.
+    ///
+    /// The token cannot include scopes which are not part of the token in use. By default, it
+    /// gets the same scopes. Any scope implies read-only, so the token can always read the
+    /// installer status.
+    Create {
+        /// Scope of the new token (read-only, answer-questions, configure or install). It can be
+        /// repeated or given as a comma-separated list.
+        #[arg(long = "scope", value_delimiter = ',')]
+        scopes: Vec<TokenScope>,
+        /// Lifetime of the token in seconds (up to 30 days).
+        #[arg(long)]
+        ttl: Option<u64>,
+    },
+    /// Revoke a token.
+    Revoke {
+        /// Identifier of the token to revoke, as printed by "agama auth token create".
+        id: String,
+    },
 }
 
 /// Main entry point called from agama CLI main loop
@@ -76,9 +176,24 @@ pub async fn run(client: BaseHTTPClient, subcommand: AuthCommands) -> anyhow::Re
     let auth_client = AuthHTTPClient::load(client)?;
 
     match subcommand {
-        AuthCommands::Login => login(auth_client, read_password()?).await,
-        AuthCommands::Logout => logout(auth_client),
+        AuthCommands::Login { ssh_key: None } => {
+            let token = auth_client.authenticate(read_password()?).await?;
+            login(auth_client, token)
+        }
+        AuthCommands::Login { ssh_key: Some(key) } => {
+            let token = auth_client.authenticate_with_key(&key).await?;
+            login(auth_client, token)
+        }
+        AuthCommands::Logout => logout(auth_client).await,
         AuthCommands::Show => show(&auth_client.api.base_url),
+        AuthCommands::Token(TokenCommands::Create { scopes, ttl }) => {
+            create_token(auth_client, scopes, ttl).await
+        }
+        AuthCommands::Token(TokenCommands::Revoke { id }) => {
+            auth_client.revoke_token(&id).await?;
+            println!("Token {id} revoked");
+            Ok(())
+        }
     }
 }
 
@@ -109,33 +224,104 @@ fn ask_password() -> Result<String, CliError> {
         .map_err(CliError::InteractivePassword)
 }
 
-/// Logs into the installation web server and stores JWT for later use.
-async fn login(client: AuthHTTPClient, password: String) -> anyhow::Result<()> {
-    // 1) ask web server for JWT
-    let res = client.authenticate(password).await?;
-    let token = AuthToken::new(&res);
+/// Signs the challenge using ssh-keygen
+///
+/// It relies on ssh-keygen, so it works with any key type it supports (including the ones
+/// protected by a passphrase).
+fn sign_challenge(key: &Path, challenge: &str) -> anyhow::Result<String> {
+    let dir = tempfile::tempdir()?;
+    let message = dir.path().join("challenge");
+    std::fs::write(&message, challenge)?;
+
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", "agama-auth", "-f"])
+        .arg(key)
+        .arg(&message)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Could not sign the challenge: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut signature = message.into_os_string();
+    signature.push(".sig");
+    Ok(std::fs::read_to_string(signature)?)
+}
+
+/// Stores the JWT for later use.
+fn login(client: AuthHTTPClient, token: String) -> anyhow::Result<()> {
+    let token = AuthToken::new(&token);
     let mut hosts_config = AuthTokensFile::read().unwrap_or_default();
     let hostname = client.api.base_url.host_str().unwrap_or("localhost");
     hosts_config.update_token(hostname, &token);
     Ok(hosts_config.write()?)
 }
 
+/// Formats a duration as days, hours and minutes (e.g., "1d 2h 30m").
+fn format_duration(duration: std::time::Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h {minutes}m"),
+    }
+}
+
 /// Releases JWT
-fn logout(client: AuthHTTPClient) -> anyhow::Result<()> {
+///
+/// It asks the server to revoke the token too. If it is not possible (e.g., the server is not
+/// reachable or the token already expired), it just removes the token locally.
+async fn logout(client: AuthHTTPClient) -> anyhow::Result<()> {
     let hostname = client.api.base_url.host_str().unwrap_or("localhost");
     if let Ok(mut file) = AuthTokensFile::read() {
+        if let Some(token) = file.get_token(hostname) {
+            if let Ok(api) = client.api.clone().authenticated(&token) {
+                let authenticated = AuthHTTPClient::load(api)?;
+                if let Err(error) = authenticated.revoke_current().await {
+                    eprintln!("Could not revoke the token: {error}");
+                }
+            }
+        }
         file.remove_host(hostname);
         file.write()?;
     }
     Ok(())
 }
 
+/// Creates a new token and prints it.
+async fn create_token(
+    client: AuthHTTPClient,
+    scopes: Vec<TokenScope>,
+    ttl: Option<u64>,
+) -> anyhow::Result<()> {
+    let response = client.create_token(scopes, ttl).await?;
+    let scopes: Vec<String> = response.scopes.iter().map(ToString::to_string).collect();
+    let expires_at = chrono::DateTime::from_timestamp(response.expires_at, 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| response.expires_at.to_string());
+    eprintln!("Token ID: {}", response.id);
+    eprintln!("Scopes: {}", scopes.join(", "));
+    eprintln!("Expires at: {expires_at}");
+    println!("{}", response.token);
+    Ok(())
+}
+
 /// Shows stored JWT on stdout
+///
+/// The remaining validity is printed to stderr, so the output can still be used in scripts.
 fn show(url: &Url) -> anyhow::Result<()> {
     let hostname = url.host_str().unwrap_or("localhost");
     if let Ok(file) = AuthTokensFile::read() {
         if let Some(token) = file.get_token(hostname) {
             println!("{}", token.as_str());
+            match token.remaining_validity() {
+                Some(remaining) if remaining.is_zero() => eprintln!("The token has expired"),
+                Some(remaining) => eprintln!("Valid for {}", format_duration(remaining)),
+                None => eprintln!("Could not determine the token validity"),
+            }
             return Ok(());
         }
     }
@@ -18,7 +18,7 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use agama_lib::utils::Transfer;
+use agama_lib::utils::{Transfer, TransferOptions, Verification};
 use anyhow::Context;
 use std::{
     collections::HashMap,
@@ -104,11 +104,34 @@ impl CliInput {
         Ok(slurp)
     }
 
+    /// Read the specified input like [Self::read_to_string], failing if it does not pass
+    /// the integrity checks.
+    pub fn read_verified(
+        self,
+        options: &TransferOptions,
+        verification: &Verification,
+    ) -> anyhow::Result<String> {
+        if verification.is_empty() {
+            return self.read_to_string(options);
+        }
+
+        let name = match &self {
+            Self::Url(url) => url.clone(),
+            Self::Path(path) => path.display().to_string(),
+            Self::Stdin | Self::Full(_) => "-".to_string(),
+        };
+        let content = self.read_to_string(options)?;
+        verification
+            .verify(&name, content.as_bytes(), options)
+            .context(format!("Could not verify {}", name))?;
+        Ok(content)
+    }
+
     /// Read the specified input (stdin, path, or URL) and return a String
     // Does it belong here?
     // vs the downloading code in web ProfileQuery::retrieve_profile
     // put it in agama-lib?
-    pub fn read_to_string(self, insecure: bool) -> anyhow::Result<String> {
+    pub fn read_to_string(self, options: &TransferOptions) -> anyhow::Result<String> {
         match self {
             Self::Full(s) => Ok(s),
             Self::Stdin => Self::stdin_to_string().map_err(|e| e.into()),
@@ -119,7 +142,8 @@ impl CliInput {
             }
             Self::Url(url_string) => {
                 let mut bytebuf = Vec::new();
-                Transfer::get(&url_string, &mut bytebuf, insecure)
+                Transfer::new(options.clone())
+                    .fetch(&url_string, &mut bytebuf)
                     .context(format!("Retrieving data from URL {}", url_string))?;
                 let s = String::from_utf8(bytebuf)
                     .context(format!("Invalid UTF-8 data at URL {}", url_string))?;
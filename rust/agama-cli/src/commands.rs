@@ -20,11 +20,13 @@
 
 use std::path::PathBuf;
 
+use crate::api::ApiCommands;
 use crate::auth::AuthCommands;
 use crate::config::ConfigCommands;
 use crate::logs::LogsCommands;
 use crate::questions::QuestionsCommands;
 use crate::FinishMethod;
+use agama_lib::utils::Checksum;
 use clap::Subcommand;
 
 #[derive(Subcommand, Debug)]
@@ -104,6 +106,16 @@ pub enum Commands {
         url: String,
         /// File name
         destination: PathBuf,
+        /// Expected checksum of the file (e.g., "sha256:9f86d0...")
+        #[arg(long)]
+        checksum: Option<Checksum>,
+        /// URL of a detached GPG or minisign (.minisig) signature of the file. A relative
+        /// URL is resolved against the current working directory
+        #[arg(long)]
+        signature: Option<String>,
+        /// Download the file again instead of using the cached copy
+        #[arg(long, default_value = "false")]
+        no_cache: bool,
     },
     /// Finish the installation.
     Finish {
@@ -124,9 +136,36 @@ pub enum Commands {
     Monitor,
 
     /// Display Agama events.
+    ///
+    /// By default, it displays all the events. Use the --type and --service options to display
+    /// only the relevant ones.
     Events {
         /// Display the events in a more human-readable way.
         #[arg(short, long)]
         pretty: bool,
+        /// Display only the events of the given type (e.g., QuestionsChanged). It can be
+        /// repeated or given as a comma-separated list.
+        #[arg(long = "type", value_name = "TYPE", value_delimiter = ',')]
+        types: Vec<String>,
+        /// Display only the events of the given service (e.g., /org/opensuse/Agama/Manager1). It
+        /// can be repeated or given as a comma-separated list.
+        #[arg(long = "service", value_name = "PATH", value_delimiter = ',')]
+        services: Vec<String>,
+        /// Use the Server-Sent Events stream instead of a WebSocket. It is useful when the
+        /// connection goes through an HTTP proxy which does not support WebSockets.
+        #[arg(long)]
+        sse: bool,
     },
+
+    /// Send requests to the HTTP API.
+    ///
+    /// It allows using any endpoint of the HTTP API, even those which do not have a specific
+    /// command. The requests (path, method and body) are validated against the OpenAPI
+    /// specification published by the server before sending them, unless --force is given. The
+    /// JSON responses are pretty-printed.
+    ///
+    /// Use the "paths" subcommand to list the available paths. The shell completion offers the
+    /// paths known when the completion scripts were generated.
+    #[command(subcommand)]
+    Api(ApiCommands),
 }
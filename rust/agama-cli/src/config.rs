@@ -18,18 +18,29 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use std::{io::Write, path::PathBuf, process::Command};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use agama_lib::profile::ProfileHTTPClient;
 use agama_lib::{
-    context::InstallationContext, http::BaseHTTPClient, install_settings::InstallSettings,
-    profile::ProfileValidator, profile::ValidationOutcome, utils::FileFormat,
-    Store as SettingsStore,
+    context::InstallationContext,
+    http::BaseHTTPClient,
+    install_settings::{InstallSettings, SettingsMerger},
+    network::{
+        model::StateConfig, settings::NetworkConnection, Adapter, KeyfileAdapter, NetworkSettings,
+    },
+    profile::ValidationOutcome,
+    profile::{IncludeResolver, ProfileEvaluator, ProfileValidator, StaticHardwareProvider},
+    utils::{Checksum, FileFormat, TransferOptions, Verification},
+    Store as SettingsStore, StoreDiff, StoreReport,
 };
 use anyhow::{anyhow, Context};
-use clap::Subcommand;
+use clap::{Args, Subcommand};
 use console::style;
-use fluent_uri::Uri;
+use fluent_uri::{Uri, UriRef};
 use tempfile::Builder;
 
 use crate::{
@@ -56,6 +67,17 @@ pub enum ConfigCommands {
     Load {
         /// JSON file: URL or path or `-` for standard input
         url_or_path: Option<CliInput>,
+
+        /// Show the changes and side effects of loading the profile without applying them
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+
+        /// Download the files again instead of using the cached copies
+        #[arg(long, default_value = "false")]
+        no_cache: bool,
+
+        #[command(flatten)]
+        verification: VerificationArgs,
     },
 
     /// Validate a profile using JSON Schema
@@ -84,10 +106,51 @@ pub enum ConfigCommands {
     ///
     /// For an example of Jsonnet-based profile, see
     /// https://github.com/openSUSE/agama/blob/master/rust/agama-lib/share/examples/profile.jsonnet
+    ///
+    /// With --local, the configuration is generated without connecting to the Agama server,
+    /// using the hardware information of the local system. AutoYaST profiles are not supported
+    /// in that case.
     #[command(verbatim_doc_comment)]
     Generate {
         /// JSON file: URL or path or `-` for standard input
         url_or_path: Option<CliInput>,
+
+        /// Generate the configuration without connecting to the Agama server
+        #[arg(long, default_value = "false")]
+        local: bool,
+
+        /// Take the network connections from the NetworkManager keyfiles of the system mounted
+        /// at this directory (e.g., "/mnt"). The connections of the profile are merged on top of
+        /// them
+        #[arg(long, value_name = "DIR", requires = "local")]
+        root: Option<PathBuf>,
+
+        /// Download the files again instead of using the cached copies
+        #[arg(long, default_value = "false")]
+        no_cache: bool,
+
+        #[command(flatten)]
+        verification: VerificationArgs,
+    },
+
+    /// Merge several profiles and print the result.
+    ///
+    /// The profiles are merged in the given order:
+    /// - Objects are merged recursively and other values are replaced.
+    /// - Network connections, scripts, files, DASD devices and repositories
+    ///   are merged by their identifier (e.g., the "id" of a connection).
+    /// - Patterns, packages and answers are appended.
+    /// - A null value removes the setting and a list element containing
+    ///   "$delete": true removes the element with the same identifier.
+    #[command(verbatim_doc_comment)]
+    Merge {
+        /// JSON files: URLs or paths or `-` for standard input
+        #[arg(required = true, num_args = 2..)]
+        urls_or_paths: Vec<CliInput>,
+
+        /// Download the files again instead of using the cached copies
+        #[arg(long, default_value = "false")]
+        no_cache: bool,
     },
 
     /// Edit and update installation option using an external editor.
@@ -103,6 +166,44 @@ pub enum ConfigCommands {
     },
 }
 
+/// Integrity checks for the profile.
+#[derive(Args, Debug)]
+pub struct VerificationArgs {
+    /// Expected checksum of the profile (e.g., "sha256:9f86d0...")
+    #[arg(long)]
+    checksum: Option<Checksum>,
+
+    /// URL of a detached GPG or minisign (.minisig) signature of the profile. A relative
+    /// URL is resolved against the profile location
+    #[arg(long)]
+    signature: Option<String>,
+}
+
+impl VerificationArgs {
+    /// Builds the verification for the profile, resolving the signature URL.
+    ///
+    /// * `context`: context of the profile.
+    fn to_verification(&self, context: &InstallationContext) -> anyhow::Result<Verification> {
+        let signature = match &self.signature {
+            Some(signature) => {
+                let uri = UriRef::parse(signature.as_str()).context("Invalid signature URL")?;
+                let absolute = if uri.has_scheme() {
+                    uri.to_string()
+                } else {
+                    uri.resolve_against(&context.source)?.to_string()
+                };
+                Some(absolute)
+            }
+            None => None,
+        };
+
+        Ok(Verification {
+            checksum: self.checksum.clone(),
+            signature,
+        })
+    }
+}
+
 pub async fn run(subcommand: ConfigCommands, opts: GlobalOpts) -> anyhow::Result<()> {
     let api_url = api_url(opts.clone().host)?;
 
@@ -120,20 +221,34 @@ pub async fn run(subcommand: ConfigCommands, opts: GlobalOpts) -> anyhow::Result
             validate(&http_client, CliInput::Full(json.clone()), false).await?;
             Ok(())
         }
-        ConfigCommands::Load { url_or_path } => {
+        ConfigCommands::Load {
+            url_or_path,
+            dry_run,
+            no_cache,
+            verification,
+        } => {
             let (http_client, monitor) = build_clients(api_url, opts.insecure).await?;
             let store = SettingsStore::new(http_client.clone()).await?;
             let url_or_path = url_or_path.unwrap_or(CliInput::Stdin);
-            let contents = url_or_path.read_to_string(opts.insecure)?;
+            let context = context_for(&url_or_path)?;
+            let verification = verification.to_verification(&context)?;
+            let options = transfer_options(&opts, no_cache);
+            let contents = url_or_path.read_verified(&options, &verification)?;
+            let contents = resolve_includes(&http_client, contents, &context, &options).await?;
             let valid = validate(&http_client, CliInput::Full(contents.clone()), false).await?;
 
             if matches!(valid, ValidationOutcome::Valid) {
-                let result =
-                    InstallSettings::from_json(&contents, &InstallationContext::from_env()?)?;
+                let result = InstallSettings::from_json(&contents, &context)?;
+                if dry_run {
+                    let diff = store.diff(&result).await?;
+                    print_diff(&diff);
+                    return Ok(());
+                }
                 tokio::spawn(async move {
                     show_progress(monitor, true).await;
                 });
-                store.store(&result).await?;
+                let report = store.store(&result).await?;
+                print_store_report(&report);
             }
 
             Ok(())
@@ -143,17 +258,31 @@ pub async fn run(subcommand: ConfigCommands, opts: GlobalOpts) -> anyhow::Result
                 let (http_client, _monitor) = build_clients(api_url, opts.insecure).await?;
                 validate(&http_client, url_or_path, false).await
             } else {
-                validate_local(url_or_path, opts.insecure)
+                validate_local(url_or_path, &transfer_options(&opts, false))
             };
 
             Ok(())
         }
-        ConfigCommands::Generate { url_or_path } => {
-            let (http_client, _monitor) = build_clients(api_url, opts.insecure).await?;
+        ConfigCommands::Generate {
+            url_or_path,
+            local,
+            root,
+            no_cache,
+            verification,
+        } => {
             let url_or_path = url_or_path.unwrap_or(CliInput::Stdin);
+            let options = transfer_options(&opts, no_cache);
+            if local {
+                return generate_local(url_or_path, &verification, &options, root.as_deref()).await;
+            }
 
-            generate(&http_client, url_or_path, opts.insecure).await
+            let (http_client, _monitor) = build_clients(api_url, opts.insecure).await?;
+            generate(&http_client, url_or_path, &verification, &options).await
         }
+        ConfigCommands::Merge {
+            urls_or_paths,
+            no_cache,
+        } => merge(urls_or_paths, &transfer_options(&opts, no_cache)),
         ConfigCommands::Edit { editor } => {
             let (http_client, monitor) = build_clients(api_url, opts.insecure).await?;
             let store = SettingsStore::new(http_client.clone()).await?;
@@ -165,15 +294,31 @@ pub async fn run(subcommand: ConfigCommands, opts: GlobalOpts) -> anyhow::Result
             tokio::spawn(async move {
                 show_progress(monitor, true).await;
             });
-            store.store(&result).await?;
+            let report = store.store(&result).await?;
+            print_store_report(&report);
             Ok(())
         }
     }
 }
 
+/// Returns the options to download the profiles.
+///
+/// * `opts`: global options.
+/// * `no_cache`: whether to ignore the cached copies.
+fn transfer_options(opts: &GlobalOpts, no_cache: bool) -> TransferOptions {
+    TransferOptions {
+        insecure: opts.insecure,
+        bypass_cache: no_cache,
+        ..Default::default()
+    }
+}
+
 /// Validates a JSON profile with locally available tools only
-fn validate_local(url_or_path: CliInput, insecure: bool) -> anyhow::Result<ValidationOutcome> {
-    let profile_string = url_or_path.read_to_string(insecure)?;
+fn validate_local(
+    url_or_path: CliInput,
+    options: &TransferOptions,
+) -> anyhow::Result<ValidationOutcome> {
+    let profile_string = url_or_path.read_to_string(options)?;
     let validator = ProfileValidator::default_schema().context("Setting up profile validator")?;
     let result = validator.validate_str(&profile_string);
 
@@ -223,6 +368,51 @@ fn validation_msg(validity: &ValidationOutcome) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Prints the sections that were stored.
+fn print_store_report(report: &StoreReport) {
+    eprintln!(
+        "{} Stored settings (applied: {})",
+        style("\u{2713}").bold().green(),
+        report.applied_names()
+    );
+}
+
+/// Prints the changes that loading a profile would introduce.
+///
+/// * `diff`: changes and side effects computed by the store.
+fn print_diff(diff: &StoreDiff) {
+    if diff.is_empty() {
+        println!("No changes.");
+        return;
+    }
+
+    for section in &diff.sections {
+        println!("{}:", style(&section.name).bold());
+        for change in &section.changes {
+            let path = if change.path.is_empty() {
+                section.name.as_str()
+            } else {
+                change.path.as_str()
+            };
+            match (&change.old, &change.new) {
+                (Some(old), Some(new)) => {
+                    println!("  {} {}: {} -> {}", style("~").yellow(), path, old, new)
+                }
+                (None, Some(new)) => println!("  {} {}: {}", style("+").green(), path, new),
+                (Some(old), None) => println!("  {} {}: {}", style("-").red(), path, old),
+                (None, None) => {}
+            }
+        }
+    }
+
+    if !diff.side_effects.is_empty() {
+        println!("{}:", style("Side effects").bold());
+        for side_effect in &diff.side_effects {
+            println!("  * {}", side_effect);
+        }
+    }
+}
+
 fn is_autoyast(url_or_path: &CliInput) -> bool {
     let path = match url_or_path {
         CliInput::Path(pathbuf) => pathbuf.as_os_str().to_str().unwrap_or_default().to_string(),
@@ -242,18 +432,21 @@ fn is_autoyast(url_or_path: &CliInput) -> bool {
 async fn generate(
     client: &BaseHTTPClient,
     url_or_path: CliInput,
-    insecure: bool,
+    verification: &VerificationArgs,
+    options: &TransferOptions,
 ) -> anyhow::Result<()> {
-    let context = match &url_or_path {
-        CliInput::Stdin | CliInput::Full(_) => InstallationContext::from_env()?,
-        CliInput::Url(url_str) => InstallationContext::from_url_str(url_str)?,
-        CliInput::Path(pathbuf) => InstallationContext::from_file(pathbuf.as_path())?,
-    };
+    let context = context_for(&url_or_path)?;
+    let verification = verification.to_verification(&context)?;
 
     // the AutoYaST profile is always downloaded insecurely
     // (https://github.com/yast/yast-installation/blob/960c66658ab317007d2e241aab7b224657970bf9/src/lib/transfer/file_from_url.rb#L188)
     // we can ignore the insecure option value in that case
     let profile_json = if is_autoyast(&url_or_path) {
+        if !verification.is_empty() {
+            return Err(anyhow!(
+                "Checksums and signatures are not supported for AutoYaST profiles"
+            ));
+        }
         // AutoYaST specific download and convert to JSON
         let config_string = match url_or_path {
             CliInput::Url(url_string) => {
@@ -276,7 +469,9 @@ async fn generate(
         };
         config_string
     } else {
-        from_json_or_jsonnet(client, url_or_path, insecure).await?
+        let profile_json =
+            from_json_or_jsonnet(client, url_or_path, &verification, options).await?;
+        resolve_includes(client, profile_json, &context, options).await?
     };
 
     let validity = validate(client, CliInput::Full(profile_json.clone()), true).await?;
@@ -305,6 +500,128 @@ async fn generate(
     Ok(())
 }
 
+/// Generates the configuration without connecting to the Agama server.
+///
+/// The Jsonnet profiles and fragments are evaluated using the hardware information of the local
+/// system.
+///
+/// * `url_or_path`: profile to convert.
+/// * `verification`: integrity checks for the profile.
+/// * `options`: options to download the profile and the included fragments.
+/// * `root`: root directory of the system to take the network connections from, if any.
+async fn generate_local(
+    url_or_path: CliInput,
+    verification: &VerificationArgs,
+    options: &TransferOptions,
+    root: Option<&Path>,
+) -> anyhow::Result<()> {
+    if is_autoyast(&url_or_path) {
+        return Err(anyhow!(
+            "AutoYaST profiles cannot be converted without the Agama server"
+        ));
+    }
+
+    let context = context_for(&url_or_path)?;
+    let verification = verification.to_verification(&context)?;
+    // used to resolve the relative Jsonnet imports
+    let file_name = match &url_or_path {
+        CliInput::Path(path) => path.canonicalize()?.display().to_string(),
+        _ => "profile.jsonnet".to_string(),
+    };
+
+    let evaluator = ProfileEvaluator::default();
+    let profile = url_or_path.read_verified(options, &verification)?;
+    let profile = match FileFormat::from_string(&profile) {
+        FileFormat::Jsonnet => {
+            evaluator.evaluate_with_vars(&profile, &file_name, &serde_json::Map::new())?
+        }
+        FileFormat::Json => profile,
+        FileFormat::Unknown => {
+            return Err(anyhow!(
+                "Unsupported file format. Expected JSON, or Jsonnet"
+            ))
+        }
+    };
+
+    let profile: serde_json::Value = serde_json::from_str(&profile)?;
+    let mut profile =
+        IncludeResolver::new(options.clone(), evaluator).resolve(profile, &context)?;
+    if let Some(root) = root {
+        let network = serde_json::json!({ "network": network_from_root(root).await? });
+        profile = SettingsMerger::default().merge_all([network, profile]);
+    }
+    let profile_json = serde_json::to_string_pretty(&profile)?;
+
+    let validator = ProfileValidator::default_schema().context("Setting up profile validator")?;
+    let validity = validator.validate_str(&profile_json)?;
+    if matches!(validity, ValidationOutcome::NotValid(_)) {
+        println!("{}", &profile_json);
+        let _ = validation_msg(&validity);
+
+        return Ok(());
+    }
+
+    // resolves relative URL references
+    let model = InstallSettings::from_json(&profile_json, &context)?;
+    let config_json = serde_json::to_string_pretty(&model)?;
+
+    println!("{}", &config_json);
+    let validity = validator.validate_str(&config_json)?;
+    let _ = validation_msg(&validity);
+    Ok(())
+}
+
+/// Reads the network connections from the NetworkManager keyfiles of a system.
+///
+/// * `root`: root directory of the system (e.g., "/mnt").
+async fn network_from_root(root: &Path) -> anyhow::Result<NetworkSettings> {
+    let adapter = KeyfileAdapter::from_root(root);
+    let state = adapter.read(StateConfig::default()).await?;
+    let connections = state
+        .connections
+        .into_iter()
+        .map(NetworkConnection::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(NetworkSettings { connections })
+}
+
+/// Returns the context of the given profile, used to resolve relative URLs.
+fn context_for(url_or_path: &CliInput) -> anyhow::Result<InstallationContext> {
+    let context = match url_or_path {
+        CliInput::Stdin | CliInput::Full(_) => InstallationContext::from_env()?,
+        CliInput::Url(url_str) => InstallationContext::from_url_str(url_str)?,
+        CliInput::Path(pathbuf) => InstallationContext::from_file(pathbuf.as_path())?,
+    };
+    Ok(context)
+}
+
+/// Resolves the fragments included in the profile (see [IncludeResolver]).
+///
+/// The Jsonnet fragments are evaluated using the hardware information of the system to install,
+/// which is read through the API.
+///
+/// If the profile is not a valid JSON, it is returned as it is so the validation
+/// can report the problem.
+async fn resolve_includes(
+    client: &BaseHTTPClient,
+    profile: String,
+    context: &InstallationContext,
+    options: &TransferOptions,
+) -> anyhow::Result<String> {
+    let Ok(json) = serde_json::from_str(&profile) else {
+        return Ok(profile);
+    };
+    if !profile.contains("\"$include\"") && !profile.contains("\"$ref\"") {
+        return Ok(profile);
+    }
+
+    let hardware: serde_json::Value = client.get("/hardware").await?;
+    let lshw: serde_json::Value = client.get("/hardware/lshw").await?;
+    let evaluator = ProfileEvaluator::new(StaticHardwareProvider::new(hardware).with_lshw(lshw));
+    let resolved = IncludeResolver::new(options.clone(), evaluator).resolve(json, context)?;
+    Ok(serde_json::to_string_pretty(&resolved)?)
+}
+
 /// Retrieve and preprocess the profile.
 ///
 /// The profile can be a JSON or a Jsonnet file.
@@ -314,9 +631,10 @@ async fn generate(
 async fn from_json_or_jsonnet(
     client: &BaseHTTPClient,
     url_or_path: CliInput,
-    insecure: bool,
+    verification: &Verification,
+    options: &TransferOptions,
 ) -> anyhow::Result<String> {
-    let any_profile = url_or_path.read_to_string(insecure)?;
+    let any_profile = url_or_path.read_verified(options, verification)?;
 
     match FileFormat::from_string(&any_profile) {
         FileFormat::Jsonnet => {
@@ -335,6 +653,31 @@ async fn from_json_or_jsonnet(
     }
 }
 
+/// Merges the given profiles and prints the result.
+///
+/// * `urls_or_paths`: profiles to merge, in order.
+/// * `options`: options to download the profiles.
+fn merge(urls_or_paths: Vec<CliInput>, options: &TransferOptions) -> anyhow::Result<()> {
+    let mut profiles = vec![];
+    for url_or_path in urls_or_paths {
+        let name = match &url_or_path {
+            CliInput::Url(url) => url.clone(),
+            CliInput::Path(path) => path.display().to_string(),
+            CliInput::Stdin | CliInput::Full(_) => "the standard input".to_string(),
+        };
+        let content = url_or_path.read_to_string(options)?;
+        let profile: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse the profile from {name}"))?;
+        profiles.push(profile);
+    }
+
+    let profile = SettingsMerger::default().merge_all(profiles);
+    let json = serde_json::to_string_pretty(&profile)?;
+    println!("{}", &json);
+    validate_local(CliInput::Full(json), options)?;
+    Ok(())
+}
+
 /// Edit the installation settings using an external editor.
 ///
 /// If the editor does not return a successful error code, it returns an error.
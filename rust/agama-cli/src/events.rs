@@ -18,12 +18,42 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use agama_lib::http::WebSocketClient;
+use agama_lib::http::{Event, EventFilter, EventsStreamClient, WebSocketClient};
+
+/// Connection used to receive the events.
+pub enum EventsSource {
+    WebSocket(Box<WebSocketClient>),
+    Sse(Box<EventsStreamClient>),
+}
+
+impl EventsSource {
+    async fn receive(&mut self) -> anyhow::Result<Event> {
+        let event = match self {
+            Self::WebSocket(client) => client.receive().await?,
+            Self::Sse(client) => client.receive().await?,
+        };
+        Ok(event)
+    }
+}
 
 /// Main entry point called from Agama CLI main loop
-pub async fn run(mut ws_client: WebSocketClient, pretty: bool) -> anyhow::Result<()> {
+///
+/// * `source`: WebSocket or Server-Sent Events client.
+/// * `filter`: events to display. The Server-Sent Events client is already filtered.
+/// * `pretty`: whether to display the events in a human-readable way.
+pub async fn run(
+    mut source: EventsSource,
+    filter: EventFilter,
+    pretty: bool,
+) -> anyhow::Result<()> {
+    if let EventsSource::WebSocket(ws_client) = &mut source {
+        if !filter.is_empty() {
+            ws_client.subscribe(filter).await?;
+        }
+    }
+
     loop {
-        let event = ws_client.receive().await?;
+        let event = source.receive().await?;
         let conversion = if pretty {
             serde_json::to_string_pretty(&event)
         } else {
@@ -27,6 +27,7 @@ use auth_tokens_file::AuthTokensFile;
 use clap::{Args, Parser};
 use fluent_uri::UriRef;
 
+mod api;
 mod auth;
 mod auth_tokens_file;
 mod cli_input;
@@ -40,18 +41,25 @@ mod progress;
 mod questions;
 
 use crate::error::CliError;
-use agama_lib::http::{BaseHTTPClient, WebSocketClient};
-use agama_lib::{error::ServiceError, utils::Transfer};
+use agama_lib::http::{BaseHTTPClient, EventFilter, EventsStreamClient, WebSocketClient};
+use agama_lib::{
+    error::ServiceError,
+    utils::{Checksum, Transfer, TransferOptions, Verification},
+};
+pub use api::complete_api_paths;
+use api::run as run_api_cmd;
 use auth::run as run_auth_cmd;
 use commands::Commands;
 use config::run as run_config_cmd;
-use events::run as run_events_cmd;
+use events::{run as run_events_cmd, EventsSource};
 use logs::run as run_logs_cmd;
 use progress::ProgressMonitor;
 use questions::run as run_questions_cmd;
 use std::fs;
+use std::io::Write;
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::{
     process::{ExitCode, Termination},
     thread::sleep,
@@ -65,7 +73,7 @@ pub struct GlobalOpts {
     #[clap(long, default_value = "http://localhost")]
     /// URI pointing to Agama's remote host.
     ///
-    /// Examples: https://my-server.lan my-server.local localhost:10443
+    /// Examples: https://my-server.lan my-server.local localhost:10443 unix:///run/agama/api.sock
     pub host: String,
 
     #[clap(long, default_value = "false")]
@@ -174,7 +182,26 @@ async fn wait_until_idle(monitor: MonitorClient) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn download_file(url: &str, path: &PathBuf, insecure: bool) -> anyhow::Result<()> {
+pub fn download_file(
+    url: &str,
+    path: &PathBuf,
+    options: TransferOptions,
+    checksum: Option<Checksum>,
+    signature: Option<String>,
+) -> anyhow::Result<()> {
+    let context = InstallationContext::from_env().unwrap();
+    let absolute_url = resolve_against(url, &context)?;
+    let verification = Verification {
+        checksum,
+        signature: signature
+            .map(|s| resolve_against(&s, &context))
+            .transpose()?,
+    };
+
+    // download first, so the file is not written if the verification fails
+    let mut data = Vec::new();
+    Transfer::new(options).fetch_verified(&absolute_url, &mut data, &verification)?;
+
     let mut file = fs::OpenOptions::new()
         .create(true)
         .write(true)
@@ -182,18 +209,20 @@ pub fn download_file(url: &str, path: &PathBuf, insecure: bool) -> anyhow::Resul
         .mode(0o600)
         .open(path)
         .context(format!("Cannot write the file '{}'", path.display()))?;
+    file.write_all(&data)?;
+    println!("File saved to {}", path.display());
+    Ok(())
+}
 
-    let context = InstallationContext::from_env().unwrap();
+/// Returns the absolute URL, resolving relative ones against the context.
+fn resolve_against(url: &str, context: &InstallationContext) -> anyhow::Result<String> {
     let uri = UriRef::parse(url).context("Invalid URL")?;
     let absolute_url = if uri.has_scheme() {
         uri.to_string()
     } else {
         uri.resolve_against(&context.source)?.to_string()
     };
-
-    Transfer::get(&absolute_url, &mut file, insecure)?;
-    println!("File saved to {}", path.display());
-    Ok(())
+    Ok(absolute_url)
 }
 
 /// * `api_url`: API URL.
@@ -216,7 +245,19 @@ async fn build_http_client(
     if authenticated {
         // this deals with authentication need inside
         if let Some(token) = find_client_token(&client.base_url) {
-            return Ok(client.authenticated(&token)?);
+            let hostname = client
+                .base_url
+                .host_str()
+                .unwrap_or("localhost")
+                .to_string();
+            let client = client
+                .authenticated(&token)?
+                .on_token_refresh(Arc::new(move |token| store_token(&hostname, token)));
+            return Ok(client);
+        }
+        // the server authenticates the root user through the Unix socket
+        if client.unix_socket().is_some() {
+            return Ok(client.unauthenticated()?);
         }
         return Err(ServiceError::NotAuthenticated.into());
     } else {
@@ -224,32 +265,64 @@ async fn build_http_client(
     }
 }
 
+/// Stores a refreshed token, so it is used by the following commands.
+///
+/// * `hostname`: host the token belongs to.
+/// * `token`: new token.
+fn store_token(hostname: &str, token: &AuthToken) {
+    let mut file = AuthTokensFile::read().unwrap_or_default();
+    file.update_token(hostname, token);
+    if let Err(error) = file.write() {
+        eprintln!("Could not store the refreshed token: {error}");
+    }
+}
+
 /// Build a WebSocket client.
 ///
-/// * `api_url`: API URL.
+/// It uses the token of the given HTTP client and keeps it fresh while listening.
+///
+/// * `client`: authenticated HTTP client.
 /// * `insecure`: whether an insecure connnection (e.g., using a self-signed certificate)
 ///   is allowed.
-async fn build_ws_client(api_url: Url, insecure: bool) -> anyhow::Result<WebSocketClient> {
+async fn build_ws_client(
+    client: &BaseHTTPClient,
+    insecure: bool,
+) -> anyhow::Result<WebSocketClient> {
+    let api_url = &client.base_url;
     let mut url = api_url.join("ws")?;
-    let scheme = if api_url.scheme() == "http" {
-        "ws"
+    client.refresh_token_if_needed().await;
+    let token = client.token().await;
+
+    let ws_client = if let Some(socket) = client.unix_socket() {
+        // Setting the scheme to a known value ("ws" should not fail).
+        url.set_scheme("ws").unwrap();
+        WebSocketClient::connect_unix(socket, &url, token.as_ref()).await?
     } else {
-        "wss"
+        let scheme = if api_url.scheme() == "http" {
+            "ws"
+        } else {
+            "wss"
+        };
+        let token = token.ok_or(ServiceError::NotAuthenticated)?;
+        // Setting the scheme to a known value ("ws" or "wss" should not fail).
+        url.set_scheme(scheme).unwrap();
+        WebSocketClient::connect(&url, &token, insecure).await?
     };
-
-    let token = find_client_token(&api_url).ok_or(ServiceError::NotAuthenticated)?;
-    // Setting the scheme to a known value ("ws" or "wss" should not fail).
-    url.set_scheme(scheme).unwrap();
-    Ok(WebSocketClient::connect(&url, &token, insecure).await?)
+    Ok(ws_client.with_token_refresh(client.clone()))
 }
 
 /// Build the API url from the host.
 ///
 /// * `host`: ip or host name. The protocol is optional, using https if omitted (e.g, "myserver",
-/// "http://myserver", "192.168.100.101").
+/// "http://myserver", "192.168.100.101"). A Unix socket can be used too (e.g.,
+/// "unix:///run/agama/api.sock").
 pub fn api_url(host: String) -> anyhow::Result<Url> {
     let sanitized_host = host.trim_end_matches('/').to_string();
 
+    if sanitized_host.starts_with("unix://") {
+        return Url::parse(&sanitized_host).context("The given URL is not valid.");
+    }
+
     let url_str = if sanitized_host.starts_with("http://") || sanitized_host.starts_with("https://")
     {
         format!("{}/api/", sanitized_host)
@@ -275,8 +348,8 @@ async fn build_clients(
     api_url: Url,
     insecure: bool,
 ) -> anyhow::Result<(BaseHTTPClient, MonitorClient)> {
-    let client = build_http_client(api_url.clone(), insecure, true).await?;
-    let ws_client = build_ws_client(api_url, insecure).await?;
+    let client = build_http_client(api_url, insecure, true).await?;
+    let ws_client = build_ws_client(&client, insecure).await?;
     let monitor = Monitor::connect(client.clone(), ws_client).await?;
     Ok((client, monitor))
 }
@@ -324,20 +397,56 @@ pub async fn run_command(cli: Cli) -> anyhow::Result<()> {
             let client = build_http_client(api_url, cli.opts.insecure, true).await?;
             run_logs_cmd(client, subcommand).await?
         }
-        Commands::Download { url, destination } => {
-            download_file(&url, &destination, cli.opts.insecure)?
+        Commands::Download {
+            url,
+            destination,
+            checksum,
+            signature,
+            no_cache,
+        } => {
+            let options = TransferOptions {
+                insecure: cli.opts.insecure,
+                bypass_cache: no_cache,
+                ..Default::default()
+            };
+            download_file(&url, &destination, options, checksum, signature)?
         }
         Commands::Auth(subcommand) => {
-            let client = build_http_client(api_url, cli.opts.insecure, false).await?;
+            // managing tokens requires to be authenticated
+            let authenticated = matches!(subcommand, auth::AuthCommands::Token(_));
+            let client = build_http_client(api_url, cli.opts.insecure, authenticated).await?;
             run_auth_cmd(client, subcommand).await?;
         }
         Commands::Monitor => {
             let (_client, monitor) = build_clients(api_url, cli.opts.insecure).await?;
             show_progress(monitor, false).await;
         }
-        Commands::Events { pretty } => {
-            let ws_client = build_ws_client(api_url, cli.opts.insecure).await?;
-            run_events_cmd(ws_client, pretty).await?;
+        Commands::Events {
+            pretty,
+            types,
+            services,
+            sse,
+        } => {
+            let client = build_http_client(api_url, cli.opts.insecure, true).await?;
+            let filter = EventFilter {
+                types,
+                services,
+                ..Default::default()
+            };
+            let source = if sse {
+                EventsSource::Sse(Box::new(
+                    EventsStreamClient::connect(client, &filter).await?,
+                ))
+            } else {
+                EventsSource::WebSocket(Box::new(
+                    build_ws_client(&client, cli.opts.insecure).await?,
+                ))
+            };
+            run_events_cmd(source, filter, pretty).await?;
+        }
+        Commands::Api(subcommand) => {
+            let client = build_http_client(api_url, cli.opts.insecure, true).await?;
+            run_api_cmd(client, subcommand).await?;
         }
     };
 
@@ -33,6 +33,10 @@ pub enum LogsCommands {
         /// Path to destination directory and, optionally, the archive file name. The extension will
         /// be added automatically.
         destination: Option<PathBuf>,
+        /// Include the content of the downloaded files (e.g., the profiles). Beware that they
+        /// might contain secrets, like passwords
+        #[clap(long, default_value = "false")]
+        cached_files: bool,
     },
     /// List the logs to collect
     List,
@@ -43,11 +47,14 @@ pub async fn run(client: BaseHTTPClient, subcommand: LogsCommands) -> anyhow::Re
     let client = ManagerHTTPClient::new(client);
 
     match subcommand {
-        LogsCommands::Store { destination } => {
+        LogsCommands::Store {
+            destination,
+            cached_files,
+        } => {
             // feed internal options structure by what was received from user
             // for now we always use / add defaults if any
             let dst_file = parse_destination(destination)?;
-            let result = client.store(dst_file.as_path()).await?;
+            let result = client.store(dst_file.as_path(), cached_files).await?;
 
             println!("{}", result.clone().display());
 
@@ -0,0 +1,349 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Helpers to work with the OpenAPI specification of the HTTP API.
+//!
+//! The server publishes the specification at `/api/openapi.json`. [ApiSpec] uses it to list the
+//! available paths and to validate the requests before sending them.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+/// Prefix of the HTTP API paths in the specification.
+const API_PREFIX: &str = "/api";
+
+/// Maximum number of suggestions when a path is not found.
+const MAX_SUGGESTIONS: usize = 10;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ApiSpecError {
+    #[error("Unknown path '{0}'{suggestions}", suggestions = suggestions_text(.1))]
+    UnknownPath(String, Vec<String>),
+    #[error("Method {0} is not supported by '{1}'. Supported methods: {methods}", methods = .2.join(", "))]
+    UnsupportedMethod(String, String, Vec<String>),
+    #[error("The request body does not match the schema:\n{}", .0.join("\n"))]
+    InvalidBody(Vec<String>),
+    #[error("Invalid schema: {0}")]
+    InvalidSchema(String),
+}
+
+fn suggestions_text(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(
+            ". Did you mean any of these?\n  {}",
+            suggestions.join("\n  ")
+        )
+    }
+}
+
+/// Path of the HTTP API and its supported methods.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiPath {
+    /// Path relative to the API (e.g., `/software/licenses/{id}`).
+    pub path: String,
+    /// Supported methods in upper case (e.g., `GET`).
+    pub methods: Vec<String>,
+}
+
+impl ApiPath {
+    /// Whether the given path, which might include actual values for the parameters, matches
+    /// this one.
+    ///
+    /// * `path`: path relative to the API (e.g., `/software/resolvables/agama`).
+    fn matches(&self, path: &str) -> bool {
+        let expected: Vec<_> = self.path.split('/').collect();
+        let actual: Vec<_> = path.split('/').collect();
+        expected.len() == actual.len()
+            && expected.iter().zip(actual).all(|(expected, actual)| {
+                (is_parameter(expected) && !actual.is_empty()) || *expected == actual
+            })
+    }
+}
+
+/// Whether a path segment is a parameter. Both the OpenAPI (`{id}`) and the axum (`:id`) styles
+/// are used in the specification.
+fn is_parameter(segment: &str) -> bool {
+    (segment.starts_with('{') && segment.ends_with('}')) || segment.starts_with(':')
+}
+
+/// OpenAPI specification of the HTTP API.
+pub struct ApiSpec {
+    spec: Value,
+}
+
+impl ApiSpec {
+    /// Builds the specification from its JSON representation.
+    ///
+    /// * `spec`: OpenAPI document.
+    pub fn new(spec: Value) -> Self {
+        Self { spec }
+    }
+
+    /// Returns the paths of the API, sorted alphabetically.
+    pub fn paths(&self) -> Vec<ApiPath> {
+        let Some(paths) = self.spec.get("paths").and_then(Value::as_object) else {
+            return vec![];
+        };
+
+        let mut result: Vec<_> = paths
+            .iter()
+            .map(|(path, operations)| {
+                let methods = operations
+                    .as_object()
+                    .map(|o| o.keys().map(|m| m.to_uppercase()).collect())
+                    .unwrap_or_default();
+                ApiPath {
+                    path: relative_path(path).to_string(),
+                    methods,
+                }
+            })
+            .collect();
+        result.sort_by(|a, b| a.path.cmp(&b.path));
+        result
+    }
+
+    /// Returns the paths starting with the given prefix, to be used for completion.
+    ///
+    /// * `prefix`: beginning of the path.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let prefix = relative_path(prefix);
+        self.paths()
+            .into_iter()
+            .map(|p| p.path)
+            .filter(|p| p.starts_with(prefix))
+            .collect()
+    }
+
+    /// Finds the path of the specification which matches the given one and supports the method.
+    ///
+    /// When several paths match, the ones with fewer parameters are preferred (e.g.,
+    /// "/storage/devices/system" over "/storage/devices/{id}").
+    ///
+    /// * `method`: HTTP method (e.g., `PUT`).
+    /// * `path`: path relative to the API. It might include a query string.
+    pub fn find_path(&self, method: &str, path: &str) -> Result<ApiPath, ApiSpecError> {
+        let path = relative_path(path.split('?').next().unwrap_or_default());
+        let method = method.to_uppercase();
+        let paths = self.paths();
+        let mut candidates: Vec<_> = paths.iter().filter(|p| p.matches(path)).collect();
+        candidates.sort_by_key(|p| p.path.split('/').filter(|s| is_parameter(s)).count());
+
+        if let Some(found) = candidates.iter().find(|p| p.methods.contains(&method)) {
+            return Ok((*found).clone());
+        }
+        if let Some(best) = candidates.first() {
+            let methods: BTreeSet<_> = candidates
+                .iter()
+                .flat_map(|p| p.methods.iter().cloned())
+                .collect();
+            return Err(ApiSpecError::UnsupportedMethod(
+                method,
+                best.path.clone(),
+                methods.into_iter().collect(),
+            ));
+        }
+
+        let section = path.split('/').nth(1).unwrap_or_default();
+        let suggestions = paths
+            .into_iter()
+            .map(|p| p.path)
+            .filter(|p| p.split('/').nth(1) == Some(section))
+            .take(MAX_SUGGESTIONS)
+            .collect();
+        Err(ApiSpecError::UnknownPath(path.to_string(), suggestions))
+    }
+
+    /// Validates a request against the specification.
+    ///
+    /// It checks whether the path and the method are known and, if the operation defines a JSON
+    /// request body, whether the given one matches its schema.
+    ///
+    /// * `method`: HTTP method (e.g., `PUT`).
+    /// * `path`: path relative to the API.
+    /// * `body`: request body.
+    pub fn validate_request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<(), ApiSpecError> {
+        let api_path = self.find_path(method, path)?;
+        let method = method.to_uppercase();
+
+        let Some(body) = body else {
+            return Ok(());
+        };
+        let Some(schema) = self.body_schema(&method, &api_path.path) else {
+            return Ok(());
+        };
+
+        let validator = jsonschema::validator_for(&schema)
+            .map_err(|e| ApiSpecError::InvalidSchema(e.to_string()))?;
+        let messages: BTreeSet<String> = validator
+            .iter_errors(body)
+            .map(|e| format!("{}. {}", e, e.instance_path))
+            .collect();
+        if messages.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiSpecError::InvalidBody(messages.into_iter().collect()))
+        }
+    }
+
+    /// Returns the JSON schema of the request body, if any.
+    ///
+    /// The components of the specification are included, so the references (e.g.,
+    /// `#/components/schemas/Config`) can be resolved.
+    fn body_schema(&self, method: &str, path: &str) -> Option<Value> {
+        let paths = self.spec.get("paths")?.as_object()?;
+        let operations = paths
+            .iter()
+            .find(|(p, _)| relative_path(p) == path)
+            .map(|(_, o)| o)?;
+        let mut schema = operations
+            .get(method.to_lowercase())?
+            .pointer("/requestBody/content/application~1json/schema")?
+            .clone();
+        if let (Some(object), Some(components)) =
+            (schema.as_object_mut(), self.spec.get("components"))
+        {
+            object.insert("components".to_string(), components.clone());
+        }
+        Some(schema)
+    }
+}
+
+/// Removes the API prefix from a path.
+fn relative_path(path: &str) -> &str {
+    match path.strip_prefix(API_PREFIX) {
+        Some(relative) if relative.is_empty() || relative.starts_with('/') => relative,
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ApiSpec, ApiSpecError};
+
+    fn spec() -> ApiSpec {
+        ApiSpec::new(json!({
+            "openapi": "3.1.0",
+            "paths": {
+                "/api/software/resolvables/:id": {
+                    "put": {
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/ResolvableParams" }
+                                }
+                            }
+                        }
+                    }
+                },
+                "/api/software/config": { "get": {}, "put": {} },
+                "/api/storage/devices/system": { "get": {} },
+                "/api/storage/devices/{id}": { "get": {}, "delete": {} }
+            },
+            "components": {
+                "schemas": {
+                    "ResolvableParams": {
+                        "type": "object",
+                        "required": ["names", "optional"],
+                        "properties": {
+                            "names": { "type": "array", "items": { "type": "string" } },
+                            "optional": { "type": "boolean" }
+                        }
+                    }
+                }
+            }
+        }))
+    }
+
+    #[test]
+    fn test_complete() {
+        let spec = spec();
+        assert_eq!(
+            spec.complete("/software"),
+            vec!["/software/config", "/software/resolvables/:id"]
+        );
+        assert_eq!(
+            spec.complete("/api/storage"),
+            vec!["/storage/devices/system", "/storage/devices/{id}"]
+        );
+    }
+
+    #[test]
+    fn test_find_path() {
+        let spec = spec();
+        let found = spec
+            .find_path("PUT", "/software/resolvables/agama?type=package")
+            .unwrap();
+        assert_eq!(found.path, "/software/resolvables/:id");
+        assert_eq!(found.methods, vec!["PUT"]);
+
+        // prefer the paths without parameters, as long as they support the method
+        let found = spec.find_path("get", "/storage/devices/system").unwrap();
+        assert_eq!(found.path, "/storage/devices/system");
+        let found = spec.find_path("DELETE", "/storage/devices/system").unwrap();
+        assert_eq!(found.path, "/storage/devices/{id}");
+
+        let Err(ApiSpecError::UnsupportedMethod(_, _, methods)) =
+            spec.find_path("PUT", "/storage/devices/system")
+        else {
+            panic!("The method should not be supported");
+        };
+        assert_eq!(methods, vec!["DELETE", "GET"]);
+
+        let Err(ApiSpecError::UnknownPath(_, suggestions)) =
+            spec.find_path("GET", "/software/unknown")
+        else {
+            panic!("The path should not be found");
+        };
+        assert_eq!(
+            suggestions,
+            vec!["/software/config", "/software/resolvables/:id"]
+        );
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let spec = spec();
+        let valid = json!({ "names": ["vim"], "optional": false });
+        assert!(spec
+            .validate_request("put", "/software/resolvables/agama", Some(&valid))
+            .is_ok());
+
+        let invalid = json!({ "names": "vim" });
+        assert!(matches!(
+            spec.validate_request("PUT", "/software/resolvables/agama", Some(&invalid)),
+            Err(ApiSpecError::InvalidBody(_))
+        ));
+
+        assert!(matches!(
+            spec.validate_request("DELETE", "/software/config", None),
+            Err(ApiSpecError::UnsupportedMethod(_, _, _))
+        ));
+    }
+}
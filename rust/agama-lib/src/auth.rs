@@ -35,6 +35,14 @@
 //! with the server. In that process, it obtains a new token that should be stored
 //! in user's home directory (`$HOME/.local/agama/token`).
 //!
+//! ## Scopes and revocation
+//!
+//! Each token has an identifier (`jti`) and a set of [scopes](TokenScope) which limit what it can
+//! be used for. The tokens obtained through the login process (and the master token) include all
+//! the scopes. Additional tokens with fewer scopes (e.g., read-only tokens for monitoring
+//! dashboards) can be created and revoked through the API. Any scope implies the read-only one,
+//! so a token to answer the questions can read them too.
+//!
 //! ## A simplistic API
 //!
 //! The current API is rather limited and it does not support, for instance,
@@ -45,6 +53,7 @@ const USER_TOKEN_PATH: &str = ".local/agama/token";
 const AGAMA_TOKEN_FILE: &str = "/run/agama/token";
 
 use std::{
+    collections::BTreeSet,
     fmt,
     fs::{self, File},
     io::{self, BufRead, BufReader, Write},
@@ -55,9 +64,17 @@ use std::{
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
 use thiserror::Error;
 use uuid::Uuid;
 
+/// Default lifetime of the tokens.
+pub const DEFAULT_TOKEN_LIFETIME: std::time::Duration = std::time::Duration::from_secs(24 * 3600);
+/// Maximum lifetime of the tokens created through the API.
+pub const MAX_TOKEN_LIFETIME: std::time::Duration = std::time::Duration::from_secs(30 * 24 * 3600);
+/// Remaining validity below which a token without a known lifetime should be refreshed.
+const REFRESH_THRESHOLD: std::time::Duration = std::time::Duration::from_secs(3600);
+
 #[derive(Error, Debug)]
 #[error("Invalid authentication token: {0}")]
 pub struct AuthTokenError(#[from] jsonwebtoken::errors::Error);
@@ -78,10 +95,17 @@ impl AuthToken {
     ///
     /// * `secret`: secret to encode the token.
     pub fn generate(secret: &str) -> Result<Self, AuthTokenError> {
-        let claims = TokenClaims::default();
+        Self::from_claims(&TokenClaims::default(), secret)
+    }
+
+    /// Generates a new token with the given claims.
+    ///
+    /// * `claims`: claims to include in the token.
+    /// * `secret`: secret to encode the token.
+    pub fn from_claims(claims: &TokenClaims, secret: &str) -> Result<Self, AuthTokenError> {
         let token = jsonwebtoken::encode(
             &Header::default(),
-            &claims,
+            claims,
             &EncodingKey::from_secret(secret.as_ref()),
         )?;
         Ok(AuthToken(token))
@@ -153,6 +177,35 @@ impl AuthToken {
         Ok(token_data.claims)
     }
 
+    /// Returns the claims from the token without checking its signature or expiration.
+    ///
+    /// It is meant to be used by the clients, which do not know the secret, to find out things
+    /// like the expiration time. Do not use it to authorize any request.
+    pub fn unverified_claims(&self) -> Result<TokenClaims, AuthTokenError> {
+        let mut validation = Validation::default();
+        validation.insecure_disable_signature_validation();
+        validation.validate_exp = false;
+        let decoding = DecodingKey::from_secret(&[]);
+        let token_data = jsonwebtoken::decode(self.0.trim(), &decoding, &validation)?;
+        Ok(token_data.claims)
+    }
+
+    /// Returns for how long the token is still valid.
+    ///
+    /// It returns `None` if the token cannot be decoded and a zero duration if it already expired.
+    pub fn remaining_validity(&self) -> Option<std::time::Duration> {
+        let claims = self.unverified_claims().ok()?;
+        Some(claims.remaining_validity())
+    }
+
+    /// Whether the token is close to expiring and should be refreshed.
+    ///
+    /// It returns `false` if the token cannot be decoded.
+    pub fn needs_refresh(&self) -> bool {
+        self.unverified_claims()
+            .is_ok_and(|claims| claims.needs_refresh())
+    }
+
     /// Returns a reference to the token's content.
     pub fn as_str(&self) -> &str {
         self.0.as_str()
@@ -193,26 +246,209 @@ impl fmt::Display for AuthToken {
 /// Claims that are included in the token.
 ///
 /// See https://datatracker.ietf.org/doc/html/rfc7519 for reference.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TokenClaims {
     pub exp: i64,
     pub client_id: ClientId,
+    /// Issue time (seconds since the epoch). It is 0 for tokens created by older versions.
+    #[serde(default)]
+    pub iat: i64,
+    /// Token identifier, used to revoke the token.
+    #[serde(default)]
+    pub jti: String,
+    /// Identifier of the token this one was refreshed from (the first one of the chain). It is
+    /// empty if the token was not refreshed. See [TokenClaims::lineage].
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub origin: String,
+    /// What the token can be used for. If the claim is missing (tokens created by older
+    /// versions), the token has full access. An empty set grants no access at all.
+    #[serde(default = "TokenScope::all")]
+    pub scopes: BTreeSet<TokenScope>,
 }
 
 // FIXME: replace with TokenClaims::new, as it does not exist a "default" token.
 impl Default for TokenClaims {
     fn default() -> Self {
-        let mut exp = Utc::now();
+        Self::new(TokenScope::all(), DEFAULT_TOKEN_LIFETIME)
+    }
+}
 
-        if let Some(days) = Duration::try_days(1) {
-            exp += days;
-        }
+impl TokenClaims {
+    /// Builds the claims for a new token.
+    ///
+    /// * `scopes`: what the token can be used for.
+    /// * `lifetime`: how long the token is valid.
+    pub fn new(scopes: BTreeSet<TokenScope>, lifetime: std::time::Duration) -> Self {
+        let lifetime = Duration::from_std(lifetime).unwrap_or(Duration::MAX);
+        let now = Utc::now();
+        let exp = now
+            .checked_add_signed(lifetime)
+            .unwrap_or(chrono::DateTime::<Utc>::MAX_UTC);
 
         Self {
             exp: exp.timestamp(),
             client_id: ClientId::new(),
+            iat: now.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            origin: String::new(),
+            scopes,
+        }
+    }
+
+    /// Builds the claims for a token refreshed from this one.
+    ///
+    /// The new token keeps the client ID, the scopes and the lineage of the current one.
+    ///
+    /// * `lifetime`: how long the new token is valid.
+    pub fn refreshed(&self, lifetime: std::time::Duration) -> Self {
+        let mut claims = Self::new(self.scopes.clone(), lifetime);
+        claims.client_id = self.client_id.clone();
+        claims.origin = self.lineage().to_string();
+        claims
+    }
+
+    /// Identifier shared by the token and the ones refreshed from it.
+    ///
+    /// It is the identifier of the first token of the chain, so revoking it revokes all of them.
+    pub fn lineage(&self) -> &str {
+        if self.origin.is_empty() {
+            &self.jti
+        } else {
+            &self.origin
         }
     }
+
+    /// Whether the token includes the given scope, directly or implied by another one.
+    ///
+    /// * `scope`: scope to check.
+    pub fn allows(&self, scope: TokenScope) -> bool {
+        self.scopes.iter().any(|s| s.implies(scope))
+    }
+
+    /// Lifetime of the token, if the issue time is known.
+    pub fn lifetime(&self) -> Option<std::time::Duration> {
+        if self.iat <= 0 || self.exp <= self.iat {
+            return None;
+        }
+        Some(std::time::Duration::from_secs((self.exp - self.iat) as u64))
+    }
+
+    /// For how long the token is still valid (zero if it already expired).
+    pub fn remaining_validity(&self) -> std::time::Duration {
+        let remaining = self.exp - Utc::now().timestamp();
+        std::time::Duration::from_secs(remaining.max(0) as u64)
+    }
+
+    /// Whether the token should be refreshed.
+    ///
+    /// It is the case when less than a quarter of its lifetime is left. If the lifetime is
+    /// unknown, it uses a fixed threshold of one hour.
+    pub fn needs_refresh(&self) -> bool {
+        let threshold = self.lifetime().map(|l| l / 4).unwrap_or(REFRESH_THRESHOLD);
+        self.remaining_validity() < threshold
+    }
+
+    /// Whether the token includes all the scopes.
+    pub fn has_full_access(&self) -> bool {
+        TokenScope::iter().all(|s| self.allows(s))
+    }
+}
+
+/// What a token can be used for.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Display,
+    EnumIter,
+    EnumString,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum TokenScope {
+    /// Read the configuration, the progress, the events, etc.
+    ReadOnly,
+    /// Answer the questions.
+    AnswerQuestions,
+    /// Change the configuration.
+    Configure,
+    /// Start and finish the installation.
+    Install,
+}
+
+impl TokenScope {
+    /// Returns all the scopes.
+    pub fn all() -> BTreeSet<TokenScope> {
+        Self::iter().collect()
+    }
+
+    /// Whether this scope includes the given one.
+    ///
+    /// Scopes are not hierarchical, except for the read-only one: it is implied by any other
+    /// scope, as changing something usually requires reading it first.
+    ///
+    /// * `scope`: scope to check.
+    pub fn implies(self, scope: TokenScope) -> bool {
+        self == scope || scope == Self::ReadOnly
+    }
+
+    /// Returns the scope required to perform an API request.
+    ///
+    /// * `method`: HTTP method (e.g., "GET").
+    /// * `path`: request path, with or without the `/api` prefix.
+    pub fn required_for(method: &str, path: &str) -> Self {
+        if matches!(method, "GET" | "HEAD" | "OPTIONS") {
+            return Self::ReadOnly;
+        }
+
+        let path = path.strip_prefix("/api").unwrap_or(path);
+        let is_under = |prefix: &str| {
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+
+        if is_under("/questions") {
+            Self::AnswerQuestions
+        } else if is_under("/manager/install") || is_under("/manager/finish") {
+            Self::Install
+        } else {
+            Self::Configure
+        }
+    }
+}
+
+/// Request to create a new token.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateTokenRequest {
+    /// Scopes of the new token. By default, the same scopes of the current token. It cannot be
+    /// empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<BTreeSet<TokenScope>>,
+    /// Lifetime of the token in seconds. It cannot exceed 30 days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+}
+
+/// A newly created token.
+#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTokenResponse {
+    /// Token identifier, used to revoke it.
+    pub id: String,
+    /// Bearer token to use on subsequent calls.
+    pub token: String,
+    /// Scopes of the token.
+    pub scopes: BTreeSet<TokenScope>,
+    /// Expiration time (seconds since the epoch).
+    pub expires_at: i64,
 }
 
 /// Identifies a client.
@@ -239,7 +475,10 @@ impl fmt::Display for ClientId {
 mod tests {
     use tempfile::tempdir;
 
-    use super::AuthToken;
+    use std::collections::BTreeSet;
+    use strum::IntoEnumIterator;
+
+    use super::{AuthToken, TokenClaims, TokenScope, DEFAULT_TOKEN_LIFETIME};
 
     #[test]
     fn test_generate_token() {
@@ -265,4 +504,99 @@ mod tests {
         let decoded = read_token.claims("nots3cr3t");
         assert!(decoded.is_ok());
     }
+
+    #[test]
+    fn test_token_scopes() {
+        let scopes = BTreeSet::from([TokenScope::ReadOnly]);
+        let claims = TokenClaims::new(scopes, DEFAULT_TOKEN_LIFETIME);
+        let token = AuthToken::from_claims(&claims, "nots3cr3t").unwrap();
+
+        let decoded = token.claims("nots3cr3t").unwrap();
+        assert_eq!(decoded.jti, claims.jti);
+        assert!(decoded.allows(TokenScope::ReadOnly));
+        assert!(!decoded.allows(TokenScope::Install));
+
+        let claims = TokenClaims::new(
+            BTreeSet::from([TokenScope::Install]),
+            DEFAULT_TOKEN_LIFETIME,
+        );
+        assert!(claims.allows(TokenScope::Install));
+        assert!(claims.allows(TokenScope::ReadOnly));
+        assert!(!claims.allows(TokenScope::Configure));
+
+        let claims = TokenClaims::new(BTreeSet::new(), DEFAULT_TOKEN_LIFETIME);
+        assert!(TokenScope::iter().all(|s| !claims.allows(s)));
+        assert!(!decoded.has_full_access());
+        assert!(TokenClaims::default().has_full_access());
+    }
+
+    #[test]
+    fn test_scopes_default_to_full_access() {
+        let claims: TokenClaims = serde_json::from_str(
+            r#"{ "exp": 0, "client_id": "3d7ac3e1-8a8a-4a3c-9f4e-2ab2f5f7b8c1" }"#,
+        )
+        .unwrap();
+        assert!(claims.has_full_access());
+        assert_eq!(
+            "answer-questions".parse::<TokenScope>().unwrap(),
+            TokenScope::AnswerQuestions
+        );
+    }
+
+    #[test]
+    fn test_token_refresh() {
+        let claims = TokenClaims::new(TokenScope::all(), DEFAULT_TOKEN_LIFETIME);
+        assert_eq!(claims.lifetime(), Some(DEFAULT_TOKEN_LIFETIME));
+        assert!(!claims.needs_refresh());
+
+        let token = AuthToken::from_claims(&claims, "nots3cr3t").unwrap();
+        assert_eq!(token.unverified_claims().unwrap().jti, claims.jti);
+        assert!(token.remaining_validity().unwrap() > DEFAULT_TOKEN_LIFETIME / 2);
+        assert!(!token.needs_refresh());
+
+        let short = TokenClaims::new(TokenScope::all(), std::time::Duration::from_secs(60));
+        assert!(!short.needs_refresh());
+        let expired = TokenClaims {
+            exp: short.iat - 10,
+            iat: short.iat - 70,
+            ..short
+        };
+        assert!(expired.needs_refresh());
+        assert_eq!(expired.remaining_validity(), std::time::Duration::ZERO);
+
+        let refreshed = claims.refreshed(DEFAULT_TOKEN_LIFETIME);
+        assert_ne!(refreshed.jti, claims.jti);
+        assert_eq!(refreshed.client_id, claims.client_id);
+        assert_eq!(claims.lineage(), claims.jti);
+        assert_eq!(refreshed.lineage(), claims.jti);
+        assert_eq!(
+            refreshed.refreshed(DEFAULT_TOKEN_LIFETIME).lineage(),
+            claims.jti
+        );
+
+        let legacy = TokenClaims { iat: 0, ..claims };
+        assert_eq!(legacy.lifetime(), None);
+        assert!(!legacy.needs_refresh());
+        assert!(AuthToken::new("not-a-token").remaining_validity().is_none());
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(
+            TokenScope::required_for("GET", "/api/manager/installer"),
+            TokenScope::ReadOnly
+        );
+        assert_eq!(
+            TokenScope::required_for("PATCH", "/api/questions/1/answer"),
+            TokenScope::AnswerQuestions
+        );
+        assert_eq!(
+            TokenScope::required_for("POST", "/manager/install"),
+            TokenScope::Install
+        );
+        assert_eq!(
+            TokenScope::required_for("PUT", "/api/v2/config"),
+            TokenScope::Configure
+        );
+    }
 }
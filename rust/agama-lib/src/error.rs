@@ -23,7 +23,10 @@ use std::io;
 use thiserror::Error;
 use zbus::{self, zvariant};
 
-use crate::utils::TransferError;
+use crate::{
+    hardware::HardwareError,
+    utils::{jsonnet::JsonnetError, TransferError},
+};
 
 #[derive(Error, Debug)]
 pub enum ServiceError {
@@ -72,11 +75,21 @@ pub enum ProfileError {
     #[error("Could not read the profile")]
     Unreachable(#[from] TransferError),
     #[error("Jsonnet evaluation failed:\n{0}")]
-    EvaluationError(String),
+    EvaluationError(#[from] JsonnetError),
+    #[error("Could not read the hardware information: {0}")]
+    HardwareError(#[from] HardwareError),
     #[error("I/O error")]
     InputOutputError(#[from] io::Error),
     #[error("The profile is not a well-formed JSON file")]
     FormatError(#[from] serde_json::Error),
+    #[error("Could not resolve the included URL {0}: {1}")]
+    InvalidIncludeUrl(String, String),
+    #[error("Could not read the included fragment {0}")]
+    IncludeUnreachable(String, #[source] TransferError),
+    #[error("Include cycle detected: {0}")]
+    IncludeCycle(String),
+    #[error("Invalid include: {0}")]
+    InvalidInclude(String),
     // `#` is std::fmt "Alternate form", anyhow::Error interprets as "include causes"
     #[error("Error: {0:#}")]
     Anyhow(#[from] anyhow::Error),
@@ -18,10 +18,16 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use crate::utils::{Transfer, TransferError};
+use crate::utils::{Checksum, Transfer, TransferError, Verification};
 use fluent_uri::{error::ResolveError, Uri, UriRef};
 use serde::{Deserialize, Serialize};
-use std::{fs::OpenOptions, io::Write, os::unix::fs::OpenOptionsExt, path::Path};
+use std::{
+    fs::{self, Permissions},
+    io::Write,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+};
+use tempfile::NamedTempFile;
 
 #[derive(Debug, thiserror::Error)]
 pub enum FileSourceError {
@@ -43,6 +49,14 @@ pub enum FileSource {
     Remote {
         #[schema(value_type = String, examples("http://example.com/script.sh", "/file.txt"))]
         url: UriRef<String>,
+        /// Expected checksum of the file (e.g., "sha256:9f86d0...").
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schema(value_type = Option<String>)]
+        checksum: Option<Checksum>,
+        /// URI or relative reference to get the detached signature of the file from.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schema(value_type = Option<String>, examples("http://example.com/script.sh.asc"))]
+        signature: Option<UriRef<String>>,
     },
 }
 
@@ -57,38 +71,68 @@ impl FileSource {
             Self::Text { content } => Self::Text {
                 content: content.clone(),
             },
-            Self::Remote { url } => {
-                let resolved_url = if url.has_scheme() {
-                    url.clone()
-                } else {
-                    let resolved = url
-                        .resolve_against(base)
-                        .map_err(|e| FileSourceError::ResolveUrlError(url.to_string(), e))?;
-                    UriRef::from(resolved)
-                };
-                Self::Remote { url: resolved_url }
-            }
+            Self::Remote {
+                url,
+                checksum,
+                signature,
+            } => Self::Remote {
+                url: Self::resolve_reference(url, base)?,
+                checksum: checksum.clone(),
+                signature: signature
+                    .as_ref()
+                    .map(|s| Self::resolve_reference(s, base))
+                    .transpose()?,
+            },
         };
         Ok(resolved)
     }
 
-    /// Writes the file to the given writer.
+    fn resolve_reference(
+        url: &UriRef<String>,
+        base: &Uri<String>,
+    ) -> Result<UriRef<String>, FileSourceError> {
+        if url.has_scheme() {
+            return Ok(url.clone());
+        }
+
+        let resolved = url
+            .resolve_against(base)
+            .map_err(|e| FileSourceError::ResolveUrlError(url.to_string(), e))?;
+        Ok(UriRef::from(resolved))
+    }
+
+    /// Writes the file to the given path.
     ///
-    /// * `file`: where to write the data.
+    /// The content is written to a temporary file which replaces the target only when the
+    /// download and the integrity checks succeed, so a failure does not leave a partial or an
+    /// unverified file behind.
+    ///
+    /// * `path`: where to write the data.
+    /// * `mode`: permissions of the file.
     pub fn write<P: AsRef<Path>>(&self, path: P, mode: u32) -> Result<(), FileSourceError> {
-        let mut file = OpenOptions::new()
-            .mode(mode)
-            .write(true)
-            .create(true)
-            .open(path)?;
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut file = NamedTempFile::new_in(dir)?;
 
         match &self {
             FileSource::Text { content } => file.write_all(content.as_bytes())?,
             // Transfer::get will fail if the URL is relative.
-            FileSource::Remote { url } => Transfer::get(&url.to_string(), &mut file, false)?,
+            FileSource::Remote {
+                url,
+                checksum,
+                signature,
+            } => {
+                let verification = Verification {
+                    checksum: checksum.clone(),
+                    signature: signature.as_ref().map(|s| s.to_string()),
+                };
+                Transfer::get_verified(url.as_ref(), &mut file, false, &verification)?
+            }
         }
 
         file.flush()?;
+        fs::set_permissions(file.path(), Permissions::from_mode(mode))?;
+        file.persist(path).map_err(|e| e.error)?;
         Ok(())
     }
 }
@@ -113,11 +157,12 @@ pub trait WithFileSource: Clone {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Write};
+    use std::{fs::File, io::Write, os::unix::fs::PermissionsExt};
 
     use fluent_uri::{Uri, UriRef};
 
-    use super::FileSource;
+    use super::{FileSource, FileSourceError};
+    use crate::utils::{Checksum, ChecksumAlgorithm, TransferError};
 
     #[test]
     fn test_write_content() {
@@ -131,6 +176,8 @@ mod tests {
 
         let written = std::fs::read_to_string(&target).unwrap();
         assert_eq!(written.as_str(), "foobar");
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o400);
     }
 
     #[test]
@@ -143,6 +190,8 @@ mod tests {
         let url = format!("file://{}", source.display());
         let file = FileSource::Remote {
             url: UriRef::parse(url).unwrap(),
+            checksum: None,
+            signature: None,
         };
         let target = tmpdir.path().join("foobar.txt");
         file.write(&target, 0o400).unwrap();
@@ -151,19 +200,56 @@ mod tests {
         assert_eq!(written.as_str(), "foobar");
     }
 
+    #[test]
+    fn test_write_with_checksum() {
+        let tmpdir = tempfile::TempDir::with_prefix("agama-tests-").unwrap();
+        let source = tmpdir.path().join("source.txt");
+        std::fs::write(&source, "foobar").unwrap();
+        let url = UriRef::parse(format!("file://{}", source.display())).unwrap();
+
+        let checksum = Checksum::compute(ChecksumAlgorithm::Sha256, b"foobar");
+        let file = FileSource::Remote {
+            url: url.clone(),
+            checksum: Some(checksum),
+            signature: None,
+        };
+        let target = tmpdir.path().join("foobar.txt");
+        file.write(&target, 0o400).unwrap();
+
+        let checksum = Checksum::compute(ChecksumAlgorithm::Sha256, b"tampered");
+        let file = FileSource::Remote {
+            url,
+            checksum: Some(checksum),
+            signature: None,
+        };
+        let target = tmpdir.path().join("tampered.txt");
+        let error = file.write(&target, 0o400).unwrap_err();
+        assert!(matches!(
+            error,
+            FileSourceError::TransferFailed(TransferError::ChecksumMismatch { .. })
+        ));
+        assert!(!target.exists());
+        // only the source and the verified file are left
+        assert_eq!(std::fs::read_dir(tmpdir.path()).unwrap().count(), 2);
+    }
+
     #[test]
     fn test_resolve_url_relative() {
         let file = FileSource::Remote {
             url: UriRef::parse("file.txt").unwrap().to_owned(),
+            checksum: None,
+            signature: Some(UriRef::parse("file.txt.asc").unwrap().to_owned()),
         };
 
         let base_url = Uri::parse("http://example.lan/sles").unwrap().to_owned();
         let resolved = file.resolve_url(&base_url).unwrap();
         let expected_url = "http://example.lan/file.txt";
+        let expected_signature = "http://example.lan/file.txt.asc";
 
         assert!(matches!(
             resolved,
-            FileSource::Remote { url } if url.as_str() == expected_url
+            FileSource::Remote { url, signature: Some(signature), .. }
+                if url.as_str() == expected_url && signature.as_str() == expected_signature
         ));
     }
 
@@ -173,6 +259,8 @@ mod tests {
             url: UriRef::parse("http://example.lan/agama/file.txt")
                 .unwrap()
                 .to_owned(),
+            checksum: None,
+            signature: None,
         };
 
         let base_url = Uri::parse("http://example.lan/sles").unwrap().to_owned();
@@ -181,7 +269,7 @@ mod tests {
 
         assert!(matches!(
             resolved,
-            FileSource::Remote { url } if url.as_str() == expected_url
+            FileSource::Remote { url, .. } if url.as_str() == expected_url
         ));
     }
 }
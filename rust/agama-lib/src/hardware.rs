@@ -0,0 +1,45 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements support for reading the hardware information.
+//!
+//! The information (CPU, memory, disks, network interfaces, firmware, virtualization and DMI
+//! data) is read from the `/proc` and `/sys` file systems and from `lsblk`. The
+//! [HardwareReader] can read it from a different root directory, so it is possible to use a
+//! recorded copy of those files (e.g., in tests).
+
+pub mod model;
+mod reader;
+
+pub use model::HardwareInfo;
+pub use reader::HardwareReader;
+
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum HardwareError {
+    #[error("Could not read {0}: {1}")]
+    IO(PathBuf, #[source] io::Error),
+    #[error("Could not run lshw: {0}")]
+    Lshw(#[source] io::Error),
+    #[error("Unexpected lshw output: {0}")]
+    LshwOutput(#[from] serde_json::Error),
+}
@@ -0,0 +1,119 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements a data model for the hardware information.
+
+use serde::{Deserialize, Serialize};
+
+/// Hardware information of the system.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareInfo {
+    pub cpu: CpuInfo,
+    pub memory: MemoryInfo,
+    pub disks: Vec<DiskInfo>,
+    pub nics: Vec<NicInfo>,
+    pub firmware: FirmwareType,
+    /// Virtualization technology (e.g., "kvm", "vmware" or "xen"). It is `None` when running on
+    /// bare metal.
+    pub virtualization: Option<String>,
+    pub dmi: DmiInfo,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuInfo {
+    pub architecture: String,
+    pub vendor: Option<String>,
+    pub model: Option<String>,
+    /// Number of logical processors.
+    pub cores: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryInfo {
+    /// Total memory in bytes.
+    pub size: u64,
+}
+
+/// Disk (physical block device).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskInfo {
+    /// Kernel name (e.g., "sda").
+    pub name: String,
+    /// Device path (e.g., "/dev/sda").
+    pub device: String,
+    /// Size in bytes.
+    pub size: u64,
+    pub vendor: Option<String>,
+    pub model: Option<String>,
+    /// Transport (e.g., "usb", "nvme" or "sata").
+    pub transport: Option<String>,
+    pub removable: bool,
+    pub rotational: bool,
+    /// File systems contained in the disk.
+    pub file_systems: Vec<DiskFileSystem>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskFileSystem {
+    /// Device path (e.g., "/dev/sda1").
+    pub device: String,
+    #[serde(rename = "type")]
+    pub fstype: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Network interface.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NicInfo {
+    pub name: String,
+    pub mac: Option<String>,
+    /// Kernel driver (e.g., "e1000e").
+    pub driver: Option<String>,
+    /// Operational state (e.g., "up" or "down").
+    pub state: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum FirmwareType {
+    #[default]
+    Bios,
+    Uefi,
+}
+
+/// Information from the DMI (SMBIOS) tables.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DmiInfo {
+    pub sys_vendor: Option<String>,
+    pub product_name: Option<String>,
+    pub product_version: Option<String>,
+    pub board_vendor: Option<String>,
+    pub board_name: Option<String>,
+    pub bios_vendor: Option<String>,
+    pub bios_version: Option<String>,
+    pub chassis_type: Option<String>,
+}
@@ -0,0 +1,443 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the logic to read the hardware information.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use super::{
+    model::{
+        CpuInfo, DiskFileSystem, DiskInfo, DmiInfo, FirmwareType, HardwareInfo, MemoryInfo, NicInfo,
+    },
+    HardwareError,
+};
+use crate::utils::FileSystemsList;
+
+/// Name of the file containing the lsblk output in a recorded directory.
+const LSBLK_FILE: &str = "lsblk.txt";
+/// Name of the file containing the `lshw -json` output in a recorded directory.
+const LSHW_FILE: &str = "lshw.json";
+
+/// Reads the hardware information.
+///
+/// By default, it reads the information from the underlying system. However, it can read the
+/// information from a directory containing a copy of the relevant files (`proc/meminfo`,
+/// `proc/cpuinfo`, `sys/block`, `sys/class/net`, `sys/class/dmi/id`, `run/udev/data`, etc.) and
+/// the output of `lsblk --output KNAME,FSTYPE,MOUNTPOINTS,TRAN,LABEL --pairs --path` in a
+/// `lsblk.txt` file.
+/// The output of `lshw -json`, if needed, is read from a `lshw.json` file.
+///
+/// ```no_run
+/// use agama_lib::hardware::HardwareReader;
+///
+/// let hardware = HardwareReader::from_system().read().unwrap();
+/// println!("Memory: {} bytes", hardware.memory.size);
+/// ```
+pub struct HardwareReader {
+    root: PathBuf,
+    // lsblk output (`None` means running lsblk).
+    lsblk: Option<String>,
+    // lshw output (`None` means running lshw).
+    lshw: Option<String>,
+}
+
+impl HardwareReader {
+    /// Builds a reader for the underlying system.
+    pub fn from_system() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            lsblk: None,
+            lshw: None,
+        }
+    }
+
+    /// Builds a reader for a directory containing a copy of the system files.
+    ///
+    /// * `dir`: directory containing the files.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Self {
+        let root = dir.as_ref().to_path_buf();
+        let lsblk = fs::read_to_string(root.join(LSBLK_FILE)).unwrap_or_default();
+        let lshw = fs::read_to_string(root.join(LSHW_FILE)).unwrap_or("{}".to_string());
+        Self {
+            root,
+            lsblk: Some(lsblk),
+            lshw: Some(lshw),
+        }
+    }
+
+    /// Reads the hardware information.
+    pub fn read(&self) -> Result<HardwareInfo, HardwareError> {
+        let cpuinfo = self.read_file("proc/cpuinfo").unwrap_or_default();
+        let dmi = self.dmi();
+        Ok(HardwareInfo {
+            cpu: self.cpu(&cpuinfo),
+            memory: self.memory()?,
+            disks: self.disks()?,
+            nics: self.nics()?,
+            firmware: self.firmware(),
+            virtualization: self.virtualization(&cpuinfo, &dmi),
+            dmi,
+        })
+    }
+
+    /// Returns the `lshw -json` output.
+    ///
+    /// It is not part of [HardwareInfo], but some profiles rely on it (see
+    /// [crate::profile::ProfileEvaluator]).
+    pub fn lshw(&self) -> Result<serde_json::Value, HardwareError> {
+        let output = match &self.lshw {
+            Some(output) => output.clone().into_bytes(),
+            None => {
+                Command::new("/usr/sbin/lshw")
+                    .arg("-json")
+                    .output()
+                    .map_err(HardwareError::Lshw)?
+                    .stdout
+            }
+        };
+        Ok(serde_json::from_slice(&output)?)
+    }
+
+    fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.root.join(path)
+    }
+
+    fn read_file(&self, path: impl AsRef<Path>) -> Option<String> {
+        fs::read_to_string(self.path(path))
+            .ok()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+    }
+
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, HardwareError> {
+        let path = self.path(path);
+        let entries = fs::read_dir(&path).map_err(|e| HardwareError::IO(path, e))?;
+        let mut names: Vec<String> = entries
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn cpu(&self, cpuinfo: &str) -> CpuInfo {
+        let value = |name: &str| {
+            cpuinfo
+                .lines()
+                .filter_map(|l| l.split_once(':'))
+                .find(|(key, _)| key.trim() == name)
+                .map(|(_, value)| value.trim().to_string())
+        };
+        let cores = cpuinfo
+            .lines()
+            .filter(|l| l.starts_with("processor"))
+            .count();
+        CpuInfo {
+            architecture: std::env::consts::ARCH.to_string(),
+            vendor: value("vendor_id"),
+            model: value("model name"),
+            cores,
+        }
+    }
+
+    fn memory(&self) -> Result<MemoryInfo, HardwareError> {
+        let path = self.path("proc/meminfo");
+        let meminfo = fs::read_to_string(&path).map_err(|e| HardwareError::IO(path, e))?;
+        let size = meminfo
+            .lines()
+            .find_map(|l| l.strip_prefix("MemTotal:"))
+            .and_then(|v| v.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+            .unwrap_or_default();
+        Ok(MemoryInfo { size })
+    }
+
+    fn disks(&self) -> Result<Vec<DiskInfo>, HardwareError> {
+        let file_systems = match &self.lsblk {
+            Some(output) => FileSystemsList::from_lsblk(output),
+            None => FileSystemsList::from_system(),
+        }
+        .to_vec();
+
+        let mut disks = vec![];
+        for name in self.list_dir("sys/block")? {
+            let block = Path::new("sys/block").join(&name);
+            // virtual devices (loop, zram, device mapper...) are not backed by a device
+            if !self.path(&block).join("device").exists() {
+                continue;
+            }
+
+            let sectors: u64 = self
+                .read_file(block.join("size"))
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default();
+            let disk_file_systems: Vec<_> = file_systems
+                .iter()
+                .filter(|fs| {
+                    fs.block_device == name || self.path(&block).join(&fs.block_device).exists()
+                })
+                .collect();
+            disks.push(DiskInfo {
+                device: format!("/dev/{name}"),
+                size: sectors * 512,
+                vendor: self.read_file(block.join("device/vendor")),
+                model: self.read_file(block.join("device/model")),
+                transport: disk_file_systems
+                    .iter()
+                    .find_map(|fs| fs.transport.clone())
+                    .or_else(|| self.disk_transport(&name, &block)),
+                removable: self.read_file(block.join("removable")).as_deref() == Some("1"),
+                rotational: self.read_file(block.join("queue/rotational")).as_deref() == Some("1"),
+                file_systems: disk_file_systems
+                    .iter()
+                    .map(|fs| DiskFileSystem {
+                        device: fs.device(),
+                        fstype: fs.fstype.clone(),
+                        label: fs.label.clone(),
+                    })
+                    .collect(),
+                name,
+            });
+        }
+        Ok(disks)
+    }
+
+    // Reads the transport of a disk from the udev database or, if it is not there, infers it from
+    // the sysfs path of the device. It works for blank disks, which are not listed by lsblk as
+    // file systems.
+    fn disk_transport(&self, name: &str, block: &Path) -> Option<String> {
+        let udev_data = self
+            .read_file(block.join("dev"))
+            .and_then(|dev| self.read_file(Path::new("run/udev/data").join(format!("b{dev}"))));
+        let bus = udev_data
+            .as_deref()
+            .and_then(|data| data.lines().find_map(|l| l.strip_prefix("E:ID_BUS=")));
+        if let Some(bus) = bus {
+            return Some(bus.to_string());
+        }
+
+        if name.starts_with("nvme") {
+            return Some("nvme".to_string());
+        }
+        let device = fs::canonicalize(self.path(block)).ok()?;
+        let device = device.to_string_lossy();
+        ["usb", "virtio", "ata", "mmc"]
+            .into_iter()
+            .find(|t| device.contains(&format!("/{t}")))
+            .map(str::to_string)
+    }
+
+    fn nics(&self) -> Result<Vec<NicInfo>, HardwareError> {
+        let mut nics = vec![];
+        for name in self.list_dir("sys/class/net")? {
+            let path = Path::new("sys/class/net").join(&name);
+            // skip virtual interfaces (loopback, bridges, etc.)
+            if !self.path(&path).join("device").exists() {
+                continue;
+            }
+            let driver = self
+                .read_file(path.join("device/uevent"))
+                .and_then(|uevent| {
+                    uevent
+                        .lines()
+                        .find_map(|l| l.strip_prefix("DRIVER=").map(str::to_string))
+                });
+            nics.push(NicInfo {
+                mac: self.read_file(path.join("address")),
+                driver,
+                state: self.read_file(path.join("operstate")),
+                name,
+            });
+        }
+        Ok(nics)
+    }
+
+    fn firmware(&self) -> FirmwareType {
+        if self.path("sys/firmware/efi").exists() {
+            FirmwareType::Uefi
+        } else {
+            FirmwareType::Bios
+        }
+    }
+
+    fn dmi(&self) -> DmiInfo {
+        let value = |name: &str| self.read_file(Path::new("sys/class/dmi/id").join(name));
+        DmiInfo {
+            sys_vendor: value("sys_vendor"),
+            product_name: value("product_name"),
+            product_version: value("product_version"),
+            board_vendor: value("board_vendor"),
+            board_name: value("board_name"),
+            bios_vendor: value("bios_vendor"),
+            bios_version: value("bios_version"),
+            chassis_type: value("chassis_type"),
+        }
+    }
+
+    // Detects the virtualization technology using the same hints as systemd-detect-virt.
+    fn virtualization(&self, cpuinfo: &str, dmi: &DmiInfo) -> Option<String> {
+        if let Some(hypervisor) = self.read_file("sys/hypervisor/type") {
+            return Some(hypervisor);
+        }
+
+        const DMI_VENDORS: [(&str, &str); 8] = [
+            ("KVM", "kvm"),
+            ("QEMU", "qemu"),
+            ("VMware", "vmware"),
+            ("VMW", "vmware"),
+            ("innotek GmbH", "oracle"),
+            ("VirtualBox", "oracle"),
+            ("Xen", "xen"),
+            ("Microsoft Corporation", "microsoft"),
+        ];
+        let candidates = [&dmi.product_name, &dmi.sys_vendor, &dmi.board_vendor];
+        for candidate in candidates.into_iter().flatten() {
+            if let Some((_, name)) = DMI_VENDORS
+                .iter()
+                .find(|(vendor, _)| candidate.starts_with(vendor))
+            {
+                return Some(name.to_string());
+            }
+        }
+
+        let has_hypervisor_flag = cpuinfo
+            .lines()
+            .filter(|l| l.starts_with("flags"))
+            .any(|l| l.split_whitespace().any(|f| f == "hypervisor"));
+        has_hypervisor_flag.then(|| "other".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use tempfile::TempDir;
+
+    use super::HardwareReader;
+    use crate::hardware::model::FirmwareType;
+
+    fn fixture(name: &str) -> HardwareReader {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/hardware")
+            .join(name);
+        HardwareReader::from_dir(path)
+    }
+
+    #[test]
+    fn test_read_qemu_uefi() {
+        let hardware = fixture("qemu-uefi").read().unwrap();
+
+        assert_eq!(hardware.cpu.cores, 2);
+        assert_eq!(hardware.cpu.vendor.as_deref(), Some("GenuineIntel"));
+        assert_eq!(
+            hardware.cpu.model.as_deref(),
+            Some("Intel Core Processor (Skylake, IBRS)")
+        );
+        assert_eq!(hardware.memory.size, 4005012 * 1024);
+        assert_eq!(hardware.firmware, FirmwareType::Uefi);
+        assert_eq!(hardware.virtualization.as_deref(), Some("qemu"));
+        assert_eq!(hardware.dmi.sys_vendor.as_deref(), Some("QEMU"));
+
+        assert_eq!(hardware.disks.len(), 2);
+        let sda = &hardware.disks[0];
+        assert_eq!(sda.device, "/dev/sda");
+        assert_eq!(sda.size, 1048576 * 512);
+        assert_eq!(sda.model.as_deref(), Some("QEMU HARDDISK"));
+        assert_eq!(sda.transport.as_deref(), Some("usb"));
+        assert!(sda.removable);
+        assert_eq!(sda.file_systems.len(), 1);
+        assert_eq!(sda.file_systems[0].device, "/dev/sda1");
+        assert_eq!(sda.file_systems[0].label.as_deref(), Some("OEMDRV"));
+
+        let vda = &hardware.disks[1];
+        assert_eq!(vda.device, "/dev/vda");
+        assert_eq!(vda.size, 41943040 * 512);
+        assert!(vda.rotational);
+        assert_eq!(vda.file_systems.len(), 2);
+
+        assert_eq!(hardware.nics.len(), 1);
+        let nic = &hardware.nics[0];
+        assert_eq!(nic.name, "enp1s0");
+        assert_eq!(nic.mac.as_deref(), Some("52:54:00:8f:3e:1a"));
+        assert_eq!(nic.driver.as_deref(), Some("virtio_net"));
+        assert_eq!(nic.state.as_deref(), Some("up"));
+    }
+
+    #[test]
+    fn test_read_bare_metal_bios() {
+        let tmp_dir = TempDir::with_prefix("hardware").unwrap();
+        let root = tmp_dir.path();
+        fs::create_dir_all(root.join("proc")).unwrap();
+        fs::create_dir_all(root.join("sys/block")).unwrap();
+        fs::create_dir_all(root.join("sys/class/net")).unwrap();
+        fs::write(root.join("proc/meminfo"), "MemTotal: 1024 kB\n").unwrap();
+        fs::write(
+            root.join("proc/cpuinfo"),
+            "processor\t: 0\nflags\t\t: fpu vme\n",
+        )
+        .unwrap();
+
+        let hardware = HardwareReader::from_dir(root).read().unwrap();
+        assert_eq!(hardware.firmware, FirmwareType::Bios);
+        assert_eq!(hardware.virtualization, None);
+        assert_eq!(hardware.memory.size, 1024 * 1024);
+        assert!(hardware.disks.is_empty());
+    }
+
+    #[test]
+    fn test_read_blank_disks_transport() {
+        let tmp_dir = TempDir::with_prefix("hardware").unwrap();
+        let root = tmp_dir.path();
+        fs::create_dir_all(root.join("proc")).unwrap();
+        fs::create_dir_all(root.join("sys/class/net")).unwrap();
+        fs::write(root.join("proc/meminfo"), "MemTotal: 1024 kB\n").unwrap();
+
+        // the transport is known by udev
+        fs::create_dir_all(root.join("sys/block/sda/device")).unwrap();
+        fs::write(root.join("sys/block/sda/dev"), "8:0\n").unwrap();
+        fs::create_dir_all(root.join("run/udev/data")).unwrap();
+        fs::write(
+            root.join("run/udev/data/b8:0"),
+            "S:disk/by-id/ata-X\nE:ID_BUS=ata\n",
+        )
+        .unwrap();
+
+        // the transport is inferred from the sysfs path
+        let vdb = root.join("sys/devices/pci0000:00/0000:00:05.0/virtio3/block/vdb");
+        fs::create_dir_all(vdb.join("device")).unwrap();
+        std::os::unix::fs::symlink(&vdb, root.join("sys/block/vdb")).unwrap();
+
+        let hardware = HardwareReader::from_dir(root).read().unwrap();
+        let transports: Vec<_> = hardware
+            .disks
+            .iter()
+            .map(|d| (d.name.as_str(), d.transport.as_deref()))
+            .collect();
+        assert_eq!(
+            transports,
+            vec![("sda", Some("ata")), ("vdb", Some("virtio"))]
+        );
+    }
+}
@@ -19,10 +19,16 @@
 // find current contact information at www.suse.com.
 
 mod base_http_client;
-pub use base_http_client::{BaseHTTPClient, BaseHTTPClientError};
+pub use base_http_client::{BaseHTTPClient, BaseHTTPClientError, TokenRefreshCallback};
 
 mod event;
-pub use event::{Event, EventPayload};
+pub use event::{Event, EventFilter, EventPayload, WebSocketCommand};
+
+mod sse;
+pub use sse::EventsStreamClient;
+
+mod unix;
+pub use unix::socket_path;
 
 mod websocket;
 pub use websocket::{WebSocketClient, WebSocketError};
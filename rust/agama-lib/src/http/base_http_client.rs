@@ -18,12 +18,33 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use reqwest::{header, IntoUrl, Response};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use reqwest::{IntoUrl, RequestBuilder, Response};
 use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::Mutex;
 use url::Url;
 
+use super::unix;
 use crate::auth::AuthToken;
 
+/// Base URL of the API when connecting through a Unix socket.
+const UNIX_BASE_URL: &str = "http://localhost/api/";
+
+/// Function to call when the authentication token is refreshed (e.g., to store it).
+pub type TokenRefreshCallback = Arc<dyn Fn(&AuthToken) + Send + Sync>;
+
+/// Authentication token shared by all the copies of a client.
+#[derive(Clone)]
+struct TokenAuth {
+    token: Arc<Mutex<AuthToken>>,
+    on_refresh: Option<TokenRefreshCallback>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum BaseHTTPClientError {
     #[error(transparent)]
@@ -36,6 +57,10 @@ pub enum BaseHTTPClientError {
     InvalidJSON(#[from] serde_json::Error),
     #[error("Backend call failed with status {0} and text '{1}'")]
     BackendError(u16, String),
+    #[error("Could not connect to the socket {0}: {1}")]
+    UnixSocket(String, #[source] std::io::Error),
+    #[error(transparent)]
+    Hyper(hyper::Error),
 }
 
 /// Base that all HTTP clients should use.
@@ -45,6 +70,9 @@ pub enum BaseHTTPClientError {
 ///
 /// Usage should be just thin layer in domain specific client.
 ///
+/// An authenticated client refreshes its token (see [AuthToken::needs_refresh]) before sending
+/// a request if it is close to expiring. The new token is shared by all the copies of the client.
+///
 /// ```no_run
 ///   use agama_lib::questions::model::Question;
 ///   use agama_lib::http::{BaseHTTPClient, BaseHTTPClientError};
@@ -60,15 +88,22 @@ pub struct BaseHTTPClient {
     pub client: reqwest::Client,
     insecure: bool,
     pub base_url: Url,
+    auth: Option<TokenAuth>,
+    unix_socket: Option<PathBuf>,
 }
 
 impl BaseHTTPClient {
     /// It builds a new client.
     ///
     /// * `base_url`: base URL of the API to connect to. A trailing "/" is relevant if the URL
-    ///   has a path.
+    ///   has a path. Use a `unix://` URL (e.g., `unix:///run/agama/api.sock`) to connect
+    ///   through a Unix domain socket.
     pub fn new<T: IntoUrl>(base_url: T) -> Result<Self, BaseHTTPClientError> {
         let mut url = base_url.into_url()?;
+        let unix_socket = unix::socket_path(&url);
+        if unix_socket.is_some() {
+            url = Url::parse(UNIX_BASE_URL)?;
+        }
 
         // A trailing slash is significant. Let's make sure that it is there.
         // See https://docs.rs/url/2.5.4/url/struct.Url.html#method.join.
@@ -80,6 +115,8 @@ impl BaseHTTPClient {
             client: reqwest::Client::new(),
             insecure: false,
             base_url: url,
+            auth: None,
+            unix_socket,
         })
     }
 
@@ -96,7 +133,13 @@ impl BaseHTTPClient {
     /// * `token`: authentication token.
     pub fn authenticated(self, token: &AuthToken) -> Result<Self, BaseHTTPClientError> {
         Ok(Self {
-            client: Self::authenticated_client(self.insecure, token)?,
+            client: reqwest::Client::builder()
+                .danger_accept_invalid_certs(self.insecure)
+                .build()?,
+            auth: Some(TokenAuth {
+                token: Arc::new(Mutex::new(token.clone())),
+                on_refresh: None,
+            }),
             ..self
         })
     }
@@ -107,25 +150,118 @@ impl BaseHTTPClient {
             client: reqwest::Client::builder()
                 .danger_accept_invalid_certs(self.insecure)
                 .build()?,
+            auth: None,
             ..self
         })
     }
 
-    fn authenticated_client(
-        insecure: bool,
+    /// Sets a function to call when the authentication token is refreshed.
+    ///
+    /// It has no effect on unauthenticated clients.
+    ///
+    /// * `callback`: function receiving the new token.
+    pub fn on_token_refresh(self, callback: TokenRefreshCallback) -> Self {
+        let auth = self.auth.map(|auth| TokenAuth {
+            on_refresh: Some(callback),
+            ..auth
+        });
+        Self { auth, ..self }
+    }
+
+    /// Returns the current authentication token, if any.
+    pub async fn token(&self) -> Option<AuthToken> {
+        let auth = self.auth.as_ref()?;
+        Some(auth.token.lock().await.clone())
+    }
+
+    /// Refreshes the authentication token if it is close to expiring.
+    ///
+    /// Failing to refresh the token is not an error: the current one might still be valid, so
+    /// the problem is logged. It returns the token to use.
+    async fn refresh_token(&self, auth: &TokenAuth) -> AuthToken {
+        let mut token = auth.token.lock().await;
+        if !token.needs_refresh() {
+            return token.clone();
+        }
+
+        match self.request_new_token(&token).await {
+            Ok(Some(new_token)) => {
+                *token = new_token;
+                if let Some(callback) = &auth.on_refresh {
+                    callback(&token);
+                }
+            }
+            Ok(None) => {}
+            Err(error) => log::warn!("Could not refresh the authentication token: {error}"),
+        }
+        token.clone()
+    }
+
+    /// Asks the server for a new token.
+    ///
+    /// * `token`: current token.
+    async fn request_new_token(
+        &self,
         token: &AuthToken,
-    ) -> Result<reqwest::Client, BaseHTTPClientError> {
-        let mut headers = header::HeaderMap::new();
-        // just use generic anyhow error here as Bearer format is constructed by us, so failures can come only from token
-        let value = header::HeaderValue::from_str(format!("Bearer {}", token).as_str())?;
-
-        headers.insert(header::AUTHORIZATION, value);
-
-        let client = reqwest::Client::builder()
-            .danger_accept_invalid_certs(insecure)
-            .default_headers(headers)
-            .build()?;
-        Ok(client)
+    ) -> Result<Option<AuthToken>, BaseHTTPClientError> {
+        let request = self
+            .client
+            .post(self.url("/auth/refresh")?)
+            .bearer_auth(token.as_str());
+        let response = self.send(request).await?;
+        if !response.status().is_success() {
+            return Err(self.build_backend_error(response).await);
+        }
+
+        let body: HashMap<String, String> = response.json().await?;
+        Ok(body.get("token").map(|t| AuthToken::new(t)))
+    }
+
+    /// Refreshes the authentication token if it is close to expiring.
+    ///
+    /// It is useful for long running processes which do not send requests often (e.g., when
+    /// listening for events). As with any other request, a failed refresh is only logged.
+    pub async fn refresh_token_if_needed(&self) {
+        if let Some(auth) = &self.auth {
+            self.refresh_token(auth).await;
+        }
+    }
+
+    /// Builds a request, including the authentication token if needed.
+    ///
+    /// * `method`: HTTP method.
+    /// * `path`: path relative to HTTP API like `/questions`
+    pub(crate) async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> Result<RequestBuilder, BaseHTTPClientError> {
+        let request = self.client.request(method, self.url(path)?);
+        match &self.auth {
+            Some(auth) => {
+                let token = self.refresh_token(auth).await;
+                Ok(request.bearer_auth(token.as_str()))
+            }
+            None => Ok(request),
+        }
+    }
+
+    /// Sends a request, through the Unix socket if needed.
+    ///
+    /// * `request`: request to send.
+    pub(crate) async fn send(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Response, BaseHTTPClientError> {
+        match &self.unix_socket {
+            Some(socket) => unix::send(socket, request.build()?).await,
+            None => Ok(request.send().await?),
+        }
+    }
+
+    /// Returns the Unix socket the client connects to, if any.
+    pub fn unix_socket(&self) -> Option<&Path> {
+        self.unix_socket.as_deref()
     }
 
     fn url(&self, path: &str) -> Result<Url, url::ParseError> {
@@ -142,13 +278,9 @@ impl BaseHTTPClient {
     where
         T: DeserializeOwned,
     {
-        let response: Result<_, BaseHTTPClientError> = self
-            .client
-            .get(self.url(path)?)
-            .send()
-            .await
-            .map_err(|e| e.into());
-        self.deserialize_or_error(response?).await
+        let request = self.request(reqwest::Method::GET, path).await?;
+        let response = self.send(request).await?;
+        self.deserialize_or_error(response).await
     }
 
     pub async fn post<T>(
@@ -256,25 +388,16 @@ impl BaseHTTPClient {
     ///
     /// * `path`: path relative to HTTP API like `/questions/1`
     pub async fn delete_void(&self, path: &str) -> Result<(), BaseHTTPClientError> {
-        let response: Result<_, BaseHTTPClientError> = self
-            .client
-            .delete(self.url(path)?)
-            .send()
-            .await
-            .map_err(|e| e.into());
-        self.unit_or_error(response?).await
+        let request = self.request(reqwest::Method::DELETE, path).await?;
+        let response = self.send(request).await?;
+        self.unit_or_error(response).await
     }
 
     /// Returns raw reqwest::Response. Use e.g. in case when response content is not
     /// JSON body but e.g. binary data
     pub async fn get_raw(&self, path: &str) -> Result<Response, BaseHTTPClientError> {
-        let raw: Result<_, BaseHTTPClientError> = self
-            .client
-            .get(self.url(path)?)
-            .send()
-            .await
-            .map_err(|e| e.into());
-        let response = raw?;
+        let request = self.request(reqwest::Method::GET, path).await?;
+        let response = self.send(request).await?;
 
         if response.status().is_success() {
             Ok(response)
@@ -283,6 +406,29 @@ impl BaseHTTPClient {
         }
     }
 
+    /// Sends a request with an optional JSON body and returns the raw response, whatever its
+    /// status is.
+    ///
+    /// It is meant for generic clients which do not know the structure of the data.
+    ///
+    /// Arguments:
+    ///
+    /// * `method`: for example `reqwest::Method::GET`
+    /// * `path`: path relative to HTTP API like `/questions`
+    /// * `body`: JSON body of the request.
+    pub async fn send_raw(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<Response, BaseHTTPClientError> {
+        let mut request = self.request(method, path).await?;
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        self.send(request).await
+    }
+
     /// POST/PUT/PATCH an object to a given path and returns server response.
     /// Reports Err only if failed to send
     /// request, but if server returns e.g. 500, it will be in Ok result.
@@ -300,12 +446,8 @@ impl BaseHTTPClient {
         path: &str,
         object: &impl Serialize,
     ) -> Result<Response, BaseHTTPClientError> {
-        self.client
-            .request(method, self.url(path)?)
-            .json(object)
-            .send()
-            .await
-            .map_err(|e| e.into())
+        let request = self.request(method, path).await?.json(object);
+        self.send(request).await
     }
 
     /// Return deserialized JSON body as `Ok(T)` or an `Err` with [`BaseHTTPClientError::BackendError`]
@@ -356,7 +498,7 @@ impl BaseHTTPClient {
     /// Arguments:
     ///
     /// * `response`: response from which generate error
-    async fn build_backend_error(&self, response: Response) -> BaseHTTPClientError {
+    pub(crate) async fn build_backend_error(&self, response: Response) -> BaseHTTPClientError {
         let code = response.status().as_u16();
         let text = response
             .text()
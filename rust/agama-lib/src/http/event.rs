@@ -36,7 +36,7 @@ use crate::{
     users::{FirstUser, RootUser},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
 use crate::issue::Issue;
 
@@ -46,6 +46,9 @@ use crate::issue::Issue;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Event {
+    /// Sequence number, assigned by the server when the event is recorded in its history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
     /// The identifier of the client which caused the event.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_id: Option<ClientId>,
@@ -60,6 +63,7 @@ impl Event {
     /// * `payload`: event payload.
     pub fn new(payload: EventPayload) -> Self {
         Event {
+            seq: None,
             client_id: None,
             payload,
         }
@@ -71,16 +75,25 @@ impl Event {
     /// * `client_id`: client ID.
     pub fn new_with_client_id(payload: EventPayload, client_id: &ClientId) -> Self {
         Event {
+            seq: None,
             client_id: Some(client_id.clone()),
             payload,
         }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, strum::IntoStaticStr)]
 #[serde(tag = "type")]
 pub enum EventPayload {
     ClientConnected,
+    /// Some events were dropped from the server history, so the client cannot resume from the
+    /// requested point. It should read the whole state again.
+    EventsGap {
+        /// Last sequence number known by the client.
+        since: u64,
+        /// Oldest sequence number available in the history.
+        oldest: u64,
+    },
     L10nConfigChanged(LocaleConfig),
     LocaleChanged {
         locale: String,
@@ -185,6 +198,102 @@ pub enum EventPayload {
     },
 }
 
+impl EventPayload {
+    /// Event type, as included in the "type" attribute of the JSON representation.
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
+    /// D-Bus path of the service the event refers to, if any.
+    ///
+    /// The [EventPayload::ServiceStatusChanged] events contain the D-Bus name of the service
+    /// (e.g., "org.opensuse.Agama.Manager1"), so it is converted to the object path
+    /// ("/org/opensuse/Agama/Manager1").
+    pub fn service_path(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Self::ProgressChanged { path, .. }
+            | Self::IssuesChanged { path, .. }
+            | Self::ValidationChanged { path, .. } => Some(Cow::Borrowed(path)),
+            Self::ServiceStatusChanged { service, .. } if service.starts_with('/') => {
+                Some(Cow::Borrowed(service))
+            }
+            Self::ServiceStatusChanged { service, .. } => {
+                Some(Cow::Owned(format!("/{}", service.replace('.', "/"))))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Selects which events a client is interested in.
+///
+/// An event matches when it fulfills all the given criteria. An empty filter matches all the
+/// events. The [EventPayload::ClientConnected] and [EventPayload::EventsGap] events always
+/// match, as they are relevant to any client.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    /// Event types (e.g., "QuestionsChanged").
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+    /// Service paths (e.g., "/org/opensuse/Agama/Manager1"). A path matches the events of its
+    /// children too.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+    /// Client which caused the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<ClientId>,
+}
+
+impl EventFilter {
+    /// Whether the filter does not restrict any event.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.services.is_empty() && self.client_id.is_none()
+    }
+
+    /// Whether the event matches the filter.
+    ///
+    /// * `event`: event to check.
+    pub fn matches(&self, event: &Event) -> bool {
+        if matches!(
+            event.payload,
+            EventPayload::ClientConnected | EventPayload::EventsGap { .. }
+        ) {
+            return true;
+        }
+
+        if !self.types.is_empty() && !self.types.iter().any(|t| t == event.payload.name()) {
+            return false;
+        }
+
+        if !self.services.is_empty() {
+            let Some(path) = event.payload.service_path() else {
+                return false;
+            };
+            let matches_path = self.services.iter().any(|service| {
+                let service = service.trim_end_matches('/');
+                path == service || path.starts_with(&format!("{service}/"))
+            });
+            if !matches_path {
+                return false;
+            }
+        }
+
+        match &self.client_id {
+            Some(client_id) => event.client_id.as_ref() == Some(client_id),
+            None => true,
+        }
+    }
+}
+
+/// Message sent by the clients through the WebSocket.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WebSocketCommand {
+    /// Receive only the events matching the filter. It replaces any previous filter.
+    Subscribe(EventFilter),
+}
+
 /// Makes it easier to create an event, reducing the boilerplate.
 ///
 /// # Event without additional data
@@ -250,3 +359,74 @@ macro_rules! event {
         agama_lib::http::Event::new(agama_lib::http::EventPayload::$variant $inner)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{EventFilter, WebSocketCommand};
+    use crate::{auth::ClientId, http::EventPayload};
+
+    // the event! macro refers to agama_lib, which is not available inside the crate
+    fn event(payload: EventPayload) -> super::Event {
+        super::Event::new(payload)
+    }
+
+    #[test]
+    fn test_filter_by_type() {
+        let filter = EventFilter {
+            types: vec!["QuestionsChanged".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&event(EventPayload::QuestionsChanged)));
+        assert!(!filter.matches(&event(EventPayload::StorageChanged)));
+        assert!(filter.matches(&event(EventPayload::ClientConnected)));
+    }
+
+    #[test]
+    fn test_filter_by_service() {
+        let filter = EventFilter {
+            services: vec!["/org/opensuse/Agama/Software1".to_string()],
+            ..Default::default()
+        };
+        let status = |service: &str| {
+            event(EventPayload::ServiceStatusChanged {
+                service: service.to_string(),
+                status: 1,
+            })
+        };
+        assert!(filter.matches(&status("org.opensuse.Agama.Software1")));
+        assert!(!filter.matches(&status("org.opensuse.Agama.Software10")));
+        assert!(!filter.matches(&status("org.opensuse.Agama.Manager1")));
+        assert!(!filter.matches(&event(EventPayload::QuestionsChanged)));
+
+        let progress = |path: &str| {
+            event(EventPayload::ProgressChanged {
+                path: path.to_string(),
+                progress: Default::default(),
+            })
+        };
+        assert!(filter.matches(&progress("/org/opensuse/Agama/Software1")));
+        assert!(filter.matches(&progress("/org/opensuse/Agama/Software1/Product")));
+        assert!(!filter.matches(&progress("/org/opensuse/Agama/Software10")));
+    }
+
+    #[test]
+    fn test_filter_by_client() {
+        let client_id = ClientId::new();
+        let filter = EventFilter {
+            client_id: Some(client_id.clone()),
+            ..Default::default()
+        };
+        let own = super::Event::new_with_client_id(EventPayload::StorageChanged, &client_id);
+        assert!(filter.matches(&own));
+        assert!(!filter.matches(&event(EventPayload::StorageChanged)));
+        assert!(EventFilter::default().matches(&own));
+    }
+
+    #[test]
+    fn test_subscribe_command() {
+        let json = r#"{ "subscribe": { "types": ["QuestionsChanged"] } }"#;
+        let WebSocketCommand::Subscribe(filter) = serde_json::from_str(json).unwrap();
+        assert_eq!(filter.types, vec!["QuestionsChanged".to_string()]);
+        assert!(filter.services.is_empty());
+    }
+}
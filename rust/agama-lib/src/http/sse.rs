@@ -0,0 +1,163 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements a client for the Server-Sent Events endpoint (`/api/events`).
+//!
+//! It is an alternative to the [super::WebSocketClient] which works through plain HTTP proxies.
+
+use reqwest::Response;
+
+use super::{
+    websocket::{RECONNECT_ATTEMPTS, RECONNECT_DELAY},
+    BaseHTTPClient, BaseHTTPClientError, Event, EventFilter,
+};
+
+/// Server-Sent Events client for the Agama service.
+///
+/// If the connection is lost, it reconnects using the `Last-Event-ID` header, so the server
+/// replays the missed events.
+pub struct EventsStreamClient {
+    http: BaseHTTPClient,
+    path: String,
+    response: Response,
+    buffer: Vec<u8>,
+    last_id: Option<String>,
+}
+
+impl EventsStreamClient {
+    /// Connects to the events stream.
+    ///
+    /// * `http`: authenticated HTTP client.
+    /// * `filter`: events to receive.
+    pub async fn connect(
+        http: BaseHTTPClient,
+        filter: &EventFilter,
+    ) -> Result<Self, BaseHTTPClientError> {
+        let path = Self::path(filter);
+        let response = Self::open(&http, &path, None).await?;
+        Ok(Self {
+            http,
+            path,
+            response,
+            buffer: vec![],
+            last_id: None,
+        })
+    }
+
+    fn path(filter: &EventFilter) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if !filter.types.is_empty() {
+            query.append_pair("types", &filter.types.join(","));
+        }
+        if !filter.services.is_empty() {
+            query.append_pair("services", &filter.services.join(","));
+        }
+        let query = query.finish();
+        if query.is_empty() {
+            "/events".to_string()
+        } else {
+            format!("/events?{query}")
+        }
+    }
+
+    async fn open(
+        http: &BaseHTTPClient,
+        path: &str,
+        last_id: Option<&str>,
+    ) -> Result<Response, BaseHTTPClientError> {
+        let mut request = http.request(reqwest::Method::GET, path).await?;
+        if let Some(last_id) = last_id {
+            request = request.header("Last-Event-ID", last_id);
+        }
+        let response = http.send(request).await?;
+        if !response.status().is_success() {
+            return Err(http.build_backend_error(response).await);
+        }
+        Ok(response)
+    }
+
+    /// Reconnects to the stream, resuming from the last received event.
+    async fn reconnect(&mut self) -> Result<(), BaseHTTPClientError> {
+        self.buffer.clear();
+        let mut attempt = 1;
+        loop {
+            tokio::time::sleep(RECONNECT_DELAY * attempt).await;
+            match Self::open(&self.http, &self.path, self.last_id.as_deref()).await {
+                Ok(response) => {
+                    self.response = response;
+                    return Ok(());
+                }
+                Err(error) if attempt >= RECONNECT_ATTEMPTS => return Err(error),
+                Err(error) => {
+                    log::warn!(
+                        "Could not reconnect to the events stream (attempt {attempt}): {error}"
+                    );
+                }
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Receive an event from the stream.
+    ///
+    /// If the connection is lost, it tries to reconnect.
+    pub async fn receive(&mut self) -> Result<Event, BaseHTTPClientError> {
+        loop {
+            while let Some(block) = self.next_block() {
+                if let Some(event) = self.parse_block(&block)? {
+                    return Ok(event);
+                }
+            }
+
+            match self.response.chunk().await {
+                Ok(Some(chunk)) => self.buffer.extend_from_slice(&chunk),
+                Ok(None) => self.reconnect().await?,
+                Err(error) => {
+                    log::warn!("Events stream error, reconnecting: {error}");
+                    self.reconnect().await?;
+                }
+            }
+        }
+    }
+
+    /// Takes the next complete block (the lines until an empty one) from the buffer.
+    fn next_block(&mut self) -> Option<String> {
+        let position = self.buffer.windows(2).position(|w| w == b"\n\n")?;
+        let block: Vec<u8> = self.buffer.drain(..position + 2).collect();
+        Some(String::from_utf8_lossy(&block).replace('\r', ""))
+    }
+
+    /// Parses a block, returning the event if it contains one.
+    fn parse_block(&mut self, block: &str) -> Result<Option<Event>, BaseHTTPClientError> {
+        let mut data = vec![];
+        for line in block.lines() {
+            if let Some(value) = line.strip_prefix("data:") {
+                data.push(value.strip_prefix(' ').unwrap_or(value));
+            } else if let Some(value) = line.strip_prefix("id:") {
+                self.last_id = Some(value.trim().to_string());
+            }
+        }
+
+        if data.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&data.join("\n"))?))
+    }
+}
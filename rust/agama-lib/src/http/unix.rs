@@ -0,0 +1,112 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Sends the HTTP requests through a Unix domain socket.
+//!
+//! The web server can listen on a Unix socket (e.g., `/run/agama/api.sock`), which is useful for
+//! local tools when TCP is disabled. The clients use URLs like `unix:///run/agama/api.sock`.
+
+use std::path::{Path, PathBuf};
+
+use http::{header, uri::PathAndQuery, HeaderValue, Uri};
+use hyper_util::rt::TokioIo;
+use reqwest::Response;
+use tokio::net::UnixStream;
+use url::Url;
+
+use super::BaseHTTPClientError;
+
+/// URL scheme for Unix domain sockets.
+pub const UNIX_SCHEME: &str = "unix";
+
+/// Returns the socket path if the URL uses the `unix` scheme.
+///
+/// * `url`: URL to check (e.g., `unix:///run/agama/api.sock`).
+pub fn socket_path(url: &Url) -> Option<PathBuf> {
+    (url.scheme() == UNIX_SCHEME).then(|| PathBuf::from(url.path()))
+}
+
+/// Connects to the socket.
+///
+/// * `socket`: socket path.
+pub(crate) async fn connect(socket: &Path) -> Result<UnixStream, BaseHTTPClientError> {
+    UnixStream::connect(socket)
+        .await
+        .map_err(|e| BaseHTTPClientError::UnixSocket(socket.display().to_string(), e))
+}
+
+/// Sends a request through the socket.
+///
+/// The response body is streamed, so it behaves like a regular [reqwest] response.
+///
+/// * `socket`: socket path.
+/// * `request`: request to send.
+pub(crate) async fn send(
+    socket: &Path,
+    request: reqwest::Request,
+) -> Result<Response, BaseHTTPClientError> {
+    let mut request = http::Request::<reqwest::Body>::try_from(request)?;
+    let host = request.uri().host().unwrap_or("localhost").to_string();
+    let path = request
+        .uri()
+        .path_and_query()
+        .cloned()
+        .unwrap_or_else(|| PathAndQuery::from_static("/"));
+    *request.uri_mut() = Uri::from(path);
+    request
+        .headers_mut()
+        .entry(header::HOST)
+        .or_insert(HeaderValue::from_str(&host)?);
+
+    let stream = connect(socket).await?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(BaseHTTPClientError::Hyper)?;
+    tokio::spawn(async move {
+        if let Err(error) = connection.await {
+            log::warn!("Unix socket connection error: {error}");
+        }
+    });
+
+    let response = sender
+        .send_request(request)
+        .await
+        .map_err(BaseHTTPClientError::Hyper)?;
+    Ok(Response::from(response.map(reqwest::Body::wrap)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::socket_path;
+    use std::path::PathBuf;
+    use url::Url;
+
+    #[test]
+    fn test_socket_path() {
+        let url = Url::parse("unix:///run/agama/api.sock").unwrap();
+        assert_eq!(
+            socket_path(&url),
+            Some(PathBuf::from("/run/agama/api.sock"))
+        );
+
+        let url = Url::parse("http://localhost/api").unwrap();
+        assert_eq!(socket_path(&url), None);
+    }
+}
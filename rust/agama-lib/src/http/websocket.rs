@@ -21,22 +21,69 @@
 //! This module implements a WSClient to connect to Agama's WebSocket and
 //! listen for events.
 
-use tokio::{net::TcpStream, sync::broadcast};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use futures_util::SinkExt;
+use tokio::{
+    net::{TcpStream, UnixStream},
+    sync::broadcast,
+};
 use tokio_native_tls::native_tls;
 use tokio_stream::StreamExt;
 use tokio_tungstenite::{
-    connect_async_tls_with_config,
+    client_async, connect_async_tls_with_config,
     tungstenite::{
         http::{self, Uri},
-        ClientRequestBuilder,
+        ClientRequestBuilder, Message,
     },
-    Connector, WebSocketStream,
+    Connector, MaybeTlsStream, WebSocketStream,
 };
 use url::Url;
 
-use super::Event;
+use super::{BaseHTTPClient, Event, EventFilter, WebSocketCommand};
 use crate::auth::AuthToken;
 
+/// How often to check whether the token needs to be refreshed.
+const TOKEN_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// How many times to try to reconnect after the connection is lost.
+pub(super) const RECONNECT_ATTEMPTS: u32 = 5;
+/// Time to wait before the first reconnection attempt. It grows with each attempt.
+pub(super) const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// WebSocket connection, through TCP or through a Unix socket.
+enum Socket {
+    Tcp(WebSocketStream<MaybeTlsStream<TcpStream>>),
+    Unix(WebSocketStream<UnixStream>),
+}
+
+impl Socket {
+    async fn send(&mut self, message: Message) -> Result<(), WebSocketError> {
+        match self {
+            Self::Tcp(socket) => socket.send(message).await?,
+            Self::Unix(socket) => socket.send(message).await?,
+        }
+        Ok(())
+    }
+
+    async fn next(&mut self) -> Option<Result<Message, tokio_tungstenite::tungstenite::Error>> {
+        match self {
+            Self::Tcp(socket) => socket.next().await,
+            Self::Unix(socket) => socket.next().await,
+        }
+    }
+}
+
+/// How to reach the WebSocket.
+enum Transport {
+    /// TCP, with or without TLS.
+    Tcp { insecure: bool },
+    /// Unix domain socket.
+    Unix(PathBuf),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum WebSocketError {
     #[error(transparent)]
@@ -60,8 +107,19 @@ pub enum WebSocketError {
 }
 
 /// WebSocket client for the Agama service.
+///
+/// If the connection is lost, it reconnects asking for the events after the last received one.
+/// The server replays them or, if they are not available anymore, it sends an
+/// [super::EventPayload::EventsGap] event.
 pub struct WebSocketClient {
-    socket: WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>>,
+    socket: Socket,
+    url: Url,
+    token: Option<AuthToken>,
+    transport: Transport,
+    last_seq: Option<u64>,
+    filter: EventFilter,
+    http: Option<BaseHTTPClient>,
+    last_token_check: Instant,
 }
 
 impl WebSocketClient {
@@ -75,37 +133,202 @@ impl WebSocketClient {
         auth_token: &AuthToken,
         insecure: bool,
     ) -> Result<Self, WebSocketError> {
-        let tls_connector = native_tls::TlsConnector::builder()
-            .danger_accept_invalid_certs(insecure)
-            .danger_accept_invalid_hostnames(insecure)
-            .build()?;
-        let tls_connector: native_tls::TlsConnector = tls_connector.into();
+        Self::start(url, Some(auth_token), Transport::Tcp { insecure }).await
+    }
 
-        let connector = Connector::NativeTls(tls_connector);
+    /// Connects to a websocket through a Unix domain socket.
+    ///
+    /// The server authorizes the local clients using the peer credentials, so the token is
+    /// optional.
+    ///
+    /// * `socket`: path of the Unix socket.
+    /// * `url`: URL of the websocket (e.g., `ws://localhost/api/ws`).
+    /// * `auth_token`: Agama authentication token, if any.
+    pub async fn connect_unix(
+        socket: &Path,
+        url: &Url,
+        auth_token: Option<&AuthToken>,
+    ) -> Result<Self, WebSocketError> {
+        Self::start(url, auth_token, Transport::Unix(socket.to_path_buf())).await
+    }
+
+    async fn start(
+        url: &Url,
+        auth_token: Option<&AuthToken>,
+        transport: Transport,
+    ) -> Result<Self, WebSocketError> {
+        let socket = Self::open(url, auth_token, &transport).await?;
+        Ok(Self {
+            socket,
+            url: url.clone(),
+            token: auth_token.cloned(),
+            transport,
+            last_seq: None,
+            filter: EventFilter::default(),
+            http: None,
+            last_token_check: Instant::now(),
+        })
+    }
+
+    async fn open(
+        url: &Url,
+        auth_token: Option<&AuthToken>,
+        transport: &Transport,
+    ) -> Result<Socket, WebSocketError> {
         let uri: Uri = url.as_str().parse()?;
-        let token = auth_token.as_str();
+        let mut request = ClientRequestBuilder::new(uri);
+        if let Some(token) = auth_token {
+            let token = token.as_str();
+            request = request.with_header("Authorization", format!("Bearer {token}"));
+        }
+
+        match transport {
+            Transport::Tcp { insecure } => {
+                let tls_connector = native_tls::TlsConnector::builder()
+                    .danger_accept_invalid_certs(*insecure)
+                    .danger_accept_invalid_hostnames(*insecure)
+                    .build()?;
+                let tls_connector: native_tls::TlsConnector = tls_connector.into();
+                let connector = Connector::NativeTls(tls_connector);
+
+                // The connect_async_tls_config receives a request, the WebSocket
+                // configuration, whether to disable the "Nagle's algorithm"
+                // (recommended to false) and the connector.
+                //
+                // See https://docs.rs/tokio-tungstenite/latest/tokio_tungstenite/fn.connect_async_tls_with_config.html.
+                let (socket, _response) =
+                    connect_async_tls_with_config(request, None, false, Some(connector)).await?;
+                Ok(Socket::Tcp(socket))
+            }
+            Transport::Unix(path) => {
+                let stream = UnixStream::connect(path).await?;
+                let (socket, _response) = client_async(request, stream).await?;
+                Ok(Socket::Unix(socket))
+            }
+        }
+    }
+
+    /// Reconnects to the websocket, resuming from the last received event.
+    async fn reconnect(&mut self) -> Result<(), WebSocketError> {
+        let mut url = self.url.clone();
+        if let Some(seq) = self.last_seq {
+            url.query_pairs_mut().append_pair("since", &seq.to_string());
+        }
+        if let Some(token) = self.current_token().await {
+            self.token = Some(token);
+        }
 
-        let request =
-            ClientRequestBuilder::new(uri).with_header("Authorization", format!("Bearer {token}"));
+        let mut attempt = 1;
+        loop {
+            tokio::time::sleep(RECONNECT_DELAY * attempt).await;
+            match Self::open(&url, self.token.as_ref(), &self.transport).await {
+                Ok(socket) => {
+                    self.socket = socket;
+                    if !self.filter.is_empty() {
+                        self.send_filter().await?;
+                    }
+                    return Ok(());
+                }
+                Err(error) if attempt >= RECONNECT_ATTEMPTS => return Err(error),
+                Err(error) => {
+                    log::warn!("Could not reconnect to the WebSocket (attempt {attempt}): {error}");
+                }
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Returns the token of the HTTP client used to refresh the token, if any.
+    async fn current_token(&self) -> Option<AuthToken> {
+        self.http.as_ref()?.token().await
+    }
+
+    /// Keeps the token of the given client fresh while listening for events.
+    ///
+    /// The WebSocket is authenticated only when connecting. However, a long running listener
+    /// should keep the token valid, so it can be used to reconnect or to send further requests.
+    ///
+    /// * `client`: authenticated HTTP client sharing the token.
+    pub fn with_token_refresh(self, client: BaseHTTPClient) -> Self {
+        Self {
+            http: Some(client),
+            ..self
+        }
+    }
 
-        // The connect_async_tls_config receives a request, the WebSocket
-        // configuration, whether to disable the "Nagle's algorithm"
-        // (recommended to false) and the connector.
-        //
-        // See https://docs.rs/tokio-tungstenite/latest/tokio_tungstenite/fn.connect_async_tls_with_config.html.
-        let (socket, _response) =
-            connect_async_tls_with_config(request, None, false, Some(connector)).await?;
+    /// Asks the server to send only the events matching the given filter.
+    ///
+    /// The filter is kept when reconnecting.
+    ///
+    /// * `filter`: events filter.
+    pub async fn subscribe(&mut self, filter: EventFilter) -> Result<(), WebSocketError> {
+        self.filter = filter;
+        self.send_filter().await
+    }
 
-        Ok(Self { socket })
+    async fn send_filter(&mut self) -> Result<(), WebSocketError> {
+        let command = WebSocketCommand::Subscribe(self.filter.clone());
+        let json = serde_json::to_string(&command)?;
+        self.socket.send(Message::text(json)).await?;
+        Ok(())
     }
 
     /// Receive an event from the websocket.
     ///
-    /// It returns the message as an event.
+    /// It returns the message as an event. If the connection is lost, it tries to reconnect.
+    /// While waiting, it keeps the token fresh (see [Self::with_token_refresh]).
     pub async fn receive(&mut self) -> Result<Event, WebSocketError> {
-        let msg = self.socket.next().await.ok_or(WebSocketError::Closed)?;
-        let content = msg?.to_string();
-        let event: Event = serde_json::from_str(&content)?;
-        Ok(event)
+        loop {
+            let delay = TOKEN_CHECK_INTERVAL.saturating_sub(self.last_token_check.elapsed());
+            let next = tokio::select! {
+                next = self.socket.next() => Some(next),
+                _ = tokio::time::sleep(delay), if self.http.is_some() => None,
+            };
+            let Some(next) = next else {
+                self.check_token().await;
+                continue;
+            };
+
+            let msg = match next {
+                Some(Ok(msg)) => msg,
+                Some(Err(error)) => {
+                    log::warn!("WebSocket error, reconnecting: {error}");
+                    self.reconnect().await?;
+                    continue;
+                }
+                None => {
+                    self.reconnect().await?;
+                    continue;
+                }
+            };
+
+            self.check_token().await;
+            if !msg.is_text() && !msg.is_binary() {
+                continue;
+            }
+
+            let content = msg.to_string();
+            let event: Event = serde_json::from_str(&content)?;
+            if event.seq.is_some() {
+                self.last_seq = event.seq;
+            }
+            // the events replayed after reconnecting might be sent before the server
+            // gets the filter
+            if self.filter.matches(&event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    async fn check_token(&mut self) {
+        let Some(http) = &self.http else {
+            return;
+        };
+
+        if self.last_token_check.elapsed() < TOKEN_CHECK_INTERVAL {
+            return;
+        }
+        self.last_token_check = Instant::now();
+        http.refresh_token_if_needed().await;
     }
 }
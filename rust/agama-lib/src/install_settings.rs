@@ -40,6 +40,9 @@ use serde_json::value::RawValue;
 use std::default::Default;
 use std::path::Path;
 
+mod merge;
+pub use merge::{MergeStrategy, SettingsMerger, DELETE_MARKER};
+
 #[derive(Debug, thiserror::Error)]
 pub enum InstallSettingsError {
     #[error("I/O error: {0}")]
@@ -0,0 +1,370 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Merges several profiles into a single one.
+//!
+//! By default, objects are merged recursively and any other value (including lists) from the
+//! later profile replaces the previous one. Some lists use a different strategy (see
+//! [MergeStrategy]):
+//!
+//! * The connections in `network.connections` are merged by `id`, the scripts by `name`, the
+//!   files by `destination`, etc.
+//! * The patterns and packages in `software` are appended.
+//! * The first `user` is merged only if it has the same `userName`. Otherwise, it is replaced.
+//!
+//! It is possible to delete values explicitly: a `null` value removes the setting, and an item
+//! containing `"$delete": true` removes the element with the same key from a merged list.
+//!
+//! ```
+//! use agama_lib::install_settings::SettingsMerger;
+//! use serde_json::json;
+//!
+//! let merger = SettingsMerger::default();
+//! let profile = merger.merge_all([
+//!     json!({ "network": { "connections": [{ "id": "eth0", "method4": "auto" }] } }),
+//!     json!({ "network": { "connections": [{ "id": "eth0", "method6": "disabled" }] } }),
+//! ]);
+//! assert_eq!(
+//!     profile,
+//!     json!({
+//!         "network": {
+//!             "connections": [{ "id": "eth0", "method4": "auto", "method6": "disabled" }]
+//!         }
+//!     })
+//! );
+//! ```
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+/// Marks an element of a list merged by key as removed.
+pub const DELETE_MARKER: &str = "$delete";
+
+/// How to merge a setting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MergeStrategy {
+    /// Merges objects recursively. Other values are replaced.
+    Merge,
+    /// Replaces the previous value.
+    Replace,
+    /// Appends the elements which are not included in the previous list.
+    Append,
+    /// Merges the elements with the same value for the given key and appends the rest. When
+    /// applied to objects, they are merged only if they have the same key.
+    MergeByKey(&'static str),
+}
+
+/// Strategies for the settings which are not merged with [MergeStrategy::Merge].
+const STRATEGIES: [(&str, MergeStrategy); 14] = [
+    ("user", MergeStrategy::MergeByKey("userName")),
+    ("files", MergeStrategy::MergeByKey("destination")),
+    ("dasd.devices", MergeStrategy::MergeByKey("channel")),
+    ("network.connections", MergeStrategy::MergeByKey("id")),
+    ("questions.answers", MergeStrategy::Append),
+    ("scripts.pre", MergeStrategy::MergeByKey("name")),
+    (
+        "scripts.postPartitioning",
+        MergeStrategy::MergeByKey("name"),
+    ),
+    ("scripts.post", MergeStrategy::MergeByKey("name")),
+    ("scripts.init", MergeStrategy::MergeByKey("name")),
+    ("software.patterns", MergeStrategy::Append),
+    ("software.patterns.add", MergeStrategy::Append),
+    ("software.patterns.remove", MergeStrategy::Append),
+    ("software.packages", MergeStrategy::Append),
+    (
+        "software.extraRepositories",
+        MergeStrategy::MergeByKey("alias"),
+    ),
+];
+
+/// Merges profiles according to the strategy defined for each setting.
+///
+/// The settings are identified by their dotted path in the profile (e.g.,
+/// "network.connections"). The elements of a list do not add any component to the path.
+#[derive(Clone, Debug)]
+pub struct SettingsMerger {
+    strategies: HashMap<String, MergeStrategy>,
+}
+
+impl Default for SettingsMerger {
+    fn default() -> Self {
+        let strategies = STRATEGIES
+            .iter()
+            .map(|(path, strategy)| (path.to_string(), *strategy))
+            .collect();
+        Self { strategies }
+    }
+}
+
+impl SettingsMerger {
+    /// Sets the strategy for the given setting.
+    ///
+    /// * `path`: dotted path of the setting (e.g., "software.patterns").
+    /// * `strategy`: strategy to use.
+    pub fn with_strategy(mut self, path: &str, strategy: MergeStrategy) -> Self {
+        self.strategies.insert(path.to_string(), strategy);
+        self
+    }
+
+    /// Merges a list of profiles, in order.
+    pub fn merge_all<I: IntoIterator<Item = Value>>(&self, profiles: I) -> Value {
+        let mut result = Value::Object(Map::new());
+        for profile in profiles {
+            self.merge(&mut result, profile);
+        }
+        result
+    }
+
+    /// Merges `patch` into `target`.
+    pub fn merge(&self, target: &mut Value, patch: Value) {
+        self.merge_value(target, patch, "");
+    }
+
+    /// Merges `patch` into `target`, which is the setting at the given path.
+    ///
+    /// It is useful to merge only a part of a profile (e.g., the "network" section).
+    ///
+    /// * `path`: dotted path of the `target` setting (e.g., "network").
+    pub fn merge_at(&self, target: &mut Value, patch: Value, path: &str) {
+        self.merge_value(target, patch, path);
+    }
+
+    fn merge_value(&self, target: &mut Value, patch: Value, path: &str) {
+        let strategy = self
+            .strategies
+            .get(path)
+            .copied()
+            .unwrap_or(MergeStrategy::Merge);
+
+        match (strategy, target, patch) {
+            (MergeStrategy::Replace, target, patch) => *target = without_markers(patch),
+            (MergeStrategy::Append, Value::Array(target), Value::Array(patch)) => {
+                for item in patch {
+                    if !target.contains(&item) {
+                        target.push(item);
+                    }
+                }
+            }
+            (MergeStrategy::MergeByKey(key), Value::Array(target), Value::Array(patch)) => {
+                self.merge_list_by_key(target, patch, key, path);
+            }
+            (MergeStrategy::MergeByKey(key), target @ Value::Object(_), Value::Object(patch)) => {
+                if target.get(key) == patch.get(key) || !patch.contains_key(key) {
+                    self.merge_value(target, Value::Object(patch), &format!("{path}[]"));
+                } else {
+                    *target = without_markers(Value::Object(patch));
+                }
+            }
+            (_, Value::Object(target), Value::Object(patch)) => {
+                self.merge_object(target, patch, path);
+            }
+            (_, target, patch) => *target = without_markers(patch),
+        }
+    }
+
+    fn merge_object(&self, target: &mut Map<String, Value>, patch: Map<String, Value>, path: &str) {
+        let path = path.strip_suffix("[]").unwrap_or(path);
+        for (name, value) in patch {
+            if value.is_null() {
+                target.remove(&name);
+                continue;
+            }
+
+            let child_path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{path}.{name}")
+            };
+            match target.get_mut(&name) {
+                Some(current) => self.merge_value(current, value, &child_path),
+                None => {
+                    target.insert(name, without_markers(value));
+                }
+            }
+        }
+    }
+
+    fn merge_list_by_key(&self, target: &mut Vec<Value>, patch: Vec<Value>, key: &str, path: &str) {
+        for item in patch {
+            let id = item.get(key).cloned();
+            let position = id
+                .as_ref()
+                .and_then(|id| target.iter().position(|i| i.get(key) == Some(id)));
+
+            match (position, is_deleted(&item)) {
+                (Some(index), true) => {
+                    target.remove(index);
+                }
+                (None, true) => {}
+                (Some(index), false) => {
+                    self.merge_value(&mut target[index], item, &format!("{path}[]"))
+                }
+                (None, false) => target.push(without_markers(item)),
+            }
+        }
+    }
+}
+
+fn is_deleted(value: &Value) -> bool {
+    value.get(DELETE_MARKER).and_then(Value::as_bool) == Some(true)
+}
+
+/// Removes the `null` values and the deleted elements so they do not end up in the result.
+fn without_markers(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(name, value)| !value.is_null() && name != DELETE_MARKER)
+                .map(|(name, value)| (name, without_markers(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .filter(|i| !is_deleted(i))
+                .map(without_markers)
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MergeStrategy, SettingsMerger};
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_objects() {
+        let result = SettingsMerger::default().merge_all([
+            json!({ "product": { "id": "Tumbleweed" }, "localization": { "language": "en_US" } }),
+            json!({ "product": { "registrationCode": "123" }, "localization": { "language": "de_DE" } }),
+        ]);
+        assert_eq!(
+            result,
+            json!({
+                "product": { "id": "Tumbleweed", "registrationCode": "123" },
+                "localization": { "language": "de_DE" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_lists() {
+        let result = SettingsMerger::default().merge_all([
+            json!({
+                "software": { "patterns": ["base"], "packages": ["vim"] },
+                "storage": { "drives": [{ "search": "/dev/sda" }] }
+            }),
+            json!({
+                "software": { "patterns": ["base", "kde"], "packages": ["git"] },
+                "storage": { "drives": [{ "search": "/dev/vda" }] }
+            }),
+        ]);
+        assert_eq!(
+            result,
+            json!({
+                "software": { "patterns": ["base", "kde"], "packages": ["vim", "git"] },
+                "storage": { "drives": [{ "search": "/dev/vda" }] }
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_by_key() {
+        let result = SettingsMerger::default().merge_all([
+            json!({
+                "network": {
+                    "connections": [
+                        { "id": "eth0", "method4": "auto", "addresses": ["10.0.0.1/24"] },
+                        { "id": "eth1", "method4": "auto" }
+                    ]
+                }
+            }),
+            json!({
+                "network": {
+                    "connections": [
+                        { "id": "eth0", "addresses": ["10.0.0.2/24"] },
+                        { "id": "eth1", "$delete": true },
+                        { "id": "wlan0", "method4": "auto" }
+                    ]
+                }
+            }),
+        ]);
+        assert_eq!(
+            result,
+            json!({
+                "network": {
+                    "connections": [
+                        { "id": "eth0", "method4": "auto", "addresses": ["10.0.0.2/24"] },
+                        { "id": "wlan0", "method4": "auto" }
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_user() {
+        let merger = SettingsMerger::default();
+        let base =
+            json!({ "user": { "userName": "jane", "fullName": "Jane Doe", "password": "1" } });
+
+        let same_user = merger.merge_all([
+            base.clone(),
+            json!({ "user": { "userName": "jane", "password": "2" } }),
+        ]);
+        assert_eq!(
+            same_user,
+            json!({ "user": { "userName": "jane", "fullName": "Jane Doe", "password": "2" } })
+        );
+
+        let other_user = merger.merge_all([
+            base,
+            json!({ "user": { "userName": "john", "password": "3" } }),
+        ]);
+        assert_eq!(
+            other_user,
+            json!({ "user": { "userName": "john", "password": "3" } })
+        );
+    }
+
+    #[test]
+    fn test_delete() {
+        let result = SettingsMerger::default().merge_all([
+            json!({ "product": { "id": "SLES", "registrationCode": "123" }, "hostname": { "static": "a" } }),
+            json!({ "product": { "registrationCode": null }, "hostname": null, "bootloader": null }),
+        ]);
+        assert_eq!(result, json!({ "product": { "id": "SLES" } }));
+    }
+
+    #[test]
+    fn test_custom_strategy() {
+        let merger =
+            SettingsMerger::default().with_strategy("software.packages", MergeStrategy::Replace);
+        let result = merger.merge_all([
+            json!({ "software": { "packages": ["vim"] } }),
+            json!({ "software": { "packages": ["git"] } }),
+        ]);
+        assert_eq!(result, json!({ "software": { "packages": ["git"] } }));
+    }
+}
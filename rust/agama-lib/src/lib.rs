@@ -43,12 +43,14 @@
 //!
 //! As said, those modules might implement additional stuff, like specific types, clients, etc.
 
+pub mod api_spec;
 pub mod auth;
 pub mod bootloader;
 pub mod context;
 pub mod error;
 pub mod file_source;
 pub mod files;
+pub mod hardware;
 pub mod hostname;
 pub mod http;
 pub mod install_settings;
@@ -70,7 +72,9 @@ pub mod software;
 pub mod storage;
 mod store;
 pub mod users;
-pub use store::Store;
+pub use store::{
+    SectionDiff, SettingChange, Store, StoreDiff, StoreReport, StoreSection, StoreSideEffect,
+};
 pub mod utils;
 pub use agama_utils::{dbus, openapi};
 
@@ -37,7 +37,7 @@ const DEFAULT_COMMANDS: [(&str, &str); 2] = [
     ("rpm -qa", "rpm-qa"),
 ];
 
-const DEFAULT_PATHS: [&str; 16] = [
+const DEFAULT_PATHS: [&str; 18] = [
     // logs
     "/var/log/build",
     "/var/log/YaST2",
@@ -49,8 +49,11 @@ const DEFAULT_PATHS: [&str; 16] = [
     "/var/log/messages",
     "/var/log/boot.msg",
     "/var/log/udev.log",
+    "/var/log/agama/audit.log",
     "/run/agama/dbus.log",
     "/run/agama/inst-scripts",
+    // downloads (URL, digest and validators of each cached file)
+    "/run/agama/cache/index",
     // config
     "/etc/install.inf",
     "/etc/os-release",
@@ -58,6 +61,9 @@ const DEFAULT_PATHS: [&str; 16] = [
     "/.packages.root",
 ];
 
+// copies of the downloaded files, only included on request as they might contain secrets
+const CACHED_FILES_PATH: &str = "/run/agama/cache/objects";
+
 const DEFAULT_RESULT: &str = "/run/agama/agama-logs";
 // what compression is used by default:
 // (<compression as distinguished by tar>, <an extension for resulting archive>)
@@ -278,10 +284,16 @@ pub fn set_archive_permissions(archive: PathBuf) -> io::Result<()> {
 }
 
 /// Handler for the "agama logs store" subcommand
-pub fn store() -> Result<PathBuf, LogsError> {
+///
+/// * `cached_files`: whether to include the content of the downloaded files (e.g., the profiles).
+///   They are identified by the digest recorded in the cache index.
+pub fn store(cached_files: bool) -> Result<PathBuf, LogsError> {
     // preparation, e.g. in later features some log commands can be added / excluded per users request or
     let commands = log_commands();
-    let paths = log_paths();
+    let mut paths = log_paths();
+    if cached_files {
+        paths.push(CACHED_FILES_PATH.to_string());
+    }
     let destination = DEFAULT_RESULT;
     let result = format!("{}.{}", destination, DEFAULT_COMPRESSION.1);
 
@@ -78,9 +78,21 @@ impl ManagerHTTPClient {
     /// will be added according to the compression type found in the response
     ///
     /// Returns path to logs
-    pub async fn store(&self, path: &Path) -> Result<PathBuf, ManagerHTTPClientError> {
+    ///
+    /// * `path`: destination file.
+    /// * `cached_files`: whether to include the content of the downloaded files.
+    pub async fn store(
+        &self,
+        path: &Path,
+        cached_files: bool,
+    ) -> Result<PathBuf, ManagerHTTPClientError> {
         // 1) response with logs
-        let response = self.client.get_raw("/manager/logs/store").await?;
+        let url = if cached_files {
+            "/manager/logs/store?cachedFiles=true"
+        } else {
+            "/manager/logs/store"
+        };
+        let response = self.client.get_raw(url).await?;
 
         // 2) find out the destination file name
         let ext = &response.headers().get(CONTENT_ENCODING).ok_or(
@@ -152,6 +152,7 @@ pub struct Monitor {
     // Channel to send updates.
     updates: broadcast::Sender<MonitorStatus>,
     status: MonitorStatus,
+    http_client: BaseHTTPClient,
     ws_client: WebSocketClient,
 }
 
@@ -180,12 +181,15 @@ impl Monitor {
             updates: updates.clone(),
         };
 
-        let status = MonitorStatusReader::with_client(http_client).read().await?;
+        let status = MonitorStatusReader::with_client(http_client.clone())
+            .read()
+            .await?;
 
         let mut monitor = Monitor {
             status,
             updates,
             commands: commands_rx,
+            http_client,
             ws_client: websocket_client,
         };
 
@@ -201,12 +205,28 @@ impl Monitor {
                     self.handle_command(cmd);
                 }
                 Ok(event) = self.ws_client.receive() => {
-                    self.handle_event(event);
+                    if matches!(event.payload, EventPayload::EventsGap { .. }) {
+                        self.reload_status().await;
+                    } else {
+                        self.handle_event(event);
+                    }
                 }
             }
         }
     }
 
+    /// Reads the whole status again (e.g., after missing some events).
+    async fn reload_status(&mut self) {
+        let reader = MonitorStatusReader::with_client(self.http_client.clone());
+        match reader.read().await {
+            Ok(status) => {
+                self.status = status;
+                let _ = self.updates.send(self.status.clone());
+            }
+            Err(error) => log::warn!("Could not read the status: {error}"),
+        }
+    }
+
     /// Handle commands from the client.
     ///
     /// * `command`: command to execute.
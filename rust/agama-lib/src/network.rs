@@ -24,8 +24,9 @@ mod client;
 mod store;
 
 pub use agama_network::{
-    error, model, settings, types, Action, Adapter, NetworkAdapterError, NetworkManagerAdapter,
-    NetworkSystem, NetworkSystemClient, NetworkSystemError,
+    connectivity, error, model, settings, types, Action, Adapter, KeyfileAdapter, MemoryAdapter,
+    NetworkAdapterError, NetworkManagerAdapter, NetworkSystem, NetworkSystemClient,
+    NetworkSystemError, Scenario, ScenarioError, ScenarioStep,
 };
 pub use client::{NetworkClient, NetworkClientError};
 pub use settings::NetworkSettings;
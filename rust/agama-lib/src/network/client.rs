@@ -87,6 +87,16 @@ impl NetworkClient {
         Ok(())
     }
 
+    /// Removes a network connection
+    pub async fn remove_connection(&self, id: &str) -> Result<(), NetworkClientError> {
+        let encoded_id = encode(id);
+        self.client
+            .delete_void(format!("/network/connections/{encoded_id}").as_str())
+            .await?;
+
+        Ok(())
+    }
+
     /// Returns an array of network connections
     pub async fn apply(&self) -> Result<(), NetworkClientError> {
         // trying to be tricky here. If something breaks then we need a put method on
@@ -61,6 +61,16 @@ impl NetworkStore {
 
         Ok(())
     }
+
+    /// Restores the given settings, removing the connections that are not included.
+    pub async fn restore(&self, settings: &NetworkSettings) -> NetworkStoreResult<()> {
+        for conn in self.network_client.connections().await? {
+            if find_connection(&conn.id, &settings.connections).is_none() {
+                self.network_client.remove_connection(&conn.id).await?;
+            }
+        }
+        self.store(settings).await
+    }
 }
 
 /// Returns the list of connections in the order they should be written to the D-Bus service.
@@ -104,6 +114,16 @@ fn add_ordered_connection(
         }
     }
 
+    if let Some(ports) = conn.ovs.as_ref().and_then(|o| o.ports()) {
+        for port in ports {
+            if let Some(conn) = find_connection(port, conns) {
+                add_ordered_connection(conn, conns, ordered);
+            } else if !ordered.contains(port) {
+                ordered.push(port.clone());
+            }
+        }
+    }
+
     if !ordered.contains(&conn.id) {
         ordered.push(conn.id.to_owned())
     }
@@ -129,7 +149,10 @@ fn default_connection(id: &str) -> NetworkConnection {
 #[cfg(test)]
 mod tests {
     use super::ordered_connections;
-    use crate::network::settings::{BondSettings, BridgeSettings, NetworkConnection};
+    use crate::network::settings::{
+        BondSettings, BridgeSettings, NetworkConnection, OvsBridgeSettings, OvsPortSettings,
+        OvsSettings,
+    };
 
     #[test]
     fn test_ordered_connections() {
@@ -190,4 +213,35 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_ordered_ovs_connections() {
+        let bridge = NetworkConnection {
+            id: "ovs-br0".to_string(),
+            ovs: Some(OvsSettings::Bridge(OvsBridgeSettings {
+                ports: vec!["ovs-port0".to_string()],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let port = NetworkConnection {
+            id: "ovs-port0".to_string(),
+            ovs: Some(OvsSettings::Port(OvsPortSettings {
+                ports: vec!["eth0".to_string()],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        let conns = vec![bridge, port];
+        let ordered = ordered_connections(&conns);
+        assert_eq!(
+            ordered,
+            vec![
+                "eth0".to_string(),
+                "ovs-port0".to_string(),
+                "ovs-br0".to_string()
+            ]
+        );
+    }
 }
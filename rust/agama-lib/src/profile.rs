@@ -22,12 +22,24 @@ use crate::error::ProfileError;
 use anyhow::Context;
 use log::info;
 use serde_json;
-use std::{fs, io::Write, path::Path, process::Command};
-use tempfile::{tempdir, TempDir};
+use std::{fs, path::Path, sync::Arc};
+use tempfile::TempDir;
 use url::Url;
 
+use crate::{
+    hardware::HardwareReader,
+    utils::jsonnet::{self, JsonnetConfig, VirtualFile},
+};
+
+pub mod hardware;
 pub mod http_client;
+mod includes;
+pub use hardware::{HardwareProvider, StaticHardwareProvider};
 pub use http_client::ProfileHTTPClient;
+pub use includes::IncludeResolver;
+
+/// Helpers to search the hardware tree (see [ProfileEvaluator]).
+const HELPERS: &str = include_str!("../../share/agama.libsonnet");
 
 /// Downloads and converts autoyast profile.
 pub struct AutoyastProfileImporter {
@@ -169,66 +181,194 @@ impl ProfileValidator {
 
 /// Evaluates a profile
 ///
-/// Evaluating a profile means injecting the hardware information (coming from D-Bus)
-/// and running the jsonnet code to generate a plain JSON file. For this struct to
-/// work, the `/usr/bin/jsonnet` command must be available.
-pub struct ProfileEvaluator {}
+/// Evaluating a profile means injecting the hardware information and running the Jsonnet code
+/// to generate a plain JSON file. The hardware information is collected by a [HardwareProvider]
+/// and it is available as the `hw` external variable (`std.extVar('hw')`).
+///
+/// For compatibility, profiles can also import the `hw.libsonnet` file, which contains the
+/// helpers from `agama.libsonnet` and the `lshw -json` output. The `lshw` output is only
+/// requested to the provider when a profile imports that file.
+///
+/// ```
+/// # use agama_lib::profile::{ProfileEvaluator, StaticHardwareProvider};
+/// # use serde_json::json;
+/// let provider = StaticHardwareProvider::new(json!({ "memory": { "size": 8589934592_u64 } }));
+/// let evaluator = ProfileEvaluator::new(provider);
+/// let profile = r#"
+///   local hw = std.extVar('hw');
+///   { product: { id: if hw.memory.size < 8e9 then 'MicroOS' else 'Tumbleweed' } }
+/// "#;
+/// let json = evaluator.evaluate_string(profile).unwrap();
+/// assert!(json.contains("Tumbleweed"));
+/// ```
+pub struct ProfileEvaluator {
+    provider: Arc<dyn HardwareProvider>,
+}
+
+impl Default for ProfileEvaluator {
+    fn default() -> Self {
+        Self::new(HardwareReader::from_system())
+    }
+}
 
 impl ProfileEvaluator {
-    // TODO: if we want the web API to distinguish 400 from 500
-    // we should use a structured error here
-    pub fn evaluate(&self, profile_path: &Path) -> anyhow::Result<String> {
-        let dir = tempdir()?;
-        let working_path = dir.path().join("profile.jsonnet");
-        fs::copy(profile_path, working_path)?;
-        self.evaluate_profile_jsonnet(&dir)
-    }
-
-    pub fn evaluate_string(&self, profile: &str) -> anyhow::Result<String> {
-        let dir = tempdir()?;
-        let working_path = dir.path().join("profile.jsonnet");
-        fs::write(working_path, profile)?;
-        self.evaluate_profile_jsonnet(&dir)
-    }
-
-    fn evaluate_profile_jsonnet(&self, dir: &TempDir) -> anyhow::Result<String> {
-        let hwinfo_path = dir.path().join("hw.libsonnet");
-        self.write_hwinfo(&hwinfo_path)
-            .context("Failed to read system's hardware information")?;
-
-        let result = Command::new("/usr/bin/jsonnet")
-            .arg("profile.jsonnet")
-            .current_dir(dir)
-            .output()
-            .context("Failed to run jsonnet")?;
-        if !result.status.success() {
-            let message =
-                String::from_utf8(result.stderr).context("Invalid UTF-8 sequence from jsonnet")?;
-            return Err(ProfileError::EvaluationError(message).into());
+    /// Builds an evaluator which uses the given hardware provider.
+    pub fn new<T: HardwareProvider + 'static>(provider: T) -> Self {
+        Self {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Evaluates a profile file.
+    ///
+    /// Relative imports are resolved from the directory containing the profile.
+    pub fn evaluate(&self, profile_path: &Path) -> Result<String, ProfileError> {
+        let profile = fs::read_to_string(profile_path)?;
+        self.evaluate_jsonnet(&profile, &profile_path.to_string_lossy())
+    }
+
+    /// Evaluates a profile from a string.
+    pub fn evaluate_string(&self, profile: &str) -> Result<String, ProfileError> {
+        self.evaluate_jsonnet(profile, "profile.jsonnet")
+    }
+
+    /// Evaluates a profile from a string, defining additional external variables.
+    ///
+    /// * `profile`: profile content.
+    /// * `file`: file name, used to report errors and to resolve relative imports.
+    /// * `vars`: external variables (available through `std.extVar`).
+    pub fn evaluate_with_vars(
+        &self,
+        profile: &str,
+        file: &str,
+        vars: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<String, ProfileError> {
+        let mut config = self.jsonnet_config()?;
+        for (name, value) in vars {
+            config.ext_vars.insert(name.clone(), value.clone());
         }
-        let output = String::from_utf8(result.stdout)
-            .context("Invalid UTF-8 sequence from jsonnet stdout")?;
-        Ok(output)
-    }
-
-    // Write the hardware information in JSON format to a given path and also helpers to help with it
-    //
-    // TODO: we need a better way to generate this information, as lshw and hwinfo are not usable
-    // out of the box.
-    fn write_hwinfo(&self, path: &Path) -> anyhow::Result<()> {
-        let result = Command::new("/usr/sbin/lshw")
-            .args(["-json"])
-            .output()
-            .context("Failed to run lshw")?;
-        let helpers = fs::read_to_string("share/agama.libsonnet")
-            .or_else(|_| fs::read_to_string("/usr/share/agama-cli/agama.libsonnet"))
-            .context("Failed to read agama.libsonnet")?;
-        let mut file = fs::File::create(path)?;
-        file.write_all(b"{\n")?;
-        file.write_all(helpers.as_bytes())?;
-        file.write_all(b"\n\"lshw\":\n")?;
-        file.write_all(&result.stdout)?;
-        file.write_all(b"\n}")?;
-        Ok(())
+        let output = jsonnet::evaluate(profile, file, &config)?;
+        Ok(serde_json::to_string_pretty(&output)?)
+    }
+
+    fn evaluate_jsonnet(&self, profile: &str, file: &str) -> Result<String, ProfileError> {
+        let config = self.jsonnet_config()?;
+        let output = jsonnet::evaluate(profile, file, &config)?;
+        Ok(serde_json::to_string_pretty(&output)?)
+    }
+
+    fn jsonnet_config(&self) -> Result<JsonnetConfig, ProfileError> {
+        let mut config = JsonnetConfig::default();
+        config
+            .ext_vars
+            .insert("hw".to_string(), self.provider.hardware()?);
+
+        let provider = Arc::clone(&self.provider);
+        let hw_libsonnet = move || {
+            let lshw = provider.lshw().map_err(|e| e.to_string())?;
+            Ok(format!(
+                "{{\n{HELPERS}\nlshw: {lshw},\nhw: std.extVar('hw'),\n}}"
+            ))
+        };
+        config.files.insert(
+            "hw.libsonnet".to_string(),
+            VirtualFile::Lazy(Arc::new(hw_libsonnet)),
+        );
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProfileEvaluator, StaticHardwareProvider};
+    use crate::{error::ProfileError, hardware::HardwareReader};
+    use serde_json::json;
+    use std::path::Path;
+
+    fn evaluator() -> ProfileEvaluator {
+        let hardware = json!({
+            "memory": { "size": 4294967296_u64 },
+            "disks": [
+                { "device": "/dev/vda", "size": 10737418240_u64 },
+                { "device": "/dev/vdb", "size": 21474836480_u64 }
+            ]
+        });
+        let lshw = json!({
+            "id": "computer",
+            "class": "system",
+            "children": [{
+                "id": "core",
+                "class": "bus",
+                "children": [
+                    { "id": "memory", "class": "memory", "size": 4294967296_u64 },
+                    {
+                        "id": "pci",
+                        "class": "bridge",
+                        "businfo": "pci@0000:00:00.0",
+                        "children": [
+                            { "id": "disk:0", "class": "disk", "logicalname": "/dev/vda", "size": 10737418240_u64 },
+                            { "id": "disk:1", "class": "disk", "logicalname": "/dev/vdb", "size": 21474836480_u64 }
+                        ]
+                    }
+                ]
+            }]
+        });
+        ProfileEvaluator::new(StaticHardwareProvider::new(hardware).with_lshw(lshw))
+    }
+
+    #[test]
+    fn test_evaluate_with_helpers() {
+        let profile = r#"
+          local agama = import 'hw.libsonnet';
+          local disks = std.sort(agama.selectByClass(agama.lshw, 'disk'), function(d) -d.size);
+          {
+            product: {
+              id: if agama.findByID(agama.lshw, 'memory').size < 8e9 then 'MicroOS' else 'Tumbleweed'
+            },
+            storage: { drives: [{ search: disks[0].logicalname }] }
+          }
+        "#;
+        let output = evaluator().evaluate_string(profile).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "product": { "id": "MicroOS" },
+                "storage": { "drives": [{ "search": "/dev/vdb" }] }
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_with_recorded_hardware() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hardware/qemu-uefi");
+        let evaluator = ProfileEvaluator::new(HardwareReader::from_dir(path));
+        let profile = r#"
+          local hw = std.extVar('hw');
+          {
+            bootloader: { stopOnBootMenu: hw.firmware == 'bios' },
+            storage: { drives: [{ search: d.device } for d in hw.disks if !d.removable] }
+          }
+        "#;
+        let output = evaluator.evaluate_string(profile).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "bootloader": { "stopOnBootMenu": false },
+                "storage": { "drives": [{ "search": "/dev/vda" }] }
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_error() {
+        let profile = "{\n  product: std.extVar('hw').unknown,\n}";
+        let error = evaluator().evaluate_string(profile).unwrap_err();
+        let ProfileError::EvaluationError(error) = error else {
+            panic!("Unexpected error: {error}");
+        };
+        let location = error.location().unwrap();
+        assert_eq!((location.line, location.column), (2, 12));
     }
 }
@@ -0,0 +1,90 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Hardware information for the profiles.
+//!
+//! The evaluator injects the hardware information into the profiles as the `hw` external
+//! variable (`std.extVar('hw')`). Additionally, the `hw.libsonnet` file contains the output of
+//! `lshw -json`, which is only read when a profile imports that file. The information is collected by a [HardwareProvider], so it can be replaced
+//! (e.g., in tests). By default, it uses the [HardwareReader].
+
+use crate::{error::ProfileError, hardware::HardwareReader};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// Collects the hardware information to inject into the profiles.
+pub trait HardwareProvider: Send + Sync {
+    /// Returns the hardware information as a JSON object.
+    fn hardware(&self) -> Result<Value, ProfileError>;
+
+    /// Returns the `lshw -json` output.
+    fn lshw(&self) -> Result<Value, ProfileError>;
+}
+
+/// Provider that always returns the same information.
+///
+/// Unless it is set with [StaticHardwareProvider::with_lshw], the `lshw` output is read from
+/// the underlying system the first time that it is needed and it is reused afterwards.
+pub struct StaticHardwareProvider {
+    hardware: Value,
+    lshw: OnceLock<Value>,
+}
+
+impl StaticHardwareProvider {
+    /// Builds a provider which returns the given hardware information.
+    pub fn new(hardware: Value) -> Self {
+        Self {
+            hardware,
+            lshw: OnceLock::new(),
+        }
+    }
+
+    /// Sets the `lshw -json` output to use.
+    pub fn with_lshw(self, lshw: Value) -> Self {
+        Self {
+            lshw: OnceLock::from(lshw),
+            ..self
+        }
+    }
+}
+
+impl HardwareProvider for StaticHardwareProvider {
+    fn hardware(&self) -> Result<Value, ProfileError> {
+        Ok(self.hardware.clone())
+    }
+
+    fn lshw(&self) -> Result<Value, ProfileError> {
+        if let Some(lshw) = self.lshw.get() {
+            return Ok(lshw.clone());
+        }
+        let lshw = HardwareReader::from_system().lshw()?;
+        Ok(self.lshw.get_or_init(|| lshw).clone())
+    }
+}
+
+impl HardwareProvider for HardwareReader {
+    fn hardware(&self) -> Result<Value, ProfileError> {
+        Ok(serde_json::to_value(self.read()?)?)
+    }
+
+    fn lshw(&self) -> Result<Value, ProfileError> {
+        Ok(HardwareReader::lshw(self)?)
+    }
+}
@@ -0,0 +1,424 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Resolves the references to other fragments in a profile.
+//!
+//! Any object in the profile can include one or several fragments (JSON or Jsonnet) using the
+//! `$include` (or `$ref`) key. The URLs are resolved against the location of the profile (see
+//! [InstallationContext]) and downloaded using [Transfer], so all the supported schemes
+//! (`label://`, `device://`, `hd://`, etc.) work.
+//!
+//! ```json
+//! {
+//!   "product": { "id": "Tumbleweed" },
+//!   "network": { "$include": "common/network.json" },
+//!   "user": {
+//!     "$include": { "url": "common/user.jsonnet", "vars": { "userName": "jane" } },
+//!     "password": "nots3cr3t"
+//!   }
+//! }
+//! ```
+//!
+//! The fragments are merged in order using the [SettingsMerger] and the rest of the keys of the
+//! object are merged on top of them. When an element of a list includes a fragment which is a
+//! list, its elements are inserted in place.
+//!
+//! The variables (`vars`) are available to Jsonnet fragments through `std.extVar` and they are
+//! inherited by the nested includes. JSON fragments are included as they are.
+
+use fluent_uri::{Uri, UriRef};
+use serde_json::{Map, Value};
+
+use crate::{
+    context::InstallationContext,
+    error::ProfileError,
+    install_settings::SettingsMerger,
+    utils::{FileFormat, Transfer, TransferOptions},
+};
+
+use super::ProfileEvaluator;
+
+const INCLUDE_KEYS: [&str; 2] = ["$include", "$ref"];
+
+/// Fragment to include.
+struct Include {
+    url: String,
+    vars: Map<String, Value>,
+}
+
+/// Resolves the `$include` and `$ref` references of a profile.
+pub struct IncludeResolver {
+    options: TransferOptions,
+    evaluator: ProfileEvaluator,
+    merger: SettingsMerger,
+}
+
+impl IncludeResolver {
+    /// Builds a new resolver.
+    ///
+    /// * `options`: options to download the fragments.
+    /// * `evaluator`: evaluator for the Jsonnet fragments. It should use the hardware
+    ///   information of the system to install, which is not necessarily the local one.
+    pub fn new(options: TransferOptions, evaluator: ProfileEvaluator) -> Self {
+        Self {
+            options,
+            evaluator,
+            merger: SettingsMerger::default(),
+        }
+    }
+
+    /// Returns the profile with all the references resolved.
+    ///
+    /// * `profile`: profile to process.
+    /// * `context`: context of the profile, used to resolve relative URLs.
+    pub fn resolve(
+        &self,
+        profile: Value,
+        context: &InstallationContext,
+    ) -> Result<Value, ProfileError> {
+        let mut stack = vec![context.source.to_string()];
+        self.resolve_value(profile, &context.source, &mut stack, &Map::new(), "")
+    }
+
+    fn resolve_value(
+        &self,
+        value: Value,
+        base: &Uri<String>,
+        stack: &mut Vec<String>,
+        vars: &Map<String, Value>,
+        path: &str,
+    ) -> Result<Value, ProfileError> {
+        match value {
+            Value::Object(mut map) => {
+                let includes = match take_includes(&mut map)? {
+                    Some(includes) => includes,
+                    None => {
+                        let mut resolved = Map::new();
+                        for (name, value) in map {
+                            let child_path = child_path(path, &name);
+                            let value =
+                                self.resolve_value(value, base, stack, vars, &child_path)?;
+                            resolved.insert(name, value);
+                        }
+                        return Ok(Value::Object(resolved));
+                    }
+                };
+
+                let mut result = Value::Object(Map::new());
+                for include in includes {
+                    let mut include_vars = vars.clone();
+                    include_vars.extend(include.vars);
+                    let fragment = self.include(&include.url, base, stack, &include_vars, path)?;
+                    self.merger.merge_at(&mut result, fragment, path);
+                }
+
+                if !map.is_empty() {
+                    let rest = self.resolve_value(Value::Object(map), base, stack, vars, path)?;
+                    self.merger.merge_at(&mut result, rest, path);
+                }
+                Ok(result)
+            }
+            Value::Array(items) => {
+                let mut resolved = vec![];
+                for item in items {
+                    let is_include = is_include(&item);
+                    match self.resolve_value(item, base, stack, vars, path)? {
+                        Value::Array(included) if is_include => resolved.extend(included),
+                        other => resolved.push(other),
+                    }
+                }
+                Ok(Value::Array(resolved))
+            }
+            other => Ok(other),
+        }
+    }
+
+    /// Downloads and resolves a fragment.
+    fn include(
+        &self,
+        url: &str,
+        base: &Uri<String>,
+        stack: &mut Vec<String>,
+        vars: &Map<String, Value>,
+        path: &str,
+    ) -> Result<Value, ProfileError> {
+        let url = resolve_url(url, base)?;
+        let url_str = url.to_string();
+        if stack.contains(&url_str) {
+            stack.push(url_str);
+            return Err(ProfileError::IncludeCycle(stack.join(" -> ")));
+        }
+
+        let mut content: Vec<u8> = vec![];
+        Transfer::new(self.options.clone())
+            .fetch(&url_str, &mut content)
+            .map_err(|e| ProfileError::IncludeUnreachable(url_str.clone(), e))?;
+        let content = String::from_utf8(content)
+            .map_err(|_| ProfileError::InvalidInclude(format!("{url_str} is not UTF-8 text")))?;
+
+        let fragment: Value = match FileFormat::from_string(&content) {
+            FileFormat::Json => serde_json::from_str(&content)?,
+            FileFormat::Jsonnet => {
+                let file = if url.scheme().as_str() == "file" {
+                    url.path().as_str().to_string()
+                } else {
+                    url_str.clone()
+                };
+                let json = self.evaluator.evaluate_with_vars(&content, &file, vars)?;
+                serde_json::from_str(&json)?
+            }
+            FileFormat::Unknown => {
+                return Err(ProfileError::InvalidInclude(format!(
+                    "{url_str} is not a JSON or Jsonnet file"
+                )))
+            }
+        };
+
+        stack.push(url_str);
+        let resolved = self.resolve_value(fragment, &url, stack, vars, path);
+        stack.pop();
+        resolved
+    }
+}
+
+/// Removes the include keys from the object and returns the fragments to include.
+fn take_includes(map: &mut Map<String, Value>) -> Result<Option<Vec<Include>>, ProfileError> {
+    let mut directives = INCLUDE_KEYS.iter().filter_map(|k| map.remove(*k));
+    let Some(directive) = directives.next() else {
+        return Ok(None);
+    };
+    if directives.next().is_some() {
+        return Err(ProfileError::InvalidInclude(
+            "\"$include\" and \"$ref\" cannot be used in the same object".to_string(),
+        ));
+    }
+
+    let items = match directive {
+        Value::Array(items) => items,
+        other => vec![other],
+    };
+    items
+        .into_iter()
+        .map(|item| match item {
+            Value::String(url) => Ok(Include {
+                url,
+                vars: Map::new(),
+            }),
+            Value::Object(mut include) => {
+                let url = include
+                    .remove("url")
+                    .and_then(|u| u.as_str().map(String::from))
+                    .ok_or(ProfileError::InvalidInclude(
+                        "missing \"url\" in the include".to_string(),
+                    ))?;
+                let vars = match include.remove("vars") {
+                    Some(Value::Object(vars)) => vars,
+                    None => Map::new(),
+                    Some(_) => {
+                        return Err(ProfileError::InvalidInclude(format!(
+                            "the \"vars\" of {url} must be an object"
+                        )))
+                    }
+                };
+                Ok(Include { url, vars })
+            }
+            other => Err(ProfileError::InvalidInclude(format!(
+                "unexpected value {other}"
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+fn is_include(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|m| INCLUDE_KEYS.iter().any(|k| m.contains_key(*k)))
+}
+
+fn resolve_url(url: &str, base: &Uri<String>) -> Result<Uri<String>, ProfileError> {
+    let url_ref = UriRef::parse(url)
+        .map_err(|e| ProfileError::InvalidIncludeUrl(url.to_string(), e.to_string()))?;
+    url_ref
+        .resolve_against(base)
+        .map_err(|e| ProfileError::InvalidIncludeUrl(url.to_string(), e.to_string()))
+}
+
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncludeResolver;
+    use crate::{
+        context::InstallationContext,
+        error::ProfileError,
+        profile::{ProfileEvaluator, StaticHardwareProvider},
+        utils::TransferOptions,
+    };
+    use serde_json::{json, Value};
+    use std::{fs, path::Path};
+    use tempfile::TempDir;
+
+    fn resolver() -> IncludeResolver {
+        let evaluator =
+            ProfileEvaluator::new(StaticHardwareProvider::new(json!({})).with_lshw(json!({})));
+        IncludeResolver::new(TransferOptions::default(), evaluator)
+    }
+
+    fn resolve(dir: &Path, profile: Value) -> Result<Value, ProfileError> {
+        let context =
+            InstallationContext::from_url_str(&format!("file://{}/profile.json", dir.display()))
+                .unwrap();
+        resolver().resolve(profile, &context)
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let tmp_dir = TempDir::with_prefix("includes").unwrap();
+        let dir = tmp_dir.path();
+        fs::create_dir(dir.join("common")).unwrap();
+        fs::write(
+            dir.join("common/network.json"),
+            r#"{ "connections": [{ "id": "eth0", "method4": "auto" }, { "$include": "wifi.json" }] }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("common/wifi.json"),
+            r#"[{ "id": "wlan0", "method4": "auto" }]"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("common/user.jsonnet"),
+            "{ userName: std.extVar('userName'), fullName: std.extVar('fullName') }",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("common/root.json"),
+            r#"{ "password": "123", "sshPublicKey": "${key}" }"#,
+        )
+        .unwrap();
+
+        let profile = json!({
+            "product": { "id": "Tumbleweed" },
+            "network": { "$include": "common/network.json" },
+            "user": {
+                "$include": {
+                    "url": "common/user.jsonnet",
+                    "vars": { "userName": "jane", "fullName": "Jane Doe" }
+                },
+                "password": "nots3cr3t"
+            },
+            "root": { "$ref": "common/root.json" }
+        });
+
+        let resolved = resolve(dir, profile).unwrap();
+        assert_eq!(
+            resolved,
+            json!({
+                "product": { "id": "Tumbleweed" },
+                "network": {
+                    "connections": [
+                        { "id": "eth0", "method4": "auto" },
+                        { "id": "wlan0", "method4": "auto" }
+                    ]
+                },
+                "user": { "userName": "jane", "fullName": "Jane Doe", "password": "nots3cr3t" },
+                "root": { "password": "123", "sshPublicKey": "${key}" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_multiple_includes() {
+        let tmp_dir = TempDir::with_prefix("includes").unwrap();
+        let dir = tmp_dir.path();
+        fs::write(
+            dir.join("a.json"),
+            r#"{ "patterns": ["base"], "packages": ["vim"] }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("b.json"), r#"{ "patterns": ["kde"] }"#).unwrap();
+
+        let profile =
+            json!({ "software": { "$include": ["a.json", "b.json"], "packages": ["git"] } });
+        let resolved = resolve(dir, profile).unwrap();
+        assert_eq!(
+            resolved,
+            json!({ "software": { "patterns": ["base", "kde"], "packages": ["vim", "git"] } })
+        );
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let tmp_dir = TempDir::with_prefix("includes").unwrap();
+        let dir = tmp_dir.path();
+        fs::write(dir.join("a.json"), r#"{ "$include": "b.json" }"#).unwrap();
+        fs::write(dir.join("b.json"), r#"{ "$include": "a.json" }"#).unwrap();
+
+        let error = resolve(dir, json!({ "network": { "$include": "a.json" } })).unwrap_err();
+        let ProfileError::IncludeCycle(chain) = error else {
+            panic!("Unexpected error {error}");
+        };
+        let dir = dir.display();
+        assert_eq!(
+            chain,
+            format!(
+                "file://{dir}/profile.json -> file://{dir}/a.json -> file://{dir}/b.json -> file://{dir}/a.json"
+            )
+        );
+    }
+
+    #[test]
+    fn test_missing_include() {
+        let tmp_dir = TempDir::with_prefix("includes").unwrap();
+        let error = resolve(tmp_dir.path(), json!({ "$include": "missing.json" })).unwrap_err();
+        assert!(matches!(error, ProfileError::IncludeUnreachable(_, _)));
+    }
+
+    #[test]
+    fn test_keep_json_fragments() {
+        let tmp_dir = TempDir::with_prefix("includes").unwrap();
+        let dir = tmp_dir.path();
+        fs::write(
+            dir.join("scripts.json"),
+            r##"{ "post": [{ "name": "${name}", "content": "#!/bin/sh\necho ${name}" }] }"##,
+        )
+        .unwrap();
+
+        let profile = json!({
+            "scripts": { "$include": { "url": "scripts.json", "vars": { "name": "hello" } } }
+        });
+        let resolved = resolve(dir, profile).unwrap();
+        assert_eq!(
+            resolved,
+            json!({
+                "scripts": {
+                    "post": [{ "name": "${name}", "content": "#!/bin/sh\necho ${name}" }]
+                }
+            })
+        );
+    }
+}
@@ -33,11 +33,11 @@ use serde_json::value::RawValue;
 pub struct StorageSettings {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[schema(value_type = String)]
+    #[schema(value_type = Object)]
     pub storage: Option<Box<RawValue>>,
     #[serde(default, rename = "legacyAutoyastStorage")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[schema(value_type = String)]
+    #[schema(value_type = Value)]
     pub storage_autoyast: Option<Box<RawValue>>,
 }
 
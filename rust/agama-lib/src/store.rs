@@ -21,6 +21,9 @@
 //! Load/store the settings from/to the D-Bus services.
 // TODO: quickly explain difference between FooSettings and FooStore, with an example
 
+mod diff;
+pub use diff::{SectionDiff, SettingChange, StoreDiff, StoreSideEffect};
+
 use crate::{
     bootloader::store::{BootloaderStore, BootloaderStoreError},
     files::store::{FilesStore, FilesStoreError},
@@ -90,6 +93,136 @@ pub enum StoreError {
     InvalidStoreContext,
     #[error("Cannot proceed with profile without specified product")]
     MissingProduct,
+    #[error("Could not compare the settings: {0}")]
+    Diff(#[from] serde_json::Error),
+    #[error("{source} (applied: {}; rolled back: {})", .report.applied_names(), .report.rolled_back_names())]
+    RolledBack {
+        source: Box<StoreError>,
+        report: StoreReport,
+    },
+}
+
+/// Sections of the installation settings, in the order they are stored.
+#[derive(Clone, Copy, Debug, PartialEq, strum::Display)]
+#[strum(serialize_all = "camelCase")]
+pub enum StoreSection {
+    Scripts,
+    Questions,
+    Network,
+    Security,
+    #[strum(serialize = "user")]
+    Users,
+    Product,
+    Localization,
+    Software,
+    #[strum(serialize = "iscsi")]
+    ISCSI,
+    #[strum(serialize = "dasd")]
+    DASD,
+    #[strum(serialize = "zfcp")]
+    ZFCP,
+    Storage,
+    Bootloader,
+    Hostname,
+    Files,
+}
+
+/// Summary of a [Store::store] operation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StoreReport {
+    /// Sections that were applied.
+    pub applied: Vec<StoreSection>,
+    /// Section that failed, if any.
+    pub failed: Option<StoreSection>,
+    /// Sections that were restored to their previous values after a failure. It might include
+    /// the failed section, as it could be partially applied.
+    pub rolled_back: Vec<StoreSection>,
+}
+
+impl StoreReport {
+    /// Applied sections that were not rolled back after a failure.
+    pub fn not_rolled_back(&self) -> Vec<StoreSection> {
+        self.applied
+            .iter()
+            .filter(|s| !self.rolled_back.contains(s))
+            .cloned()
+            .collect()
+    }
+
+    /// Names of the applied sections.
+    pub fn applied_names(&self) -> String {
+        Self::join_sections(&self.applied)
+    }
+
+    fn rolled_back_names(&self) -> String {
+        Self::join_sections(&self.rolled_back)
+    }
+
+    fn join_sections(sections: &[StoreSection]) -> String {
+        if sections.is_empty() {
+            return "none".to_string();
+        }
+        sections
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// Keeps track of the sections that are being stored.
+#[derive(Default)]
+struct StoreProgress {
+    applied: Vec<StoreSection>,
+    // Section that is being stored.
+    current: Option<StoreSection>,
+}
+
+impl StoreProgress {
+    fn start(&mut self, section: StoreSection) {
+        self.current = Some(section);
+    }
+
+    fn done(&mut self) {
+        self.applied.extend(self.current.take());
+    }
+}
+
+// Settings of the sections to change, as they were before storing the new ones.
+#[derive(Default)]
+struct Snapshot {
+    settings: InstallSettings,
+    // Sections that could be read.
+    sections: SnapshotSections,
+}
+
+#[derive(Default)]
+struct SnapshotSections(Vec<StoreSection>);
+
+impl SnapshotSections {
+    // Registers the result of reading a section, returning its value if it succeeded.
+    fn read<T, E: std::fmt::Display>(
+        &mut self,
+        section: StoreSection,
+        result: Result<T, E>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.0.push(section);
+                Some(value)
+            }
+            Err(error) => {
+                log::warn!(
+                    "Could not read the {section} settings, they will not be rolled back: {error}"
+                );
+                None
+            }
+        }
+    }
+
+    fn contains(&self, section: &StoreSection) -> bool {
+        self.0.contains(section)
+    }
 }
 
 /// Struct that loads/stores the settings from/to the D-Bus services.
@@ -176,10 +309,169 @@ impl Store {
     /// "config". It causes the storage proposal to be reset. This behavior should be revisited in
     /// the future but it might be the storage service the responsible for dealing with this.
     ///
+    /// Before storing anything, it takes a snapshot of the sections to change. If any section
+    /// fails, that section and the ones that were already applied are restored from the snapshot
+    /// (see [StoreError::RolledBack]). If no section was applied, it returns the original error.
+    ///
+    /// Rolling back is a best-effort process and some changes cannot be undone:
+    ///
+    /// * The pre-scripts that already ran.
+    /// * The iSCSI configuration, as it cannot be read back.
+    /// * The side effects of storing a section, like registering a product or reprobing the
+    ///   storage devices.
+    /// * The sections that could not be read when taking the snapshot.
+    ///
     /// * `settings`: installation settings.
-    pub async fn store(&self, settings: &InstallSettings) -> Result<(), StoreError> {
+    pub async fn store(&self, settings: &InstallSettings) -> Result<StoreReport, StoreError> {
+        let snapshot = self.snapshot(settings).await;
+        let mut progress = StoreProgress::default();
+
+        match self.store_sections(settings, &mut progress).await {
+            Ok(()) => Ok(StoreReport {
+                applied: progress.applied,
+                ..Default::default()
+            }),
+            Err(error) => {
+                // the failed section might be partially applied, so it is restored too
+                let mut to_restore = progress.applied.clone();
+                to_restore.extend(progress.current);
+                let rolled_back = self.rollback(&snapshot, &to_restore).await;
+                if progress.applied.is_empty() {
+                    return Err(error);
+                }
+
+                Err(StoreError::RolledBack {
+                    source: Box::new(error),
+                    report: StoreReport {
+                        applied: progress.applied,
+                        failed: progress.current,
+                        rolled_back,
+                    },
+                })
+            }
+        }
+    }
+
+    // Takes a snapshot of the sections included in the given settings.
+    //
+    // The sections that cannot be read are left out of the snapshot (so they are not rolled
+    // back) instead of preventing the settings from being stored.
+    async fn snapshot(&self, settings: &InstallSettings) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        let current = &mut snapshot.settings;
+
+        if settings.scripts.is_some() {
+            if let Some(scripts) = snapshot
+                .sections
+                .read(StoreSection::Scripts, self.scripts.load().await)
+            {
+                current.scripts = scripts.to_option();
+            }
+        }
+        if settings.questions.is_some() {
+            if let Some(questions) = snapshot
+                .sections
+                .read(StoreSection::Questions, self.questions.load().await)
+            {
+                current.questions = questions;
+            }
+        }
+        if settings.network.is_some() {
+            current.network = snapshot
+                .sections
+                .read(StoreSection::Network, self.network.load().await);
+        }
+        if settings.security.is_some() {
+            if let Some(security) = snapshot
+                .sections
+                .read(StoreSection::Security, self.security.load().await)
+            {
+                current.security = security.to_option();
+            }
+        }
+        if settings.user.is_some() {
+            current.user = snapshot
+                .sections
+                .read(StoreSection::Users, self.users.load().await);
+        }
+        if settings.product.is_some() {
+            current.product = snapshot
+                .sections
+                .read(StoreSection::Product, self.product.load().await);
+        }
+        if settings.localization.is_some() {
+            current.localization = snapshot
+                .sections
+                .read(StoreSection::Localization, self.localization.load().await);
+        }
+        if settings.software.is_some() {
+            if let Some(software) = snapshot
+                .sections
+                .read(StoreSection::Software, self.software.load().await)
+            {
+                current.software = software.to_option();
+            }
+        }
+        if settings.dasd.is_some() {
+            if let Some(dasd) = snapshot
+                .sections
+                .read(StoreSection::DASD, self.dasd.load().await)
+            {
+                current.dasd = dasd;
+            }
+        }
+        if settings.zfcp.is_some() {
+            if let Some(zfcp) = snapshot
+                .sections
+                .read(StoreSection::ZFCP, self.zfcp.load().await)
+            {
+                current.zfcp = zfcp;
+            }
+        }
+        if settings.storage.is_some() || settings.storage_autoyast.is_some() {
+            if let Some(Some(storage)) = snapshot
+                .sections
+                .read(StoreSection::Storage, self.storage.load().await)
+            {
+                current.storage = storage.storage;
+                current.storage_autoyast = storage.storage_autoyast;
+            }
+        }
+        if settings.bootloader.is_some() {
+            if let Some(bootloader) = snapshot
+                .sections
+                .read(StoreSection::Bootloader, self.bootloader.load().await)
+            {
+                current.bootloader = bootloader;
+            }
+        }
+        if settings.hostname.is_some() {
+            current.hostname = snapshot
+                .sections
+                .read(StoreSection::Hostname, self.hostname.load().await);
+        }
+        if settings.files.is_some() {
+            if let Some(files) = snapshot
+                .sections
+                .read(StoreSection::Files, self.files.load().await)
+            {
+                current.files = files;
+            }
+        }
+
+        snapshot
+    }
+
+    // Stores the given settings, keeping track of the sections that were applied.
+    async fn store_sections(
+        &self,
+        settings: &InstallSettings,
+        progress: &mut StoreProgress,
+    ) -> Result<(), StoreError> {
         if let Some(scripts) = &settings.scripts {
+            progress.start(StoreSection::Scripts);
             self.scripts.store(scripts).await?;
+            progress.done();
 
             if scripts.pre.as_ref().is_some_and(|s| !s.is_empty()) {
                 self.run_pre_scripts().await?;
@@ -187,54 +479,74 @@ impl Store {
         }
 
         if let Some(questions) = &settings.questions {
+            progress.start(StoreSection::Questions);
             self.questions.store(questions).await?;
+            progress.done();
         }
 
         if let Some(network) = &settings.network {
+            progress.start(StoreSection::Network);
             self.network.store(network).await?;
+            progress.done();
         }
         // security has to be done before product to allow registration against
         // self-signed RMT
         if let Some(security) = &settings.security {
+            progress.start(StoreSection::Security);
             self.security.store(security).await?;
+            progress.done();
         }
         if let Some(user) = &settings.user {
+            progress.start(StoreSection::Users);
             self.users.store(user).await?;
+            progress.done();
         }
         // order is important here as network can be critical for connection
         // to registration server and selecting product is important for rest
         if let Some(product) = &settings.product {
+            progress.start(StoreSection::Product);
             self.product.store(product).await?;
+            progress.done();
         }
         // here detect if product is properly selected, so later it can be checked
         let is_product_selected = self.detect_selected_product().await?;
         // ordering: localization after product as some product may miss some locales
         if let Some(localization) = &settings.localization {
             Store::ensure_selected_product(is_product_selected)?;
+            progress.start(StoreSection::Localization);
             self.localization.store(localization).await?;
+            progress.done();
         }
         if let Some(software) = &settings.software {
             Store::ensure_selected_product(is_product_selected)?;
+            progress.start(StoreSection::Software);
             self.software.store(software).await?;
+            progress.done();
         }
         let mut dirty_flag_set = false;
         // iscsi has to be done before storage
         if let Some(iscsi) = &settings.iscsi {
             Store::ensure_selected_product(is_product_selected)?;
             dirty_flag_set = true;
-            self.iscsi_client.set_config(iscsi).await?
+            progress.start(StoreSection::ISCSI);
+            self.iscsi_client.set_config(iscsi).await?;
+            progress.done();
         }
         // dasd devices has to be activated before storage
         if let Some(dasd) = &settings.dasd {
             Store::ensure_selected_product(is_product_selected)?;
             dirty_flag_set = true;
-            self.dasd.store(dasd).await?
+            progress.start(StoreSection::DASD);
+            self.dasd.store(dasd).await?;
+            progress.done();
         }
         // zfcp devices has to be activated before storage
         if let Some(zfcp) = &settings.zfcp {
             Store::ensure_selected_product(is_product_selected)?;
             dirty_flag_set = true;
-            self.zfcp.store(zfcp).await?
+            progress.start(StoreSection::ZFCP);
+            self.zfcp.store(zfcp).await?;
+            progress.done();
         }
         // Reprobing storage is not directly done by zFCP, DASD or iSCSI services for a matter of
         // efficiency. For now, clients are expected to explicitly reprobe. It is important to
@@ -247,22 +559,192 @@ impl Store {
 
         if settings.storage.is_some() || settings.storage_autoyast.is_some() {
             Store::ensure_selected_product(is_product_selected)?;
-            self.storage.store(&settings.into()).await?
+            progress.start(StoreSection::Storage);
+            self.storage.store(&settings.into()).await?;
+            progress.done();
         }
         if let Some(bootloader) = &settings.bootloader {
+            progress.start(StoreSection::Bootloader);
             self.bootloader.store(bootloader).await?;
+            progress.done();
         }
         if let Some(hostname) = &settings.hostname {
             Store::ensure_selected_product(is_product_selected)?;
+            progress.start(StoreSection::Hostname);
             self.hostname.store(hostname).await?;
+            progress.done();
         }
         if let Some(files) = &settings.files {
+            progress.start(StoreSection::Files);
             self.files.store(files).await?;
+            progress.done();
         }
 
         Ok(())
     }
 
+    // Restores the given sections from the snapshot, in reverse order.
+    //
+    // It is a best-effort process: the sections that cannot be restored (because they are not
+    // included in the snapshot or the service fails) are skipped. It returns the sections that
+    // were rolled back.
+    async fn rollback(&self, snapshot: &Snapshot, sections: &[StoreSection]) -> Vec<StoreSection> {
+        let mut rolled_back = vec![];
+        for section in sections.iter().rev() {
+            if !snapshot.sections.contains(section) {
+                continue;
+            }
+            match self.restore_section(&snapshot.settings, *section).await {
+                Ok(true) => rolled_back.push(*section),
+                Ok(false) => {}
+                Err(error) => {
+                    log::warn!("Could not roll back the {section} settings: {error}");
+                }
+            }
+        }
+        rolled_back
+    }
+
+    // Restores a single section from the snapshot.
+    //
+    // It returns whether the section was restored.
+    async fn restore_section(
+        &self,
+        snapshot: &InstallSettings,
+        section: StoreSection,
+    ) -> Result<bool, StoreError> {
+        match section {
+            StoreSection::Scripts => match &snapshot.scripts {
+                Some(scripts) => self.scripts.store(scripts).await?,
+                None => self.scripts.store(&Default::default()).await?,
+            },
+            StoreSection::Questions => match &snapshot.questions {
+                Some(questions) => self.questions.store(questions).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Network => match &snapshot.network {
+                Some(network) => self.network.restore(network).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Security => match &snapshot.security {
+                Some(security) => self.security.store(security).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Users => match &snapshot.user {
+                Some(user) => self.users.store(user).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Product => match &snapshot.product {
+                Some(product) if product.id.as_ref().is_some_and(|id| !id.is_empty()) => {
+                    self.product.store(product).await?
+                }
+                _ => return Ok(false),
+            },
+            StoreSection::Localization => match &snapshot.localization {
+                Some(localization) => self.localization.store(localization).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Software => match &snapshot.software {
+                Some(software) => self.software.store(software).await?,
+                None => return Ok(false),
+            },
+            // the iSCSI configuration cannot be read, so it is not included in the snapshot
+            StoreSection::ISCSI => return Ok(false),
+            StoreSection::DASD => match &snapshot.dasd {
+                Some(dasd) => self.dasd.store(dasd).await?,
+                None => return Ok(false),
+            },
+            StoreSection::ZFCP => match &snapshot.zfcp {
+                Some(zfcp) => self.zfcp.store(zfcp).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Storage => {
+                if snapshot.storage.is_none() && snapshot.storage_autoyast.is_none() {
+                    return Ok(false);
+                }
+                self.storage.store(&snapshot.into()).await?
+            }
+            StoreSection::Bootloader => match &snapshot.bootloader {
+                Some(bootloader) => self.bootloader.store(bootloader).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Hostname => match &snapshot.hostname {
+                Some(hostname) => self.hostname.store(hostname).await?,
+                None => return Ok(false),
+            },
+            StoreSection::Files => match &snapshot.files {
+                Some(files) => self.files.store(files).await?,
+                None => self.files.store(&vec![]).await?,
+            },
+        }
+        Ok(true)
+    }
+
+    /// Computes the changes that storing the given settings would introduce, without storing them.
+    ///
+    /// Besides the changes for each section, it reports the side effects of
+    /// [store](Self::store), like running the pre-scripts or failing due to a missing product.
+    ///
+    /// * `settings`: installation settings.
+    pub async fn diff(&self, settings: &InstallSettings) -> Result<StoreDiff, StoreError> {
+        let current = self.load().await?;
+        let sections = diff::diff_settings(&current, settings)?;
+        let mut side_effects = vec![];
+
+        let has_pre_scripts = settings
+            .scripts
+            .as_ref()
+            .and_then(|s| s.pre.as_ref())
+            .is_some_and(|s| !s.is_empty());
+        if has_pre_scripts {
+            side_effects.push(StoreSideEffect::RunPreScripts);
+            let status = self.manager_client.status().await;
+            if status.is_ok_and(|s| s.phase == InstallationPhase::Config) {
+                side_effects.push(StoreSideEffect::Probe);
+            }
+        }
+
+        if settings.iscsi.is_some() || settings.dasd.is_some() || settings.zfcp.is_some() {
+            side_effects.push(StoreSideEffect::ReprobeStorage);
+        }
+
+        let product_requested = settings
+            .product
+            .as_ref()
+            .and_then(|p| p.id.as_ref())
+            .is_some_and(|id| !id.is_empty());
+        if !product_requested && !self.detect_selected_product().await? {
+            let sections = Store::product_dependent_sections(settings);
+            if !sections.is_empty() {
+                side_effects.push(StoreSideEffect::MissingProduct(sections));
+            }
+        }
+
+        Ok(StoreDiff {
+            sections,
+            side_effects,
+        })
+    }
+
+    // Returns the names of the given sections that require a selected product.
+    fn product_dependent_sections(settings: &InstallSettings) -> Vec<String> {
+        let sections = [
+            ("localization", settings.localization.is_some()),
+            ("software", settings.software.is_some()),
+            ("iscsi", settings.iscsi.is_some()),
+            ("dasd", settings.dasd.is_some()),
+            ("zfcp", settings.zfcp.is_some()),
+            ("storage", settings.storage.is_some()),
+            ("legacyAutoyastStorage", settings.storage_autoyast.is_some()),
+            ("hostname", settings.hostname.is_some()),
+        ];
+        sections
+            .into_iter()
+            .filter(|(_, present)| *present)
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
     // Reprobes the storage devices if the system was marked as deprecated.
     async fn reprobe_storage(&self) -> Result<(), StorageStoreError> {
         let storage_client = StorageHTTPClient::new(self.http_client.clone());
@@ -298,3 +780,179 @@ impl Store {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bootloader::model::BootloaderSettings, localization::LocalizationSettings};
+    use httpmock::prelude::*;
+    use httpmock::Method::PATCH;
+    use std::error::Error;
+    use tokio::test; // without this, "error: async functions cannot be used for tests"
+
+    async fn store(mock_server_url: String) -> Result<Store, Box<dyn Error>> {
+        let client = BaseHTTPClient::new(mock_server_url)?;
+        Ok(Store::new(client).await?)
+    }
+
+    fn mock_product(server: &MockServer) {
+        server.mock(|when, then| {
+            when.method(GET).path("/api/software/config");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{ "product": "Tumbleweed" }"#);
+        });
+    }
+
+    fn settings() -> InstallSettings {
+        InstallSettings {
+            localization: Some(LocalizationSettings {
+                language: Some("es_ES.UTF-8".to_owned()),
+                keyboard: Some("es".to_owned()),
+                timezone: Some("Europe/Madrid".to_owned()),
+            }),
+            bootloader: Some(BootloaderSettings {
+                timeout: Some(10),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    async fn test_store_rolls_back_on_failure() -> Result<(), Box<dyn Error>> {
+        let server = MockServer::start();
+        mock_product(&server);
+        server.mock(|when, then| {
+            when.method(GET).path("/api/l10n/config");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{ "locales": ["en_US.UTF-8"], "keymap": "us", "timezone": "Europe/Berlin" }"#);
+        });
+        let bootloader_get_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/bootloader/config");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{ "timeout": 5 }"#);
+        });
+        let l10n_mock = server.mock(|when, then| {
+            when.method(PATCH)
+                .path("/api/l10n/config")
+                .json_body_partial(r#"{ "keymap": "es" }"#);
+            then.status(200);
+        });
+        let l10n_restore_mock = server.mock(|when, then| {
+            when.method(PATCH)
+                .path("/api/l10n/config")
+                .json_body_partial(r#"{ "locales": ["en_US.UTF-8"], "keymap": "us", "timezone": "Europe/Berlin" }"#);
+            then.status(200);
+        });
+        let bootloader_mock = server.mock(|when, then| {
+            when.method(PUT).path("/api/bootloader/config");
+            then.status(500).body("Bootloader error");
+        });
+
+        let store = store(server.url("/api")).await?;
+        let result = store.store(&settings()).await;
+
+        let Err(StoreError::RolledBack { source, report }) = result else {
+            panic!("Unexpected result: {result:?}");
+        };
+        assert!(matches!(*source, StoreError::Bootloader(_)));
+        assert_eq!(report.applied, vec![StoreSection::Localization]);
+        assert_eq!(report.failed, Some(StoreSection::Bootloader));
+        assert_eq!(report.rolled_back, vec![StoreSection::Localization]);
+
+        bootloader_get_mock.assert();
+        l10n_mock.assert();
+        l10n_restore_mock.assert();
+        // the failed section is restored too
+        bootloader_mock.assert_hits(2);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_store_returns_original_error() -> Result<(), Box<dyn Error>> {
+        let server = MockServer::start();
+        mock_product(&server);
+        server.mock(|when, then| {
+            when.method(GET).path("/api/bootloader/config");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{ "timeout": 5 }"#);
+        });
+        let bootloader_restore_mock = server.mock(|when, then| {
+            when.method(PUT)
+                .path("/api/bootloader/config")
+                .json_body_partial(r#"{ "timeout": 5 }"#);
+            then.status(200);
+        });
+        let bootloader_mock = server.mock(|when, then| {
+            when.method(PUT)
+                .path("/api/bootloader/config")
+                .json_body_partial(r#"{ "timeout": 10 }"#);
+            then.status(500).body("Bootloader error");
+        });
+
+        let store = store(server.url("/api")).await?;
+        let settings = InstallSettings {
+            localization: None,
+            ..settings()
+        };
+        let result = store.store(&settings).await;
+
+        assert!(matches!(result, Err(StoreError::Bootloader(_))));
+        bootloader_mock.assert();
+        bootloader_restore_mock.assert();
+        Ok(())
+    }
+
+    #[test]
+    async fn test_store_without_snapshot() -> Result<(), Box<dyn Error>> {
+        let server = MockServer::start();
+        mock_product(&server);
+        server.mock(|when, then| {
+            when.method(GET).path("/api/l10n/config");
+            then.status(500).body("Localization error");
+        });
+        let l10n_mock = server.mock(|when, then| {
+            when.method(PATCH).path("/api/l10n/config");
+            then.status(200);
+        });
+
+        let store = store(server.url("/api")).await?;
+        let settings = InstallSettings {
+            bootloader: None,
+            ..settings()
+        };
+        let report = store.store(&settings).await?;
+
+        assert_eq!(report.applied, vec![StoreSection::Localization]);
+        l10n_mock.assert();
+        Ok(())
+    }
+
+    #[test]
+    async fn test_store_report() {
+        let report = StoreReport {
+            applied: vec![
+                StoreSection::Network,
+                StoreSection::Users,
+                StoreSection::ISCSI,
+            ],
+            failed: Some(StoreSection::Storage),
+            rolled_back: vec![StoreSection::Users, StoreSection::Network],
+        };
+        assert_eq!(report.not_rolled_back(), vec![StoreSection::ISCSI]);
+
+        let error = StoreError::RolledBack {
+            source: Box::new(StoreError::MissingProduct),
+            report,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Cannot proceed with profile without specified product \
+             (applied: network, user, iscsi; rolled back: user, network)"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Computes the changes that storing a profile would introduce.
+//!
+//! The comparison works on the JSON representation of the [InstallSettings], so the paths of the
+//! changes match the keys of the profile (e.g., `network.connections[0].method4`).
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::install_settings::InstallSettings;
+
+/// A single change in the settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingChange {
+    /// Path to the setting, relative to its section (e.g., `connections[0].id`).
+    pub path: String,
+    /// Current value, if any.
+    pub old: Option<Value>,
+    /// Incoming value, if any.
+    pub new: Option<Value>,
+}
+
+/// Changes for a section of the settings ("network", "software", etc.).
+#[derive(Clone, Debug, PartialEq)]
+pub struct SectionDiff {
+    /// Section name, as it appears in the profile.
+    pub name: String,
+    pub changes: Vec<SettingChange>,
+}
+
+/// Side effects that [Store::store](super::Store::store) would trigger.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StoreSideEffect {
+    /// The pre-installation scripts are executed.
+    RunPreScripts,
+    /// The system is probed again because the installer is in the "config" phase.
+    Probe,
+    /// The storage devices are reprobed if iSCSI, DASD or zFCP changes make it needed.
+    ReprobeStorage,
+    /// Storing fails with [StoreError::MissingProduct](super::StoreError::MissingProduct) because
+    /// the given sections require a product and none is selected.
+    MissingProduct(Vec<String>),
+}
+
+impl fmt::Display for StoreSideEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RunPreScripts => write!(f, "Run the pre-installation scripts"),
+            Self::Probe => write!(f, "Probe the system again"),
+            Self::ReprobeStorage => write!(f, "Reprobe the storage devices (if needed)"),
+            Self::MissingProduct(sections) => write!(
+                f,
+                "Fail because no product is selected (required by: {})",
+                sections.join(", ")
+            ),
+        }
+    }
+}
+
+/// Result of comparing the current settings with the incoming ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StoreDiff {
+    /// Sections that would change.
+    pub sections: Vec<SectionDiff>,
+    /// Side effects of storing the settings.
+    pub side_effects: Vec<StoreSideEffect>,
+}
+
+impl StoreDiff {
+    /// Whether storing the settings would not change anything.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty() && self.side_effects.is_empty()
+    }
+}
+
+/// Compares the current settings with the incoming ones, section by section.
+///
+/// Only the sections and keys that are present in the incoming settings are considered. Missing
+/// keys are kept by the services, so they are not reported as removed. Lists are compared item by
+/// item.
+///
+/// * `current`: settings loaded from the services.
+/// * `incoming`: settings to store.
+pub fn diff_settings(
+    current: &InstallSettings,
+    incoming: &InstallSettings,
+) -> Result<Vec<SectionDiff>, serde_json::Error> {
+    let current = serde_json::to_value(current)?;
+    let incoming = serde_json::to_value(incoming)?;
+
+    let Value::Object(incoming) = incoming else {
+        return Ok(vec![]);
+    };
+
+    let mut sections = vec![];
+    for (name, new) in incoming.iter() {
+        let mut changes = vec![];
+        match current.get(name) {
+            Some(old) => diff_values("", old, new, &mut changes),
+            None => changes.push(SettingChange {
+                path: String::new(),
+                old: None,
+                new: Some(new.clone()),
+            }),
+        }
+
+        if !changes.is_empty() {
+            sections.push(SectionDiff {
+                name: name.to_string(),
+                changes,
+            });
+        }
+    }
+
+    Ok(sections)
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<SettingChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, new_value) in new.iter() {
+                let key_path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match old.get(key) {
+                    Some(old_value) => diff_values(&key_path, old_value, new_value, changes),
+                    None => changes.push(SettingChange {
+                        path: key_path,
+                        old: None,
+                        new: Some(new_value.clone()),
+                    }),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let item_path = format!("{path}[{index}]");
+                match (old.get(index), new.get(index)) {
+                    (Some(old_item), Some(new_item)) => {
+                        diff_values(&item_path, old_item, new_item, changes)
+                    }
+                    (old_item, new_item) => changes.push(SettingChange {
+                        path: item_path,
+                        old: old_item.cloned(),
+                        new: new_item.cloned(),
+                    }),
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(SettingChange {
+            path: path.to_string(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::InstallationContext;
+    use serde_json::json;
+
+    fn settings(json: &str) -> InstallSettings {
+        InstallSettings::from_json(json, &InstallationContext::from_env().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_unchanged_settings() {
+        let current = settings(r#"{ "product": { "id": "Tumbleweed" } }"#);
+        let incoming = settings(r#"{ "product": { "id": "Tumbleweed" } }"#);
+
+        assert!(diff_settings(&current, &incoming).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_modified_settings() {
+        let current = settings(
+            r#"{
+                "product": { "id": "Tumbleweed" },
+                "software": { "patterns": ["gnome"] }
+            }"#,
+        );
+        let incoming = settings(
+            r#"{
+                "product": { "id": "SLES", "registrationCode": "123" },
+                "software": { "patterns": ["kde", "base"] }
+            }"#,
+        );
+
+        let sections = diff_settings(&current, &incoming).unwrap();
+        assert_eq!(sections.len(), 2);
+
+        let product = sections.iter().find(|s| s.name == "product").unwrap();
+        assert_eq!(
+            product.changes,
+            vec![
+                SettingChange {
+                    path: "id".to_string(),
+                    old: Some(json!("Tumbleweed")),
+                    new: Some(json!("SLES"))
+                },
+                SettingChange {
+                    path: "registrationCode".to_string(),
+                    old: None,
+                    new: Some(json!("123"))
+                }
+            ]
+        );
+
+        let software = sections.iter().find(|s| s.name == "software").unwrap();
+        assert_eq!(
+            software.changes,
+            vec![
+                SettingChange {
+                    path: "patterns[0]".to_string(),
+                    old: Some(json!("gnome")),
+                    new: Some(json!("kde"))
+                },
+                SettingChange {
+                    path: "patterns[1]".to_string(),
+                    old: None,
+                    new: Some(json!("base"))
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_new_section() {
+        let current = settings(r#"{ "product": { "id": "Tumbleweed" } }"#);
+        let incoming = settings(r#"{ "hostname": { "static": "test" } }"#);
+
+        let sections = diff_settings(&current, &incoming).unwrap();
+        assert_eq!(
+            sections,
+            vec![SectionDiff {
+                name: "hostname".to_string(),
+                changes: vec![SettingChange {
+                    path: "".to_string(),
+                    old: None,
+                    new: Some(json!({ "static": "test" }))
+                }]
+            }]
+        );
+    }
+}
@@ -21,6 +21,7 @@
 //! Utility module for Agama.
 
 mod file_format;
+pub mod jsonnet;
 mod transfer;
 pub mod url;
 
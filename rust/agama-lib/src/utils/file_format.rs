@@ -23,11 +23,12 @@
 //! It implements a simple API to detect the file formats that are relevent for Agama.
 
 use std::{
-    io::Write,
-    path::Path,
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
+    rc::Rc,
 };
 
+use jrsonnet_parser::ParserSettings;
+
 /// Relevant file formats for Agama.
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum FileFormat {
@@ -36,8 +37,6 @@ pub enum FileFormat {
     Unknown,
 }
 
-const JSONNETFMT_BIN: &str = "jsonnetfmt";
-
 impl FileFormat {
     /// Tries to guess the file format from the content of a file.
     pub fn from_file<P: AsRef<Path>>(file_path: P) -> Result<Self, std::io::Error> {
@@ -66,29 +65,13 @@ impl FileFormat {
 
     /// Whether the format is Jsonnet.
     ///
-    /// It tries to process the content with the jsonnetfmt tool and returns `true` if it succeeds.
+    /// It tries to parse the content as Jsonnet and returns `true` if it succeeds.
     fn is_jsonnet(content: &str) -> bool {
-        let child = Command::new(JSONNETFMT_BIN)
-            .args(["-"])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn();
-
-        let Ok(mut child) = child else {
-            return false;
+        let settings = ParserSettings {
+            loc_data: false,
+            file_name: Rc::from(PathBuf::from("profile.jsonnet")),
         };
-
-        let Some(mut stdin) = child.stdin.take() else {
-            return false;
-        };
-
-        stdin
-            .write_all(content.as_bytes())
-            .expect("Failed to write to stdin");
-        drop(stdin);
-
-        child.wait().is_ok_and(|s| s.success())
+        jrsonnet_parser::parse(content, &settings).is_ok()
     }
 }
 
@@ -0,0 +1,333 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Evaluates [Jsonnet](https://jsonnet.org/) programs.
+//!
+//! The evaluation is done in-process by [jrsonnet](https://github.com/CertainLach/jrsonnet), so
+//! the `jsonnet` command is not needed. This module takes care of passing the external variables
+//! and the virtual files to the evaluator and of telling apart the errors in the program (which
+//! are reported with their location) from the errors loading the virtual files.
+//!
+//! ```
+//! use agama_lib::utils::jsonnet::{evaluate, JsonnetConfig};
+//!
+//! let mut config = JsonnetConfig::default();
+//! config.ext_vars.insert("name".to_string(), "agama".into());
+//! let json = evaluate("{ product: std.extVar('name') }", "profile.jsonnet", &config).unwrap();
+//! assert_eq!(json["product"], "agama");
+//! ```
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use jrsonnet_evaluator::{
+    error::{Error, LocError},
+    EvaluationState, FileImportResolver, IStr, ImportResolver, Val,
+};
+
+/// Position of an error in a Jsonnet file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub file: String,
+    /// Line number (starting at 1).
+    pub line: usize,
+    /// Column number (starting at 1).
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JsonnetError {
+    /// The program could not be evaluated (syntax or runtime error).
+    #[error("{message}")]
+    Evaluation {
+        message: String,
+        location: Option<Location>,
+    },
+    /// A virtual file could not be loaded.
+    #[error("Could not load {0}: {1}")]
+    File(String, String),
+}
+
+impl JsonnetError {
+    /// Location of the evaluation error, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Self::Evaluation { location, .. } => location.as_ref(),
+            Self::File(_, _) => None,
+        }
+    }
+}
+
+pub type JsonnetResult<T> = Result<T, JsonnetError>;
+
+/// Function which returns the content of a virtual file.
+pub type FileLoader = Arc<dyn Fn() -> Result<String, String> + Send + Sync>;
+
+/// Virtual file which can be imported.
+#[derive(Clone)]
+pub enum VirtualFile {
+    /// File with the given content.
+    Content(String),
+    /// File whose content is loaded when it is imported for the first time (e.g., because
+    /// it is expensive to compute).
+    Lazy(FileLoader),
+}
+
+impl fmt::Debug for VirtualFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Content(content) => f.debug_tuple("Content").field(content).finish(),
+            Self::Lazy(_) => f.write_str("Lazy"),
+        }
+    }
+}
+
+/// Evaluation settings.
+#[derive(Clone, Debug, Default)]
+pub struct JsonnetConfig {
+    /// External variables (available through `std.extVar`).
+    pub ext_vars: HashMap<String, serde_json::Value>,
+    /// Virtual files which can be imported (name and content).
+    pub files: HashMap<String, VirtualFile>,
+    /// Additional directories to search for imported files.
+    pub import_paths: Vec<PathBuf>,
+}
+
+/// Evaluates a Jsonnet program and returns the resulting JSON.
+///
+/// * `source`: program source code.
+/// * `file`: file name. It is used to report errors and, if it exists, to resolve relative
+///   imports.
+/// * `config`: evaluation settings.
+pub fn evaluate(
+    source: &str,
+    file: &str,
+    config: &JsonnetConfig,
+) -> JsonnetResult<serde_json::Value> {
+    let state = EvaluationState::default();
+    state.with_stdlib();
+
+    let file_error = Rc::new(RefCell::new(None));
+    state.set_import_resolver(Box::new(Resolver {
+        files: config.files.clone(),
+        file_error: Rc::clone(&file_error),
+        inner: FileImportResolver {
+            library_paths: config.import_paths.clone(),
+        },
+    }));
+    for (name, value) in &config.ext_vars {
+        state.add_ext_var(name.as_str().into(), Val::from(value));
+    }
+
+    let path: Rc<Path> = PathBuf::from(file).into();
+    let result = state
+        .evaluate_snippet_raw(path, source.into())
+        .and_then(|value| state.manifest(value));
+
+    if let Some(error) = file_error.take() {
+        return Err(error);
+    }
+    let output = result.map_err(|e| evaluation_error(&state, &e))?;
+    serde_json::from_str(&output).map_err(|e| JsonnetError::Evaluation {
+        message: format!("The result is not a valid JSON: {e}"),
+        location: None,
+    })
+}
+
+/// Converts the jrsonnet error into a [JsonnetError::Evaluation].
+///
+/// The location is the innermost one of the stack trace or, for syntax errors, the position
+/// reported by the parser.
+fn evaluation_error(state: &EvaluationState, error: &LocError) -> JsonnetError {
+    let location = match error.error() {
+        Error::ImportSyntaxError { path, error, .. } => Some(Location {
+            file: path.to_string_lossy().to_string(),
+            line: error.location.line,
+            column: error.location.column,
+        }),
+        _ => error.trace().0.iter().find_map(|element| {
+            let location = element.location.as_ref()?;
+            let source = state.get_source(&location.0)?;
+            let before = source.get(..location.1)?;
+            Some(Location {
+                file: location.0.to_string_lossy().to_string(),
+                line: before.matches('\n').count() + 1,
+                column: before
+                    .rsplit('\n')
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .count()
+                    + 1,
+            })
+        }),
+    };
+    JsonnetError::Evaluation {
+        message: state.stringify_err(error),
+        location,
+    }
+}
+
+/// Resolves the imports, looking for the virtual files after the ones relative to the
+/// importing file.
+struct Resolver {
+    files: HashMap<String, VirtualFile>,
+    // error loading a virtual file, which is not an error in the program
+    file_error: Rc<RefCell<Option<JsonnetError>>>,
+    inner: FileImportResolver,
+}
+
+impl ImportResolver for Resolver {
+    fn resolve_file(
+        &self,
+        from: &Path,
+        path: &Path,
+    ) -> jrsonnet_evaluator::error::Result<Rc<Path>> {
+        // snippets without a directory should not import files from the working directory
+        if from.as_os_str().is_empty() || !from.join(path).exists() {
+            if let Some(name) = path.to_str().filter(|n| self.files.contains_key(*n)) {
+                return Ok(PathBuf::from(name).into());
+            }
+        }
+        self.inner.resolve_file(from, path)
+    }
+
+    fn load_file_contents(&self, resolved: &Path) -> jrsonnet_evaluator::error::Result<IStr> {
+        let Some((name, file)) = resolved
+            .to_str()
+            .and_then(|name| self.files.get_key_value(name))
+        else {
+            return self.inner.load_file_contents(resolved);
+        };
+
+        match file {
+            VirtualFile::Content(content) => Ok(content.as_str().into()),
+            VirtualFile::Lazy(loader) => loader().map(|c| c.as_str().into()).map_err(|e| {
+                self.file_error
+                    .replace(Some(JsonnetError::File(name.clone(), e.clone())));
+                Error::ImportCallbackError(e).into()
+            }),
+        }
+    }
+
+    unsafe fn as_any(&self) -> &dyn Any {
+        panic!("the resolver cannot be used as Any")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, JsonnetConfig, JsonnetError, VirtualFile};
+    use serde_json::json;
+    use std::{
+        fs,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_evaluate() {
+        let mut config = JsonnetConfig::default();
+        config.ext_vars.insert(
+            "hw".to_string(),
+            json!({ "memory": { "size": 8589934592_u64 } }),
+        );
+        let json = evaluate(
+            "local hw = std.extVar('hw'); { size: hw.memory.size, half: -1.5 }",
+            "profile.jsonnet",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(json, json!({ "size": 8589934592_u64, "half": -1.5 }));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let config = JsonnetConfig::default();
+        let error = evaluate("{\n  a: 1,\n  b: 2\n", "profile.jsonnet", &config).unwrap_err();
+        let location = error.location().unwrap();
+        assert_eq!(location.file, "profile.jsonnet");
+        assert_eq!(location.line, 4);
+
+        let error = evaluate("{\n  a: {}.unknown,\n}", "profile.jsonnet", &config).unwrap_err();
+        let location = error.location().unwrap();
+        assert_eq!(location.to_string(), "profile.jsonnet:2:6");
+    }
+
+    #[test]
+    fn test_import_files() {
+        let tmp_dir = TempDir::with_prefix("jsonnet").unwrap();
+        let dir = tmp_dir.path();
+        fs::write(dir.join("common.libsonnet"), "{ name: 'common' }").unwrap();
+        let profile = dir.join("profile.jsonnet");
+
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&loads);
+        let mut config = JsonnetConfig::default();
+        config.files.insert(
+            "lazy.libsonnet".to_string(),
+            VirtualFile::Lazy(Arc::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok("{ name: 'lazy' }".to_string())
+            })),
+        );
+        config.files.insert(
+            "failing.libsonnet".to_string(),
+            VirtualFile::Lazy(Arc::new(|| Err("not available".to_string()))),
+        );
+
+        let json = evaluate(
+            "{ common: (import 'common.libsonnet').name }",
+            &profile.to_string_lossy(),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(json, json!({ "common": "common" }));
+        assert_eq!(loads.load(Ordering::SeqCst), 0);
+
+        let json = evaluate(
+            "{ lazy: (import 'lazy.libsonnet').name }",
+            "profile.jsonnet",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(json, json!({ "lazy": "lazy" }));
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        let error = evaluate("import 'failing.libsonnet'", "profile.jsonnet", &config).unwrap_err();
+        assert!(matches!(error, JsonnetError::File(_, _)));
+    }
+}
@@ -26,8 +26,9 @@
 //! information.
 //!
 //! This API supports the following URLs from YaST: `device:`, `usb:`, `label:`, ! `hd:`, `dvd:` and
-//! `cd:`. The support for well-known URLs (e.g., `file:`, `http:`, `https:`, ! `ftp:`, `nfs:`,
-//!  etc.) is implemented using CURL.
+//! `cd:`. The `nfs:` and `smb:` (or `cifs:`) URLs are handled by mounting the remote share. The
+//! support for other well-known URLs (e.g., `file:`, `http:`, `https:`, `ftp:`, `tftp:`, etc.) is
+//! implemented using CURL. See [HandlerRegistry] to add or replace handlers.
 //!
 //! ## SSL
 //!
@@ -35,6 +36,27 @@
 //! `/etc/sssl/clientcerts/client-cert.pem`. Agama does not use such a certificate and it only
 //! relies on those that are installed in the installation media.
 //!
+//! ## Retries, timeouts and proxy
+//!
+//! Transient errors (e.g., connection problems or HTTP 5xx responses) are retried with an
+//! exponential backoff. The number of retries, the timeouts and the proxy can be adjusted through
+//! [TransferOptions]. If no proxy is set, curl honors the usual environment variables (e.g.,
+//! `http_proxy` or `no_proxy`).
+//!
+//! ## Progress
+//!
+//! A [ProgressCallback] receives the progress of the download. The [DBusProgressReporter] forwards
+//! it as `ProgressChanged` signals, so it is visible to the web server clients. Additionally,
+//! [Transfer::downloaded_bytes] keeps the count of the bytes retrieved by the current process.
+//!
+//! ## Cache
+//!
+//! When running in the installer, the files downloaded through HTTP(S) are kept in a
+//! [TransferCache] under `/run/agama/cache`, provided that the server returns an `ETag` or a
+//! `Last-Modified` header. They are revalidated using those headers, so a file is downloaded
+//! again only when it changed. Other schemes are not cached, as there is no way to revalidate
+//! the copies. Set [TransferOptions::bypass_cache] to ignore the cached copies.
+//!
 //! ## Examples
 //! Requires working localectl.
 //!
@@ -42,17 +64,55 @@
 //! use agama_lib::utils::Transfer;
 //! Transfer::get("label://OEMDRV/autoinst.xml", &mut std::io::stdout(), false).unwrap();
 //! ````
+//!
+//! ```no_run
+//! use agama_lib::utils::{Transfer, TransferOptions};
+//! let options = TransferOptions {
+//!     retries: 5,
+//!     ..Default::default()
+//! };
+//! Transfer::new(options)
+//!     .with_progress(|progress| println!("{progress}"))
+//!     .fetch("tftp://192.168.122.1/autoinst.json", &mut std::io::stdout())
+//!     .unwrap();
+//! ```
 
-use std::io::Write;
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 use thiserror::Error;
 use url::Url;
 
+mod cache;
 mod file_finder;
 mod file_systems;
 mod handlers;
+mod options;
+mod progress;
+mod verification;
+
+pub use cache::{CacheEntry, TransferCache, Validators, DEFAULT_CACHE_DIR};
+pub use file_systems::{FileSystem, FileSystemsList};
+pub use handlers::{
+    DeviceHandler, GenericHandler, HandlerRegistry, HdHandler, LabelHandler, NfsHandler,
+    SmbHandler, TransferContext, TransferHandler,
+};
+pub use options::{
+    ProgressCallback, TransferOptions, TransferProgress, DEFAULT_BACKOFF, DEFAULT_CONNECT_TIMEOUT,
+    DEFAULT_RETRIES,
+};
+pub use progress::DBusProgressReporter;
+pub use verification::{
+    Checksum, ChecksumAlgorithm, SignatureVerifier, Verification, TRUSTED_KEYS_DIR,
+};
 
-use handlers::{DeviceHandler, GenericHandler, HdHandler, LabelHandler};
+/// Bytes retrieved by the transfers of the current process.
+static DOWNLOADED_BYTES: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Error, Debug)]
 pub enum TransferError {
@@ -68,31 +128,412 @@ pub enum TransferError {
     IO(#[from] std::io::Error),
     #[error("Could not mount the file system {0}")]
     FileSystemMount(String),
+    #[error("Timed out mounting the file system {0}")]
+    FileSystemMountTimeout(String),
+    #[error("Invalid credentials for {0}")]
+    InvalidCredentials(String),
     #[error("Missing file path {0}")]
     MissingPath(Url),
     #[error("Missing device {0}")]
     MissingDevice(Url),
     #[error("Missing file system label {0}")]
     MissingLabel(Url),
+    #[error("Missing host {0}")]
+    MissingHost(Url),
+    #[error("Could not retrieve {0}: the server returned {1}")]
+    ServerError(String, u32),
+    #[error("{0} was not modified")]
+    NotModified(String),
+    #[error("Invalid checksum {0}")]
+    InvalidChecksum(String),
+    #[error("Checksum mismatch for {url}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Invalid signature for {0}: {1}")]
+    InvalidSignature(String, String),
 }
 pub type TransferResult<T> = Result<T, TransferError>;
 
+impl TransferError {
+    /// Whether the error might go away by trying again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::ServerError(_, _)
+            | Self::FileSystemMount(_)
+            | Self::FileSystemMountTimeout(_) => true,
+            Self::CurlError(error) | Self::CurlTransferError(_, error) => {
+                error.is_couldnt_connect()
+                    || error.is_couldnt_resolve_host()
+                    || error.is_couldnt_resolve_proxy()
+                    || error.is_operation_timedout()
+                    || error.is_partial_file()
+                    || error.is_got_nothing()
+                    || error.is_send_error()
+                    || error.is_recv_error()
+            }
+            _ => false,
+        }
+    }
+}
+
 /// File transfer API
-pub struct Transfer {}
+///
+/// The associated functions [Transfer::get] and [Transfer::get_verified] use the default
+/// options. Build an instance to adjust the options or the handlers, or to track the progress.
+#[derive(Clone)]
+pub struct Transfer {
+    options: TransferOptions,
+    registry: HandlerRegistry,
+    progress: Option<ProgressCallback>,
+    cache: Option<TransferCache>,
+}
+
+impl Default for Transfer {
+    fn default() -> Self {
+        Self {
+            options: TransferOptions::default(),
+            registry: HandlerRegistry::default(),
+            progress: None,
+            cache: TransferCache::system(),
+        }
+    }
+}
 
 impl Transfer {
-    /// Retrieves and writes the data from an URL
+    /// * `options`: transfer options.
+    pub fn new(options: TransferOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the cache to use. By default, it uses the system one (see [TransferCache::system]).
+    ///
+    /// * `cache`: cache to use or `None` to disable it.
+    pub fn with_cache(mut self, cache: Option<TransferCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Registers a handler for the given schemes.
+    ///
+    /// * `schemes`: URL schemes to handle.
+    /// * `handler`: handler to use.
+    pub fn with_handler(
+        mut self,
+        schemes: &[&str],
+        handler: impl TransferHandler + 'static,
+    ) -> Self {
+        self.registry.register(schemes, handler);
+        self
+    }
+
+    /// Sets the callback to report the progress.
+    ///
+    /// * `callback`: function to call when there is some progress.
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&TransferProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Transfer options.
+    pub fn options(&self) -> &TransferOptions {
+        &self.options
+    }
+
+    /// Returns the number of bytes retrieved by the transfers of the current process.
+    ///
+    /// The files served from the cache are not counted.
+    pub fn downloaded_bytes() -> u64 {
+        DOWNLOADED_BYTES.load(Ordering::Relaxed)
+    }
+
+    /// Retrieves and writes the data from an URL.
+    ///
+    /// Transient errors are retried according to the options. The data is written only when the
+    /// download succeeds, so a failed attempt does not leave partial content in `out_fd`.
+    ///
+    /// * `url`: URL to get the data from.
+    /// * `out_fd`: where to write the data.
+    pub fn fetch(&self, url: &str, out_fd: &mut impl Write) -> TransferResult<()> {
+        let parsed = Url::parse(url)?;
+        let handler = self.registry.handler_for(parsed.scheme());
+        let mut context = TransferContext::new(url, &self.options, self.progress.as_ref());
+
+        let cache = self
+            .cache
+            .as_ref()
+            .filter(|_| matches!(parsed.scheme(), "http" | "https"));
+        let cached = cache
+            .filter(|_| !self.options.bypass_cache)
+            .and_then(|c| c.lookup(url))
+            .filter(|(entry, _)| !entry.validators.is_empty());
+        if let Some((entry, _)) = &cached {
+            context = context.with_conditions(entry.validators.clone());
+        }
+
+        let mut retry = 0;
+        loop {
+            let mut data = vec![];
+            match handler.fetch(parsed.clone(), &mut data, &context) {
+                Ok(()) => {
+                    DOWNLOADED_BYTES.fetch_add(data.len() as u64, Ordering::Relaxed);
+                    if let Some(cache) = cache {
+                        Self::store_in_cache(cache, url, &data, context.validators());
+                    }
+                    context.finish(data.len() as u64);
+                    out_fd.write_all(&data)?;
+                    return Ok(());
+                }
+                Err(TransferError::NotModified(not_modified)) => {
+                    let Some((_, data)) = cached else {
+                        return Err(TransferError::NotModified(not_modified));
+                    };
+                    context.finish(data.len() as u64);
+                    out_fd.write_all(&data)?;
+                    return Ok(());
+                }
+                Err(error) if error.is_transient() && retry < self.options.retries => {
+                    retry += 1;
+                    let delay = self.options.delay_for(retry);
+                    log::warn!(
+                        "{error}. Retrying in {delay:?} ({retry}/{}).",
+                        self.options.retries
+                    );
+                    std::thread::sleep(delay);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Stores the downloaded data in the cache, unless there is no way to revalidate it.
+    fn store_in_cache(cache: &TransferCache, url: &str, data: &[u8], validators: Validators) {
+        if validators.is_empty() {
+            return;
+        }
+
+        if let Err(error) = cache.store(url, data, validators) {
+            log::warn!("Could not cache {url}: {error}");
+        }
+    }
+
+    /// Retrieves the data from an URL and writes it only if it passes the integrity checks.
+    ///
+    /// * `url`: URL to get the data from.
+    /// * `out_fd`: where to write the data.
+    /// * `verification`: checksum and signature to check.
+    pub fn fetch_verified(
+        &self,
+        url: &str,
+        out_fd: &mut impl Write,
+        verification: &Verification,
+    ) -> TransferResult<()> {
+        if verification.is_empty() {
+            return self.fetch(url, out_fd);
+        }
+
+        let mut data = vec![];
+        self.fetch(url, &mut data)?;
+        verification.verify(url, &data, &self.options)?;
+        out_fd.write_all(&data)?;
+        Ok(())
+    }
+
+    /// Retrieves and writes the data from an URL using the default options.
     ///
     /// * `url`: URL to get the data from.
     /// * `out_fd`: where to write the data.
     /// * `insecure`: ignore SSL problems in HTTPS downloads.
     pub fn get(url: &str, out_fd: &mut impl Write, insecure: bool) -> TransferResult<()> {
-        let url = Url::parse(url)?;
-        match url.scheme() {
-            "device" | "usb" => DeviceHandler::default().get(url, out_fd),
-            "label" => LabelHandler::default().get(url, out_fd),
-            "cd" | "dvd" | "hd" => HdHandler::default().get(url, out_fd),
-            _ => GenericHandler::default().get(url, out_fd, insecure),
+        Self::new(TransferOptions {
+            insecure,
+            ..Default::default()
+        })
+        .fetch(url, out_fd)
+    }
+
+    /// Retrieves the data from an URL and writes it only if it passes the integrity checks.
+    ///
+    /// * `url`: URL to get the data from.
+    /// * `out_fd`: where to write the data.
+    /// * `insecure`: ignore SSL problems in HTTPS downloads.
+    /// * `verification`: checksum and signature to check.
+    pub fn get_verified(
+        url: &str,
+        out_fd: &mut impl Write,
+        insecure: bool,
+        verification: &Verification,
+    ) -> TransferResult<()> {
+        Self::new(TransferOptions {
+            insecure,
+            ..Default::default()
+        })
+        .fetch_verified(url, out_fd, verification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use httpmock::prelude::*;
+    use tempfile::TempDir;
+    use url::Url;
+
+    use super::{
+        Transfer, TransferCache, TransferContext, TransferError, TransferHandler, TransferOptions,
+        TransferResult,
+    };
+
+    /// Handler that fails with a transient error a given number of times.
+    struct FlakyHandler {
+        failures: u32,
+        attempts: Arc<AtomicU32>,
+    }
+
+    impl TransferHandler for FlakyHandler {
+        fn fetch(
+            &self,
+            url: Url,
+            out_fd: &mut dyn Write,
+            context: &TransferContext,
+        ) -> TransferResult<()> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            out_fd.write_all(b"partial")?;
+            if attempt <= self.failures {
+                return Err(TransferError::ServerError(url.to_string(), 503));
+            }
+            context.report(2, Some(4));
+            out_fd.write_all(b" content")?;
+            Ok(())
+        }
+    }
+
+    fn build_transfer(failures: u32, retries: u32) -> (Transfer, Arc<AtomicU32>) {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let options = TransferOptions {
+            retries,
+            backoff: Duration::ZERO,
+            ..Default::default()
+        };
+        let handler = FlakyHandler {
+            failures,
+            attempts: Arc::clone(&attempts),
+        };
+        (
+            Transfer::new(options).with_handler(&["test"], handler),
+            attempts,
+        )
+    }
+
+    #[test]
+    fn test_fetch_retries_transient_errors() {
+        let (transfer, attempts) = build_transfer(2, 2);
+        let mut data = vec![];
+        transfer.fetch("test://server/file", &mut data).unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(data, b"partial content");
+    }
+
+    #[test]
+    fn test_fetch_gives_up() {
+        let (transfer, attempts) = build_transfer(3, 2);
+        let mut data = vec![];
+        let result = transfer.fetch("test://server/file", &mut data);
+        assert!(matches!(result, Err(TransferError::ServerError(_, 503))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_fetch_reports_progress() {
+        let (transfer, _) = build_transfer(0, 0);
+        let reports = Arc::new(Mutex::new(vec![]));
+        let reports_clone = Arc::clone(&reports);
+        let transfer = transfer.with_progress(move |progress| {
+            reports_clone
+                .lock()
+                .unwrap()
+                .push((progress.percentage(), progress.finished))
+        });
+
+        let before = Transfer::downloaded_bytes();
+        let mut data = vec![];
+        transfer.fetch("test://server/file", &mut data).unwrap();
+        let reports = reports.lock().unwrap();
+        assert_eq!(*reports, vec![(Some(50), false), (Some(100), true)]);
+        // other tests might be downloading files at the same time
+        assert!(Transfer::downloaded_bytes() >= before + data.len() as u64);
+    }
+
+    #[test]
+    fn test_fetch_revalidates_cached_files() {
+        let server = MockServer::start();
+        let not_modified = server.mock(|when, then| {
+            when.method(GET)
+                .path("/profile.json")
+                .header("If-None-Match", "\"v1\"");
+            then.status(304);
+        });
+        // defined after the conditional request mock, so it only gets the plain requests
+        let first = server.mock(|when, then| {
+            when.method(GET).path("/profile.json");
+            then.status(200)
+                .header("ETag", "\"v1\"")
+                .body("{ \"product\": {} }");
+        });
+
+        let tmp_dir = TempDir::with_prefix("agama-cache-").unwrap();
+        let transfer = Transfer::default().with_cache(Some(TransferCache::new(tmp_dir.path())));
+        let url = server.url("/profile.json");
+
+        for _ in 0..2 {
+            let mut data = vec![];
+            transfer.fetch(&url, &mut data).unwrap();
+            assert_eq!(data, b"{ \"product\": {} }");
         }
+        first.assert_hits(1);
+        not_modified.assert_hits(1);
+
+        let bypass = Transfer::new(TransferOptions {
+            bypass_cache: true,
+            ..Default::default()
+        })
+        .with_cache(Some(TransferCache::new(tmp_dir.path())));
+        let mut data = vec![];
+        bypass.fetch(&url, &mut data).unwrap();
+        first.assert_hits(2);
+    }
+
+    #[test]
+    fn test_fetch_without_validators_is_not_cached() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/profile.json");
+            then.status(200).body("{ \"product\": {} }");
+        });
+
+        let tmp_dir = TempDir::with_prefix("agama-cache-").unwrap();
+        let transfer = Transfer::default().with_cache(Some(TransferCache::new(tmp_dir.path())));
+        let mut data = vec![];
+        transfer
+            .fetch(&server.url("/profile.json"), &mut data)
+            .unwrap();
+        assert!(!tmp_dir.path().join("objects").exists());
     }
 }
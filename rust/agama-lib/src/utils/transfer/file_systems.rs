@@ -135,6 +135,16 @@ impl FileSystemsList {
         Self::new(file_systems)
     }
 
+    /// Creates a list for the file systems from the output of lsblk.
+    ///
+    /// The output is expected to contain the KNAME, FSTYPE, MOUNTPOINTS, TRAN and LABEL columns
+    /// using the `--pairs` format.
+    ///
+    /// * `output`: lsblk output.
+    pub fn from_lsblk(output: &str) -> Self {
+        Self::new(FileSystemsReader::read_from_string(output))
+    }
+
     pub fn to_vec(&self) -> Vec<FileSystem> {
         self.file_systems.clone()
     }
@@ -18,8 +18,225 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+//! Handlers to retrieve files depending on the URL scheme.
+//!
+//! Each handler implements the [TransferHandler] trait and it is registered for one or more
+//! schemes in a [HandlerRegistry]. URLs whose scheme is not registered are handled by the
+//! [GenericHandler], which relies on curl.
+
+use std::{cell::RefCell, collections::HashMap, io::Write, sync::Arc};
+
+use url::Url;
+
+use super::{ProgressCallback, TransferOptions, TransferProgress, TransferResult, Validators};
+
 mod generic;
+mod network_fs;
 mod yast;
 
 pub use generic::GenericHandler;
+pub use network_fs::{NfsHandler, SmbHandler};
 pub use yast::{DeviceHandler, HdHandler, LabelHandler};
+
+/// Retrieves files for a set of URL schemes.
+pub trait TransferHandler: Send + Sync {
+    /// Retrieves the file from the given URL and writes it to `out_fd`.
+    ///
+    /// * `url`: URL to get the data from.
+    /// * `out_fd`: where to write the data.
+    /// * `context`: transfer options and progress reporting.
+    fn fetch(
+        &self,
+        url: Url,
+        out_fd: &mut dyn Write,
+        context: &TransferContext,
+    ) -> TransferResult<()>;
+}
+
+/// Options, progress reporting and cache validators for a single transfer.
+pub struct TransferContext<'a> {
+    url: &'a str,
+    options: &'a TransferOptions,
+    progress: Option<&'a ProgressCallback>,
+    conditions: Option<Validators>,
+    validators: RefCell<Validators>,
+}
+
+impl<'a> TransferContext<'a> {
+    /// * `url`: URL being retrieved, as given by the caller.
+    /// * `options`: transfer options.
+    /// * `progress`: callback to report the progress, if any.
+    pub fn new(
+        url: &'a str,
+        options: &'a TransferOptions,
+        progress: Option<&'a ProgressCallback>,
+    ) -> Self {
+        Self {
+            url,
+            options,
+            progress,
+            conditions: None,
+            validators: RefCell::new(Validators::default()),
+        }
+    }
+
+    /// Sets the validators of the cached copy.
+    ///
+    /// Handlers supporting conditional requests should return [super::TransferError::NotModified]
+    /// if the file did not change.
+    ///
+    /// * `conditions`: validators of the cached copy.
+    pub fn with_conditions(mut self, conditions: Validators) -> Self {
+        self.conditions = Some(conditions);
+        self
+    }
+
+    /// Validators of the cached copy, if any.
+    pub fn conditions(&self) -> Option<&Validators> {
+        self.conditions.as_ref()
+    }
+
+    /// Records the validators returned by the server.
+    ///
+    /// * `validators`: values to revalidate the file later.
+    pub fn set_validators(&self, validators: Validators) {
+        *self.validators.borrow_mut() = validators;
+    }
+
+    /// Validators returned by the server.
+    pub fn validators(&self) -> Validators {
+        self.validators.borrow().clone()
+    }
+
+    /// Transfer options.
+    pub fn options(&self) -> &TransferOptions {
+        self.options
+    }
+
+    /// Whether a progress callback is set.
+    pub fn reports_progress(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    /// Reports the progress of the transfer.
+    ///
+    /// * `downloaded`: bytes downloaded so far.
+    /// * `total`: total size, if known.
+    pub fn report(&self, downloaded: u64, total: Option<u64>) {
+        self.notify(downloaded, total, false);
+    }
+
+    /// Reports that the transfer has finished.
+    ///
+    /// * `size`: size of the retrieved file.
+    pub fn finish(&self, size: u64) {
+        self.notify(size, Some(size), true);
+    }
+
+    fn notify(&self, downloaded: u64, total: Option<u64>, finished: bool) {
+        let Some(callback) = self.progress else {
+            return;
+        };
+
+        callback(&TransferProgress {
+            url: self.url.to_string(),
+            downloaded,
+            total,
+            finished,
+        });
+    }
+}
+
+/// Maps URL schemes to the handlers that retrieve them.
+///
+/// By default, it includes the handlers for the YaST-like schemes (`device:`, `usb:`, `label:`,
+/// `hd:`, `cd:` and `dvd:`), `nfs:` and `smb:`/`cifs:`. Any other scheme (e.g., `http:`, `https:`,
+/// `ftp:`, `tftp:` or `file:`) is handled by curl.
+#[derive(Clone)]
+pub struct HandlerRegistry {
+    handlers: HashMap<String, Arc<dyn TransferHandler>>,
+    fallback: Arc<dyn TransferHandler>,
+}
+
+impl Default for HandlerRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            handlers: HashMap::new(),
+            fallback: Arc::new(GenericHandler::default()),
+        };
+        registry.register(&["device", "usb"], DeviceHandler::default());
+        registry.register(&["label"], LabelHandler::default());
+        registry.register(&["cd", "dvd", "hd"], HdHandler::default());
+        registry.register(&["nfs"], NfsHandler::default());
+        registry.register(&["smb", "cifs"], SmbHandler::default());
+        registry
+    }
+}
+
+impl HandlerRegistry {
+    /// Registers a handler for the given schemes, replacing any previous one.
+    ///
+    /// * `schemes`: URL schemes to handle.
+    /// * `handler`: handler to use.
+    pub fn register(&mut self, schemes: &[&str], handler: impl TransferHandler + 'static) {
+        let handler: Arc<dyn TransferHandler> = Arc::new(handler);
+        for scheme in schemes {
+            self.handlers
+                .insert(scheme.to_lowercase(), Arc::clone(&handler));
+        }
+    }
+
+    /// Returns the handler for the given scheme.
+    ///
+    /// * `scheme`: URL scheme.
+    pub fn handler_for(&self, scheme: &str) -> Arc<dyn TransferHandler> {
+        self.handlers
+            .get(&scheme.to_lowercase())
+            .cloned()
+            .unwrap_or_else(|| Arc::clone(&self.fallback))
+    }
+}
+
+impl TransferHandler for DeviceHandler {
+    fn fetch(
+        &self,
+        url: Url,
+        mut out_fd: &mut dyn Write,
+        _: &TransferContext,
+    ) -> TransferResult<()> {
+        self.get(url, &mut out_fd)
+    }
+}
+
+impl TransferHandler for LabelHandler {
+    fn fetch(
+        &self,
+        url: Url,
+        mut out_fd: &mut dyn Write,
+        _: &TransferContext,
+    ) -> TransferResult<()> {
+        self.get(url, &mut out_fd)
+    }
+}
+
+impl TransferHandler for HdHandler {
+    fn fetch(
+        &self,
+        url: Url,
+        mut out_fd: &mut dyn Write,
+        _: &TransferContext,
+    ) -> TransferResult<()> {
+        self.get(url, &mut out_fd)
+    }
+}
+
+impl TransferHandler for GenericHandler {
+    fn fetch(
+        &self,
+        url: Url,
+        mut out_fd: &mut dyn Write,
+        context: &TransferContext,
+    ) -> TransferResult<()> {
+        self.get(url, &mut out_fd, context)
+    }
+}
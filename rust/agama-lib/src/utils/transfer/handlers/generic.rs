@@ -20,36 +20,117 @@
 
 use std::io::Write;
 
-use curl::easy::Easy;
+use curl::easy::{Easy, List};
 use url::Url;
 
-use crate::utils::{TransferError, TransferResult};
+use crate::utils::{TransferError, TransferResult, Validators};
+
+use super::TransferContext;
 
 /// Generic handler to retrieve any URL.
 ///
-/// It uses curl under the hood.
+/// It uses curl under the hood, so it supports any protocol curl was built with (e.g., `http:`,
+/// `https:`, `ftp:`, `tftp:` or `file:`).
 #[derive(Default)]
 pub struct GenericHandler {}
 
 impl GenericHandler {
-    pub fn get(&self, url: Url, out_fd: &mut impl Write, insecure: bool) -> TransferResult<()> {
+    pub fn get(
+        &self,
+        url: Url,
+        out_fd: &mut impl Write,
+        context: &TransferContext,
+    ) -> TransferResult<()> {
+        let options = context.options();
         let mut handle = Easy::new();
         handle.follow_location(true)?;
         handle.fail_on_error(true)?;
         handle.url(url.as_ref())?;
 
-        if insecure {
+        if options.insecure {
             // allow self-signed certificate, ignore verification failures
             handle.ssl_verify_peer(false)?;
             // allow not matching hostname
             handle.ssl_verify_host(false)?;
         }
 
+        if let Some(timeout) = options.connect_timeout {
+            handle.connect_timeout(timeout)?;
+        }
+
+        if let Some(timeout) = options.timeout {
+            handle.timeout(timeout)?;
+        }
+
+        if let Some(proxy) = &options.proxy {
+            handle.proxy(proxy)?;
+        }
+
+        let is_http = url.scheme().starts_with("http");
+        if let Some(conditions) = context.conditions().filter(|_| is_http) {
+            let mut headers = List::new();
+            if let Some(etag) = &conditions.etag {
+                headers.append(&format!("If-None-Match: {etag}"))?;
+            }
+            if let Some(last_modified) = &conditions.last_modified {
+                headers.append(&format!("If-Modified-Since: {last_modified}"))?;
+            }
+            handle.http_headers(headers)?;
+        }
+
+        handle.progress(context.reports_progress())?;
+
+        let mut validators = Validators::default();
         let mut transfer = handle.transfer();
         transfer.write_function(|buf| Ok(out_fd.write(buf).unwrap()))?;
-        transfer
-            .perform()
-            .map_err(|e| TransferError::CurlTransferError(url.to_string(), e))?;
+        transfer.header_function(|header| {
+            if is_http {
+                read_validator(header, &mut validators);
+            }
+            true
+        })?;
+        transfer.progress_function(|total, downloaded, _, _| {
+            let total = (total > 0.0).then_some(total as u64);
+            context.report(downloaded as u64, total);
+            true
+        })?;
+        let result = transfer.perform();
+        drop(transfer);
+
+        let code = handle.response_code().unwrap_or_default();
+        if let Err(error) = result {
+            // FTP also uses 5xx codes for permanent errors, so only HTTP ones are reported.
+            if is_http && code >= 500 {
+                return Err(TransferError::ServerError(url.to_string(), code));
+            }
+            return Err(TransferError::CurlTransferError(url.to_string(), error));
+        }
+
+        if is_http && code == 304 {
+            return Err(TransferError::NotModified(url.to_string()));
+        }
+        context.set_validators(validators);
         Ok(())
     }
 }
+
+/// Reads the ETag and Last-Modified values from a response header.
+///
+/// Headers from intermediate responses (e.g., redirections) are overwritten by the last ones.
+fn read_validator(header: &[u8], validators: &mut Validators) {
+    let header = String::from_utf8_lossy(header);
+    if header.starts_with("HTTP/") {
+        *validators = Validators::default();
+        return;
+    }
+
+    let Some((name, value)) = header.split_once(':') else {
+        return;
+    };
+    let value = Some(value.trim().to_string());
+    if name.eq_ignore_ascii_case("etag") {
+        validators.etag = value;
+    } else if name.eq_ignore_ascii_case("last-modified") {
+        validators.last_modified = value;
+    }
+}
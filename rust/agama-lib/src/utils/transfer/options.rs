@@ -0,0 +1,145 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Options and progress reporting for file transfers.
+
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Default number of retries after a failed attempt.
+pub const DEFAULT_RETRIES: u32 = 2;
+/// Default delay before the first retry. It is doubled after each attempt.
+pub const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+/// Default timeout to establish a connection.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Settings that control how a file is retrieved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferOptions {
+    /// Ignore SSL problems in HTTPS downloads.
+    pub insecure: bool,
+    /// Number of retries after a transient failure (e.g., a connection error or a 5xx response).
+    pub retries: u32,
+    /// Delay before the first retry. It is doubled after each failed attempt.
+    pub backoff: Duration,
+    /// Maximum time to establish a connection.
+    pub connect_timeout: Option<Duration>,
+    /// Maximum time for the whole transfer. `None` means no limit.
+    pub timeout: Option<Duration>,
+    /// Proxy URL. If it is not set, the usual environment variables (e.g., `http_proxy`) apply.
+    pub proxy: Option<String>,
+    /// Do not use the cached copies. The downloaded files are still added to the cache.
+    pub bypass_cache: bool,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            insecure: false,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
+            timeout: None,
+            proxy: None,
+            bypass_cache: false,
+        }
+    }
+}
+
+impl TransferOptions {
+    /// Returns the delay before the given retry (starting at 1).
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+/// Progress of an ongoing transfer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferProgress {
+    /// URL being retrieved.
+    pub url: String,
+    /// Bytes downloaded so far.
+    pub downloaded: u64,
+    /// Total size, if known.
+    pub total: Option<u64>,
+    /// Whether the transfer has finished.
+    pub finished: bool,
+}
+
+impl TransferProgress {
+    /// Returns the completed percentage, if the total size is known.
+    pub fn percentage(&self) -> Option<u32> {
+        if self.finished {
+            return Some(100);
+        }
+
+        match self.total {
+            Some(total) if total > 0 => Some((self.downloaded.min(total) * 100 / total) as u32),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TransferProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.percentage() {
+            Some(percentage) => write!(f, "Downloading {} ({}%)", self.url, percentage),
+            None => write!(f, "Downloading {} ({} bytes)", self.url, self.downloaded),
+        }
+    }
+}
+
+/// Callback to report the progress of a transfer.
+pub type ProgressCallback = Arc<dyn Fn(&TransferProgress) + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{TransferOptions, TransferProgress};
+
+    #[test]
+    fn test_delay_for() {
+        let options = TransferOptions {
+            backoff: Duration::from_millis(100),
+            ..Default::default()
+        };
+        assert_eq!(options.delay_for(1), Duration::from_millis(100));
+        assert_eq!(options.delay_for(2), Duration::from_millis(200));
+        assert_eq!(options.delay_for(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_percentage() {
+        let mut progress = TransferProgress {
+            url: "http://example.net/profile.json".to_string(),
+            downloaded: 25,
+            total: Some(200),
+            finished: false,
+        };
+        assert_eq!(progress.percentage(), Some(12));
+
+        progress.total = None;
+        assert_eq!(progress.percentage(), None);
+
+        progress.finished = true;
+        assert_eq!(progress.percentage(), Some(100));
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Reports the progress of the transfers through D-Bus.
+
+use tokio::sync::mpsc;
+
+use super::TransferProgress;
+
+const PROGRESS_INTERFACE: &str = "org.opensuse.Agama1.Progress";
+
+/// Forwards the progress of the transfers as `ProgressChanged` signals.
+///
+/// The signals are emitted from the `/org/opensuse/Agama/Transfer1` path, so the web server turns
+/// them into `ProgressChanged` events. The progress is expressed as a percentage (100 steps). When
+/// the size of the file is unknown, it only reports the start and the end of the transfer.
+///
+/// The signals are emitted from a separate task because the transfers are blocking. Hence, it
+/// must be created within a multi-threaded Tokio runtime.
+#[derive(Clone)]
+pub struct DBusProgressReporter {
+    sender: mpsc::UnboundedSender<TransferProgress>,
+}
+
+impl DBusProgressReporter {
+    /// D-Bus path used to emit the signals.
+    pub const PATH: &'static str = "/org/opensuse/Agama/Transfer1";
+
+    /// * `connection`: connection to Agama's D-Bus.
+    pub fn new(connection: zbus::Connection) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(Self::run(connection, receiver));
+        Self { sender }
+    }
+
+    /// Returns a callback to use with [super::Transfer::with_progress].
+    pub fn callback(&self) -> impl Fn(&TransferProgress) + Send + Sync + 'static {
+        let sender = self.sender.clone();
+        move |progress| {
+            // if the task is gone, there is nothing to report
+            _ = sender.send(progress.clone());
+        }
+    }
+
+    async fn run(
+        connection: zbus::Connection,
+        mut receiver: mpsc::UnboundedReceiver<TransferProgress>,
+    ) {
+        let mut last = None;
+        while let Some(progress) = receiver.recv().await {
+            let current = (
+                progress.url.clone(),
+                progress.percentage(),
+                progress.finished,
+            );
+            if last.as_ref() == Some(&current) {
+                continue;
+            }
+
+            let title = format!("Downloading {}", &progress.url);
+            let steps: Vec<&str> = vec![];
+            let args = (
+                100u32,
+                (current.1.unwrap_or_default(), title.as_str()),
+                progress.finished,
+                steps,
+            );
+            if let Err(error) = connection
+                .emit_signal(
+                    None::<zbus::names::BusName>,
+                    Self::PATH,
+                    PROGRESS_INTERFACE,
+                    "ProgressChanged",
+                    &args,
+                )
+                .await
+            {
+                log::warn!(
+                    "Could not report the progress of {}: {error}",
+                    &progress.url
+                );
+            }
+            last = Some(current);
+        }
+    }
+}
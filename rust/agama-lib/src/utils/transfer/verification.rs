@@ -0,0 +1,341 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Integrity checks for the downloaded files.
+//!
+//! A file can be verified using a checksum (`sha256:<hex>` or `sha512:<hex>`) and a detached
+//! signature. The signature is downloaded from its own URL and checked using the trusted keys
+//! from [TRUSTED_KEYS_DIR]:
+//!
+//! * Signatures with the `.minisig` extension are checked with `minisign`, using the `*.pub`
+//!   keys.
+//! * Any other signature is checked with GPG, using the `*.asc` and `*.gpg` keys.
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use sha2::{Digest, Sha256, Sha512};
+use tempfile::TempDir;
+
+use super::{Transfer, TransferError, TransferOptions, TransferResult};
+
+/// Directory containing the keys to verify the signatures.
+pub const TRUSTED_KEYS_DIR: &str = "/etc/agama.d/keys";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// Length of the digest as an hexadecimal string.
+    fn hex_len(&self) -> usize {
+        match self {
+            Self::Sha256 => 64,
+            Self::Sha512 => 128,
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Sha512 => write!(f, "sha512"),
+        }
+    }
+}
+
+/// Expected checksum of a file.
+///
+/// It is written as `<algorithm>:<hex digest>` (e.g., "sha256:9f86d0..."). The algorithm can be
+/// omitted, in which case it is inferred from the length of the digest.
+#[derive(Clone, Debug, PartialEq, SerializeDisplay, DeserializeFromStr)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    /// Digest as a lowercase hexadecimal string.
+    pub digest: String,
+}
+
+impl Checksum {
+    /// Computes the checksum of the given data.
+    pub fn compute(algorithm: ChecksumAlgorithm, data: &[u8]) -> Self {
+        let digest: Vec<u8> = match algorithm {
+            ChecksumAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+            ChecksumAlgorithm::Sha512 => Sha512::digest(data).to_vec(),
+        };
+        let digest = digest.iter().map(|b| format!("{b:02x}")).collect();
+        Self { algorithm, digest }
+    }
+
+    /// Checks whether the data matches the checksum.
+    ///
+    /// * `url`: URL of the data, used in the error message.
+    /// * `data`: data to check.
+    pub fn verify(&self, url: &str, data: &[u8]) -> TransferResult<()> {
+        let actual = Self::compute(self.algorithm, data);
+        if actual.digest != self.digest {
+            return Err(TransferError::ChecksumMismatch {
+                url: url.to_string(),
+                expected: self.to_string(),
+                actual: actual.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.digest)
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = TransferError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TransferError::InvalidChecksum(s.to_string());
+        let digest = match s.split_once(':') {
+            Some((_, digest)) => digest,
+            None => s,
+        }
+        .to_ascii_lowercase();
+
+        let algorithm = match s.split_once(':').map(|(a, _)| a.to_ascii_lowercase()) {
+            Some(name) if name == "sha256" => ChecksumAlgorithm::Sha256,
+            Some(name) if name == "sha512" => ChecksumAlgorithm::Sha512,
+            Some(_) => return Err(invalid()),
+            None if digest.len() == ChecksumAlgorithm::Sha256.hex_len() => {
+                ChecksumAlgorithm::Sha256
+            }
+            None => ChecksumAlgorithm::Sha512,
+        };
+
+        if digest.len() != algorithm.hex_len() || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        Ok(Self { algorithm, digest })
+    }
+}
+
+/// Integrity checks for a download.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Verification {
+    pub checksum: Option<Checksum>,
+    /// Absolute URL of the detached signature.
+    pub signature: Option<String>,
+}
+
+impl Verification {
+    /// Whether there is nothing to verify.
+    pub fn is_empty(&self) -> bool {
+        self.checksum.is_none() && self.signature.is_none()
+    }
+
+    /// Checks the data, failing if it does not match the checksum or the signature.
+    ///
+    /// * `url`: URL of the data.
+    /// * `data`: downloaded data.
+    /// * `options`: options to download the signature.
+    pub fn verify(&self, url: &str, data: &[u8], options: &TransferOptions) -> TransferResult<()> {
+        if let Some(checksum) = &self.checksum {
+            checksum.verify(url, data)?;
+        }
+
+        if let Some(signature_url) = &self.signature {
+            let mut signature = vec![];
+            Transfer::new(options.clone()).fetch(signature_url, &mut signature)?;
+            SignatureVerifier::default().verify(url, data, signature_url, &signature)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks detached signatures using the keys from a directory.
+pub struct SignatureVerifier {
+    keys_dir: PathBuf,
+}
+
+impl Default for SignatureVerifier {
+    fn default() -> Self {
+        Self::new(TRUSTED_KEYS_DIR)
+    }
+}
+
+impl SignatureVerifier {
+    /// Builds a verifier which uses the keys from the given directory.
+    pub fn new<P: AsRef<Path>>(keys_dir: P) -> Self {
+        Self {
+            keys_dir: keys_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Checks the signature of the data.
+    ///
+    /// * `url`: URL of the data, used in the error messages.
+    /// * `data`: signed data.
+    /// * `signature_url`: URL of the signature, used to find out its format.
+    /// * `signature`: signature content.
+    pub fn verify(
+        &self,
+        url: &str,
+        data: &[u8],
+        signature_url: &str,
+        signature: &[u8],
+    ) -> TransferResult<()> {
+        let tmp_dir = TempDir::with_prefix("agama-signature")?;
+        let data_path = tmp_dir.path().join("data");
+        let signature_path = tmp_dir.path().join("data.sig");
+        File::create(&data_path)?.write_all(data)?;
+        File::create(&signature_path)?.write_all(signature)?;
+
+        let invalid =
+            |reason: &str| TransferError::InvalidSignature(url.to_string(), reason.to_string());
+        if signature_url.ends_with(".minisig") {
+            let keys = self.keys(&["pub"])?;
+            if keys.is_empty() {
+                return Err(invalid("no trusted minisign keys"));
+            }
+            let verified = keys.iter().any(|key| {
+                Command::new("minisign")
+                    .arg("-Vq")
+                    .arg("-p")
+                    .arg(key)
+                    .arg("-m")
+                    .arg(&data_path)
+                    .arg("-x")
+                    .arg(&signature_path)
+                    .status()
+                    .is_ok_and(|s| s.success())
+            });
+            return if verified {
+                Ok(())
+            } else {
+                Err(invalid(
+                    "the minisign signature does not match any trusted key",
+                ))
+            };
+        }
+
+        let keys = self.keys(&["asc", "gpg"])?;
+        if keys.is_empty() {
+            return Err(invalid("no trusted GPG keys"));
+        }
+        let home_dir = tmp_dir.path().join("gnupg");
+        fs::create_dir(&home_dir)?;
+        let import = Command::new("gpg")
+            .arg("--homedir")
+            .arg(&home_dir)
+            .args(["--batch", "--quiet", "--import"])
+            .args(&keys)
+            .output()?;
+        if !import.status.success() {
+            return Err(invalid("could not import the trusted GPG keys"));
+        }
+
+        let verify = Command::new("gpg")
+            .arg("--homedir")
+            .arg(&home_dir)
+            .args(["--batch", "--quiet", "--verify"])
+            .arg(&signature_path)
+            .arg(&data_path)
+            .output()?;
+        if !verify.status.success() {
+            let message = String::from_utf8_lossy(&verify.stderr);
+            return Err(invalid(message.trim()));
+        }
+        Ok(())
+    }
+
+    /// Returns the keys with the given extensions.
+    fn keys(&self, extensions: &[&str]) -> TransferResult<Vec<PathBuf>> {
+        if !self.keys_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut keys: Vec<PathBuf> = fs::read_dir(&self.keys_dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.extension()
+                    .is_some_and(|e| extensions.iter().any(|x| e == *x))
+            })
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Checksum, ChecksumAlgorithm, SignatureVerifier};
+    use crate::utils::TransferError;
+
+    const SHA256_AGAMA: &str =
+        "sha256:52589fac98630c603bd5c2b08cb0f6ccf273cc4a4772f0ff28d49a01bc7d2f4b";
+
+    #[test]
+    fn test_parse_checksum() {
+        let checksum: Checksum = SHA256_AGAMA.parse().unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::Sha256);
+        assert_eq!(checksum.to_string(), SHA256_AGAMA);
+
+        let sha512 = "A".repeat(128);
+        let checksum: Checksum = sha512.parse().unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::Sha512);
+        assert_eq!(checksum.digest, "a".repeat(128));
+
+        assert!("md5:abcd".parse::<Checksum>().is_err());
+        assert!("sha256:abcd".parse::<Checksum>().is_err());
+        assert!(format!("sha256:{}", "z".repeat(64))
+            .parse::<Checksum>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let checksum = Checksum::compute(ChecksumAlgorithm::Sha256, b"agama");
+        assert_eq!(checksum.to_string(), SHA256_AGAMA);
+        assert!(checksum.verify("file:///agama.txt", b"agama").is_ok());
+
+        let error = checksum
+            .verify("file:///agama.txt", b"tampered")
+            .unwrap_err();
+        assert!(matches!(error, TransferError::ChecksumMismatch { .. }));
+    }
+
+    #[test]
+    fn test_signature_without_keys() {
+        let tmp_dir = tempfile::TempDir::with_prefix("agama-keys").unwrap();
+        let verifier = SignatureVerifier::new(tmp_dir.path());
+        let error = verifier
+            .verify("file:///a.json", b"{}", "file:///a.json.asc", b"")
+            .unwrap_err();
+        assert!(matches!(error, TransferError::InvalidSignature(_, _)));
+    }
+}
@@ -18,6 +18,7 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+use crate::connectivity::{ConnectivityChecks, ConnectivityReport};
 use crate::model::{AccessPoint, Connection, Device};
 use crate::types::{ConnectionState, DeviceType};
 use tokio::sync::oneshot;
@@ -81,4 +82,15 @@ pub enum Action {
     RemoveConnection(String, Responder<Result<(), NetworkStateError>>),
     /// Apply the current configuration.
     Apply(Responder<Result<(), NetworkAdapterError>>),
+    /// Apply the current configuration and verify the connectivity.
+    ApplyAndVerify(
+        Box<ConnectivityChecks>,
+        Responder<Result<ConnectivityReport, NetworkAdapterError>>,
+    ),
+    /// The configuration was applied and the connectivity verified (see
+    /// [Action::ApplyAndVerify]). It is sent by the task running the checks.
+    ConnectivityVerified(
+        Box<Result<ConnectivityReport, NetworkAdapterError>>,
+        Responder<Result<ConnectivityReport, NetworkAdapterError>>,
+    ),
 }
@@ -18,7 +18,11 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use crate::{model::StateConfig, Action, NetworkState};
+use crate::{
+    connectivity::{ConnectivityChecks, ConnectivityReport},
+    model::StateConfig,
+    Action, NetworkState,
+};
 use async_trait::async_trait;
 use thiserror::Error;
 use tokio::sync::mpsc::UnboundedSender;
@@ -40,6 +44,21 @@ pub enum NetworkAdapterError {
 pub trait Adapter {
     async fn read(&self, config: StateConfig) -> Result<NetworkState, NetworkAdapterError>;
     async fn write(&self, network: &NetworkState) -> Result<(), NetworkAdapterError>;
+    /// Writes the network configuration and verifies that the system is still reachable.
+    ///
+    /// Adapters which can undo the changes (e.g., using checkpoints) should roll them back when
+    /// the checks fail. By default, the changes are kept and only the result is reported.
+    ///
+    /// * `network`: network configuration to write.
+    /// * `checks`: checks to run after writing the configuration.
+    async fn write_and_verify(
+        &self,
+        network: &NetworkState,
+        checks: &ConnectivityChecks,
+    ) -> Result<ConnectivityReport, NetworkAdapterError> {
+        self.write(network).await?;
+        Ok(checks.verify(self).await)
+    }
     /// Returns the watcher, which is responsible for listening for network changes.
     fn watcher(&self) -> Option<Box<dyn Watcher + Send>> {
         None
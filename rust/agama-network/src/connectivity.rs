@@ -0,0 +1,370 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Connectivity checks to run after applying the network configuration.
+//!
+//! Changing the network configuration of a remote system can leave it unreachable. These checks
+//! make sure that the gateways, the DNS servers and some relevant hosts (like the registration
+//! server or a repository) are still reachable, so the adapter can roll the changes back
+//! otherwise.
+
+use crate::{model::StateConfig, Adapter};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, net::IpAddr, time::Duration};
+use tokio::{
+    net::lookup_host,
+    process::Command,
+    time::{timeout_at, Instant},
+};
+
+/// Maximum time for a single check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time to wait before running the checks again.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+/// Maximum time (in seconds) to wait for the checks to succeed.
+pub const MAX_TIMEOUT: u64 = 3600;
+
+/// Checks to run after applying the configuration.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ConnectivityChecks {
+    /// Whether to ping the gateways of the devices.
+    pub gateway: bool,
+    /// Host names to resolve.
+    pub dns: Vec<String>,
+    /// URLs to reach (e.g., the registration server or a repository).
+    pub http: Vec<String>,
+    /// Seconds to wait for the checks to succeed.
+    pub timeout: u64,
+}
+
+impl Default for ConnectivityChecks {
+    fn default() -> Self {
+        Self {
+            gateway: false,
+            dns: vec![],
+            http: vec![],
+            timeout: 30,
+        }
+    }
+}
+
+impl ConnectivityChecks {
+    /// Returns the time to wait for the checks to succeed.
+    ///
+    /// It is limited to [MAX_TIMEOUT].
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.min(MAX_TIMEOUT))
+    }
+
+    /// Runs the checks until all of them succeed or the timeout expires.
+    ///
+    /// The gateways are read from the adapter on each attempt, as they might not be known until
+    /// the devices get an address (e.g., using DHCP). The checks run concurrently and none of
+    /// them goes beyond the timeout.
+    ///
+    /// * `adapter`: adapter to read the devices from.
+    pub async fn verify<T: Adapter + Sync + ?Sized>(&self, adapter: &T) -> ConnectivityReport {
+        let deadline = Instant::now() + self.timeout();
+        loop {
+            let gateways = if self.gateway {
+                let gateways = timeout_at(deadline, gateways(adapter)).await;
+                Some(gateways.unwrap_or_default())
+            } else {
+                None
+            };
+
+            let checks = self.run(gateways, deadline).await;
+            let success = checks.iter().all(|c| c.success);
+            if success || Instant::now() + RETRY_INTERVAL >= deadline {
+                return ConnectivityReport {
+                    success,
+                    rolled_back: false,
+                    checks,
+                };
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    }
+
+    /// Runs each check once.
+    ///
+    /// * `gateways`: gateways to ping, if they should be checked.
+    /// * `deadline`: time after which the pending checks fail.
+    async fn run(
+        &self,
+        gateways: Option<Vec<IpAddr>>,
+        deadline: Instant,
+    ) -> Vec<ConnectivityCheck> {
+        let mut checks = vec![];
+
+        let gateways: Option<Vec<String>> =
+            gateways.map(|g| g.iter().map(IpAddr::to_string).collect());
+        if gateways.as_ref().is_some_and(|g| g.is_empty()) {
+            checks.push(ConnectivityCheck::new(
+                ConnectivityCheckKind::Gateway,
+                "",
+                Err("No gateway found".to_string()),
+            ));
+        }
+
+        let gateway_checks = join_all(gateways.iter().flatten().map(|target| {
+            check(
+                ConnectivityCheckKind::Gateway,
+                target,
+                ping(target),
+                deadline,
+            )
+        }));
+        let dns_checks = join_all(
+            self.dns
+                .iter()
+                .map(|host| check(ConnectivityCheckKind::Dns, host, resolve(host), deadline)),
+        );
+        let http_checks = join_all(
+            self.http
+                .iter()
+                .map(|url| check(ConnectivityCheckKind::Http, url, probe(url), deadline)),
+        );
+        let (gateway_checks, dns_checks, http_checks) =
+            tokio::join!(gateway_checks, dns_checks, http_checks);
+
+        checks.extend(gateway_checks);
+        checks.extend(dns_checks);
+        checks.extend(http_checks);
+        checks
+    }
+}
+
+/// Result of verifying the connectivity.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityReport {
+    /// Whether all the checks succeeded and the configuration was kept.
+    pub success: bool,
+    /// Whether the configuration was rolled back.
+    pub rolled_back: bool,
+    /// Result of each check.
+    pub checks: Vec<ConnectivityCheck>,
+}
+
+/// Result of a single connectivity check.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityCheck {
+    /// Kind of check.
+    pub kind: ConnectivityCheckKind,
+    /// Checked gateway, host name or URL.
+    pub target: String,
+    /// Whether the check succeeded.
+    pub success: bool,
+    /// Reason of the failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ConnectivityCheck {
+    fn new(kind: ConnectivityCheckKind, target: &str, result: Result<(), String>) -> Self {
+        Self {
+            kind,
+            target: target.to_string(),
+            success: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+/// Kind of connectivity check.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectivityCheckKind {
+    /// Ping a gateway.
+    Gateway,
+    /// Resolve a host name.
+    Dns,
+    /// Reach a URL.
+    Http,
+}
+
+/// Returns the gateways of the devices.
+///
+/// * `adapter`: adapter to read the devices from.
+async fn gateways<T: Adapter + Sync + ?Sized>(adapter: &T) -> Vec<IpAddr> {
+    let config = StateConfig {
+        access_points: false,
+        devices: true,
+        connections: false,
+        general_state: false,
+    };
+    let state = match adapter.read(config).await {
+        Ok(state) => state,
+        Err(error) => {
+            tracing::warn!("Could not read the devices: {error}");
+            return vec![];
+        }
+    };
+
+    let gateways: BTreeSet<IpAddr> = state
+        .devices
+        .iter()
+        .filter_map(|d| d.ip_config.as_ref())
+        .flat_map(|c| [c.gateway4, c.gateway6])
+        .flatten()
+        .collect();
+    gateways.into_iter().collect()
+}
+
+/// Runs a check, which fails if it takes longer than [CHECK_TIMEOUT] or goes beyond the deadline.
+///
+/// * `kind`: kind of check.
+/// * `target`: checked gateway, host name or URL.
+/// * `check`: check to run.
+/// * `deadline`: time after which the check fails.
+async fn check(
+    kind: ConnectivityCheckKind,
+    target: &str,
+    check: impl std::future::Future<Output = Result<(), String>>,
+    deadline: Instant,
+) -> ConnectivityCheck {
+    let deadline = deadline.min(Instant::now() + CHECK_TIMEOUT);
+    let result = timeout_at(deadline, check)
+        .await
+        .unwrap_or_else(|_| Err("Timed out".to_string()));
+    ConnectivityCheck::new(kind, target, result)
+}
+
+async fn ping(address: &str) -> Result<(), String> {
+    let output = Command::new("ping")
+        .args(["-n", "-c", "1", "-W", "1", address])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("No answer from {address}"))
+    }
+}
+
+async fn resolve(host: &str) -> Result<(), String> {
+    let mut addresses = lookup_host((host, 0)).await.map_err(|e| e.to_string())?;
+    match addresses.next() {
+        Some(_) => Ok(()),
+        None => Err(format!("No address found for {host}")),
+    }
+}
+
+/// Any HTTP response, even an error one, means that the server is reachable.
+async fn probe(url: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    client.head(url).send().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnectivityCheckKind, ConnectivityChecks};
+    use crate::{model::NetworkState, MemoryAdapter};
+    use std::time::{Duration, Instant};
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+    #[tokio::test]
+    async fn test_verify() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                _ = stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let adapter = MemoryAdapter::new(NetworkState::default());
+        let checks = ConnectivityChecks {
+            dns: vec!["localhost".to_string()],
+            http: vec![format!("http://{address}/repo")],
+            timeout: 1,
+            ..Default::default()
+        };
+        let report = checks.verify(&adapter).await;
+        assert!(report.success);
+        assert_eq!(report.checks.len(), 2);
+        assert!(!report.rolled_back);
+    }
+
+    #[tokio::test]
+    async fn test_verify_failed() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let adapter = MemoryAdapter::new(NetworkState::default());
+        let checks = ConnectivityChecks {
+            gateway: true,
+            http: vec![format!("http://{address}/repo")],
+            timeout: 1,
+            ..Default::default()
+        };
+        let report = checks.verify(&adapter).await;
+        assert!(!report.success);
+
+        let gateway = &report.checks[0];
+        assert_eq!(gateway.kind, ConnectivityCheckKind::Gateway);
+        assert!(!gateway.success);
+        let http = &report.checks[1];
+        assert_eq!(http.kind, ConnectivityCheckKind::Http);
+        assert!(http.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_verify_deadline() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut streams = vec![];
+            while let Ok((stream, _)) = listener.accept().await {
+                streams.push(stream);
+            }
+        });
+
+        let adapter = MemoryAdapter::new(NetworkState::default());
+        let checks = ConnectivityChecks {
+            http: vec![
+                format!("http://{address}/first"),
+                format!("http://{address}/second"),
+            ],
+            timeout: 1,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let report = checks.verify(&adapter).await;
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(!report.success);
+        assert_eq!(report.checks.len(), 2);
+        assert_eq!(report.checks[0].error, Some("Timed out".to_string()));
+    }
+}
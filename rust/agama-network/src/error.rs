@@ -72,6 +72,16 @@ pub enum NetworkStateError {
     InvalidWirelessBand(String),
     #[error("Invalid bssid: '{0}'")]
     InvalidBssid(String),
+    #[error("Invalid InfiniBand transport mode: '{0}'")]
+    InvalidInfinibandTransportMode(String),
+    #[error("Invalid InfiniBand partition key: '{0}'")]
+    InvalidInfinibandPKey(i32),
+    #[error("Invalid TUN mode: '{0}'")]
+    InvalidTunMode(String),
+    #[error("Invalid OVS interface type: '{0}'")]
+    InvalidOvsInterfaceType(String),
+    #[error("Connection '{0}' includes several device types: {1}")]
+    SeveralDeviceTypes(String, String),
 }
 
 impl From<NetworkStateError> for zbus::fdo::Error {
@@ -0,0 +1,40 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Support for reading and writing [NetworkManager
+//! keyfiles](https://networkmanager.dev/docs/api/latest/nm-settings-keyfile.html).
+//!
+//! This module defines [an adapter](KeyfileAdapter) which works directly on the
+//! `*.nmconnection` files, so it does not need a running NetworkManager (or D-Bus). Only the
+//! connections are supported: the changes are written to the files, but they are not applied to
+//! the running system. It is used:
+//!
+//! * by the web server, which falls back to it when it cannot connect to NetworkManager.
+//! * to configure the network of a target system offline, pointing the adapter to its root
+//!   directory (e.g., `KeyfileAdapter::from_root("/mnt")`).
+//! * by `agama config generate --local --root <DIR>`, which takes the connections of a system
+//!   without connecting to the Agama server.
+
+mod adapter;
+mod connection;
+mod error;
+mod file;
+
+pub use adapter::KeyfileAdapter;
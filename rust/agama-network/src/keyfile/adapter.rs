@@ -0,0 +1,312 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+use super::{
+    connection::{connection_from_keyfile, connection_to_keyfile, controller_from_keyfile},
+    error::KeyfileError,
+    file::Keyfile,
+};
+use crate::{
+    model::{Connection, NetworkState, StateConfig},
+    Adapter, NetworkAdapterError,
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use uuid::Uuid;
+
+/// Directory containing the NetworkManager connection profiles.
+pub const CONNECTIONS_DIR: &str = "/etc/NetworkManager/system-connections";
+const EXTENSION: &str = "nmconnection";
+
+/// An adapter for NetworkManager keyfiles.
+///
+/// It reads and writes the `*.nmconnection` files of a directory, so NetworkManager does not
+/// need to be running. Devices, access points and the general state are not available, so
+/// they are left empty.
+pub struct KeyfileAdapter {
+    path: PathBuf,
+}
+
+impl KeyfileAdapter {
+    /// Returns an adapter for the given directory.
+    ///
+    /// * `path`: directory containing the keyfiles (e.g., `/mnt/etc/NetworkManager/system-connections`).
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the adapter for the connections directory of the system mounted at the given root.
+    ///
+    /// * `root`: root directory of the system (e.g., `/mnt` for the target system).
+    pub fn from_root<P: AsRef<Path>>(root: P) -> Self {
+        Self::new(root.as_ref().join(CONNECTIONS_DIR.trim_start_matches('/')))
+    }
+
+    /// Returns the adapter for the system's connections directory.
+    pub fn from_system() -> Self {
+        Self::from_root("/")
+    }
+
+    /// Directory containing the keyfiles.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the connections, returning the file where each one is defined.
+    ///
+    /// Invalid or unsupported files are skipped.
+    fn read_files(&self) -> Result<Vec<(PathBuf, Connection)>, KeyfileError> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.path)
+            .map_err(|e| KeyfileError::IO(self.path.clone(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .collect();
+        paths.sort();
+
+        let mut files = vec![];
+        let mut controlled_by: HashMap<Uuid, String> = HashMap::new();
+        for path in paths {
+            let result = fs::read_to_string(&path)
+                .map_err(|e| KeyfileError::IO(path.clone(), e))
+                .and_then(|content| Keyfile::parse(&content))
+                .and_then(|keyfile| {
+                    let connection = connection_from_keyfile(&keyfile)?;
+                    Ok((connection, controller_from_keyfile(&keyfile)))
+                });
+
+            match result {
+                Ok((connection, controller)) => {
+                    if let Some(controller) = controller {
+                        controlled_by.insert(connection.uuid, controller);
+                    }
+                    files.push((path, connection));
+                }
+                Err(e) => {
+                    tracing::warn!("Could not process keyfile {}: {}", path.display(), e);
+                }
+            }
+        }
+
+        // The controller can be referenced by its UUID or by its interface name.
+        let uuids_map: HashMap<String, Uuid> = files
+            .iter()
+            .flat_map(|(_, conn)| {
+                let mut keys = vec![(conn.uuid.to_string(), conn.uuid)];
+                if let Some(interface) = &conn.interface {
+                    keys.push((interface.to_string(), conn.uuid));
+                }
+                keys
+            })
+            .collect();
+        for (_, conn) in files.iter_mut() {
+            if let Some(controller) = controlled_by.get(&conn.uuid) {
+                conn.controller = Uuid::from_str(controller)
+                    .ok()
+                    .or_else(|| uuids_map.get(controller).copied());
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Returns the path for a new connection.
+    ///
+    /// It uses the connection ID as file name, adding the UUID if there is already a file with
+    /// the same name.
+    ///
+    /// * `conn`: connection to get the path for.
+    fn path_for(&self, conn: &Connection) -> PathBuf {
+        let name = conn.id.replace('/', "_");
+        let path = self.path.join(format!("{name}.{EXTENSION}"));
+        if path.exists() {
+            self.path.join(format!("{name}-{}.{EXTENSION}", conn.uuid))
+        } else {
+            path
+        }
+    }
+
+    /// Writes the keyfile, which is only readable by the owner as it might contain secrets.
+    ///
+    /// * `path`: keyfile path.
+    /// * `keyfile`: keyfile to write.
+    fn write_file(&self, path: &Path, keyfile: &Keyfile) -> Result<(), KeyfileError> {
+        fs::create_dir_all(&self.path).map_err(|e| KeyfileError::IO(self.path.clone(), e))?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| KeyfileError::IO(path.to_path_buf(), e))?;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .and_then(|_| file.write_all(keyfile.to_string().as_bytes()))
+            .map_err(|e| KeyfileError::IO(path.to_path_buf(), e))
+    }
+}
+
+#[async_trait]
+impl Adapter for KeyfileAdapter {
+    async fn read(&self, config: StateConfig) -> Result<NetworkState, NetworkAdapterError> {
+        let mut state = NetworkState::default();
+
+        if config.connections {
+            state.connections = self
+                .read_files()
+                .map_err(|e| NetworkAdapterError::Read(anyhow!(e)))?
+                .into_iter()
+                .map(|(_, conn)| conn)
+                .collect();
+        }
+
+        Ok(state)
+    }
+
+    /// Writes the connections to the keyfiles directory.
+    ///
+    /// * `network`: network model.
+    async fn write(&self, network: &NetworkState) -> Result<(), NetworkAdapterError> {
+        let files = self
+            .read_files()
+            .map_err(|e| NetworkAdapterError::Write(anyhow!(e)))?;
+
+        for conn in &network.connections {
+            let ctrl = conn
+                .controller
+                .and_then(|uuid| network.get_connection_by_uuid(uuid));
+
+            /* Consider the connection as removed, whenever the controller connection
+            was removed */
+            let is_removed = conn.is_removed() || ctrl.is_some_and(|c| c.is_removed());
+            let current = files.iter().find(|(_, c)| c.uuid == conn.uuid);
+
+            let result = match current {
+                Some((path, _)) if is_removed => {
+                    tracing::info!("Deleting connection {} ({})", conn.id, conn.uuid);
+                    fs::remove_file(path).map_err(|e| KeyfileError::IO(path.to_path_buf(), e))
+                }
+                None if is_removed => continue,
+                Some((_, old_conn)) if old_conn == conn => {
+                    tracing::info!(
+                        "No change detected for connection {} ({})",
+                        conn.id,
+                        conn.uuid
+                    );
+                    continue;
+                }
+                _ => {
+                    tracing::info!("Updating connection {} ({})", conn.id, conn.uuid);
+                    let path = match current {
+                        Some((path, _)) => path.to_path_buf(),
+                        None => self.path_for(conn),
+                    };
+                    self.write_file(&path, &connection_to_keyfile(conn, ctrl))
+                }
+            };
+
+            if let Err(e) = result {
+                tracing::error!("Could not process the connection {}: {}", conn.id, &e);
+                return Err(NetworkAdapterError::Write(anyhow!(e)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyfileAdapter;
+    use crate::{
+        model::{Connection, GeneralState, NetworkState, StateConfig},
+        types::DeviceType,
+        Adapter,
+    };
+    use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+    #[tokio::test]
+    async fn test_write_and_read_connections() {
+        let dir = tempfile::tempdir().unwrap();
+        let adapter = KeyfileAdapter::new(dir.path().join("system-connections"));
+
+        let mut bond = Connection::new("bond0".to_string(), DeviceType::Bond);
+        bond.interface = Some("bond0".to_string());
+        let mut eth0 = Connection::new("eth0".to_string(), DeviceType::Ethernet);
+        eth0.interface = Some("eth0".to_string());
+        eth0.controller = Some(bond.uuid);
+        let mut state = NetworkState::new(
+            GeneralState::default(),
+            vec![],
+            vec![],
+            vec![bond.clone(), eth0.clone()],
+        );
+        adapter.write(&state).await.unwrap();
+
+        let path = dir.path().join("system-connections/eth0.nmconnection");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let read = adapter.read(StateConfig::default()).await.unwrap();
+        assert_eq!(read.connections.len(), 2);
+        let read_eth0 = read.get_connection("eth0").unwrap();
+        assert_eq!(read_eth0, &eth0);
+
+        // removing the controller removes the ports too
+        state.get_connection_mut("bond0").unwrap().remove();
+        adapter.write(&state).await.unwrap();
+        let read = adapter.read(StateConfig::default()).await.unwrap();
+        assert!(read.connections.is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_from_root() {
+        let adapter = KeyfileAdapter::from_root("/mnt");
+        assert_eq!(
+            adapter.path(),
+            Path::new("/mnt/etc/NetworkManager/system-connections")
+        );
+        assert_eq!(
+            KeyfileAdapter::from_system().path(),
+            Path::new("/etc/NetworkManager/system-connections")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let adapter = KeyfileAdapter::new(dir.path().join("missing"));
+        let state = adapter.read(StateConfig::default()).await.unwrap();
+        assert!(state.connections.is_empty());
+    }
+}
@@ -0,0 +1,1146 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Conversion between [Connection] and [Keyfile].
+//!
+//! The keys follow the [keyfile
+//! format](https://networkmanager.dev/docs/api/latest/nm-settings-keyfile.html), which
+//! differs from the D-Bus one in some details (e.g., addresses and routes are written as
+//! `address1`, `route1`, etc.).
+use super::{
+    error::KeyfileError,
+    file::{group_alias, Keyfile},
+};
+use crate::model::*;
+use crate::settings::{Dhcp4ClientSettings, Dhcp6ClientSettings};
+use crate::types::{BondMode, SSID};
+use cidr::IpInet;
+use macaddr::MacAddr6;
+use std::{net::IpAddr, str::FromStr};
+use uuid::Uuid;
+
+const CONNECTION_KEY: &str = "connection";
+const ETHERNET_KEY: &str = "ethernet";
+const BOND_KEY: &str = "bond";
+const WIRELESS_KEY: &str = "wifi";
+const WIRELESS_SECURITY_KEY: &str = "wifi-security";
+const LOOPBACK_KEY: &str = "loopback";
+const DUMMY_KEY: &str = "dummy";
+const VLAN_KEY: &str = "vlan";
+const BRIDGE_KEY: &str = "bridge";
+const BRIDGE_PORT_KEY: &str = "bridge-port";
+const INFINIBAND_KEY: &str = "infiniband";
+const TUN_KEY: &str = "tun";
+const IEEE_8021X_KEY: &str = "802-1x";
+const OVS_PORT: &str = "ovs-port";
+const OVS_INTERFACE: &str = "ovs-interface";
+const OVS_BRIDGE: &str = "ovs-bridge";
+const MATCH_KEY: &str = "match";
+const IPV4_KEY: &str = "ipv4";
+const IPV6_KEY: &str = "ipv6";
+
+/// Converts a connection into a keyfile.
+///
+/// * `conn`: connection to convert.
+/// * `controller`: controller connection, if any.
+pub fn connection_to_keyfile(conn: &Connection, controller: Option<&Connection>) -> Keyfile {
+    let mut keyfile = Keyfile::default();
+    keyfile.set(CONNECTION_KEY, "id", &conn.id);
+    keyfile.set(CONNECTION_KEY, "uuid", conn.uuid);
+    keyfile.set(CONNECTION_KEY, "type", connection_type(&conn.config));
+    if let Some(interface) = &conn.interface {
+        keyfile.set(CONNECTION_KEY, "interface-name", interface);
+    } else if matches!(conn.config, ConnectionConfig::Bond(_)) {
+        keyfile.set(CONNECTION_KEY, "interface-name", &conn.id);
+    }
+    if !conn.autoconnect {
+        keyfile.set(CONNECTION_KEY, "autoconnect", false);
+    }
+    if let Some(zone) = &conn.firewall_zone {
+        keyfile.set(CONNECTION_KEY, "zone", zone);
+    }
+
+    // "master" and "slave-type" are still understood by the newer versions, which prefer
+    // "controller" and "port-type".
+    if let Some(controller) = controller {
+        let port_type = match controller.config {
+            ConnectionConfig::Bond(_) => BOND_KEY,
+            ConnectionConfig::Bridge(_) => BRIDGE_KEY,
+            ConnectionConfig::OvsPort(_) => OVS_PORT,
+            ConnectionConfig::OvsBridge(_) => OVS_BRIDGE,
+            _ => {
+                tracing::error!("Controller {} has unhandled config type", controller.id);
+                ""
+            }
+        };
+        keyfile.set(CONNECTION_KEY, "master", controller.uuid);
+        keyfile.set(CONNECTION_KEY, "slave-type", port_type);
+    }
+
+    if matches!(
+        conn.config,
+        ConnectionConfig::Bond(_)
+            | ConnectionConfig::Bridge(_)
+            | ConnectionConfig::OvsBridge(_)
+            | ConnectionConfig::OvsPort(_)
+    ) {
+        keyfile.set(CONNECTION_KEY, "autoconnect-slaves", 1);
+    }
+
+    match &conn.config {
+        ConnectionConfig::Wireless(wireless) => {
+            link_to_keyfile(&mut keyfile, WIRELESS_KEY, conn);
+            wireless_config_to_keyfile(&mut keyfile, wireless);
+        }
+        ConnectionConfig::Bond(bond) => {
+            keyfile.set(BOND_KEY, "mode", bond.mode);
+            let mut options: Vec<_> = bond.options.0.iter().collect();
+            options.sort();
+            for (key, value) in options {
+                keyfile.set(BOND_KEY, key, value);
+            }
+        }
+        ConnectionConfig::Vlan(vlan) => {
+            keyfile.set(VLAN_KEY, "id", vlan.id);
+            keyfile.set(VLAN_KEY, "parent", &vlan.parent);
+            keyfile.set(VLAN_KEY, "protocol", &vlan.protocol);
+        }
+        ConnectionConfig::Bridge(bridge) => bridge_config_to_keyfile(&mut keyfile, bridge),
+        ConnectionConfig::Infiniband(infiniband) => {
+            keyfile.set(INFINIBAND_KEY, "transport-mode", &infiniband.transport_mode);
+            keyfile.set(INFINIBAND_KEY, "p-key", infiniband.p_key.unwrap_or(-1));
+            if let Some(parent) = &infiniband.parent {
+                keyfile.set(INFINIBAND_KEY, "parent", parent);
+            }
+        }
+        ConnectionConfig::Tun(tun) => {
+            keyfile.set(TUN_KEY, "mode", tun.mode.clone() as u32);
+            if let Some(owner) = &tun.owner {
+                keyfile.set(TUN_KEY, "owner", owner);
+            }
+            if let Some(group) = &tun.group {
+                keyfile.set(TUN_KEY, "group", group);
+            }
+        }
+        ConnectionConfig::OvsBridge(bridge) => {
+            if let Some(mcast_snooping) = bridge.mcast_snooping_enable {
+                keyfile.set(OVS_BRIDGE, "mcast-snooping-enable", mcast_snooping);
+            }
+            if let Some(rstp) = bridge.rstp_enable {
+                keyfile.set(OVS_BRIDGE, "rstp-enable", rstp);
+            }
+            if let Some(stp) = bridge.stp_enable {
+                keyfile.set(OVS_BRIDGE, "stp-enable", stp);
+            }
+        }
+        ConnectionConfig::OvsPort(port) => {
+            if let Some(tag) = port.tag {
+                keyfile.set(OVS_PORT, "tag", tag);
+            }
+        }
+        ConnectionConfig::OvsInterface(interface) => {
+            keyfile.set(OVS_INTERFACE, "type", &interface.interface_type);
+        }
+        _ => {}
+    }
+
+    if conn.is_ethernet() {
+        link_to_keyfile(&mut keyfile, ETHERNET_KEY, conn);
+    }
+
+    if let PortConfig::Bridge(bridge_port) = &conn.port_config {
+        if let Some(priority) = bridge_port.priority {
+            keyfile.set(BRIDGE_PORT_KEY, "priority", priority);
+        }
+        if let Some(path_cost) = bridge_port.path_cost {
+            keyfile.set(BRIDGE_PORT_KEY, "path-cost", path_cost);
+        }
+    }
+
+    if let Some(ieee_8021x) = &conn.ieee_8021x_config {
+        ieee_8021x_config_to_keyfile(&mut keyfile, ieee_8021x);
+    }
+
+    match_config_to_keyfile(&mut keyfile, &conn.match_config);
+    ip_config_to_keyfile(&mut keyfile, &conn.ip_config);
+    keyfile
+}
+
+/// Converts a keyfile into a connection.
+///
+/// The controller is not set, as it might be referenced by its interface name. Use
+/// [controller_from_keyfile] to find out the controller.
+///
+/// * `keyfile`: keyfile to convert.
+pub fn connection_from_keyfile(keyfile: &Keyfile) -> Result<Connection, KeyfileError> {
+    let id = required(keyfile, CONNECTION_KEY, "id")?;
+    let uuid = required(keyfile, CONNECTION_KEY, "uuid")?;
+    let uuid = Uuid::from_str(&uuid).map_err(|_| invalid(CONNECTION_KEY, "uuid", &uuid))?;
+    let connection_type = required(keyfile, CONNECTION_KEY, "type")?;
+
+    let config = match group_alias(&connection_type) {
+        ETHERNET_KEY => ConnectionConfig::Ethernet,
+        WIRELESS_KEY => ConnectionConfig::Wireless(wireless_config_from_keyfile(keyfile)?),
+        LOOPBACK_KEY => ConnectionConfig::Loopback,
+        DUMMY_KEY => ConnectionConfig::Dummy,
+        BOND_KEY => ConnectionConfig::Bond(bond_config_from_keyfile(keyfile)?),
+        VLAN_KEY => ConnectionConfig::Vlan(VlanConfig {
+            id: value(keyfile, VLAN_KEY, "id", u32::from_str)?.unwrap_or_default(),
+            parent: required(keyfile, VLAN_KEY, "parent")?,
+            protocol: value(keyfile, VLAN_KEY, "protocol", VlanProtocol::from_str)?
+                .unwrap_or_default(),
+        }),
+        BRIDGE_KEY => ConnectionConfig::Bridge(BridgeConfig {
+            stp: value(keyfile, BRIDGE_KEY, "stp", parse_bool)?,
+            priority: value(keyfile, BRIDGE_KEY, "priority", u32::from_str)?,
+            forward_delay: value(keyfile, BRIDGE_KEY, "forward-delay", u32::from_str)?,
+            hello_time: value(keyfile, BRIDGE_KEY, "hello-time", u32::from_str)?,
+            max_age: value(keyfile, BRIDGE_KEY, "max-age", u32::from_str)?,
+            ageing_time: value(keyfile, BRIDGE_KEY, "ageing-time", u32::from_str)?,
+        }),
+        INFINIBAND_KEY => ConnectionConfig::Infiniband(InfinibandConfig {
+            p_key: value(keyfile, INFINIBAND_KEY, "p-key", i32::from_str)?
+                .filter(|p_key| *p_key != -1),
+            parent: value(keyfile, INFINIBAND_KEY, "parent", String::from_str)?,
+            transport_mode: value(
+                keyfile,
+                INFINIBAND_KEY,
+                "transport-mode",
+                InfinibandTransportMode::from_str,
+            )?
+            .unwrap_or_default(),
+        }),
+        TUN_KEY => ConnectionConfig::Tun(TunConfig {
+            mode: match value(keyfile, TUN_KEY, "mode", u32::from_str)? {
+                Some(2) => TunMode::Tap,
+                _ => TunMode::Tun,
+            },
+            owner: value(keyfile, TUN_KEY, "owner", String::from_str)?,
+            group: value(keyfile, TUN_KEY, "group", String::from_str)?,
+        }),
+        OVS_BRIDGE => ConnectionConfig::OvsBridge(OvsBridgeConfig {
+            mcast_snooping_enable: value(keyfile, OVS_BRIDGE, "mcast-snooping-enable", parse_bool)?,
+            rstp_enable: value(keyfile, OVS_BRIDGE, "rstp-enable", parse_bool)?,
+            stp_enable: value(keyfile, OVS_BRIDGE, "stp-enable", parse_bool)?,
+        }),
+        OVS_PORT => ConnectionConfig::OvsPort(OvsPortConfig {
+            tag: value(keyfile, OVS_PORT, "tag", u16::from_str)?,
+        }),
+        OVS_INTERFACE => ConnectionConfig::OvsInterface(OvsInterfaceConfig {
+            interface_type: value(keyfile, OVS_INTERFACE, "type", OvsInterfaceType::from_str)?
+                .unwrap_or_default(),
+        }),
+        _ => return Err(KeyfileError::UnsupportedConnectionType(connection_type)),
+    };
+
+    let mut connection = Connection {
+        id,
+        uuid,
+        interface: value(keyfile, CONNECTION_KEY, "interface-name", String::from_str)?,
+        firewall_zone: value(keyfile, CONNECTION_KEY, "zone", String::from_str)?,
+        config,
+        ..Default::default()
+    };
+
+    if let Some(autoconnect) = value(keyfile, CONNECTION_KEY, "autoconnect", parse_bool)? {
+        connection.autoconnect = autoconnect;
+    }
+
+    let link = if keyfile.has_group(WIRELESS_KEY) {
+        WIRELESS_KEY
+    } else {
+        ETHERNET_KEY
+    };
+    connection.mac_address = value(keyfile, link, "mac-address", MacAddr6::from_str)?;
+    connection.custom_mac_address =
+        value(keyfile, link, "cloned-mac-address", MacAddress::from_str)?.unwrap_or_default();
+    connection.mtu = value(keyfile, link, "mtu", u32::from_str)?.unwrap_or_default();
+
+    if keyfile.has_group(BRIDGE_PORT_KEY) {
+        connection.port_config = PortConfig::Bridge(BridgePortConfig {
+            priority: value(keyfile, BRIDGE_PORT_KEY, "priority", u32::from_str)?,
+            path_cost: value(keyfile, BRIDGE_PORT_KEY, "path-cost", u32::from_str)?,
+        });
+    }
+
+    if keyfile.has_group(IEEE_8021X_KEY) {
+        connection.ieee_8021x_config = Some(ieee_8021x_config_from_keyfile(keyfile)?);
+    }
+
+    connection.match_config = MatchConfig {
+        driver: keyfile.get_list(MATCH_KEY, "driver"),
+        interface: keyfile.get_list(MATCH_KEY, "interface-name"),
+        path: keyfile.get_list(MATCH_KEY, "path"),
+        kernel: keyfile.get_list(MATCH_KEY, "kernel-command-line"),
+    };
+    connection.ip_config = ip_config_from_keyfile(keyfile)?;
+    Ok(connection)
+}
+
+/// Returns the controller (UUID or interface name) of the connection, if any.
+///
+/// * `keyfile`: keyfile to get the controller from.
+pub fn controller_from_keyfile(keyfile: &Keyfile) -> Option<String> {
+    keyfile
+        .get(CONNECTION_KEY, "controller")
+        .or_else(|| keyfile.get(CONNECTION_KEY, "master"))
+        .filter(|controller| !controller.is_empty())
+}
+
+fn connection_type(config: &ConnectionConfig) -> &'static str {
+    match config {
+        ConnectionConfig::Ethernet => ETHERNET_KEY,
+        ConnectionConfig::Wireless(_) => WIRELESS_KEY,
+        ConnectionConfig::Loopback => LOOPBACK_KEY,
+        ConnectionConfig::Dummy => DUMMY_KEY,
+        ConnectionConfig::Bond(_) => BOND_KEY,
+        ConnectionConfig::Vlan(_) => VLAN_KEY,
+        ConnectionConfig::Bridge(_) => BRIDGE_KEY,
+        ConnectionConfig::Infiniband(_) => INFINIBAND_KEY,
+        ConnectionConfig::Tun(_) => TUN_KEY,
+        ConnectionConfig::OvsBridge(_) => OVS_BRIDGE,
+        ConnectionConfig::OvsPort(_) => OVS_PORT,
+        ConnectionConfig::OvsInterface(_) => OVS_INTERFACE,
+    }
+}
+
+/// Writes the link settings (MAC address and MTU) to the given group.
+fn link_to_keyfile(keyfile: &mut Keyfile, group: &str, conn: &Connection) {
+    if let Some(mac) = conn.mac_address {
+        keyfile.set(group, "mac-address", mac);
+    }
+    if conn.custom_mac_address != MacAddress::Unset {
+        keyfile.set(group, "cloned-mac-address", &conn.custom_mac_address);
+    }
+    if conn.mtu != 0 {
+        keyfile.set(group, "mtu", conn.mtu);
+    }
+}
+
+fn wireless_config_to_keyfile(keyfile: &mut Keyfile, config: &WirelessConfig) {
+    keyfile.set(WIRELESS_KEY, "mode", config.mode);
+    match std::str::from_utf8(config.ssid.to_vec()) {
+        Ok(ssid) if !ssid.contains(';') => keyfile.set(WIRELESS_KEY, "ssid", ssid),
+        _ => keyfile.set_list(WIRELESS_KEY, "ssid", config.ssid.to_vec()),
+    }
+    if config.hidden {
+        keyfile.set(WIRELESS_KEY, "hidden", true);
+    }
+    if let Some(band) = &config.band {
+        keyfile.set(WIRELESS_KEY, "band", band);
+        keyfile.set(WIRELESS_KEY, "channel", config.channel);
+    }
+    if let Some(bssid) = &config.bssid {
+        keyfile.set(WIRELESS_KEY, "bssid", bssid);
+    }
+
+    if config.security == SecurityProtocol::WEP && config.wep_security.is_none() {
+        return;
+    }
+
+    keyfile.set(WIRELESS_SECURITY_KEY, "key-mgmt", config.security);
+    keyfile.set_list(WIRELESS_SECURITY_KEY, "group", &config.group_algorithms);
+    keyfile.set_list(
+        WIRELESS_SECURITY_KEY,
+        "pairwise",
+        &config.pairwise_algorithms,
+    );
+    keyfile.set_list(
+        WIRELESS_SECURITY_KEY,
+        "proto",
+        &config.wpa_protocol_versions,
+    );
+    if config.pmf != 0 {
+        keyfile.set(WIRELESS_SECURITY_KEY, "pmf", config.pmf);
+    }
+    if let Some(password) = &config.password {
+        keyfile.set(WIRELESS_SECURITY_KEY, "psk", password);
+    }
+    if let Some(wep_security) = &config.wep_security {
+        keyfile.set(
+            WIRELESS_SECURITY_KEY,
+            "wep-key-type",
+            wep_security.wep_key_type.clone() as u32,
+        );
+        keyfile.set(WIRELESS_SECURITY_KEY, "auth-alg", &wep_security.auth_alg);
+        for (index, key) in wep_security.keys.iter().take(4).enumerate() {
+            keyfile.set(WIRELESS_SECURITY_KEY, &format!("wep-key{index}"), key);
+        }
+        keyfile.set(
+            WIRELESS_SECURITY_KEY,
+            "wep-tx-keyidx",
+            wep_security.wep_key_index,
+        );
+    }
+}
+
+fn wireless_config_from_keyfile(keyfile: &Keyfile) -> Result<WirelessConfig, KeyfileError> {
+    let mut config = WirelessConfig {
+        mode: value(keyfile, WIRELESS_KEY, "mode", |v| WirelessMode::try_from(v))?
+            .unwrap_or_default(),
+        ssid: value(keyfile, WIRELESS_KEY, "ssid", parse_ssid)?.unwrap_or_default(),
+        hidden: value(keyfile, WIRELESS_KEY, "hidden", parse_bool)?.unwrap_or_default(),
+        band: value(keyfile, WIRELESS_KEY, "band", |v| WirelessBand::try_from(v))?,
+        channel: value(keyfile, WIRELESS_KEY, "channel", u32::from_str)?.unwrap_or_default(),
+        bssid: value(keyfile, WIRELESS_KEY, "bssid", MacAddr6::from_str)?,
+        ..Default::default()
+    };
+
+    if !keyfile.has_group(WIRELESS_SECURITY_KEY) {
+        return Ok(config);
+    }
+
+    let group = WIRELESS_SECURITY_KEY;
+    config.security = value(keyfile, group, "key-mgmt", |v| {
+        SecurityProtocol::try_from(v)
+    })?
+    .unwrap_or_default();
+    config.password = value(keyfile, group, "psk", String::from_str)?;
+    config.group_algorithms = list(keyfile, group, "group", GroupAlgorithm::from_str)?;
+    config.pairwise_algorithms = list(keyfile, group, "pairwise", PairwiseAlgorithm::from_str)?;
+    config.wpa_protocol_versions = list(keyfile, group, "proto", WPAProtocolVersion::from_str)?;
+    config.pmf = value(keyfile, group, "pmf", i32::from_str)?.unwrap_or_default();
+
+    if config.security == SecurityProtocol::WEP {
+        let keys = (0..4)
+            .filter_map(|index| keyfile.get(group, &format!("wep-key{index}")))
+            .collect();
+        config.wep_security = Some(WEPSecurity {
+            wep_key_type: value(keyfile, group, "wep-key-type", |v| {
+                u32::from_str(v).map(|t| WEPKeyType::try_from(t).unwrap_or_default())
+            })?
+            .unwrap_or_default(),
+            auth_alg: value(keyfile, group, "auth-alg", |v| WEPAuthAlg::try_from(v))?
+                .unwrap_or_default(),
+            wep_key_index: value(keyfile, group, "wep-tx-keyidx", u32::from_str)?
+                .unwrap_or_default(),
+            keys,
+        });
+    }
+
+    Ok(config)
+}
+
+fn bond_config_from_keyfile(keyfile: &Keyfile) -> Result<BondConfig, KeyfileError> {
+    let mut bond = BondConfig::default();
+    for (key, value) in keyfile.entries(BOND_KEY) {
+        if key == "mode" {
+            bond.mode =
+                BondMode::try_from(value.as_str()).map_err(|_| invalid(BOND_KEY, key, &value))?;
+        } else {
+            bond.options.0.insert(key.to_string(), value);
+        }
+    }
+    Ok(bond)
+}
+
+fn bridge_config_to_keyfile(keyfile: &mut Keyfile, bridge: &BridgeConfig) {
+    // make sure that the group is written even if all the values are missing
+    keyfile.set(BRIDGE_KEY, "stp", bridge.stp.unwrap_or(true));
+    if let Some(priority) = bridge.priority {
+        keyfile.set(BRIDGE_KEY, "priority", priority);
+    }
+    if let Some(forward_delay) = bridge.forward_delay {
+        keyfile.set(BRIDGE_KEY, "forward-delay", forward_delay);
+    }
+    if let Some(hello_time) = bridge.hello_time {
+        keyfile.set(BRIDGE_KEY, "hello-time", hello_time);
+    }
+    if let Some(max_age) = bridge.max_age {
+        keyfile.set(BRIDGE_KEY, "max-age", max_age);
+    }
+    if let Some(ageing_time) = bridge.ageing_time {
+        keyfile.set(BRIDGE_KEY, "ageing-time", ageing_time);
+    }
+}
+
+fn ieee_8021x_config_to_keyfile(keyfile: &mut Keyfile, config: &IEEE8021XConfig) {
+    let group = IEEE_8021X_KEY;
+    keyfile.set_list(group, "eap", &config.eap);
+    let values = [
+        (
+            "phase2-auth",
+            config.phase2_auth.as_ref().map(|m| m.to_string()),
+        ),
+        ("identity", config.identity.clone()),
+        ("password", config.password.clone()),
+        ("ca-cert", config.ca_cert.clone()),
+        ("ca-cert-password", config.ca_cert_password.clone()),
+        ("client-cert", config.client_cert.clone()),
+        ("client-cert-password", config.client_cert_password.clone()),
+        ("private-key", config.private_key.clone()),
+        ("private-key-password", config.private_key_password.clone()),
+        ("anonymous-identity", config.anonymous_identity.clone()),
+        ("phase1-peapver", config.peap_version.clone()),
+    ];
+    for (key, value) in values {
+        if let Some(value) = value {
+            keyfile.set(group, key, value);
+        }
+    }
+    if config.peap_label {
+        keyfile.set(group, "phase1-peaplabel", 1);
+    }
+}
+
+fn ieee_8021x_config_from_keyfile(keyfile: &Keyfile) -> Result<IEEE8021XConfig, KeyfileError> {
+    let group = IEEE_8021X_KEY;
+    let path = |key: &str| {
+        keyfile
+            .get(group, key)
+            .map(|p| p.strip_prefix("file://").unwrap_or(&p).to_string())
+    };
+
+    Ok(IEEE8021XConfig {
+        eap: list(keyfile, group, "eap", EAPMethod::from_str)?,
+        phase2_auth: value(keyfile, group, "phase2-auth", Phase2AuthMethod::from_str)?,
+        identity: keyfile.get(group, "identity"),
+        password: keyfile.get(group, "password"),
+        ca_cert: path("ca-cert"),
+        ca_cert_password: keyfile.get(group, "ca-cert-password"),
+        client_cert: path("client-cert"),
+        client_cert_password: keyfile.get(group, "client-cert-password"),
+        private_key: path("private-key"),
+        private_key_password: keyfile.get(group, "private-key-password"),
+        anonymous_identity: keyfile.get(group, "anonymous-identity"),
+        peap_version: keyfile.get(group, "phase1-peapver"),
+        peap_label: keyfile.get(group, "phase1-peaplabel").as_deref() == Some("1"),
+    })
+}
+
+fn match_config_to_keyfile(keyfile: &mut Keyfile, config: &MatchConfig) {
+    keyfile.set_list(MATCH_KEY, "driver", &config.driver);
+    keyfile.set_list(MATCH_KEY, "interface-name", &config.interface);
+    keyfile.set_list(MATCH_KEY, "path", &config.path);
+    keyfile.set_list(MATCH_KEY, "kernel-command-line", &config.kernel);
+}
+
+fn ip_config_to_keyfile(keyfile: &mut Keyfile, config: &IpConfig) {
+    keyfile.set(IPV4_KEY, "method", config.method4);
+    ip_family_to_keyfile(keyfile, IPV4_KEY, config, |ip| ip.is_ipv4());
+    if let Some(gateway) = &config.gateway4 {
+        keyfile.set(IPV4_KEY, "gateway", gateway);
+    }
+    routes_to_keyfile(keyfile, IPV4_KEY, &config.routes4);
+    if let Some(dns_priority) = config.dns_priority4 {
+        keyfile.set(IPV4_KEY, "dns-priority", dns_priority);
+    }
+    if let Some(may_fail) = config.may_fail4 {
+        keyfile.set(IPV4_KEY, "may-fail", may_fail);
+    }
+    if config.link_local4 != LinkLocal::Default {
+        keyfile.set(IPV4_KEY, "link-local", config.link_local4 as i32);
+    }
+    if let Some(dhcp) = &config.dhcp4_settings {
+        dhcp_to_keyfile(keyfile, IPV4_KEY, dhcp.send_hostname, &dhcp.hostname);
+        dhcp_send_release_to_keyfile(keyfile, IPV4_KEY, dhcp.send_release);
+        if dhcp.client_id != DhcpClientId::Unset {
+            keyfile.set(IPV4_KEY, "dhcp-client-id", &dhcp.client_id);
+        }
+        if dhcp.iaid != DhcpIaid::Unset {
+            keyfile.set(IPV4_KEY, "dhcp-iaid", &dhcp.iaid);
+        }
+    }
+
+    keyfile.set(IPV6_KEY, "method", config.method6);
+    ip_family_to_keyfile(keyfile, IPV6_KEY, config, |ip| ip.is_ipv6());
+    if let Some(gateway) = &config.gateway6 {
+        keyfile.set(IPV6_KEY, "gateway", gateway);
+    }
+    routes_to_keyfile(keyfile, IPV6_KEY, &config.routes6);
+    if let Some(ip6_privacy) = config.ip6_privacy {
+        keyfile.set(IPV6_KEY, "ip6-privacy", ip6_privacy);
+    }
+    if let Some(dns_priority) = config.dns_priority6 {
+        keyfile.set(IPV6_KEY, "dns-priority", dns_priority);
+    }
+    if let Some(may_fail) = config.may_fail6 {
+        keyfile.set(IPV6_KEY, "may-fail", may_fail);
+    }
+    if let Some(dhcp) = &config.dhcp6_settings {
+        dhcp_to_keyfile(keyfile, IPV6_KEY, dhcp.send_hostname, &dhcp.hostname);
+        dhcp_send_release_to_keyfile(keyfile, IPV6_KEY, dhcp.send_release);
+        if dhcp.duid != DhcpDuid::Unset {
+            keyfile.set(IPV6_KEY, "dhcp-duid", &dhcp.duid);
+        }
+        if dhcp.iaid != DhcpIaid::Unset {
+            keyfile.set(IPV6_KEY, "dhcp-iaid", &dhcp.iaid);
+        }
+    }
+}
+
+/// Writes the addresses and DNS settings of the given IP family.
+fn ip_family_to_keyfile(
+    keyfile: &mut Keyfile,
+    group: &str,
+    config: &IpConfig,
+    belongs: impl Fn(&IpAddr) -> bool,
+) {
+    let addresses = config
+        .addresses
+        .iter()
+        .filter(|inet| belongs(&inet.address()));
+    for (index, address) in addresses.enumerate() {
+        keyfile.set(group, &format!("address{}", index + 1), address);
+    }
+
+    let nameservers: Vec<_> = config.nameservers.iter().filter(|ip| belongs(ip)).collect();
+    keyfile.set_list(group, "dns", &nameservers);
+    keyfile.set_list(group, "dns-search", &config.dns_searchlist);
+    if config.ignore_auto_dns {
+        keyfile.set(group, "ignore-auto-dns", true);
+    }
+}
+
+/// Writes the routes using the "route1=dest/prefix,next-hop,metric" format.
+fn routes_to_keyfile(keyfile: &mut Keyfile, group: &str, routes: &[IpRoute]) {
+    for (index, route) in routes.iter().enumerate() {
+        let key = format!("route{}", index + 1);
+        let mut value = route.destination.to_string();
+        if route.next_hop.is_some() || route.metric.is_some() {
+            let next_hop = match (route.next_hop, route.destination.address()) {
+                (Some(next_hop), _) => next_hop.to_string(),
+                (None, IpAddr::V4(_)) => "0.0.0.0".to_string(),
+                (None, IpAddr::V6(_)) => "::".to_string(),
+            };
+            value.push_str(&format!(",{next_hop}"));
+        }
+        if let Some(metric) = route.metric {
+            value.push_str(&format!(",{metric}"));
+        }
+        keyfile.set(group, &key, value);
+
+        if let Some(table) = route.table {
+            keyfile.set(group, &format!("{key}_options"), format!("table={table}"));
+        }
+    }
+}
+
+fn dhcp_to_keyfile(
+    keyfile: &mut Keyfile,
+    group: &str,
+    send_hostname: Option<bool>,
+    hostname: &Option<String>,
+) {
+    if let Some(send_hostname) = send_hostname {
+        keyfile.set(group, "dhcp-send-hostname", send_hostname);
+    }
+    if let Some(hostname) = hostname {
+        keyfile.set(group, "dhcp-hostname", hostname);
+    }
+}
+
+fn dhcp_send_release_to_keyfile(keyfile: &mut Keyfile, group: &str, send_release: Option<bool>) {
+    if let Some(send_release) = send_release {
+        keyfile.set(group, "dhcp-send-release", send_release as i32);
+    }
+}
+
+fn ip_config_from_keyfile(keyfile: &Keyfile) -> Result<IpConfig, KeyfileError> {
+    let mut config = IpConfig::default();
+
+    if keyfile.has_group(IPV4_KEY) {
+        let (mut addresses, gateway) = addresses_from_keyfile(keyfile, IPV4_KEY)?;
+        config.addresses.append(&mut addresses);
+        config.method4 =
+            value(keyfile, IPV4_KEY, "method", Ipv4Method::from_str)?.unwrap_or_default();
+        config.gateway4 = value(keyfile, IPV4_KEY, "gateway", IpAddr::from_str)?.or(gateway);
+        config.routes4 = routes_from_keyfile(keyfile, IPV4_KEY)?;
+        config.dns_priority4 = value(keyfile, IPV4_KEY, "dns-priority", i32::from_str)?;
+        config.may_fail4 = value(keyfile, IPV4_KEY, "may-fail", parse_bool)?;
+        config.link_local4 = value(keyfile, IPV4_KEY, "link-local", |v| {
+            i32::from_str(v).map(|l| LinkLocal::try_from(l).unwrap_or_default())
+        })?
+        .unwrap_or_default();
+        dns_from_keyfile(keyfile, IPV4_KEY, &mut config)?;
+
+        let common = dhcp_from_keyfile(keyfile, IPV4_KEY)?;
+        let dhcp4 = Dhcp4Settings::from(Dhcp4ClientSettings {
+            send_hostname: common.send_hostname,
+            hostname: common.hostname,
+            send_release: common.send_release,
+            client_id: value(keyfile, IPV4_KEY, "dhcp-client-id", String::from_str)?,
+            iaid: value(keyfile, IPV4_KEY, "dhcp-iaid", String::from_str)?,
+        });
+        if dhcp4 != Dhcp4Settings::default() {
+            config.dhcp4_settings = Some(dhcp4);
+        }
+    }
+
+    if keyfile.has_group(IPV6_KEY) {
+        let (mut addresses, gateway) = addresses_from_keyfile(keyfile, IPV6_KEY)?;
+        config.addresses.append(&mut addresses);
+        config.method6 =
+            value(keyfile, IPV6_KEY, "method", Ipv6Method::from_str)?.unwrap_or_default();
+        config.gateway6 = value(keyfile, IPV6_KEY, "gateway", IpAddr::from_str)?.or(gateway);
+        config.routes6 = routes_from_keyfile(keyfile, IPV6_KEY)?;
+        config.ip6_privacy = value(keyfile, IPV6_KEY, "ip6-privacy", i32::from_str)?;
+        config.dns_priority6 = value(keyfile, IPV6_KEY, "dns-priority", i32::from_str)?;
+        config.may_fail6 = value(keyfile, IPV6_KEY, "may-fail", parse_bool)?;
+        dns_from_keyfile(keyfile, IPV6_KEY, &mut config)?;
+
+        let common = dhcp_from_keyfile(keyfile, IPV6_KEY)?;
+        let dhcp6 = Dhcp6Settings::from(Dhcp6ClientSettings {
+            send_hostname: common.send_hostname,
+            hostname: common.hostname,
+            send_release: common.send_release,
+            duid: value(keyfile, IPV6_KEY, "dhcp-duid", String::from_str)?,
+            iaid: value(keyfile, IPV6_KEY, "dhcp-iaid", String::from_str)?,
+        });
+        if dhcp6 != Dhcp6Settings::default() {
+            config.dhcp6_settings = Some(dhcp6);
+        }
+    }
+
+    Ok(config)
+}
+
+/// Reads the addresses ("address1", "address2", etc.).
+///
+/// It returns the gateway too, as old keyfiles include it in the first address
+/// ("address1=192.168.1.10/24,192.168.1.1").
+fn addresses_from_keyfile(
+    keyfile: &Keyfile,
+    group: &str,
+) -> Result<(Vec<IpInet>, Option<IpAddr>), KeyfileError> {
+    let mut addresses = vec![];
+    let mut gateway = None;
+    for (key, raw) in numbered_entries(keyfile, group, "address") {
+        let (address, next_hop) = match raw.split_once(',') {
+            Some((address, next_hop)) => (address, Some(next_hop)),
+            None => (raw.as_str(), None),
+        };
+        addresses.push(IpInet::from_str(address).map_err(|_| invalid(group, &key, &raw))?);
+        if let Some(next_hop) = next_hop {
+            gateway = gateway.or(IpAddr::from_str(next_hop).ok());
+        }
+    }
+    Ok((addresses, gateway))
+}
+
+fn routes_from_keyfile(keyfile: &Keyfile, group: &str) -> Result<Vec<IpRoute>, KeyfileError> {
+    let mut routes = vec![];
+    for (key, raw) in numbered_entries(keyfile, group, "route") {
+        let mut parts = raw.split(',').map(str::trim);
+        let destination = parts
+            .next()
+            .and_then(|d| IpInet::from_str(d).ok())
+            .ok_or_else(|| invalid(group, &key, &raw))?;
+        let next_hop = match parts.next().filter(|n| !n.is_empty()) {
+            Some(next_hop) => {
+                let next_hop =
+                    IpAddr::from_str(next_hop).map_err(|_| invalid(group, &key, &raw))?;
+                Some(next_hop).filter(|n| !n.is_unspecified())
+            }
+            None => None,
+        };
+        let metric = match parts.next().filter(|m| !m.is_empty()) {
+            Some(metric) => Some(u32::from_str(metric).map_err(|_| invalid(group, &key, &raw))?),
+            None => None,
+        };
+        let options = keyfile.get(group, &format!("{key}_options"));
+        let table = options
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|o| o.trim().strip_prefix("table="))
+            .find_map(|t| u32::from_str(t).ok());
+
+        routes.push(IpRoute {
+            destination,
+            next_hop,
+            metric,
+            table,
+        });
+    }
+    Ok(routes)
+}
+
+fn dns_from_keyfile(
+    keyfile: &Keyfile,
+    group: &str,
+    config: &mut IpConfig,
+) -> Result<(), KeyfileError> {
+    let mut nameservers = list(keyfile, group, "dns", IpAddr::from_str)?;
+    config.nameservers.append(&mut nameservers);
+    for domain in keyfile.get_list(group, "dns-search") {
+        if !config.dns_searchlist.contains(&domain) {
+            config.dns_searchlist.push(domain);
+        }
+    }
+    if let Some(ignore_auto_dns) = value(keyfile, group, "ignore-auto-dns", parse_bool)? {
+        config.ignore_auto_dns = ignore_auto_dns;
+    }
+    Ok(())
+}
+
+/// DHCP settings shared by both IP families.
+struct DhcpCommon {
+    send_hostname: Option<bool>,
+    hostname: Option<String>,
+    send_release: Option<bool>,
+}
+
+/// Reads the DHCP settings shared by both IP families.
+fn dhcp_from_keyfile(keyfile: &Keyfile, group: &str) -> Result<DhcpCommon, KeyfileError> {
+    let send_hostname = match value(keyfile, group, "dhcp-send-hostname-v2", i32::from_str)? {
+        Some(value) => ternary(value),
+        None => value(keyfile, group, "dhcp-send-hostname", parse_bool)?,
+    };
+    Ok(DhcpCommon {
+        send_hostname,
+        hostname: value(keyfile, group, "dhcp-hostname", String::from_str)?,
+        send_release: value(keyfile, group, "dhcp-send-release", i32::from_str)?.and_then(ternary),
+    })
+}
+
+/// Returns the entries which keys are the prefix followed by a number, sorted by that number.
+///
+/// * `prefix`: key prefix (e.g., "address" for "address1", "address2", etc.).
+fn numbered_entries(keyfile: &Keyfile, group: &str, prefix: &str) -> Vec<(String, String)> {
+    let mut entries: Vec<(u32, String, String)> = keyfile
+        .entries(group)
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let index = key.strip_prefix(prefix)?.parse::<u32>().ok()?;
+            Some((index, key.to_string(), value))
+        })
+        .collect();
+    entries.sort_by_key(|(index, _, _)| *index);
+    entries
+        .into_iter()
+        .map(|(_, key, value)| (key, value))
+        .collect()
+}
+
+/// Parses a value, if it exists and it is not empty.
+///
+/// * `parse`: function to parse the value.
+fn value<T, E>(
+    keyfile: &Keyfile,
+    group: &str,
+    key: &str,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> Result<Option<T>, KeyfileError> {
+    match keyfile.get(group, key) {
+        Some(raw) if !raw.is_empty() => {
+            parse(&raw).map(Some).map_err(|_| invalid(group, key, &raw))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parses the elements of a list.
+///
+/// * `parse`: function to parse each element.
+fn list<T, E>(
+    keyfile: &Keyfile,
+    group: &str,
+    key: &str,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> Result<Vec<T>, KeyfileError> {
+    keyfile
+        .get_list(group, key)
+        .iter()
+        .map(|item| parse(item).map_err(|_| invalid(group, key, item)))
+        .collect()
+}
+
+fn required(keyfile: &Keyfile, group: &str, key: &str) -> Result<String, KeyfileError> {
+    keyfile
+        .get(group, key)
+        .ok_or_else(|| KeyfileError::MissingKey(group.to_string(), key.to_string()))
+}
+
+fn invalid(group: &str, key: &str, value: &str) -> KeyfileError {
+    KeyfileError::InvalidValue(group.to_string(), key.to_string(), value.to_string())
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(value.to_string()),
+    }
+}
+
+/// Parses an SSID, which can be written as a string or as a list of bytes ("97;103;97;").
+fn parse_ssid(value: &str) -> Result<SSID, String> {
+    if value.contains(';') {
+        let bytes: Result<Vec<u8>, _> = value
+            .split(';')
+            .filter(|b| !b.is_empty())
+            .map(u8::from_str)
+            .collect();
+        if let Ok(bytes) = bytes {
+            return Ok(SSID(bytes));
+        }
+    }
+    Ok(SSID(value.as_bytes().to_vec()))
+}
+
+fn ternary(value: i32) -> Option<bool> {
+    match value {
+        -1 => None,
+        0 => Some(false),
+        _ => Some(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{connection_from_keyfile, connection_to_keyfile, controller_from_keyfile};
+    use crate::keyfile::file::Keyfile;
+    use crate::model::*;
+    use crate::types::{BondMode, SSID};
+    use cidr::IpInet;
+    use std::{net::IpAddr, str::FromStr};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_connection_from_keyfile() {
+        let content = r#"
+[connection]
+id=Wired connection 1
+uuid=3f0a6d8c-0c3b-4b6d-8a7c-3ef4a1b6e0a1
+type=802-3-ethernet
+interface-name=eth0
+autoconnect=false
+master=bond0
+slave-type=bond
+
+[ethernet]
+mac-address=12:34:56:78:9A:BC
+cloned-mac-address=random
+mtu=9000
+
+[match]
+driver=e1000e;
+
+[ipv4]
+method=manual
+address1=192.168.1.100/24,192.168.1.1
+dns=192.168.1.1;
+dns-search=example.com;
+route1=10.0.0.0/8,192.168.1.254,100
+route1_options=table=200
+route2=172.16.0.0/12
+dhcp-send-hostname=false
+
+[ipv6]
+method=auto
+dhcp-duid=ll
+dhcp-send-release=1
+"#;
+        let keyfile = Keyfile::parse(content).unwrap();
+        let conn = connection_from_keyfile(&keyfile).unwrap();
+
+        assert_eq!(conn.id, "Wired connection 1");
+        assert_eq!(
+            conn.uuid,
+            Uuid::from_str("3f0a6d8c-0c3b-4b6d-8a7c-3ef4a1b6e0a1").unwrap()
+        );
+        assert_eq!(conn.config, ConnectionConfig::Ethernet);
+        assert_eq!(conn.interface, Some("eth0".to_string()));
+        assert!(!conn.autoconnect);
+        assert_eq!(
+            conn.mac_address.unwrap().to_string(),
+            "12:34:56:78:9A:BC".to_string()
+        );
+        assert_eq!(conn.custom_mac_address, MacAddress::Random);
+        assert_eq!(conn.mtu, 9000);
+        assert_eq!(conn.match_config.driver, vec!["e1000e".to_string()]);
+        assert_eq!(controller_from_keyfile(&keyfile), Some("bond0".to_string()));
+
+        let ip_config = &conn.ip_config;
+        assert_eq!(ip_config.method4, Ipv4Method::Manual);
+        assert_eq!(ip_config.method6, Ipv6Method::Auto);
+        assert_eq!(
+            ip_config.addresses,
+            vec![IpInet::from_str("192.168.1.100/24").unwrap()]
+        );
+        assert_eq!(
+            ip_config.gateway4,
+            Some(IpAddr::from_str("192.168.1.1").unwrap())
+        );
+        assert_eq!(
+            ip_config.nameservers,
+            vec![IpAddr::from_str("192.168.1.1").unwrap()]
+        );
+        assert_eq!(ip_config.dns_searchlist, vec!["example.com".to_string()]);
+        assert_eq!(
+            ip_config.routes4,
+            vec![
+                IpRoute {
+                    destination: IpInet::from_str("10.0.0.0/8").unwrap(),
+                    next_hop: Some(IpAddr::from_str("192.168.1.254").unwrap()),
+                    metric: Some(100),
+                    table: Some(200)
+                },
+                IpRoute {
+                    destination: IpInet::from_str("172.16.0.0/12").unwrap(),
+                    next_hop: None,
+                    metric: None,
+                    table: None
+                }
+            ]
+        );
+        let dhcp4 = ip_config.dhcp4_settings.as_ref().unwrap();
+        assert_eq!(dhcp4.send_hostname, Some(false));
+        let dhcp6 = ip_config.dhcp6_settings.as_ref().unwrap();
+        assert_eq!(dhcp6.duid, DhcpDuid::Ll);
+        assert_eq!(dhcp6.send_release, Some(true));
+    }
+
+    #[test]
+    fn test_unsupported_connection_type() {
+        let content = r#"
+[connection]
+id=vpn
+uuid=3f0a6d8c-0c3b-4b6d-8a7c-3ef4a1b6e0a1
+type=vpn
+"#;
+        let keyfile = Keyfile::parse(content).unwrap();
+        assert!(connection_from_keyfile(&keyfile).is_err());
+    }
+
+    #[test]
+    fn test_ethernet_connection_to_keyfile() {
+        let mut conn = Connection::new("eth0".to_string(), crate::types::DeviceType::Ethernet);
+        conn.interface = Some("eth0".to_string());
+        conn.mtu = 1500;
+        conn.ip_config = IpConfig {
+            method4: Ipv4Method::Manual,
+            method6: Ipv6Method::Manual,
+            addresses: vec![
+                IpInet::from_str("192.168.1.100/24").unwrap(),
+                IpInet::from_str("2001:db8::10/64").unwrap(),
+            ],
+            nameservers: vec![
+                IpAddr::from_str("192.168.1.1").unwrap(),
+                IpAddr::from_str("2001:db8::1").unwrap(),
+            ],
+            gateway4: Some(IpAddr::from_str("192.168.1.1").unwrap()),
+            routes6: vec![IpRoute {
+                destination: IpInet::from_str("2001:db8:1::/48").unwrap(),
+                next_hop: None,
+                metric: Some(50),
+                table: None,
+            }],
+            link_local4: LinkLocal::Disabled,
+            may_fail6: Some(false),
+            ..Default::default()
+        };
+        let bond = Connection::new("bond0".to_string(), crate::types::DeviceType::Bond);
+
+        let keyfile = connection_to_keyfile(&conn, Some(&bond));
+        let get = |group, key| keyfile.get(group, key);
+        assert_eq!(get("connection", "type"), Some("ethernet".to_string()));
+        assert_eq!(get("connection", "master"), Some(bond.uuid.to_string()));
+        assert_eq!(get("connection", "slave-type"), Some("bond".to_string()));
+        assert_eq!(get("connection", "autoconnect"), None);
+        assert_eq!(get("ethernet", "mtu"), Some("1500".to_string()));
+        assert_eq!(
+            get("ipv4", "address1"),
+            Some("192.168.1.100/24".to_string())
+        );
+        assert_eq!(get("ipv4", "dns"), Some("192.168.1.1;".to_string()));
+        assert_eq!(get("ipv4", "link-local"), Some("2".to_string()));
+        assert_eq!(get("ipv6", "address1"), Some("2001:db8::10/64".to_string()));
+        assert_eq!(
+            get("ipv6", "route1"),
+            Some("2001:db8:1::/48,::,50".to_string())
+        );
+        assert_eq!(get("ipv6", "may-fail"), Some("false".to_string()));
+
+        let read = connection_from_keyfile(&keyfile).unwrap();
+        assert_eq!(read.ip_config, conn.ip_config);
+        assert_eq!(read.uuid, conn.uuid);
+        assert_eq!(read.interface, conn.interface);
+    }
+
+    #[test]
+    fn test_connection_round_trip() {
+        let mut wireless = Connection::new("wlan0".to_string(), crate::types::DeviceType::Wireless);
+        wireless.config = ConnectionConfig::Wireless(WirelessConfig {
+            ssid: SSID(b"agama;net".to_vec()),
+            password: Some("nots3cr3t".to_string()),
+            security: SecurityProtocol::WPA2,
+            band: Some(WirelessBand::A),
+            channel: 36,
+            hidden: true,
+            pairwise_algorithms: vec![PairwiseAlgorithm::Ccmp],
+            ..Default::default()
+        });
+        wireless.ieee_8021x_config = Some(IEEE8021XConfig {
+            eap: vec![EAPMethod::PEAP],
+            identity: Some("agama".to_string()),
+            ca_cert: Some("/etc/pki/ca.pem".to_string()),
+            peap_label: true,
+            ..Default::default()
+        });
+
+        let mut bond = Connection::new("bond0".to_string(), crate::types::DeviceType::Bond);
+        bond.config = ConnectionConfig::Bond(BondConfig {
+            mode: BondMode::ActiveBackup,
+            options: BondOptions::try_from("miimon=100 primary=eth0").unwrap(),
+        });
+        bond.interface = Some("bond0".to_string());
+
+        let mut bridge_port =
+            Connection::new("eth1".to_string(), crate::types::DeviceType::Ethernet);
+        bridge_port.port_config = PortConfig::Bridge(BridgePortConfig {
+            priority: Some(10),
+            path_cost: None,
+        });
+
+        let vlan = Connection {
+            config: ConnectionConfig::Vlan(VlanConfig {
+                parent: "eth0".to_string(),
+                id: 10,
+                protocol: VlanProtocol::IEEE802_1ad,
+            }),
+            ..Connection::new("eth0.10".to_string(), crate::types::DeviceType::Vlan)
+        };
+
+        let tun = Connection {
+            config: ConnectionConfig::Tun(TunConfig {
+                mode: TunMode::Tap,
+                owner: Some("1000".to_string()),
+                group: None,
+            }),
+            ..Connection::new("tap0".to_string(), crate::types::DeviceType::Tun)
+        };
+
+        let ovs_interface = Connection {
+            config: ConnectionConfig::OvsInterface(OvsInterfaceConfig {
+                interface_type: OvsInterfaceType::Internal,
+            }),
+            ..Connection::new("ovs-if".to_string(), crate::types::DeviceType::OvsInterface)
+        };
+
+        for conn in [wireless, bond, bridge_port, vlan, tun, ovs_interface] {
+            let keyfile = connection_to_keyfile(&conn, None);
+            let content = keyfile.to_string();
+            let read = connection_from_keyfile(&Keyfile::parse(&content).unwrap()).unwrap();
+            assert_eq!(read, conn, "{content}");
+        }
+    }
+}
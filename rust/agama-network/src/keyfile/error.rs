@@ -0,0 +1,46 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Keyfile error types
+use crate::error::NetworkStateError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum KeyfileError {
+    #[error("Could not access '{0}': {1}")]
+    IO(PathBuf, #[source] std::io::Error),
+    #[error("Invalid keyfile: {0}")]
+    Parse(#[from] ini::ParseError),
+    #[error("Key '{0}' does not belong to any group")]
+    KeyWithoutGroup(String),
+    #[error("Missing key '{0}.{1}'")]
+    MissingKey(String, String),
+    #[error("Invalid value for '{0}.{1}': '{2}'")]
+    InvalidValue(String, String, String),
+    #[error("Connection type not supported: '{0}'")]
+    UnsupportedConnectionType(String),
+}
+
+impl From<KeyfileError> for NetworkStateError {
+    fn from(value: KeyfileError) -> NetworkStateError {
+        NetworkStateError::AdapterError(value.to_string())
+    }
+}
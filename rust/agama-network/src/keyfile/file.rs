@@ -0,0 +1,309 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Keyfile format.
+//!
+//! NetworkManager keyfiles follow the [GLib key-value file
+//! format](https://docs.gtk.org/glib/struct.KeyFile.html): a set of groups (one per setting)
+//! containing `key=value` pairs. Lists are separated (and terminated) by `;`.
+//!
+//! The INI syntax is handled by the [ini] crate, while the GLib escaping rules are implemented
+//! here.
+use super::error::KeyfileError;
+use ini::{EscapePolicy, Ini, LineSeparator, ParseOption, Properties, WriteOption};
+use std::fmt;
+
+/// Groups which can be written using the long name (D-Bus) or the short one (keyfile).
+const GROUP_ALIASES: [(&str, &str); 3] = [
+    ("802-3-ethernet", "ethernet"),
+    ("802-11-wireless", "wifi"),
+    ("802-11-wireless-security", "wifi-security"),
+];
+
+/// Returns the name used in keyfiles for the given setting.
+///
+/// * `name`: setting name (e.g., "802-3-ethernet" or "ethernet").
+pub fn group_alias(name: &str) -> &str {
+    GROUP_ALIASES
+        .iter()
+        .find(|(long, _)| *long == name)
+        .map(|(_, short)| *short)
+        .unwrap_or(name)
+}
+
+/// In-memory representation of a keyfile.
+///
+/// The order of the groups and keys is kept, so reading and writing a file does not shuffle
+/// its content. The values are stored escaped.
+#[derive(Debug, Default, Clone)]
+pub struct Keyfile {
+    ini: Ini,
+}
+
+impl Keyfile {
+    /// Parses the content of a keyfile.
+    ///
+    /// * `content`: keyfile content.
+    pub fn parse(content: &str) -> Result<Self, KeyfileError> {
+        // GLib does not support quoting and it uses its own escaping rules
+        let options = ParseOption {
+            enabled_quote: false,
+            enabled_escape: false,
+        };
+        let ini = Ini::load_from_str_opt(content, options)?;
+        if let Some((key, _)) = ini.general_section().iter().next() {
+            return Err(KeyfileError::KeyWithoutGroup(key.to_string()));
+        }
+
+        // merge the groups using the long and the short names
+        let mut keyfile = Keyfile::default();
+        for (name, properties) in ini.iter() {
+            let Some(name) = name else {
+                continue;
+            };
+            let group = keyfile.group_mut(name);
+            for (key, value) in properties.iter() {
+                group.insert(key, value);
+            }
+        }
+
+        Ok(keyfile)
+    }
+
+    /// Determines whether the keyfile contains the given group.
+    ///
+    /// * `group`: group name.
+    pub fn has_group(&self, group: &str) -> bool {
+        self.group(group).is_some()
+    }
+
+    /// Returns the value of a key, if it exists.
+    ///
+    /// * `group`: group name.
+    /// * `key`: key name.
+    pub fn get(&self, group: &str, key: &str) -> Option<String> {
+        self.group(group)?.get(key).map(unescape)
+    }
+
+    /// Returns the elements of a list. If the key does not exist, it returns an empty list.
+    ///
+    /// * `group`: group name.
+    /// * `key`: key name.
+    pub fn get_list(&self, group: &str, key: &str) -> Vec<String> {
+        let Some(raw) = self.group(group).and_then(|g| g.get(key)) else {
+            return vec![];
+        };
+
+        let mut items = vec![];
+        let mut item = String::new();
+        let mut chars = raw.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    item.push(c);
+                    if let Some(next) = chars.next() {
+                        item.push(next);
+                    }
+                }
+                ';' => items.push(unescape(&std::mem::take(&mut item))),
+                _ => item.push(c),
+            }
+        }
+        if !item.is_empty() {
+            items.push(unescape(&item));
+        }
+        items
+    }
+
+    /// Returns all the key/value pairs of a group.
+    ///
+    /// * `group`: group name.
+    pub fn entries(&self, group: &str) -> Vec<(&str, String)> {
+        self.group(group)
+            .map(|g| {
+                g.iter()
+                    .map(|(key, value)| (key, unescape(value)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sets the value of a key, adding the group if needed.
+    ///
+    /// * `group`: group name.
+    /// * `key`: key name.
+    /// * `value`: value to set.
+    pub fn set(&mut self, group: &str, key: &str, value: impl ToString) {
+        self.group_mut(group)
+            .insert(key, escape(&value.to_string(), false));
+    }
+
+    /// Sets a list of values. Empty lists are not written.
+    ///
+    /// * `group`: group name.
+    /// * `key`: key name.
+    /// * `values`: values to set.
+    pub fn set_list<T: ToString>(&mut self, group: &str, key: &str, values: &[T]) {
+        if values.is_empty() {
+            return;
+        }
+
+        let raw: String = values
+            .iter()
+            .map(|v| format!("{};", escape(&v.to_string(), true)))
+            .collect();
+        self.group_mut(group).insert(key, raw);
+    }
+
+    fn group(&self, name: &str) -> Option<&Properties> {
+        self.ini.section(Some(group_alias(name)))
+    }
+
+    fn group_mut(&mut self, name: &str) -> &mut Properties {
+        self.ini
+            .entry(Some(group_alias(name).to_string()))
+            .or_insert_with(Properties::new)
+    }
+}
+
+impl fmt::Display for Keyfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the values are already escaped
+        let options = WriteOption {
+            escape_policy: EscapePolicy::Nothing,
+            line_separator: LineSeparator::CR,
+            kv_separator: "=",
+        };
+        let mut content = vec![];
+        self.ini
+            .write_to_opt(&mut content, options)
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&content))
+    }
+}
+
+/// Escapes a value following the GLib rules.
+///
+/// * `value`: value to escape.
+/// * `in_list`: whether the value is an element of a list, so `;` must be escaped too.
+fn escape(value: &str, in_list: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (index, c) in value.chars().enumerate() {
+        match c {
+            ' ' if index == 0 => escaped.push_str("\\s"),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            ';' if in_list => escaped.push_str("\\;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keyfile;
+
+    #[test]
+    fn test_parse_keyfile() {
+        let content = r#"
+# generated by hand
+[connection]
+id=Wired connection 1
+type=802-3-ethernet
+
+[802-3-ethernet]
+mtu = 1500
+
+[ipv4]
+dns=192.168.1.1;8.8.8.8;
+dns-search=
+"#;
+        let keyfile = Keyfile::parse(content).unwrap();
+        assert_eq!(
+            keyfile.get("connection", "id"),
+            Some("Wired connection 1".to_string())
+        );
+        assert!(keyfile.has_group("ethernet"));
+        assert_eq!(keyfile.get("ethernet", "mtu"), Some("1500".to_string()));
+        assert_eq!(
+            keyfile.get_list("ipv4", "dns"),
+            vec!["192.168.1.1".to_string(), "8.8.8.8".to_string()]
+        );
+        assert!(keyfile.get_list("ipv4", "dns-search").is_empty());
+        assert_eq!(keyfile.get("ipv6", "method"), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_keyfile() {
+        assert!(Keyfile::parse("id=eth0\n").is_err());
+        assert!(Keyfile::parse("[connection\n").is_err());
+        assert!(Keyfile::parse("[connection]\nid\n").is_err());
+    }
+
+    #[test]
+    fn test_write_keyfile() {
+        let mut keyfile = Keyfile::default();
+        keyfile.set("connection", "id", " my;connection");
+        keyfile.set("wifi", "ssid", "agama");
+        keyfile.set_list("ipv4", "dns-search", &["example.net", "a;b"]);
+        keyfile.set_list::<String>("ipv4", "dns", &[]);
+        keyfile.set("connection", "id", " my;connection\\");
+
+        let content = keyfile.to_string();
+        assert_eq!(
+            content,
+            "[connection]\nid=\\smy;connection\\\\\n\n[wifi]\nssid=agama\n\n[ipv4]\ndns-search=example.net;a\\;b;\n"
+        );
+
+        let keyfile = Keyfile::parse(&content).unwrap();
+        assert_eq!(
+            keyfile.get("connection", "id"),
+            Some(" my;connection\\".to_string())
+        );
+        assert_eq!(
+            keyfile.get_list("ipv4", "dns-search"),
+            vec!["example.net".to_string(), "a;b".to_string()]
+        );
+    }
+}
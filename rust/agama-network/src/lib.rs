@@ -24,7 +24,10 @@
 
 pub mod action;
 pub mod adapter;
+pub mod connectivity;
 pub mod error;
+mod keyfile;
+mod memory;
 pub mod model;
 mod nm;
 pub mod settings;
@@ -33,6 +36,8 @@ pub mod types;
 
 pub use action::Action;
 pub use adapter::{Adapter, NetworkAdapterError};
+pub use keyfile::KeyfileAdapter;
+pub use memory::{MemoryAdapter, Scenario, ScenarioError, ScenarioStep};
 pub use model::NetworkState;
 pub use nm::NetworkManagerAdapter;
 pub use system::{NetworkSystem, NetworkSystemClient, NetworkSystemError};
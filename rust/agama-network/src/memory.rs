@@ -0,0 +1,34 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! In-memory network simulator.
+//!
+//! This module defines [an adapter](MemoryAdapter) which keeps the network state in memory
+//! instead of talking to a real network service. It can be driven by a [scenario](Scenario) which
+//! describes the initial state and how it evolves (devices appearing and disappearing,
+//! connections getting activated, access point scans, apply failures, etc.). It makes it possible
+//! to test the network service deterministically without NetworkManager or a system bus.
+
+mod adapter;
+mod scenario;
+mod watcher;
+
+pub use adapter::MemoryAdapter;
+pub use scenario::{Scenario, ScenarioError, ScenarioStep};
@@ -0,0 +1,227 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+use super::{
+    scenario::{Scenario, ScenarioError},
+    watcher::MemoryWatcher,
+};
+use crate::{
+    adapter::Watcher,
+    connectivity::{ConnectivityChecks, ConnectivityReport},
+    model::{AccessPoint, NetworkState, StateConfig},
+    Adapter, NetworkAdapterError,
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// State shared by the adapter and the watcher.
+#[derive(Default)]
+pub(super) struct Simulation {
+    pub state: NetworkState,
+    scans: VecDeque<Vec<AccessPoint>>,
+    apply_errors: VecDeque<Option<String>>,
+    started: bool,
+}
+
+/// An adapter which keeps the network state in memory.
+///
+/// Writing the configuration replaces the connections and the general state. If the adapter was
+/// built from a [Scenario], it takes care of the scans, the apply failures and the steps.
+pub struct MemoryAdapter {
+    simulation: Arc<Mutex<Simulation>>,
+    applied: UnboundedSender<()>,
+    watcher: Mutex<Option<MemoryWatcher>>,
+}
+
+impl MemoryAdapter {
+    /// Returns an adapter with the given state and no further changes.
+    ///
+    /// * `state`: initial network state.
+    pub fn new(state: NetworkState) -> Self {
+        Self::build(state, Scenario::default())
+    }
+
+    /// Returns an adapter which simulates the given scenario.
+    ///
+    /// * `scenario`: scenario to simulate.
+    pub fn from_scenario(scenario: Scenario) -> Result<Self, ScenarioError> {
+        let state = NetworkState::new(
+            scenario.general_state.clone(),
+            scenario.access_points.clone(),
+            scenario.devices.clone(),
+            scenario.connections()?,
+        );
+        Ok(Self::build(state, scenario))
+    }
+
+    fn build(state: NetworkState, scenario: Scenario) -> Self {
+        let simulation = Arc::new(Mutex::new(Simulation {
+            state,
+            scans: scenario.scans.into(),
+            apply_errors: scenario.apply_errors.into(),
+            started: false,
+        }));
+        let (applied_tx, applied_rx) = mpsc::unbounded_channel();
+        let watcher = MemoryWatcher::new(
+            Arc::clone(&simulation),
+            scenario.steps,
+            scenario.on_apply,
+            applied_rx,
+        );
+
+        Self {
+            simulation,
+            applied: applied_tx,
+            watcher: Mutex::new(Some(watcher)),
+        }
+    }
+
+    /// Returns the current state of the simulated network.
+    pub fn state(&self) -> NetworkState {
+        self.simulation.lock().unwrap().state.clone()
+    }
+}
+
+#[async_trait]
+impl Adapter for MemoryAdapter {
+    /// Returns the simulated network state.
+    ///
+    /// Each read which includes the access points, except the first one, performs the next scan.
+    async fn read(&self, config: StateConfig) -> Result<NetworkState, NetworkAdapterError> {
+        let mut simulation = self.simulation.lock().unwrap();
+        if config.access_points {
+            if simulation.started {
+                if let Some(access_points) = simulation.scans.pop_front() {
+                    simulation.state.access_points = access_points;
+                }
+            }
+            simulation.started = true;
+        }
+        Ok(simulation.state.clone())
+    }
+
+    /// Writes the connections and the general state, unless the scenario says that it fails.
+    async fn write(&self, network: &NetworkState) -> Result<(), NetworkAdapterError> {
+        {
+            let mut simulation = self.simulation.lock().unwrap();
+            if let Some(Some(message)) = simulation.apply_errors.pop_front() {
+                return Err(NetworkAdapterError::Write(anyhow!(message)));
+            }
+
+            simulation.state.general_state = network.general_state.clone();
+            simulation.state.connections = network
+                .connections
+                .iter()
+                .filter(|c| !c.is_removed())
+                .cloned()
+                .collect();
+        }
+
+        _ = self.applied.send(());
+        Ok(())
+    }
+
+    /// Writes the configuration and verifies the connectivity, restoring the previous
+    /// connections and general state if the checks fail.
+    async fn write_and_verify(
+        &self,
+        network: &NetworkState,
+        checks: &ConnectivityChecks,
+    ) -> Result<ConnectivityReport, NetworkAdapterError> {
+        let previous = self.state();
+        self.write(network).await?;
+
+        let mut report = checks.verify(self).await;
+        if !report.success {
+            let mut simulation = self.simulation.lock().unwrap();
+            simulation.state.general_state = previous.general_state;
+            simulation.state.connections = previous.connections;
+            report.rolled_back = true;
+        }
+        Ok(report)
+    }
+
+    fn watcher(&self) -> Option<Box<dyn Watcher + Send>> {
+        self.watcher
+            .lock()
+            .unwrap()
+            .take()
+            .map(|w| Box::new(w) as Box<dyn Watcher + Send>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryAdapter;
+    use crate::{
+        memory::Scenario,
+        model::{Connection, StateConfig},
+        types::DeviceType,
+        Adapter,
+    };
+
+    #[tokio::test]
+    async fn test_read_scans() {
+        let scenario = Scenario::from_json(
+            r#"{
+                "accessPoints": [{ "ssid": "Initial" }],
+                "scans": [[{ "ssid": "First" }], [{ "ssid": "Second" }]]
+            }"#,
+        )
+        .unwrap();
+        let adapter = MemoryAdapter::from_scenario(scenario).unwrap();
+
+        let mut ssids = vec![];
+        for _ in 0..4 {
+            let state = adapter.read(StateConfig::default()).await.unwrap();
+            ssids.push(state.access_points[0].ssid.to_string());
+        }
+        assert_eq!(ssids, vec!["Initial", "First", "Second", "Second"]);
+    }
+
+    #[tokio::test]
+    async fn test_write() {
+        let scenario = Scenario::from_json(r#"{ "applyErrors": ["Device not ready"] }"#).unwrap();
+        let adapter = MemoryAdapter::from_scenario(scenario).unwrap();
+
+        let mut state = adapter.read(StateConfig::default()).await.unwrap();
+        state
+            .add_connection(Connection::new("eth0".to_string(), DeviceType::Ethernet))
+            .unwrap();
+        state
+            .add_connection(Connection::new("eth1".to_string(), DeviceType::Ethernet))
+            .unwrap();
+        state.remove_connection("eth1").unwrap();
+
+        let error = adapter.write(&state).await.unwrap_err();
+        assert!(error.to_string().contains("Device not ready"));
+        assert!(adapter.state().connections.is_empty());
+
+        adapter.write(&state).await.unwrap();
+        let connections = adapter.state().connections;
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].id, "eth0");
+    }
+}
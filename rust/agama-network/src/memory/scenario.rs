@@ -0,0 +1,161 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Scenarios for the in-memory network adapter.
+
+use crate::{
+    error::NetworkStateError,
+    model::{AccessPoint, Connection, Device, GeneralState},
+    settings::NetworkConnection,
+    types::ConnectionState,
+};
+use serde::Deserialize;
+use std::{fs, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ScenarioError {
+    #[error("Could not read the scenario '{0}': {1}")]
+    IO(PathBuf, #[source] std::io::Error),
+    #[error("Invalid scenario: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid connection in the scenario: {0}")]
+    InvalidConnection(#[from] NetworkStateError),
+}
+
+/// Describes how the simulated network behaves.
+///
+/// ```json
+/// {
+///   "devices": [{ "name": "eth0", "type": "ethernet", "state": "disconnected" }],
+///   "connections": [{ "id": "Wired", "interface": "eth0", "method4": "auto" }],
+///   "scans": [[{ "ssid": "Agama", "strength": 80 }]],
+///   "applyErrors": [null, "Device not ready"],
+///   "steps": [{ "wait": 100 }, { "removeDevice": "eth0" }],
+///   "onApply": [{ "connectionState": { "id": "Wired", "state": "activated" } }]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Scenario {
+    /// Initial general state.
+    pub general_state: GeneralState,
+    /// Initial devices.
+    pub devices: Vec<Device>,
+    /// Initial connections.
+    pub connections: Vec<NetworkConnection>,
+    /// Access points found before any scan.
+    pub access_points: Vec<AccessPoint>,
+    /// Access points found by each scan. Once they are exhausted, the last scan is kept.
+    pub scans: Vec<Vec<AccessPoint>>,
+    /// Result of each write: `null` means success and a string is the error message. Once they
+    /// are exhausted, writing always succeeds.
+    pub apply_errors: Vec<Option<String>>,
+    /// Steps to run when the network service starts.
+    pub steps: Vec<ScenarioStep>,
+    /// Steps to run each time the configuration is successfully written.
+    pub on_apply: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    /// Reads the scenario from a JSON file.
+    ///
+    /// * `path`: scenario file path.
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Result<Self, ScenarioError> {
+        let path = path.into();
+        let content = fs::read_to_string(&path).map_err(|e| ScenarioError::IO(path, e))?;
+        Self::from_json(&content)
+    }
+
+    /// Parses the scenario from a JSON string.
+    ///
+    /// * `json`: scenario in JSON format.
+    pub fn from_json(json: &str) -> Result<Self, ScenarioError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Returns the initial connections.
+    pub fn connections(&self) -> Result<Vec<Connection>, ScenarioError> {
+        let connections = self
+            .connections
+            .iter()
+            .cloned()
+            .map(Connection::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(connections)
+    }
+}
+
+/// Represents a change in the simulated network.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScenarioStep {
+    /// Waits for the given number of milliseconds.
+    Wait(u64),
+    /// A new device appears.
+    AddDevice(Device),
+    /// An existing device changes (e.g., its state).
+    UpdateDevice(Device),
+    /// A device disappears.
+    RemoveDevice(String),
+    /// A connection changes its state.
+    ConnectionState { id: String, state: ConnectionState },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scenario, ScenarioStep};
+    use crate::types::{ConnectionState, DeviceType};
+
+    #[test]
+    fn test_scenario_from_json() {
+        let scenario = Scenario::from_json(
+            r#"{
+                "devices": [{ "name": "eth0", "type": "ethernet", "state": "disconnected" }],
+                "connections": [{ "id": "Wired", "interface": "eth0", "method4": "auto" }],
+                "scans": [[{ "ssid": "Agama", "strength": 80 }]],
+                "applyErrors": [null, "Device not ready"],
+                "steps": [{ "wait": 100 }, { "removeDevice": "eth0" }],
+                "onApply": [{ "connectionState": { "id": "Wired", "state": "activated" } }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(scenario.devices[0].type_, DeviceType::Ethernet);
+        assert_eq!(scenario.scans[0][0].ssid.to_string(), "Agama");
+        assert_eq!(scenario.scans[0][0].strength, 80);
+        assert_eq!(
+            scenario.apply_errors,
+            vec![None, Some("Device not ready".to_string())]
+        );
+        assert!(matches!(scenario.steps[0], ScenarioStep::Wait(100)));
+        assert!(matches!(
+            &scenario.steps[1],
+            ScenarioStep::RemoveDevice(name) if name == "eth0"
+        ));
+        assert!(matches!(
+            &scenario.on_apply[0],
+            ScenarioStep::ConnectionState { id, state: ConnectionState::Activated } if id == "Wired"
+        ));
+
+        let connections = scenario.connections().unwrap();
+        assert_eq!(connections[0].interface, Some("eth0".to_string()));
+    }
+}
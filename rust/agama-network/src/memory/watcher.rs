@@ -0,0 +1,131 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+use super::{adapter::Simulation, scenario::ScenarioStep};
+use crate::{adapter::Watcher, Action, NetworkAdapterError};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+/// Plays the scenario steps, emitting the corresponding actions.
+///
+/// The initial steps run as soon as the watcher starts. The `onApply` ones run again after each
+/// successful write.
+pub struct MemoryWatcher {
+    simulation: Arc<Mutex<Simulation>>,
+    steps: Vec<ScenarioStep>,
+    on_apply: Vec<ScenarioStep>,
+    applied: UnboundedReceiver<()>,
+}
+
+impl MemoryWatcher {
+    pub(super) fn new(
+        simulation: Arc<Mutex<Simulation>>,
+        steps: Vec<ScenarioStep>,
+        on_apply: Vec<ScenarioStep>,
+        applied: UnboundedReceiver<()>,
+    ) -> Self {
+        Self {
+            simulation,
+            steps,
+            on_apply,
+            applied,
+        }
+    }
+
+    /// Runs the given steps.
+    ///
+    /// * `steps`: steps to run.
+    /// * `actions`: channel to emit the actions.
+    async fn play(
+        &self,
+        steps: &[ScenarioStep],
+        actions: &UnboundedSender<Action>,
+    ) -> Result<(), NetworkAdapterError> {
+        for step in steps {
+            if let ScenarioStep::Wait(millis) = step {
+                tokio::time::sleep(Duration::from_millis(*millis)).await;
+                continue;
+            }
+
+            let action = self.update_state(step.clone());
+            actions
+                .send(action)
+                .map_err(|e| NetworkAdapterError::Watcher(anyhow!(e)))?;
+        }
+        Ok(())
+    }
+
+    /// Applies the step to the simulated state and returns the action to emit.
+    ///
+    /// Errors are only logged: the network system will find them when handling the action.
+    ///
+    /// * `step`: step to apply. It cannot be a [ScenarioStep::Wait].
+    fn update_state(&self, step: ScenarioStep) -> Action {
+        let mut simulation = self.simulation.lock().unwrap();
+        let state = &mut simulation.state;
+        let (result, action) = match step {
+            ScenarioStep::AddDevice(device) => (
+                state.add_device(device.clone()),
+                Action::AddDevice(Box::new(device)),
+            ),
+            ScenarioStep::UpdateDevice(device) => (
+                state.update_device(&device.name, device.clone()),
+                Action::UpdateDevice(device.name.clone(), Box::new(device)),
+            ),
+            ScenarioStep::RemoveDevice(name) => {
+                (state.remove_device(&name), Action::RemoveDevice(name))
+            }
+            ScenarioStep::ConnectionState {
+                id,
+                state: new_state,
+            } => {
+                if let Some(conn) = state.get_connection_mut(&id) {
+                    conn.state = new_state;
+                }
+                (Ok(()), Action::ChangeConnectionState(id, new_state))
+            }
+            ScenarioStep::Wait(_) => unreachable!("waiting does not change the state"),
+        };
+
+        if let Err(error) = result {
+            tracing::warn!("Could not simulate a network change: {error}");
+        }
+        action
+    }
+}
+
+#[async_trait]
+impl Watcher for MemoryWatcher {
+    async fn run(
+        mut self: Box<Self>,
+        actions: UnboundedSender<Action>,
+    ) -> Result<(), NetworkAdapterError> {
+        self.play(&self.steps, &actions).await?;
+        while self.applied.recv().await.is_some() {
+            self.play(&self.on_apply, &actions).await?;
+        }
+        Ok(())
+    }
+}
@@ -22,9 +22,12 @@
 //!
 //! * This module contains the types that represent the network concepts. They are supposed to be
 //!   agnostic from the real network service (e.g., NetworkManager).
+use crate::connectivity::ConnectivityReport;
 use crate::error::NetworkStateError;
 use crate::settings::{
-    BondSettings, BridgeSettings, IEEE8021XSettings, NetworkConnection, VlanSettings,
+    BondSettings, BridgeSettings, Dhcp4ClientSettings, Dhcp6ClientSettings, DummySettings,
+    IEEE8021XSettings, InfinibandSettings, NetworkConnection, OvsBridgeSettings,
+    OvsInterfaceSettings, OvsPortSettings, OvsSettings, TunSettings, VlanSettings,
     WirelessSettings,
 };
 use crate::types::{BondMode, ConnectionState, DeviceState, DeviceType, Status, SSID};
@@ -225,7 +228,10 @@ impl NetworkState {
         ports: Vec<String>,
     ) -> Result<(), NetworkStateError> {
         match &controller.config {
-            ConnectionConfig::Bond(_) | ConnectionConfig::Bridge(_) => {
+            ConnectionConfig::Bond(_)
+            | ConnectionConfig::Bridge(_)
+            | ConnectionConfig::OvsBridge(_)
+            | ConnectionConfig::OvsPort(_) => {
                 let mut controlled = vec![];
                 for port in ports {
                     let connection = self
@@ -456,6 +462,150 @@ mod tests {
             NetworkStateError::NotControllerConnection(_),
         ));
     }
+
+    #[test]
+    fn test_network_connection_ip_settings() {
+        let json = serde_json::json!({
+            "id": "eth0",
+            "routes4": [
+                { "destination": "10.0.0.0/8", "nextHop": "192.168.1.1", "metric": 100, "table": 200 }
+            ],
+            "dhcp4": { "clientId": "mac", "iaid": "ifname", "sendHostname": false },
+            "dhcp6": { "duid": "llt" },
+            "linkLocal4": "disabled",
+            "dnsPriority4": 50,
+            "mayFail4": false
+        });
+        let settings: NetworkConnection = serde_json::from_value(json).unwrap();
+        let conn = Connection::try_from(settings).unwrap();
+
+        let ip_config = &conn.ip_config;
+        assert_eq!(ip_config.routes4[0].table, Some(200));
+        assert_eq!(ip_config.link_local4, LinkLocal::Disabled);
+        assert_eq!(ip_config.dns_priority4, Some(50));
+        assert_eq!(ip_config.may_fail4, Some(false));
+        let dhcp4 = ip_config.dhcp4_settings.as_ref().unwrap();
+        assert_eq!(dhcp4.client_id, DhcpClientId::Mac);
+        assert_eq!(dhcp4.iaid, DhcpIaid::Ifname);
+        assert_eq!(dhcp4.send_hostname, Some(false));
+        let dhcp6 = ip_config.dhcp6_settings.as_ref().unwrap();
+        assert_eq!(dhcp6.duid, DhcpDuid::Llt);
+
+        let settings = NetworkConnection::try_from(conn).unwrap();
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["routes4"][0]["table"], 200);
+        assert_eq!(json["dhcp4"]["clientId"], "mac");
+        assert_eq!(json["dhcp6"]["duid"], "llt");
+        assert!(json["dhcp6"].get("iaid").is_none());
+        assert_eq!(json["linkLocal4"], "disabled");
+        assert_eq!(json["mayFail4"], false);
+        assert!(json.get("mayFail6").is_none());
+    }
+
+    #[test]
+    fn test_network_connection_inapplicable_dhcp_settings() {
+        let json = serde_json::json!({ "id": "eth0", "dhcp4": { "duid": "llt" } });
+        assert!(serde_json::from_value::<NetworkConnection>(json).is_err());
+
+        let json = serde_json::json!({ "id": "eth0", "dhcp6": { "clientId": "mac" } });
+        assert!(serde_json::from_value::<NetworkConnection>(json).is_err());
+    }
+
+    #[test]
+    fn test_network_connection_device_settings() {
+        let settings: NetworkConnection = serde_json::from_value(serde_json::json!({
+            "id": "ovs-if0",
+            "ovs": { "interface": { "type": "internal" } }
+        }))
+        .unwrap();
+        let conn = Connection::try_from(settings).unwrap();
+        assert_eq!(
+            conn.config,
+            ConnectionConfig::OvsInterface(OvsInterfaceConfig {
+                interface_type: OvsInterfaceType::Internal
+            })
+        );
+
+        let settings = NetworkConnection {
+            id: "ib0".to_string(),
+            infiniband: Some(InfinibandSettings {
+                p_key: Some(0x8000),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Connection::try_from(settings),
+            Err(NetworkStateError::InvalidInfinibandPKey(0x8000))
+        ));
+
+        let settings = NetworkConnection {
+            id: "tun0".to_string(),
+            tun: Some(TunSettings {
+                mode: Some("tunnel".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Connection::try_from(settings),
+            Err(NetworkStateError::InvalidTunMode(_))
+        ));
+
+        let settings = NetworkConnection {
+            id: "ovs-if1".to_string(),
+            ovs: Some(OvsSettings::Interface(OvsInterfaceSettings {
+                interface_type: Some("virtual".to_string()),
+            })),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Connection::try_from(settings),
+            Err(NetworkStateError::InvalidOvsInterfaceType(_))
+        ));
+    }
+
+    #[test]
+    fn test_network_connection_several_device_types() {
+        let settings: NetworkConnection = serde_json::from_value(serde_json::json!({
+            "id": "dummy0",
+            "dummy": {},
+            "tun": { "mode": "tap" }
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.device_sections(),
+            vec![("tun", DeviceType::Tun), ("dummy", DeviceType::Dummy)]
+        );
+        assert_eq!(settings.device_type(), DeviceType::Tun);
+        let error = Connection::try_from(settings).unwrap_err();
+        assert!(matches!(
+            error,
+            NetworkStateError::SeveralDeviceTypes(id, sections) if id == "dummy0" && sections == "tun, dummy"
+        ));
+    }
+
+    #[test]
+    fn test_set_ovs_ports() {
+        let mut state = NetworkState::default();
+        let eth0 = Connection::new("eth0".to_string(), DeviceType::Ethernet);
+        let port = Connection::new("ovs-port0".to_string(), DeviceType::OvsPort);
+        let bridge = Connection::new("ovs-br0".to_string(), DeviceType::OvsBridge);
+        state.add_connection(eth0).unwrap();
+        state.add_connection(port.clone()).unwrap();
+        state.add_connection(bridge.clone()).unwrap();
+
+        state.set_ports(&port, vec!["eth0".to_string()]).unwrap();
+        state
+            .set_ports(&bridge, vec!["ovs-port0".to_string()])
+            .unwrap();
+
+        let eth0 = state.get_connection("eth0").unwrap();
+        assert_eq!(eth0.controller, Some(port.uuid));
+        let port = state.get_connection("ovs-port0").unwrap();
+        assert_eq!(port.controller, Some(bridge.uuid));
+        assert!(port.is_ovs());
+    }
 }
 
 pub const NOT_COPY_NETWORK_PATH: &str = "/run/agama/not_copy_network";
@@ -474,8 +624,8 @@ pub struct GeneralState {
 
 /// Access Point
 #[serde_as]
-#[derive(Default, Debug, Clone, Serialize, utoipa::ToSchema)]
-#[serde(rename_all = "camelCase")]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default, rename_all = "camelCase")]
 pub struct AccessPoint {
     #[serde_as(as = "DisplayFromStr")]
     pub ssid: SSID,
@@ -490,7 +640,7 @@ pub struct AccessPoint {
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Default, Debug, Clone, PartialEq, Deserialize, Serialize, utoipa::ToSchema)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Device {
     pub name: String,
     #[serde(rename = "type")]
@@ -541,6 +691,11 @@ impl Connection {
             DeviceType::Bond => ConnectionConfig::Bond(Default::default()),
             DeviceType::Vlan => ConnectionConfig::Vlan(Default::default()),
             DeviceType::Bridge => ConnectionConfig::Bridge(Default::default()),
+            DeviceType::Infiniband => ConnectionConfig::Infiniband(Default::default()),
+            DeviceType::Tun => ConnectionConfig::Tun(Default::default()),
+            DeviceType::OvsBridge => ConnectionConfig::OvsBridge(Default::default()),
+            DeviceType::OvsPort => ConnectionConfig::OvsPort(Default::default()),
+            DeviceType::OvsInterface => ConnectionConfig::OvsInterface(Default::default()),
         };
         Self {
             id,
@@ -582,6 +737,16 @@ impl Connection {
         matches!(self.config, ConnectionConfig::Loopback)
     }
 
+    /// Determines whether it is an Open vSwitch bridge, port or interface.
+    pub fn is_ovs(&self) -> bool {
+        matches!(
+            self.config,
+            ConnectionConfig::OvsBridge(_)
+                | ConnectionConfig::OvsPort(_)
+                | ConnectionConfig::OvsInterface(_)
+        )
+    }
+
     pub fn is_ethernet(&self) -> bool {
         matches!(self.config, ConnectionConfig::Loopback)
             || matches!(self.config, ConnectionConfig::Ethernet)
@@ -622,6 +787,11 @@ impl TryFrom<NetworkConnection> for Connection {
 
     fn try_from(conn: NetworkConnection) -> Result<Self, Self::Error> {
         let id = conn.clone().id;
+        let sections = conn.device_sections();
+        if sections.len() > 1 {
+            let names: Vec<_> = sections.iter().map(|(name, _)| *name).collect();
+            return Err(NetworkStateError::SeveralDeviceTypes(id, names.join(", ")));
+        }
         let mut connection = Connection::new(id, conn.device_type());
 
         if let Some(method) = conn.clone().method4 {
@@ -650,22 +820,33 @@ impl TryFrom<NetworkConnection> for Connection {
             connection.ip_config.ignore_auto_dns = ignore_auto_dns;
         }
 
+        if let Some(link_local4) = conn.link_local4 {
+            connection.ip_config.link_local4 = link_local4;
+        }
+
+        // there is, at most, one device-specific section (see above)
         if let Some(vlan_config) = conn.vlan {
             let config = VlanConfig::try_from(vlan_config)?;
             connection.config = config.into();
-        }
-        if let Some(wireless_config) = conn.wireless {
+        } else if let Some(wireless_config) = conn.wireless {
             let config = WirelessConfig::try_from(wireless_config)?;
             connection.config = config.into();
-        }
-
-        if let Some(bond_config) = conn.bond {
+        } else if let Some(bond_config) = conn.bond {
             let config = BondConfig::try_from(bond_config)?;
             connection.config = config.into();
-        }
-        if let Some(bridge_config) = conn.bridge {
+        } else if let Some(bridge_config) = conn.bridge {
             let config = BridgeConfig::try_from(bridge_config)?;
             connection.config = config.into();
+        } else if let Some(ovs_settings) = conn.ovs {
+            connection.config = ConnectionConfig::try_from(ovs_settings)?;
+        } else if let Some(infiniband_settings) = conn.infiniband {
+            let config = InfinibandConfig::try_from(infiniband_settings)?;
+            connection.config = ConnectionConfig::Infiniband(config);
+        } else if let Some(tun_settings) = conn.tun {
+            let config = TunConfig::try_from(tun_settings)?;
+            connection.config = ConnectionConfig::Tun(config);
+        } else if conn.dummy.is_some() {
+            connection.config = ConnectionConfig::Dummy;
         }
 
         if let Some(ieee_8021x_config) = conn.ieee_8021x {
@@ -684,6 +865,14 @@ impl TryFrom<NetworkConnection> for Connection {
         connection.ip_config.dns_searchlist = conn.dns_searchlist;
         connection.ip_config.gateway4 = conn.gateway4;
         connection.ip_config.gateway6 = conn.gateway6;
+        connection.ip_config.routes4 = conn.routes4;
+        connection.ip_config.routes6 = conn.routes6;
+        connection.ip_config.dhcp4_settings = conn.dhcp4.map(Dhcp4Settings::from);
+        connection.ip_config.dhcp6_settings = conn.dhcp6.map(Dhcp6Settings::from);
+        connection.ip_config.dns_priority4 = conn.dns_priority4;
+        connection.ip_config.dns_priority6 = conn.dns_priority6;
+        connection.ip_config.may_fail4 = conn.may_fail4;
+        connection.ip_config.may_fail6 = conn.may_fail6;
         connection.interface = conn.interface;
         connection.mtu = conn.mtu;
 
@@ -707,6 +896,16 @@ impl TryFrom<Connection> for NetworkConnection {
         let addresses = conn.ip_config.addresses;
         let gateway4 = conn.ip_config.gateway4;
         let gateway6 = conn.ip_config.gateway6;
+        let routes4 = conn.ip_config.routes4;
+        let routes6 = conn.ip_config.routes6;
+        let dhcp4 = conn.ip_config.dhcp4_settings.map(Dhcp4ClientSettings::from);
+        let dhcp6 = conn.ip_config.dhcp6_settings.map(Dhcp6ClientSettings::from);
+        let dns_priority4 = conn.ip_config.dns_priority4;
+        let dns_priority6 = conn.ip_config.dns_priority6;
+        let link_local4 = (conn.ip_config.link_local4 != LinkLocal::Default)
+            .then_some(conn.ip_config.link_local4);
+        let may_fail4 = conn.ip_config.may_fail4;
+        let may_fail6 = conn.ip_config.may_fail6;
         let interface = conn.interface;
         let status = Some(conn.status);
         let mtu = conn.mtu;
@@ -726,6 +925,15 @@ impl TryFrom<Connection> for NetworkConnection {
             nameservers,
             dns_searchlist,
             ignore_auto_dns,
+            dns_priority4,
+            dns_priority6,
+            routes4,
+            routes6,
+            dhcp4,
+            dhcp6,
+            link_local4,
+            may_fail4,
+            may_fail6,
             custom_mac_address,
             mac_address,
             interface,
@@ -750,6 +958,24 @@ impl TryFrom<Connection> for NetworkConnection {
             ConnectionConfig::Vlan(config) => {
                 connection.vlan = Some(VlanSettings::try_from(config)?);
             }
+            ConnectionConfig::OvsBridge(config) => {
+                connection.ovs = Some(OvsSettings::Bridge(config.into()));
+            }
+            ConnectionConfig::OvsPort(config) => {
+                connection.ovs = Some(OvsSettings::Port(config.into()));
+            }
+            ConnectionConfig::OvsInterface(config) => {
+                connection.ovs = Some(OvsSettings::Interface(config.into()));
+            }
+            ConnectionConfig::Infiniband(config) => {
+                connection.infiniband = Some(config.into());
+            }
+            ConnectionConfig::Tun(config) => {
+                connection.tun = Some(config.into());
+            }
+            ConnectionConfig::Dummy => {
+                connection.dummy = Some(DummySettings::default());
+            }
             _ => {}
         }
 
@@ -930,6 +1156,8 @@ pub struct IpConfig {
     pub dns_priority4: Option<i32>,
     pub dns_priority6: Option<i32>,
     pub link_local4: LinkLocal,
+    pub may_fail4: Option<bool>,
+    pub may_fail6: Option<bool>,
 }
 
 #[skip_serializing_none]
@@ -942,6 +1170,31 @@ pub struct Dhcp4Settings {
     pub iaid: DhcpIaid,
 }
 
+impl From<Dhcp4ClientSettings> for Dhcp4Settings {
+    fn from(settings: Dhcp4ClientSettings) -> Self {
+        Self {
+            send_hostname: settings.send_hostname,
+            hostname: settings.hostname,
+            send_release: settings.send_release,
+            client_id: settings.client_id.into(),
+            iaid: settings.iaid.into(),
+        }
+    }
+}
+
+impl From<Dhcp4Settings> for Dhcp4ClientSettings {
+    fn from(settings: Dhcp4Settings) -> Self {
+        Self {
+            send_hostname: settings.send_hostname,
+            hostname: settings.hostname,
+            send_release: settings.send_release,
+            client_id: (settings.client_id != DhcpClientId::Unset)
+                .then(|| settings.client_id.to_string()),
+            iaid: (settings.iaid != DhcpIaid::Unset).then(|| settings.iaid.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, utoipa::ToSchema)]
 pub enum DhcpClientId {
     Id(String),
@@ -1052,6 +1305,30 @@ pub struct Dhcp6Settings {
     pub iaid: DhcpIaid,
 }
 
+impl From<Dhcp6ClientSettings> for Dhcp6Settings {
+    fn from(settings: Dhcp6ClientSettings) -> Self {
+        Self {
+            send_hostname: settings.send_hostname,
+            hostname: settings.hostname,
+            send_release: settings.send_release,
+            duid: settings.duid.into(),
+            iaid: settings.iaid.into(),
+        }
+    }
+}
+
+impl From<Dhcp6Settings> for Dhcp6ClientSettings {
+    fn from(settings: Dhcp6Settings) -> Self {
+        Self {
+            send_hostname: settings.send_hostname,
+            hostname: settings.hostname,
+            send_release: settings.send_release,
+            duid: (settings.duid != DhcpDuid::Unset).then(|| settings.duid.to_string()),
+            iaid: (settings.iaid != DhcpIaid::Unset).then(|| settings.iaid.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, utoipa::ToSchema)]
 pub enum DhcpDuid {
     Id(String),
@@ -1216,6 +1493,8 @@ pub struct IpRoute {
     pub next_hop: Option<IpAddr>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metric: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<u32>,
 }
 
 impl From<&IpRoute> for HashMap<&str, Value<'_>> {
@@ -1233,6 +1512,9 @@ impl From<&IpRoute> for HashMap<&str, Value<'_>> {
         if let Some(metric) = route.metric {
             map.insert("metric", Value::new(metric));
         }
+        if let Some(table) = route.table {
+            map.insert("table", Value::new(table));
+        }
         map
     }
 }
@@ -1913,6 +2195,42 @@ impl fmt::Display for InfinibandTransportMode {
     }
 }
 
+impl TryFrom<InfinibandSettings> for InfinibandConfig {
+    type Error = NetworkStateError;
+
+    fn try_from(settings: InfinibandSettings) -> Result<Self, Self::Error> {
+        let mut config = InfinibandConfig {
+            parent: settings.parent,
+            ..Default::default()
+        };
+
+        if let Some(p_key) = settings.p_key {
+            // -1 means "no partition"; 0x8000 is the full membership bit alone
+            if !matches!(p_key, -1 | 0x0001..=0x7fff | 0x8001..=0xffff) {
+                return Err(NetworkStateError::InvalidInfinibandPKey(p_key));
+            }
+            config.p_key = Some(p_key);
+        }
+
+        if let Some(mode) = &settings.transport_mode {
+            config.transport_mode = InfinibandTransportMode::from_str(mode)
+                .map_err(|_| NetworkStateError::InvalidInfinibandTransportMode(mode.to_string()))?;
+        }
+
+        Ok(config)
+    }
+}
+
+impl From<InfinibandConfig> for InfinibandSettings {
+    fn from(config: InfinibandConfig) -> Self {
+        Self {
+            p_key: config.p_key,
+            parent: config.parent,
+            transport_mode: Some(config.transport_mode.to_string()),
+        }
+    }
+}
+
 #[derive(Default, Debug, PartialEq, Clone, Serialize, utoipa::ToSchema)]
 pub enum TunMode {
     #[default]
@@ -1920,6 +2238,32 @@ pub enum TunMode {
     Tap = 2,
 }
 
+#[derive(Debug, Error)]
+#[error("Invalid TUN mode: {0}")]
+pub struct InvalidTunMode(String);
+
+impl FromStr for TunMode {
+    type Err = InvalidTunMode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tun" => Ok(Self::Tun),
+            "tap" => Ok(Self::Tap),
+            _ => Err(InvalidTunMode(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for TunMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match &self {
+            TunMode::Tun => "tun",
+            TunMode::Tap => "tap",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Default, Debug, PartialEq, Clone, Serialize, utoipa::ToSchema)]
 pub struct TunConfig {
     pub mode: TunMode,
@@ -1927,6 +2271,35 @@ pub struct TunConfig {
     pub owner: Option<String>,
 }
 
+impl TryFrom<TunSettings> for TunConfig {
+    type Error = NetworkStateError;
+
+    fn try_from(settings: TunSettings) -> Result<Self, Self::Error> {
+        let mut config = TunConfig {
+            group: settings.group,
+            owner: settings.owner,
+            ..Default::default()
+        };
+
+        if let Some(mode) = &settings.mode {
+            config.mode = TunMode::from_str(mode)
+                .map_err(|_| NetworkStateError::InvalidTunMode(mode.to_string()))?;
+        }
+
+        Ok(config)
+    }
+}
+
+impl From<TunConfig> for TunSettings {
+    fn from(config: TunConfig) -> Self {
+        Self {
+            mode: Some(config.mode.to_string()),
+            owner: config.owner,
+            group: config.group,
+        }
+    }
+}
+
 /// Represents a network change.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -1941,6 +2314,8 @@ pub enum NetworkChange {
     DeviceUpdated(String, Device),
     /// A connection state has changed.
     ConnectionStateChanged { id: String, state: ConnectionState },
+    /// The connectivity was verified after applying the configuration.
+    ConnectivityChecked(ConnectivityReport),
 }
 
 #[derive(Default, Debug, PartialEq, Clone, Serialize, utoipa::ToSchema)]
@@ -2192,3 +2567,57 @@ pub struct OvsInterfaceConfig {
 
 #[derive(Debug, Default, PartialEq, Clone, Serialize, utoipa::ToSchema)]
 pub struct OvsBridgePortConfig {}
+
+impl TryFrom<OvsSettings> for ConnectionConfig {
+    type Error = NetworkStateError;
+
+    fn try_from(settings: OvsSettings) -> Result<Self, Self::Error> {
+        let config = match settings {
+            OvsSettings::Bridge(bridge) => ConnectionConfig::OvsBridge(OvsBridgeConfig {
+                mcast_snooping_enable: bridge.mcast_snooping_enable,
+                rstp_enable: bridge.rstp_enable,
+                stp_enable: bridge.stp_enable,
+            }),
+            OvsSettings::Port(port) => ConnectionConfig::OvsPort(OvsPortConfig { tag: port.tag }),
+            OvsSettings::Interface(interface) => {
+                let interface_type = match &interface.interface_type {
+                    Some(name) => OvsInterfaceType::from_str(name).map_err(|_| {
+                        NetworkStateError::InvalidOvsInterfaceType(name.to_string())
+                    })?,
+                    None => OvsInterfaceType::default(),
+                };
+                ConnectionConfig::OvsInterface(OvsInterfaceConfig { interface_type })
+            }
+        };
+        Ok(config)
+    }
+}
+
+impl From<OvsBridgeConfig> for OvsBridgeSettings {
+    fn from(config: OvsBridgeConfig) -> Self {
+        Self {
+            mcast_snooping_enable: config.mcast_snooping_enable,
+            rstp_enable: config.rstp_enable,
+            stp_enable: config.stp_enable,
+            ports: vec![],
+        }
+    }
+}
+
+impl From<OvsPortConfig> for OvsPortSettings {
+    fn from(config: OvsPortConfig) -> Self {
+        Self {
+            tag: config.tag,
+            ports: vec![],
+        }
+    }
+}
+
+impl From<OvsInterfaceConfig> for OvsInterfaceSettings {
+    fn from(config: OvsInterfaceConfig) -> Self {
+        let interface_type = config.interface_type.to_string();
+        Self {
+            interface_type: (!interface_type.is_empty()).then_some(interface_type),
+        }
+    }
+}
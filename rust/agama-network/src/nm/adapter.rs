@@ -20,6 +20,7 @@
 
 use crate::{
     adapter::Watcher,
+    connectivity::{ConnectivityChecks, ConnectivityReport},
     model::{Connection, NetworkState, StateConfig},
     nm::{NetworkManagerClient, NetworkManagerWatcher},
     Adapter, NetworkAdapterError,
@@ -28,9 +29,17 @@ use anyhow::anyhow;
 use async_trait::async_trait;
 use core::time;
 use std::thread;
+use zbus::zvariant::OwnedObjectPath;
 
 use super::error::NmError;
 
+/// Seconds before NetworkManager rolls a checkpoint back on its own while the connections are
+/// written.
+const CHECKPOINT_WRITE_TIMEOUT: u32 = 300;
+/// Seconds, on top of the checks timeout, before NetworkManager rolls a checkpoint back on its
+/// own.
+const CHECKPOINT_ROLLBACK_MARGIN: u64 = 30;
+
 /// An adapter for NetworkManager
 pub struct NetworkManagerAdapter<'a> {
     client: NetworkManagerClient<'a>,
@@ -97,18 +106,129 @@ impl Adapter for NetworkManagerAdapter<'_> {
 
     /// Writes the connections to NetworkManager.
     ///
-    /// Internally, it creates an ordered list of connections before processing them. The reason is
-    /// that using async recursive functions is giving us some troubles, so we decided to go with a
-    /// simpler approach.
-    ///
     /// * `network`: network model.
     async fn write(&self, network: &NetworkState) -> Result<(), NetworkAdapterError> {
-        let old_state = self.read(StateConfig::default()).await?;
         let checkpoint = self
             .client
-            .create_checkpoint()
+            .create_checkpoint(0)
+            .await
+            .map_err(|e| NetworkAdapterError::Checkpoint(anyhow!(e)))?;
+        self.write_with_checkpoint(network, &checkpoint).await?;
+
+        self.client
+            .destroy_checkpoint(&checkpoint.as_ref())
+            .await
+            .map_err(|e| NetworkAdapterError::Checkpoint(anyhow!(e)))?;
+        Ok(())
+    }
+
+    /// Writes the connections to NetworkManager and verifies the connectivity.
+    ///
+    /// If the checks fail, the checkpoint is rolled back. NetworkManager rolls it back on its own
+    /// if Agama does not destroy it in time (e.g., because it crashed), so the system cannot get
+    /// locked out. The rollback timeout covers the writing first and, once the connections are
+    /// written, the verification.
+    ///
+    /// * `network`: network model.
+    /// * `checks`: checks to run after writing the connections.
+    async fn write_and_verify(
+        &self,
+        network: &NetworkState,
+        checks: &ConnectivityChecks,
+    ) -> Result<ConnectivityReport, NetworkAdapterError> {
+        let checkpoint = self
+            .client
+            .create_checkpoint(CHECKPOINT_WRITE_TIMEOUT)
+            .await
+            .map_err(|e| NetworkAdapterError::Checkpoint(anyhow!(e)))?;
+        self.write_with_checkpoint(network, &checkpoint).await?;
+
+        let rollback_timeout = checks
+            .timeout()
+            .as_secs()
+            .saturating_add(CHECKPOINT_ROLLBACK_MARGIN);
+        let result = self
+            .client
+            .adjust_checkpoint_rollback_timeout(
+                &checkpoint.as_ref(),
+                rollback_timeout.try_into().unwrap_or(u32::MAX),
+            )
+            .await;
+        if let Err(error) = result {
+            self.check_rolled_back(&checkpoint, error).await?;
+            return Ok(ConnectivityReport {
+                success: false,
+                rolled_back: true,
+                checks: vec![],
+            });
+        }
+
+        let mut report = checks.verify(self).await;
+        let result = if report.success {
+            self.client.destroy_checkpoint(&checkpoint.as_ref()).await
+        } else {
+            tracing::warn!("Connectivity checks failed, rolling back: {:?}", &report);
+            self.client.rollback_checkpoint(&checkpoint.as_ref()).await
+        };
+
+        match result {
+            Ok(()) => report.rolled_back = !report.success,
+            Err(error) => {
+                self.check_rolled_back(&checkpoint, error).await?;
+                report.success = false;
+                report.rolled_back = true;
+            }
+        }
+        Ok(report)
+    }
+
+    fn watcher(&self) -> Option<Box<dyn Watcher + Send>> {
+        Some(Box::new(NetworkManagerWatcher::new(&self.connection)))
+    }
+}
+
+impl NetworkManagerAdapter<'_> {
+    /// Checks whether NetworkManager already rolled the checkpoint back on its own.
+    ///
+    /// It returns the given error if the checkpoint still exists.
+    ///
+    /// * `checkpoint`: checkpoint's D-Bus path.
+    /// * `error`: error when handling the checkpoint.
+    async fn check_rolled_back(
+        &self,
+        checkpoint: &OwnedObjectPath,
+        error: NmError,
+    ) -> Result<(), NetworkAdapterError> {
+        let exists = self
+            .client
+            .has_checkpoint(&checkpoint.as_ref())
             .await
             .map_err(|e| NetworkAdapterError::Checkpoint(anyhow!(e)))?;
+        if exists {
+            return Err(NetworkAdapterError::Checkpoint(anyhow!(error)));
+        }
+        tracing::warn!(
+            "NetworkManager already rolled back the checkpoint {}: {}",
+            checkpoint.as_str(),
+            &error
+        );
+        Ok(())
+    }
+
+    /// Writes the general state and the connections, rolling back the checkpoint on failure.
+    ///
+    /// Internally, it creates an ordered list of connections before processing them. The reason is
+    /// that using async recursive functions is giving us some troubles, so we decided to go with a
+    /// simpler approach.
+    ///
+    /// * `network`: network model.
+    /// * `checkpoint`: checkpoint to roll back if something goes wrong.
+    async fn write_with_checkpoint(
+        &self,
+        network: &NetworkState,
+        checkpoint: &OwnedObjectPath,
+    ) -> Result<(), NetworkAdapterError> {
+        let old_state = self.read(StateConfig::default()).await?;
 
         tracing::info!("Updating the general state {:?}", &network.general_state);
         let result = self
@@ -175,16 +295,8 @@ impl Adapter for NetworkManagerAdapter<'_> {
             }
         }
 
-        self.client
-            .destroy_checkpoint(&checkpoint.as_ref())
-            .await
-            .map_err(|e| NetworkAdapterError::Checkpoint(anyhow!(e)))?;
         Ok(())
     }
-
-    fn watcher(&self) -> Option<Box<dyn Watcher + Send>> {
-        Some(Box::new(NetworkManagerWatcher::new(&self.connection)))
-    }
 }
 
 /// Returns the connections in the order they should be processed.
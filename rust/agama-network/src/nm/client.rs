@@ -382,8 +382,17 @@ impl<'a> NetworkManagerClient<'a> {
     }
 
     /// Creates a checkpoint.
-    pub async fn create_checkpoint(&self) -> Result<OwnedObjectPath, NmError> {
-        let path = self.nm_proxy.checkpoint_create(&[], 0, 0).await?;
+    ///
+    /// * `rollback_timeout`: seconds after which NetworkManager rolls the checkpoint back
+    ///   automatically (0 means never).
+    pub async fn create_checkpoint(
+        &self,
+        rollback_timeout: u32,
+    ) -> Result<OwnedObjectPath, NmError> {
+        let path = self
+            .nm_proxy
+            .checkpoint_create(&[], rollback_timeout, 0)
+            .await?;
         Ok(path)
     }
 
@@ -395,6 +404,32 @@ impl<'a> NetworkManagerClient<'a> {
         Ok(())
     }
 
+    /// Sets the time after which NetworkManager rolls the checkpoint back automatically.
+    ///
+    /// * `checkpoint`: checkpoint's D-Bus path.
+    /// * `rollback_timeout`: seconds, from now, after which the checkpoint is rolled back (0 means
+    ///   never).
+    pub async fn adjust_checkpoint_rollback_timeout(
+        &self,
+        checkpoint: &ObjectPath<'_>,
+        rollback_timeout: u32,
+    ) -> Result<(), NmError> {
+        self.nm_proxy
+            .checkpoint_adjust_rollback_timeout(checkpoint, rollback_timeout)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the checkpoint still exists.
+    ///
+    /// NetworkManager removes the checkpoint when it is rolled back or destroyed.
+    ///
+    /// * `checkpoint`: checkpoint's D-Bus path.
+    pub async fn has_checkpoint(&self, checkpoint: &ObjectPath<'_>) -> Result<bool, NmError> {
+        let checkpoints = self.nm_proxy.checkpoints().await?;
+        Ok(checkpoints.iter().any(|c| c.as_ref() == *checkpoint))
+    }
+
     /// Rolls the configuration back to the given checkpoint.
     ///
     /// * `checkpoint`: checkpoint's D-Bus path.
@@ -471,6 +471,10 @@ fn ip_config_to_ipv4_dbus<'a>(
         ipv4_dbus.insert("dns-priority", dns_priority4.into());
     }
 
+    if let Some(may_fail4) = ip_config.may_fail4 {
+        ipv4_dbus.insert("may-fail", may_fail4.into());
+    }
+
     if let Some(dhcp4_settings) = &ip_config.dhcp4_settings {
         if VersionReq::parse(">=1.52.0").unwrap().matches(nm_version) {
             let dhcp_send_hostname = match dhcp4_settings.send_hostname {
@@ -575,6 +579,10 @@ fn ip_config_to_ipv6_dbus<'a>(
         ipv6_dbus.insert("dns-priority", dns_priority6.into());
     }
 
+    if let Some(may_fail6) = ip_config.may_fail6 {
+        ipv6_dbus.insert("may-fail", may_fail6.into());
+    }
+
     if let Some(dhcp6_settings) = &ip_config.dhcp6_settings {
         if VersionReq::parse(">=1.52.0").unwrap().matches(nm_version) {
             let dhcp_send_hostname = match dhcp6_settings.send_hostname {
@@ -1094,6 +1102,8 @@ fn ip_config_from_dbus(conn: &OwnedNestedHash) -> Result<IpConfig, NmError> {
             ip_config.link_local4 = link_local4.try_into().unwrap_or_default();
         }
 
+        ip_config.may_fail4 = get_optional_property(ipv4, "may-fail")?;
+
         let mut dhcp4_settings = Dhcp4Settings::default();
         if let Some(dhcp_send_hostname) = get_optional_property(ipv4, "dhcp-send-hostname-v2")? {
             dhcp4_settings.send_hostname = match dhcp_send_hostname {
@@ -1168,6 +1178,8 @@ fn ip_config_from_dbus(conn: &OwnedNestedHash) -> Result<IpConfig, NmError> {
             ip_config.dns_priority6 = Some(dns_priority6);
         }
 
+        ip_config.may_fail6 = get_optional_property(ipv6, "may-fail")?;
+
         let mut dhcp6_settings = Dhcp6Settings::default();
         if let Some(dhcp_send_hostname) = get_optional_property(ipv6, "dhcp-send-hostname-v2")? {
             dhcp6_settings.send_hostname = match dhcp_send_hostname {
@@ -1229,11 +1241,13 @@ fn routes_from_dbus(route_data: &OwnedValue) -> Result<Vec<IpRoute>, NmError> {
             destination,
             next_hop: None,
             metric: None,
+            table: None,
         };
         if let Some(next_hop) = get_optional_property::<String>(&map, "next-hop")? {
             new_route.next_hop = Some(IpAddr::from_str(next_hop.as_str()).unwrap());
         }
         new_route.metric = get_optional_property(&map, "metric")?;
+        new_route.table = get_optional_property(&map, "table")?;
         routes.push(new_route)
     }
     Ok(routes)
@@ -1721,7 +1735,8 @@ mod test {
             vec![IpRoute {
                 destination: IpInet::new("192.168.0.0".parse().unwrap(), 24_u8).unwrap(),
                 next_hop: Some(IpAddr::from_str("192.168.0.1").unwrap()),
-                metric: Some(100)
+                metric: Some(100),
+                table: None
             }]
         );
         assert_eq!(
@@ -1729,7 +1744,8 @@ mod test {
             vec![IpRoute {
                 destination: IpInet::new("2001:db8::".parse().unwrap(), 64_u8).unwrap(),
                 next_hop: Some(IpAddr::from_str("2001:db8::1").unwrap()),
-                metric: Some(100)
+                metric: Some(100),
+                table: None
             }]
         );
         assert!(!connection.autoconnect);
@@ -2363,11 +2379,13 @@ mod test {
                 destination: IpInet::new("192.168.0.0".parse().unwrap(), 24_u8).unwrap(),
                 next_hop: Some(IpAddr::from_str("192.168.0.1").unwrap()),
                 metric: Some(100),
+                table: None,
             }],
             routes6: vec![IpRoute {
                 destination: IpInet::new("2001:db8::".parse().unwrap(), 64_u8).unwrap(),
                 next_hop: Some(IpAddr::from_str("2001:db8::1").unwrap()),
                 metric: Some(100),
+                table: None,
             }],
             dns_searchlist: vec!["suse.com".to_string(), "suse.de".to_string()],
             dhcp4_settings: Some(Dhcp4Settings {
@@ -20,6 +20,7 @@
 
 //! Representation of the network settings
 
+use super::model::{IpRoute, LinkLocal};
 use super::types::{DeviceState, DeviceType, Status};
 use agama_utils::openapi::schemas;
 use cidr::IpInet;
@@ -151,6 +152,106 @@ pub struct VlanSettings {
     pub protocol: Option<String>,
 }
 
+/// InfiniBand (IPoIB) settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InfinibandSettings {
+    /// Partition key (P_Key) to create a child interface
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p_key: Option<i32>,
+    /// Parent interface of the partition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Transport mode ("datagram" or "connected")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transport_mode: Option<String>,
+}
+
+/// TUN/TAP device settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TunSettings {
+    /// Device mode ("tun" or "tap")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// User allowed to use the device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Group allowed to use the device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+/// Dummy device settings
+///
+/// It does not have any option, but it is needed to create a dummy device.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DummySettings {}
+
+/// Open vSwitch settings
+///
+/// An OVS setup is composed by a bridge, its ports and the interfaces of each port.
+#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum OvsSettings {
+    /// OVS bridge
+    Bridge(OvsBridgeSettings),
+    /// OVS port, which belongs to a bridge
+    Port(OvsPortSettings),
+    /// OVS interface, which belongs to a port
+    Interface(OvsInterfaceSettings),
+}
+
+impl OvsSettings {
+    /// Ports or interfaces controlled by this connection, if it is a bridge or a port.
+    pub fn ports(&self) -> Option<&Vec<String>> {
+        match self {
+            Self::Bridge(bridge) => Some(&bridge.ports),
+            Self::Port(port) => Some(&port.ports),
+            Self::Interface(_) => None,
+        }
+    }
+}
+
+/// Open vSwitch bridge settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OvsBridgeSettings {
+    /// Whether multicast snooping is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mcast_snooping_enable: Option<bool>,
+    /// Whether the Rapid Spanning Tree Protocol is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rstp_enable: Option<bool>,
+    /// Whether the Spanning Tree Protocol is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stp_enable: Option<bool>,
+    /// OVS ports of the bridge
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub ports: Vec<String>,
+}
+
+/// Open vSwitch port settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OvsPortSettings {
+    /// VLAN tag of the port
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<u16>,
+    /// Interfaces of the port (OVS interfaces or regular devices)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub ports: Vec<String>,
+}
+
+/// Open vSwitch interface settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OvsInterfaceSettings {
+    /// Interface type ("internal", "system", "patch" or "dpdk")
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub interface_type: Option<String>,
+}
+
 /// IEEE 802.1x (EAP) settings
 #[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -196,6 +297,50 @@ pub struct IEEE8021XSettings {
     pub peap_label: bool,
 }
 
+/// DHCPv4 client settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Dhcp4ClientSettings {
+    /// Whether the hostname is sent to the DHCP server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_hostname: Option<bool>,
+    /// Hostname to send to the DHCP server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Whether a release message is sent when the connection goes down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_release: Option<bool>,
+    /// Client identifier ("mac", "perm-mac", "ipv6-duid", "duid", "stable", "none" or a custom
+    /// value)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Identity Association Identifier ("mac", "perm-mac", "ifname", "stable" or a custom value)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iaid: Option<String>,
+}
+
+/// DHCPv6 client settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Dhcp6ClientSettings {
+    /// Whether the hostname is sent to the DHCP server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_hostname: Option<bool>,
+    /// Hostname to send to the DHCP server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Whether a release message is sent when the connection goes down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_release: Option<bool>,
+    /// DHCP unique identifier ("lease", "llt", "ll", "stable-llt", "stable-ll", "stable-uuid"
+    /// or a custom value)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duid: Option<String>,
+    /// Identity Association Identifier ("mac", "perm-mac", "ifname", "stable" or a custom value)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iaid: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NetworkDevice {
     pub id: String,
@@ -237,6 +382,35 @@ pub struct NetworkConnection {
     /// Specifies whether to ignore automatically assigned DNS settings
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ignore_auto_dns: Option<bool>,
+    /// Priority of the IPv4 DNS servers (lower values have higher priority)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_priority4: Option<i32>,
+    /// Priority of the IPv6 DNS servers (lower values have higher priority)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_priority6: Option<i32>,
+    /// Static IPv4 routes
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub routes4: Vec<IpRoute>,
+    /// Static IPv6 routes
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub routes6: Vec<IpRoute>,
+    /// DHCPv4 client settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dhcp4: Option<Dhcp4ClientSettings>,
+    /// DHCPv6 client settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dhcp6: Option<Dhcp6ClientSettings>,
+    /// IPv4 link-local addressing mode. There is no IPv6 counterpart: the IPv6 link-local
+    /// address is always configured unless IPv6 is disabled (use `method6` "link-local" to
+    /// configure only that address).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_local4: Option<LinkLocal>,
+    /// Whether the connection can be activated even if the IPv4 configuration fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub may_fail4: Option<bool>,
+    /// Whether the connection can be activated even if the IPv6 configuration fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub may_fail6: Option<bool>,
     /// VLAN settings for the connection
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vlan: Option<VlanSettings>,
@@ -258,6 +432,18 @@ pub struct NetworkConnection {
     /// Bridge settings if part of a bridge
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bridge: Option<BridgeSettings>,
+    /// Open vSwitch settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ovs: Option<OvsSettings>,
+    /// InfiniBand settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub infiniband: Option<InfinibandSettings>,
+    /// TUN/TAP settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tun: Option<TunSettings>,
+    /// Dummy device settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dummy: Option<DummySettings>,
     /// Custom MAC address of the connection's interface
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_mac_address: Option<String>,
@@ -289,16 +475,48 @@ impl NetworkConnection {
     /// Device type expected for the network connection.
     ///
     /// Which device type to use is inferred from the included settings. For instance, if it has
-    /// wireless settings, it should be applied to a wireless device.
+    /// wireless settings, it should be applied to a wireless device. Without any device-specific
+    /// section, it is an Ethernet device.
+    ///
+    /// A connection can include only one device-specific section (see [Self::device_sections]).
+    /// Otherwise, the first one is used.
     pub fn device_type(&self) -> DeviceType {
-        if self.wireless.is_some() {
-            DeviceType::Wireless
-        } else if self.bond.is_some() {
-            DeviceType::Bond
-        } else if self.bridge.is_some() {
-            DeviceType::Bridge
-        } else {
-            DeviceType::Ethernet
-        }
+        self.device_sections()
+            .first()
+            .map(|(_, device_type)| *device_type)
+            .unwrap_or(DeviceType::Ethernet)
+    }
+
+    /// Device-specific sections (e.g., "wireless" or "bond") included in the settings and the
+    /// device type each one implies.
+    pub fn device_sections(&self) -> Vec<(&'static str, DeviceType)> {
+        let ovs = self.ovs.as_ref().map(|ovs| match ovs {
+            OvsSettings::Bridge(_) => DeviceType::OvsBridge,
+            OvsSettings::Port(_) => DeviceType::OvsPort,
+            OvsSettings::Interface(_) => DeviceType::OvsInterface,
+        });
+        let sections = [
+            ("vlan", self.vlan.is_some().then_some(DeviceType::Vlan)),
+            (
+                "wireless",
+                self.wireless.is_some().then_some(DeviceType::Wireless),
+            ),
+            ("bond", self.bond.is_some().then_some(DeviceType::Bond)),
+            (
+                "bridge",
+                self.bridge.is_some().then_some(DeviceType::Bridge),
+            ),
+            ("ovs", ovs),
+            (
+                "infiniband",
+                self.infiniband.is_some().then_some(DeviceType::Infiniband),
+            ),
+            ("tun", self.tun.is_some().then_some(DeviceType::Tun)),
+            ("dummy", self.dummy.is_some().then_some(DeviceType::Dummy)),
+        ];
+        sections
+            .into_iter()
+            .filter_map(|(name, device_type)| Some((name, device_type?)))
+            .collect()
     }
 }
@@ -19,7 +19,8 @@
 // find current contact information at www.suse.com.
 
 use crate::{
-    action::Action,
+    action::{Action, Responder},
+    connectivity::{ConnectivityChecks, ConnectivityReport},
     error::NetworkStateError,
     model::{
         AccessPoint, Connection, Device, GeneralState, NetworkChange, NetworkState, StateConfig,
@@ -27,7 +28,7 @@ use crate::{
     types::DeviceType,
     Adapter, NetworkAdapterError,
 };
-use std::error::Error;
+use std::{error::Error, sync::Arc};
 use tokio::sync::{
     broadcast::{self, Receiver},
     mpsc::{self, error::SendError, UnboundedReceiver, UnboundedSender},
@@ -104,12 +105,14 @@ impl<T: Adapter + Send + Sync + 'static> NetworkSystem<T> {
         }
 
         let updates_tx_clone = updates_tx.clone();
+        let actions_tx_server = actions_tx.clone();
         tokio::spawn(async move {
             let mut server = NetworkSystemServer {
                 state,
                 input: actions_rx,
+                actions: actions_tx_server,
                 output: updates_tx_clone,
-                adapter: self.adapter,
+                adapter: Arc::new(self.adapter),
             };
 
             server.listen().await;
@@ -229,6 +232,22 @@ impl NetworkSystemClient {
         Ok(result?)
     }
 
+    /// Applies the network configuration and verifies the connectivity.
+    ///
+    /// If the checks fail, the adapter might roll the changes back. See [Adapter::write_and_verify].
+    ///
+    /// * `checks`: checks to run after applying the configuration.
+    pub async fn apply_and_verify(
+        &self,
+        checks: ConnectivityChecks,
+    ) -> Result<ConnectivityReport, NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions
+            .send(Action::ApplyAndVerify(Box::new(checks), tx))?;
+        let result = rx.await?;
+        Ok(result?)
+    }
+
     /// Returns the collection of access points.
     pub async fn get_access_points(&self) -> Result<Vec<AccessPoint>, NetworkSystemError> {
         let (tx, rx) = oneshot::channel();
@@ -248,11 +267,13 @@ impl NetworkSystemClient {
 struct NetworkSystemServer<T: Adapter> {
     state: NetworkState,
     input: UnboundedReceiver<Action>,
+    // Sender to send actions to the server itself (e.g., from the spawned tasks).
+    actions: UnboundedSender<Action>,
     output: broadcast::Sender<NetworkChange>,
-    adapter: T,
+    adapter: Arc<T>,
 }
 
-impl<T: Adapter> NetworkSystemServer<T> {
+impl<T: Adapter + Send + Sync + 'static> NetworkSystemServer<T> {
     /// Process incoming actions.
     ///
     /// This function is expected to be executed on a separate thread.
@@ -376,6 +397,28 @@ impl<T: Adapter> NetworkSystemServer<T> {
                 let result = self.write().await;
                 tx.send(result).unwrap();
             }
+            Action::ApplyAndVerify(checks, tx) => {
+                self.write_and_verify(*checks, tx);
+            }
+            Action::ConnectivityVerified(result, tx) => {
+                let result = match *result {
+                    Ok(report) => self
+                        .adapter
+                        .read(StateConfig::default())
+                        .await
+                        .map(|state| {
+                            self.state = state;
+                            report
+                        }),
+                    Err(error) => Err(error),
+                };
+                let change = result
+                    .as_ref()
+                    .ok()
+                    .map(|r| NetworkChange::ConnectivityChecked(r.clone()));
+                tx.send(result).unwrap();
+                return Ok(change);
+            }
         }
 
         Ok(None)
@@ -430,4 +473,25 @@ impl<T: Adapter> NetworkSystemServer<T> {
         self.state = self.adapter.read(StateConfig::default()).await?;
         Ok(())
     }
+
+    /// Writes the network configuration and verifies the connectivity.
+    ///
+    /// The checks can take a while, so they run on a separate task to keep processing other
+    /// actions. The result is sent back as an [Action::ConnectivityVerified] action.
+    ///
+    /// * `checks`: checks to run after writing the configuration.
+    /// * `tx`: channel to send the result to.
+    pub fn write_and_verify(
+        &self,
+        checks: ConnectivityChecks,
+        tx: Responder<Result<ConnectivityReport, NetworkAdapterError>>,
+    ) {
+        let adapter = Arc::clone(&self.adapter);
+        let actions = self.actions.clone();
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            let result = adapter.write_and_verify(&state, &checks).await;
+            _ = actions.send(Action::ConnectivityVerified(Box::new(result), tx));
+        });
+    }
 }
@@ -78,6 +78,11 @@ pub enum DeviceType {
     Bond = 4,
     Vlan = 5,
     Bridge = 6,
+    Infiniband = 7,
+    Tun = 8,
+    OvsBridge = 9,
+    OvsPort = 10,
+    OvsInterface = 11,
 }
 
 /// Network device state.
@@ -334,6 +339,11 @@ impl TryFrom<u8> for DeviceType {
             4 => Ok(DeviceType::Bond),
             5 => Ok(DeviceType::Vlan),
             6 => Ok(DeviceType::Bridge),
+            7 => Ok(DeviceType::Infiniband),
+            8 => Ok(DeviceType::Tun),
+            9 => Ok(DeviceType::OvsBridge),
+            10 => Ok(DeviceType::OvsPort),
+            11 => Ok(DeviceType::OvsInterface),
             _ => Err(InvalidDeviceType(value)),
         }
     }
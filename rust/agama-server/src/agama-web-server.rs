@@ -19,6 +19,8 @@
 // find current contact information at www.suse.com.
 
 use std::{
+    fs, io,
+    os::unix::fs::{FileTypeExt, PermissionsExt},
     path::{Path, PathBuf},
     pin::Pin,
     process::{ExitCode, Termination},
@@ -29,11 +31,11 @@ use agama_server::{
     cert::Certificate,
     l10n::helpers,
     logs::init_logging,
-    web::{self, run_monitor},
+    web::{self, run_monitor, ClientCertificate, PeerCredentials},
 };
 use anyhow::Context;
 use axum::{
-    extract::Request as AxumRequest,
+    extract::{ConnectInfo, Request as AxumRequest},
     http::{Request, Response},
     Router,
 };
@@ -42,8 +44,14 @@ use futures_util::pin_mut;
 use hyper::body::Incoming;
 use hyper_util::rt::{TokioExecutor, TokioIo};
 use hyper_util::server::conn::auto::Builder;
-use openssl::ssl::{Ssl, SslAcceptor, SslMethod};
-use tokio::sync::broadcast::channel;
+use openssl::{
+    ssl::{Ssl, SslAcceptor, SslMethod, SslVerifyMode},
+    x509::{X509Name, X509VerifyResult},
+};
+use tokio::{
+    net::{UnixListener, UnixStream},
+    sync::broadcast::channel,
+};
 use tokio_openssl::SslStream;
 use tower::Service;
 
@@ -92,6 +100,20 @@ struct ServeArgs {
     #[arg(long, default_value = "/etc/agama.d/ssl/cert.pem")]
     cert: Option<PathBuf>,
 
+    /// CA certificates to verify the TLS client certificates. If it is set, the clients can
+    /// authenticate using a certificate signed by one of those CAs
+    #[arg(long)]
+    client_ca: Option<PathBuf>,
+
+    /// Unix domain socket to listen on (e.g., /run/agama/api.sock). The processes running as root
+    /// can use it without authenticating
+    #[arg(long)]
+    socket: Option<PathBuf>,
+
+    /// Do not listen on any TCP address, only on the Unix socket
+    #[arg(long, requires = "socket")]
+    no_tcp: bool,
+
     // Agama D-Bus address
     #[arg(long, default_value = "unix:path=/run/agama/bus")]
     dbus_address: String,
@@ -135,7 +157,13 @@ impl ServeArgs {
 }
 
 /// Builds an SSL acceptor using a provided SSL certificate or generates a self-signed one
-fn ssl_acceptor(certificate: &Certificate) -> Result<SslAcceptor, openssl::error::ErrorStack> {
+///
+/// `certificate`: server certificate.
+/// `client_ca`: optional CA file to request and verify the client certificates.
+fn ssl_acceptor(
+    certificate: &Certificate,
+    client_ca: Option<&Path>,
+) -> Result<SslAcceptor, openssl::error::ErrorStack> {
     let mut tls_builder = SslAcceptor::mozilla_modern_v5(SslMethod::tls_server())?;
 
     tls_builder.set_private_key(&certificate.key)?;
@@ -144,6 +172,14 @@ fn ssl_acceptor(certificate: &Certificate) -> Result<SslAcceptor, openssl::error
     // check that the key belongs to the certificate
     tls_builder.check_private_key()?;
 
+    // the client certificate is optional: clients without a certificate can still use
+    // the password or the SSH key to authenticate
+    if let Some(client_ca) = client_ca {
+        tls_builder.set_ca_file(client_ca)?;
+        tls_builder.set_client_ca_list(X509Name::load_client_ca_file(client_ca)?);
+        tls_builder.set_verify(SslVerifyMode::PEER);
+    }
+
     Ok(tls_builder.build())
 }
 
@@ -214,6 +250,18 @@ fn https_redirect() -> Router {
         .route_service("/*path", redirect_service)
 }
 
+/// Returns the verified client certificate of the TLS connection, if any
+fn client_certificate(ssl: &openssl::ssl::SslRef) -> Option<ClientCertificate> {
+    if ssl.verify_result() != X509VerifyResult::OK {
+        return None;
+    }
+
+    let cert = ssl.peer_certificate()?;
+    ClientCertificate::from_x509(&cert)
+        .inspect_err(|e| tracing::warn!("Could not read the client certificate: {}", e))
+        .ok()
+}
+
 /// handle the HTTPS connection
 async fn handle_https_stream(
     tls_acceptor: SslAcceptor,
@@ -227,8 +275,22 @@ async fn handle_https_stream(
     if let Err(err) = SslStream::accept(Pin::new(&mut tls_stream)).await {
         tracing::error!("Error during TSL handshake from {}: {}", addr, err);
     } else {
+        let certificate = client_certificate(tls_stream.ssl());
+        if let Some(certificate) = &certificate {
+            tracing::info!(
+                "Client certificate from {}: {} ({})",
+                addr,
+                certificate.subject,
+                certificate.fingerprint
+            );
+        }
+
         let stream = TokioIo::new(tls_stream);
-        let hyper_service = hyper::service::service_fn(move |request: Request<Incoming>| {
+        let hyper_service = hyper::service::service_fn(move |mut request: Request<Incoming>| {
+            request.extensions_mut().insert(ConnectInfo(addr));
+            if let Some(certificate) = &certificate {
+                request.extensions_mut().insert(certificate.clone());
+            }
             service.clone().call(request)
         });
 
@@ -250,7 +312,8 @@ async fn handle_http_stream(
     redirector_service: axum::Router,
 ) {
     let stream = TokioIo::new(tcp_stream);
-    let hyper_service = hyper::service::service_fn(move |request: Request<Incoming>| {
+    let hyper_service = hyper::service::service_fn(move |mut request: Request<Incoming>| {
+        request.extensions_mut().insert(ConnectInfo(addr));
         // check if it is local connection or external
         // the to_canonical() converts IPv4-mapped IPv6 addresses
         // to plain IPv4, then is_loopback() works correctly for the IPv4 connections
@@ -313,6 +376,83 @@ async fn start_server(address: String, service: Router, ssl_acceptor: SslAccepto
     }
 }
 
+/// Binds the Unix socket, replacing the one left by a previous run.
+///
+/// The socket is accessible by any user because the access is checked on each request.
+fn bind_unix_socket(path: &Path) -> io::Result<UnixListener> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "the file exists and it is not a socket",
+            ))
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
+    }
+
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o666))?;
+    Ok(listener)
+}
+
+/// handle the connection through the Unix socket
+async fn handle_unix_stream(unix_stream: UnixStream, service: axum::Router) {
+    let credentials = match unix_stream.peer_cred() {
+        Ok(cred) => PeerCredentials::new(cred.uid(), cred.pid()),
+        Err(err) => {
+            tracing::error!("Could not read the peer credentials: {}", err);
+            return;
+        }
+    };
+    tracing::debug!(
+        "Connection through the Unix socket from uid {} (pid {:?})",
+        credentials.uid,
+        credentials.pid
+    );
+
+    let stream = TokioIo::new(unix_stream);
+    let hyper_service = hyper::service::service_fn(move |mut request: Request<Incoming>| {
+        request.extensions_mut().insert(credentials.clone());
+        service.clone().call(request)
+    });
+
+    let ret = Builder::new(TokioExecutor::new())
+        .serve_connection_with_upgrades(stream, hyper_service)
+        .await;
+
+    if let Err(err) = ret {
+        tracing::error!("Error serving connection through the Unix socket: {}", err);
+    }
+}
+
+/// Starts the web server on a Unix domain socket
+async fn start_unix_server(path: PathBuf, service: Router) {
+    tracing::info!("Starting Agama web server at {}", path.display());
+
+    let listener = bind_unix_socket(&path).unwrap_or_else(|error| {
+        let msg = format!("Error: could not listen on {}: {}", path.display(), error);
+        tracing::error!(msg);
+        panic!("{}", msg)
+    });
+
+    loop {
+        let tower_service = service.clone();
+
+        let (unix_stream, _addr) = listener
+            .accept()
+            .await
+            .expect("Failed to accept connections on the Unix socket");
+
+        tokio::spawn(handle_unix_stream(unix_stream, tower_service));
+    }
+}
+
 /// Start serving the API.
 /// `options`: command-line arguments.
 async fn serve_command(args: ServeArgs) -> anyhow::Result<()> {
@@ -320,7 +460,6 @@ async fn serve_command(args: ServeArgs) -> anyhow::Result<()> {
     init_logging().context("Could not initialize the logger")?;
 
     let (tx, _) = channel(16);
-    run_monitor(tx.clone()).await?;
 
     let config = web::ServiceConfig::load()?;
 
@@ -331,31 +470,41 @@ async fn serve_command(args: ServeArgs) -> anyhow::Result<()> {
         .web_ui_dir
         .clone()
         .unwrap_or_else(|| PathBuf::from(DEFAULT_WEB_UI_DIR));
-    let service = web::service(config, tx, dbus, web_ui_dir).await?;
-    // TODO: Move elsewhere? Use a singleton? (It would be nice to use the same
-    // generated self-signed certificate on both ports.)
-    let ssl_acceptor = if let Ok(ssl_acceptor) = ssl_acceptor(&args.to_certificate()?) {
-        ssl_acceptor
-    } else {
-        return Err(anyhow::anyhow!("SSL initialization failed"));
-    };
+    let service = web::service(config, tx.clone(), dbus, web_ui_dir).await?;
+    // start the monitor once the service is subscribed to the events, so the initial
+    // values (e.g., the installation phase) are not lost
+    run_monitor(tx).await?;
+
+    let mut servers = vec![];
+    if let Some(socket) = args.socket.clone() {
+        servers.push(tokio::spawn(start_unix_server(socket, service.clone())));
+    }
 
-    let mut addresses = vec![args.address];
+    if !args.no_tcp {
+        // TODO: Move elsewhere? Use a singleton? (It would be nice to use the same
+        // generated self-signed certificate on both ports.)
+        let certificate = args.to_certificate()?;
+        let ssl_acceptor =
+            if let Ok(ssl_acceptor) = ssl_acceptor(&certificate, args.client_ca.as_deref()) {
+                ssl_acceptor
+            } else {
+                return Err(anyhow::anyhow!("SSL initialization failed"));
+            };
 
-    if let Some(a) = args.address2 {
-        addresses.push(a)
-    }
+        let mut addresses = vec![args.address];
 
-    let servers: Vec<_> = addresses
-        .iter()
-        .map(|a| {
+        if let Some(a) = args.address2 {
+            addresses.push(a)
+        }
+
+        servers.extend(addresses.iter().map(|a| {
             tokio::spawn(start_server(
                 a.clone(),
                 service.clone(),
                 ssl_acceptor.clone(),
             ))
-        })
-        .collect();
+        }));
+    }
 
     // notify systemd that web server start serving
     if let Ok(true) = sd_notify::booted() {
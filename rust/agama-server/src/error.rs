@@ -18,7 +18,7 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use agama_lib::{error::ServiceError, questions::QuestionsError};
+use agama_lib::{error::ServiceError, hardware::HardwareError, questions::QuestionsError};
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -50,6 +50,8 @@ pub enum Error {
     Progress(#[from] ProgressServiceError),
     #[error("Could not check the password")]
     PasswordCheck(#[from] PasswordCheckerError),
+    #[error("Could not read the hardware information: {0}")]
+    Hardware(#[from] HardwareError),
 }
 
 // This would be nice, but using it for a return type
@@ -73,9 +75,13 @@ impl From<Error> for zbus::fdo::Error {
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         tracing::warn!("Server return error {}", self);
+        let status = match self {
+            Self::Hardware(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
         let body = json!({
             "error": self.to_string()
         });
-        (StatusCode::BAD_REQUEST, Json(body)).into_response()
+        (status, Json(body)).into_response()
     }
 }
@@ -0,0 +1,77 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements the web API for the hardware information.
+//!
+//! The module offers one public function:
+//!
+//! * `hardware_service` which returns the Axum service.
+
+use agama_lib::{
+    error::ServiceError,
+    hardware::{HardwareInfo, HardwareReader},
+};
+use axum::{routing::get, Json, Router};
+
+use crate::error;
+
+/// Sets up and returns the axum service for the hardware information.
+pub async fn hardware_service() -> Result<Router, ServiceError> {
+    let router = Router::new()
+        .route("/", get(get_hardware))
+        .route("/lshw", get(get_lshw));
+    Ok(router)
+}
+
+/// Returns the hardware information.
+#[utoipa::path(
+    get,
+    path = "/",
+    context_path = "/api/hardware",
+    responses(
+        (status = 200, description = "Hardware information", body = HardwareInfo),
+        (status = 500, description = "The hardware information could not be read")
+    )
+)]
+async fn get_hardware() -> Result<Json<HardwareInfo>, error::Error> {
+    let hardware = tokio::task::spawn_blocking(|| HardwareReader::from_system().read())
+        .await
+        .map_err(|e| error::Error::Anyhow(e.to_string()))??;
+    Ok(Json(hardware))
+}
+
+/// Returns the output of `lshw -json`.
+///
+/// It is used to evaluate the profiles for this system from a different machine.
+#[utoipa::path(
+    get,
+    path = "/lshw",
+    context_path = "/api/hardware",
+    responses(
+        (status = 200, description = "Output of lshw -json", body = Object),
+        (status = 500, description = "The lshw output could not be read")
+    )
+)]
+async fn get_lshw() -> Result<Json<serde_json::Value>, error::Error> {
+    let lshw = tokio::task::spawn_blocking(|| HardwareReader::from_system().lshw())
+        .await
+        .map_err(|e| error::Error::Anyhow(e.to_string()))??;
+    Ok(Json(lshw))
+}
@@ -23,6 +23,7 @@ pub mod cert;
 pub mod dbus;
 pub mod error;
 pub mod files;
+pub mod hardware;
 pub mod hostname;
 pub mod l10n;
 pub mod logs;
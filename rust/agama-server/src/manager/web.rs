@@ -35,12 +35,13 @@ use agama_lib::{
 use anyhow::Context;
 use axum::{
     body::Body,
-    extract::State,
+    extract::{Query, State},
     http::{header, status::StatusCode, HeaderMap, HeaderValue},
     response::IntoResponse,
     routing::{get, post},
     Extension, Json, Router,
 };
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -257,20 +258,30 @@ fn logs_router() -> Router<ManagerState<'static>> {
         .route("/list", get(list_logs))
 }
 
+#[derive(Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+struct LogsQuery {
+    /// Include the content of the downloaded files (e.g., the profiles), which might contain
+    /// secrets. By default, only the cache index is included.
+    #[serde(default)]
+    cached_files: bool,
+}
+
 #[utoipa::path(get,
     path = "/logs/store",
     context_path = "/api/manager",
+    params(LogsQuery),
     responses(
         (status = 200, description = "Compressed Agama logs", content_type="application/octet-stream"),
         (status = 500, description = "Cannot collect the logs"),
         (status = 507, description = "Server is probably out of space"),
     )
 )]
-async fn download_logs() -> impl IntoResponse {
+async fn download_logs(Query(query): Query<LogsQuery>) -> impl IntoResponse {
     let mut headers = HeaderMap::new();
     let err_response = (headers.clone(), Body::empty());
 
-    match logs::store() {
+    match logs::store(query.cached_files) {
         Ok(path) => {
             if let Ok(file) = tokio::fs::File::open(path.clone()).await {
                 let stream = ReaderStream::new(file);
@@ -21,5 +21,6 @@
 pub mod web;
 
 pub use agama_lib::network::{
-    model::NetworkState, Action, Adapter, NetworkAdapterError, NetworkManagerAdapter, NetworkSystem,
+    model::NetworkState, Action, Adapter, KeyfileAdapter, MemoryAdapter, NetworkAdapterError,
+    NetworkManagerAdapter, NetworkSystem, Scenario,
 };
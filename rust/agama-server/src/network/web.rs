@@ -23,6 +23,7 @@
 use crate::{error::Error, web::EventsSender};
 use anyhow::Context;
 use axum::{
+    body::Bytes,
     extract::{Path, State},
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -35,9 +36,10 @@ use agama_lib::{
     error::ServiceError,
     event,
     network::{
+        connectivity::{ConnectivityChecks, ConnectivityReport, MAX_TIMEOUT},
         error::NetworkStateError,
         model::{AccessPoint, Connection, Device, GeneralState},
-        settings::NetworkConnection,
+        settings::{NetworkConnection, OvsSettings},
         types::NetworkConnectionWithState,
         Adapter, NetworkSystem, NetworkSystemClient, NetworkSystemError,
     },
@@ -59,6 +61,12 @@ pub enum NetworkError {
     CannotUpdate(String),
     #[error("Cannot apply configuration")]
     CannotApplyConfig,
+    #[error("Invalid connectivity checks: {0}")]
+    InvalidConnectivityChecks(#[from] serde_json::Error),
+    #[error("The connectivity checks timeout cannot exceed {MAX_TIMEOUT} seconds")]
+    InvalidConnectivityTimeout,
+    #[error("Connectivity checks failed (rolled back: {})", .0.rolled_back)]
+    ConnectivityChecksFailed(ConnectivityReport),
     // TODO: to be removed after adapting to the NetworkSystemServer API
     #[error("Network state error: {0}")]
     Error(#[from] NetworkStateError),
@@ -68,6 +76,14 @@ pub enum NetworkError {
 
 impl IntoResponse for NetworkError {
     fn into_response(self) -> Response {
+        if let Self::ConnectivityChecksFailed(report) = &self {
+            let body = json!({
+                "error": self.to_string(),
+                "report": report
+            });
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+        }
+
         let body = json!({
             "error": self.to_string()
         });
@@ -217,7 +233,8 @@ async fn connections(
 
     let network_connections = connections
         .iter()
-        .filter(|c| c.controller.is_none())
+        // OVS ports and interfaces are listed because they have their own settings
+        .filter(|c| c.controller.is_none() || c.is_ovs())
         .map(|c| {
             let state = c.state;
             let mut conn = NetworkConnection::try_from(c.clone()).unwrap();
@@ -227,6 +244,15 @@ async fn connections(
             if let Some(ref mut bridge) = conn.bridge {
                 bridge.ports = ports_for(connections.to_owned(), c.uuid);
             };
+            match conn.ovs {
+                Some(OvsSettings::Bridge(ref mut bridge)) => {
+                    bridge.ports = ports_for(connections.to_owned(), c.uuid);
+                }
+                Some(OvsSettings::Port(ref mut port)) => {
+                    port.ports = ports_for(connections.to_owned(), c.uuid);
+                }
+                _ => {}
+            }
             NetworkConnectionWithState {
                 connection: conn,
                 state,
@@ -265,6 +291,7 @@ async fn add_connection(
 ) -> Result<Json<Connection>, NetworkError> {
     let bond = net_conn.bond.clone();
     let bridge = net_conn.bridge.clone();
+    let ovs_ports = net_conn.ovs.as_ref().and_then(|o| o.ports()).cloned();
     let conn = Connection::try_from(net_conn)?;
     let id = conn.id.clone();
 
@@ -279,6 +306,9 @@ async fn add_connection(
             if let Some(bridge) = bridge {
                 state.network.set_ports(conn.uuid, bridge.ports).await?;
             }
+            if let Some(ports) = ovs_ports {
+                state.network.set_ports(conn.uuid, ports).await?;
+            }
             Ok(Json(conn))
         }
     }
@@ -346,6 +376,7 @@ async fn update_connection(
         .ok_or_else(|| NetworkError::UnknownConnection(id.clone()))?;
     let bond = conn.bond.clone();
     let bridge = conn.bridge.clone();
+    let ovs_ports = conn.ovs.as_ref().and_then(|o| o.ports()).cloned();
 
     let mut conn = Connection::try_from(conn)?;
     conn.uuid = orig_conn.uuid;
@@ -358,6 +389,9 @@ async fn update_connection(
     if let Some(bridge) = bridge {
         state.network.set_ports(conn.uuid, bridge.ports).await?;
     }
+    if let Some(ports) = ovs_ports {
+        state.network.set_ports(conn.uuid, ports).await?;
+    }
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -469,22 +503,44 @@ async fn persist(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Applies the network configuration.
+///
+/// If some connectivity checks are given, they run after applying the configuration. When they
+/// fail, the configuration is rolled back (if the backend supports it) and the report is included
+/// in the error.
 #[utoipa::path(
     post,
     path = "/system/apply",
     context_path = "/api/network",
+    request_body(content = Option<ConnectivityChecks>, description = "Connectivity checks to run after applying the configuration"),
     responses(
-      (status = 204, description = "Apply configuration")
+      (status = 200, description = "Configuration applied and connectivity checked", body = ConnectivityReport),
+      (status = 204, description = "Apply configuration"),
+      (status = 400, description = "The configuration could not be applied or the checks are not valid"),
+      (status = 422, description = "The connectivity checks failed")
     )
 )]
 async fn apply(
     State(state): State<NetworkServiceState>,
-) -> Result<impl IntoResponse, NetworkError> {
-    state
-        .network
-        .apply()
-        .await
-        .map_err(|_| NetworkError::CannotApplyConfig)?;
+    body: Bytes,
+) -> Result<Response, NetworkError> {
+    if body.is_empty() {
+        state
+            .network
+            .apply()
+            .await
+            .map_err(|_| NetworkError::CannotApplyConfig)?;
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
 
-    Ok(StatusCode::NO_CONTENT)
+    let checks: ConnectivityChecks = serde_json::from_slice(&body)?;
+    if checks.timeout > MAX_TIMEOUT {
+        return Err(NetworkError::InvalidConnectivityTimeout);
+    }
+
+    let report = state.network.apply_and_verify(checks).await?;
+    if !report.success {
+        return Err(NetworkError::ConnectivityChecksFailed(report));
+    }
+    Ok(Json(report).into_response())
 }
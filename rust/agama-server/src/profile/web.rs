@@ -20,9 +20,9 @@
 
 use anyhow::Context;
 
-use agama_lib::utils::Transfer;
+use agama_lib::utils::{jsonnet::JsonnetError, Transfer};
 use agama_lib::{
-    error::ServiceError,
+    error::{ProfileError, ServiceError},
     profile::{AutoyastProfileImporter, ProfileEvaluator, ProfileValidator, ValidationOutcome},
 };
 use axum::{
@@ -169,7 +169,8 @@ async fn validate(body: String) -> Result<Json<ValidationOutcome>, ProfileServic
     context_path = "/api/profile",
     responses(
         (status = 200, description = "Evaluated profile", body = String, content_type = "application/json"),
-        (status = 400, description = "Some error has occurred")
+        (status = 400, description = "The profile could not be evaluated"),
+        (status = 500, description = "Some error has occurred")
     )
 )]
 async fn evaluate(body: String) -> Result<String, ProfileServiceError> {
@@ -178,10 +179,20 @@ async fn evaluate(body: String) -> Result<String, ProfileServiceError> {
         Some(retrieved) => retrieved,
         None => profile.json.expect("Missing profile"),
     };
-    let evaluator = ProfileEvaluator {};
-    let output = evaluator
-        .evaluate_string(&profile_string)
-        .context("Could not evaluate the profile".to_string())?;
+    let evaluator = ProfileEvaluator::default();
+    let output = evaluator.evaluate_string(&profile_string).map_err(|e| {
+        // errors in the profile itself are user errors, the rest are system errors
+        let user_error = matches!(
+            e,
+            ProfileError::EvaluationError(JsonnetError::Evaluation { .. })
+        );
+        let error = anyhow::Error::new(e).context("Could not evaluate the profile");
+        if user_error {
+            error.into()
+        } else {
+            make_internal(error)
+        }
+    })?;
 
     Ok(output)
 }
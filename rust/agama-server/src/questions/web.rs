@@ -283,6 +283,14 @@ pub async fn questions_service(dbus: zbus::Connection) -> Result<Router, Service
     Ok(router)
 }
 
+/// Returns the number of questions waiting for an answer.
+///
+/// * `dbus`: D-Bus connection.
+pub async fn pending_questions(dbus: &zbus::Connection) -> Result<usize, ServiceError> {
+    let questions = QuestionsClient::new(dbus.clone()).await?;
+    Ok(questions.questions().await?.len())
+}
+
 pub async fn questions_stream(
     dbus: zbus::Connection,
 ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Error> {
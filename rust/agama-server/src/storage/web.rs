@@ -285,7 +285,7 @@ async fn reset_config(
 /// * `config_model`: storage config model.
 #[utoipa::path(
     put,
-    request_body = String,
+    request_body = Object,
     path = "/config_model",
     context_path = "/api/storage",
     operation_id = "set_storage_config_model",
@@ -28,10 +28,11 @@ use crate::{
     bootloader::web::bootloader_service,
     error::Error,
     files::web::files_service,
+    hardware::web::hardware_service,
     hostname::web::hostname_service,
     l10n::web::l10n_service,
     manager::web::{manager_service, manager_stream},
-    network::{web::network_service, NetworkManagerAdapter},
+    network::{web::network_service, KeyfileAdapter, NetworkManagerAdapter},
     profile::web::profile_service,
     questions::web::{questions_service, questions_stream},
     scripts::web::scripts_service,
@@ -43,20 +44,26 @@ use crate::{
 };
 use axum::Router;
 
+mod audit;
 mod auth;
 pub mod common;
 mod config;
 pub mod docs;
 mod event;
 mod http;
+mod metrics;
 mod service;
+mod sse;
 mod state;
 mod ws;
 
 use agama_lib::{connection, error::ServiceError, http::Event};
+pub use audit::AuditLog;
+pub use auth::{ClientCertificate, PeerCredentials, RevokedTokens};
 use common::{IssuesService, ProgressService};
 pub use config::ServiceConfig;
-pub use event::{EventsReceiver, EventsSender};
+pub use event::{EventHistory, EventsReceiver, EventsSender};
+pub use metrics::Metrics;
 pub use service::MainServiceBuilder;
 use std::path::Path;
 use tokio_stream::{StreamExt, StreamMap};
@@ -76,9 +83,17 @@ pub async fn service<P>(
 where
     P: AsRef<Path>,
 {
-    let network_adapter = NetworkManagerAdapter::from_system()
-        .await
-        .expect("Could not connect to NetworkManager to read the configuration");
+    // Without NetworkManager, fall back to handle the configuration files directly.
+    let network = match NetworkManagerAdapter::from_system().await {
+        Ok(adapter) => network_service(adapter, events.clone()).await?,
+        Err(error) => {
+            tracing::warn!(
+                "Could not connect to NetworkManager ({}), using the keyfiles instead",
+                error
+            );
+            network_service(KeyfileAdapter::from_system(), events.clone()).await?
+        }
+    };
 
     let issues = IssuesService::start(dbus.clone(), events.clone()).await;
     let progress = ProgressService::start(dbus.clone(), events.clone()).await;
@@ -106,14 +121,18 @@ where
         )
         .add_service("/iscsi", iscsi_service(dbus.clone(), issues.clone()).await?)
         .add_service("/bootloader", bootloader_service(dbus.clone()).await?)
-        .add_service("/network", network_service(network_adapter, events).await?)
+        .add_service("/network", network)
         .add_service("/questions", questions_service(dbus.clone()).await?)
         .add_service("/users", users_service(dbus.clone(), issues).await?)
         .add_service("/scripts", scripts_service().await?)
         .add_service("/files", files_service().await?)
         .add_service("/hostname", hostname_service().await?)
+        .add_service("/hardware", hardware_service().await?)
         .add_service("/profile", profile_service().await?)
         .with_config(config)
+        .with_audit_log(AuditLog::system())
+        .with_revoked_tokens(RevokedTokens::system())
+        .with_metrics(Metrics::with_dbus(dbus))
         .build();
     Ok(router)
 }
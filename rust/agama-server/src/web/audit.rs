@@ -0,0 +1,220 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Authentication audit log.
+//!
+//! It records, in an append-only file, the login attempts, the logouts, the first use of each
+//! token and every request which changes the system. Each line is a JSON object including a
+//! timestamp (in seconds since the epoch) and the kind of event.
+
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    net::IpAddr,
+    os::unix::fs::OpenOptionsExt,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use agama_lib::auth::{ClientId, TokenClaims};
+use serde::Serialize;
+
+use super::auth::now;
+
+/// Default location of the audit log.
+pub const DEFAULT_AUDIT_LOG: &str = "/var/log/agama/audit.log";
+
+/// How the client tried to authenticate.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LoginMethod {
+    Password,
+    Key,
+}
+
+/// Audited event.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum AuditEvent {
+    /// Login attempt.
+    Login {
+        method: LoginMethod,
+        source: Option<IpAddr>,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client: Option<ClientId>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// The client logged out.
+    Logout { client: ClientId, token: String },
+    /// A token was used for the first time.
+    TokenUse { client: ClientId, token: String },
+    /// A new token was created.
+    TokenCreated { client: ClientId, token: String },
+    /// A token was refreshed. The lineage identifies the first token of the chain.
+    TokenRefreshed {
+        client: ClientId,
+        token: String,
+        new_token: String,
+        lineage: String,
+    },
+    /// A token was revoked.
+    TokenRevoked { client: ClientId, token: String },
+    /// Request which changes the system.
+    Request {
+        client: ClientId,
+        method: String,
+        path: String,
+        status: u16,
+    },
+}
+
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    timestamp: i64,
+    #[serde(flatten)]
+    event: &'a AuditEvent,
+}
+
+/// Append-only audit log.
+///
+/// The default instance does not write anything, which is useful for testing.
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    file: Option<Arc<Mutex<File>>>,
+    used_tokens: Arc<Mutex<HashSet<String>>>,
+}
+
+impl AuditLog {
+    /// Opens the audit log at the given path, creating it if needed.
+    ///
+    /// * `path`: path to the audit log.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o600)
+            .open(path)?;
+
+        Ok(Self {
+            file: Some(Arc::new(Mutex::new(file))),
+            ..Default::default()
+        })
+    }
+
+    /// Opens the audit log at the default location.
+    ///
+    /// If it is not possible, the events are not recorded.
+    pub fn system() -> Self {
+        Self::open(Path::new(DEFAULT_AUDIT_LOG)).unwrap_or_else(|error| {
+            tracing::warn!("Could not open the audit log: {}", error);
+            Self::default()
+        })
+    }
+
+    /// Records an event.
+    ///
+    /// Writing errors are logged but not propagated: they should not prevent the API from
+    /// working.
+    ///
+    /// * `event`: event to record.
+    pub fn record(&self, event: AuditEvent) {
+        let Some(file) = &self.file else {
+            return;
+        };
+
+        let record = AuditRecord {
+            timestamp: now(),
+            event: &event,
+        };
+        let Ok(mut line) = serde_json::to_string(&record) else {
+            tracing::warn!("Could not serialize the audit event: {:?}", event);
+            return;
+        };
+        line.push('\n');
+
+        let mut file = file.lock().unwrap();
+        if let Err(error) = file.write_all(line.as_bytes()) {
+            tracing::warn!("Could not write to the audit log: {}", error);
+        }
+    }
+
+    /// Records the use of a token, only the first time it is seen.
+    ///
+    /// * `claims`: claims of the token.
+    pub fn token_used(&self, claims: &TokenClaims) {
+        if self.file.is_none() {
+            return;
+        }
+
+        let first_use = self.used_tokens.lock().unwrap().insert(claims.jti.clone());
+        if first_use {
+            self.record(AuditEvent::TokenUse {
+                client: claims.client_id.clone(),
+                token: claims.jti.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditEvent, AuditLog, LoginMethod};
+    use agama_lib::auth::{TokenClaims, TokenScope, DEFAULT_TOKEN_LIFETIME};
+    use std::net::{IpAddr, Ipv4Addr};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_events() {
+        let tmp_dir = TempDir::with_prefix("agama-tests-").unwrap();
+        let path = tmp_dir.path().join("agama/audit.log");
+        let audit = AuditLog::open(&path).unwrap();
+
+        audit.record(AuditEvent::Login {
+            method: LoginMethod::Password,
+            source: Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10))),
+            success: false,
+            client: None,
+            error: Some("Authentication error".to_string()),
+        });
+        let claims = TokenClaims::new(TokenScope::all(), DEFAULT_TOKEN_LIFETIME);
+        audit.token_used(&claims);
+        audit.token_used(&claims);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "login");
+        assert_eq!(lines[0]["method"], "password");
+        assert_eq!(lines[0]["source"], "192.168.1.10");
+        assert_eq!(lines[0]["success"], false);
+        assert!(lines[0]["timestamp"].is_i64());
+        assert_eq!(lines[1]["event"], "token-use");
+        assert_eq!(lines[1]["token"], claims.jti.as_str());
+    }
+}
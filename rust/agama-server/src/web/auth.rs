@@ -19,13 +19,28 @@
 // find current contact information at www.suse.com.
 
 //! Contains the code to handle access authorization.
+//!
+//! Besides the root password (through PAM), the clients can authenticate using an SSH key (see
+//! [keys]) or a TLS client certificate (see [certificate]). The processes running as root can
+//! connect through a Unix socket without any token (see [peer]). All of them result in the same
+//! kind of [TokenClaims].
+
+mod certificate;
+pub mod keys;
+mod peer;
+mod throttle;
+
+pub use certificate::ClientCertificate;
+pub use keys::{AuthorizedKeys, Challenges, PublicKey};
+pub use peer::PeerCredentials;
+pub use throttle::{LoginAttempt, LoginAttempts};
 
 use super::state::ServiceState;
-use agama_lib::auth::{AuthToken, AuthTokenError, TokenClaims};
+use agama_lib::auth::{AuthToken, AuthTokenError, TokenClaims, TokenScope, MAX_TOKEN_LIFETIME};
 use async_trait::async_trait;
 use axum::{
     extract::FromRequestParts,
-    http::{request, StatusCode},
+    http::{header, request, StatusCode},
     response::{IntoResponse, Response},
     Json, RequestPartsExt,
 };
@@ -34,7 +49,17 @@ use axum_extra::{
     TypedHeader,
 };
 use pam::PamError;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
 use thiserror::Error;
 
 /// Represents an authentication error.
@@ -49,14 +74,255 @@ pub enum AuthError {
     /// The authentication failed (most probably the password is wrong)
     #[error("Authentication via PAM failed: {0}")]
     Failed(#[from] PamError),
+    /// The token has been revoked.
+    #[error("The authentication token has been revoked")]
+    RevokedToken,
+    /// The token does not have an identifier, so it cannot be revoked (tokens created by older
+    /// versions).
+    #[error("The authentication token has no identifier")]
+    MissingTokenId,
+    /// The requested token would not have any scope.
+    #[error("The token must include at least one scope")]
+    EmptyScopes,
+    /// The token does not include the required scope.
+    #[error("The authentication token does not include the \"{0}\" scope")]
+    Forbidden(TokenScope),
+    /// The public key could not be parsed.
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+    /// The public key is not authorized.
+    #[error("The public key is not authorized")]
+    UnauthorizedKey,
+    /// The challenge is unknown or expired.
+    #[error("Unknown or expired challenge")]
+    InvalidChallenge,
+    /// The signature of the challenge is not valid.
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+    /// The signature could not be verified.
+    #[error("Could not verify the signature: {0}")]
+    KeyVerification(#[source] io::Error),
+    /// Too many failed login attempts. It contains the seconds to wait before trying again.
+    #[error("Too many failed login attempts, try again in {0} seconds")]
+    TooManyAttempts(u64),
 }
 
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
+        let status = match self {
+            Self::EmptyScopes => StatusCode::BAD_REQUEST,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::KeyVerification(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::TooManyAttempts(_) => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::UNAUTHORIZED,
+        };
         let body = json!({
             "error": self.to_string()
         });
-        (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+        if let Self::TooManyAttempts(seconds) = self {
+            return (status, [(header::RETRY_AFTER, seconds)], Json(body)).into_response();
+        }
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Default location of the list of revoked tokens.
+pub const DEFAULT_REVOKED_TOKENS: &str = "/run/agama/revoked-tokens.json";
+
+/// Identifiers of the revoked tokens.
+///
+/// Each entry keeps the token expiration time, so it can be forgotten once the token is not
+/// valid anymore. However, the client certificates and the Unix socket connections are not
+/// tokens: they can be used again after any time, so their revocation never expires. The list is
+/// written to a file, so the tokens are still revoked after restarting the server. The default
+/// instance keeps the list only in memory, which is useful for testing.
+#[derive(Clone, Default)]
+pub struct RevokedTokens {
+    path: Option<PathBuf>,
+    tokens: Arc<RwLock<RevokedList>>,
+}
+
+/// Content of the revoked tokens file.
+#[derive(Default, Serialize, Deserialize)]
+struct RevokedList {
+    /// Revoked tokens and their expiration time.
+    #[serde(default)]
+    tokens: HashMap<String, i64>,
+    /// Revoked certificates and connections (see [is_credential_id]).
+    #[serde(default)]
+    credentials: BTreeSet<String>,
+}
+
+impl RevokedTokens {
+    /// Reads the list of revoked tokens from the given path.
+    ///
+    /// If the file does not exist, it starts with an empty list.
+    ///
+    /// * `path`: path to the list of revoked tokens.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let tokens = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => RevokedList::default(),
+            Err(error) => return Err(error),
+        };
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            tokens: Arc::new(RwLock::new(tokens)),
+        })
+    }
+
+    /// Reads the list of revoked tokens from the default location.
+    ///
+    /// If it is not possible, the list is kept only in memory.
+    pub fn system() -> Self {
+        let path = Path::new(DEFAULT_REVOKED_TOKENS);
+        Self::open(path).unwrap_or_else(|error| {
+            tracing::warn!("Could not read the revoked tokens: {}", error);
+            Self {
+                path: Some(path.to_path_buf()),
+                ..Default::default()
+            }
+        })
+    }
+
+    /// Revokes a token.
+    ///
+    /// The revocation of a client certificate or a connection never expires.
+    ///
+    /// * `id`: token identifier.
+    /// * `exp`: token expiration time (seconds since the epoch).
+    pub fn revoke(&self, id: &str, exp: i64) {
+        let now = now();
+        let mut revoked = self.tokens.write().unwrap();
+        revoked.tokens.retain(|_, exp| *exp > now);
+        if is_credential_id(id) {
+            revoked.credentials.insert(id.to_string());
+        } else {
+            revoked.tokens.insert(id.to_string(), exp);
+        }
+        if let Err(error) = self.write(&revoked) {
+            tracing::warn!("Could not write the revoked tokens: {}", error);
+        }
+    }
+
+    /// Revokes a token whose expiration time is unknown.
+    ///
+    /// It is kept during the longest possible lifetime of a token.
+    ///
+    /// * `id`: token identifier.
+    pub fn revoke_unknown(&self, id: &str) {
+        self.revoke(id, now() + MAX_TOKEN_LIFETIME.as_secs() as i64);
+    }
+
+    /// Revokes a token and all the tokens of its lineage (see [TokenClaims::lineage]).
+    ///
+    /// The lineage is kept during the longest possible lifetime of a token, as the refreshed
+    /// tokens might outlive the current one. If the lineage is a client certificate or a
+    /// connection, only the given token is revoked.
+    ///
+    /// * `claims`: claims of the token to revoke.
+    pub fn revoke_lineage(&self, claims: &TokenClaims) {
+        self.revoke(&claims.jti, claims.exp);
+        if !is_credential_id(claims.lineage()) {
+            self.revoke_unknown(claims.lineage());
+        }
+    }
+
+    /// Whether the token with the given identifier has been revoked.
+    ///
+    /// * `id`: token identifier.
+    pub fn is_revoked(&self, id: &str) -> bool {
+        let revoked = self.tokens.read().unwrap();
+        revoked.tokens.contains_key(id) || revoked.credentials.contains(id)
+    }
+
+    fn write(&self, tokens: &RevokedList) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(serde_json::to_string(tokens)?.as_bytes())
+    }
+}
+
+/// Whether the identifier belongs to a client certificate or a Unix socket connection (see
+/// [ClientCertificate::token_id] and [PeerCredentials::token_id]).
+///
+/// * `id`: token identifier.
+pub(super) fn is_credential_id(id: &str) -> bool {
+    id.starts_with("certificate:") || id.starts_with("peer:")
+}
+
+/// Current time, in seconds since the epoch.
+pub(super) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+impl ServiceState {
+    /// Decodes the token and checks whether it is still valid.
+    ///
+    /// The tokens without an identifier are rejected, as they cannot be revoked. A token is not
+    /// valid either if the token it was refreshed from has been revoked.
+    ///
+    /// * `token`: token to check.
+    pub fn validate_token(&self, token: &AuthToken) -> Result<TokenClaims, AuthError> {
+        let claims = token.claims(&self.config.jwt_secret)?;
+        if claims.jti.is_empty() {
+            return Err(AuthError::MissingTokenId);
+        }
+        if self.revoked_tokens.is_revoked(&claims.jti)
+            || self.revoked_tokens.is_revoked(claims.lineage())
+        {
+            return Err(AuthError::RevokedToken);
+        }
+        Ok(claims)
+    }
+
+    /// Returns the claims for a request authenticated with a client certificate.
+    ///
+    /// * `certificate`: verified client certificate.
+    pub fn certificate_claims(
+        &self,
+        certificate: &ClientCertificate,
+    ) -> Result<TokenClaims, AuthError> {
+        let mut claims = TokenClaims::new(TokenScope::all(), self.config.token_lifetime());
+        claims.jti = certificate.token_id();
+        claims.client_id = certificate.client_id.clone();
+        if self.revoked_tokens.is_revoked(&claims.jti) {
+            return Err(AuthError::RevokedToken);
+        }
+        Ok(claims)
+    }
+
+    /// Returns the claims for a request coming through the Unix socket.
+    ///
+    /// Only the processes running as root are authenticated.
+    ///
+    /// * `peer`: credentials of the connected process.
+    pub fn peer_claims(&self, peer: &PeerCredentials) -> Result<TokenClaims, AuthError> {
+        if !peer.is_root() {
+            return Err(AuthError::MissingToken);
+        }
+
+        let mut claims = TokenClaims::new(TokenScope::all(), self.config.token_lifetime());
+        claims.jti = peer.token_id();
+        claims.client_id = peer.client_id.clone();
+        if self.revoked_tokens.is_revoked(&claims.jti) {
+            return Err(AuthError::RevokedToken);
+        }
+        Ok(claims)
     }
 }
 
@@ -72,20 +338,69 @@ impl FromRequestParts<ServiceState> for TokenClaims {
             .extract::<TypedHeader<headers::Authorization<Bearer>>>()
             .await
         {
-            Ok(TypedHeader(headers::Authorization(bearer))) => bearer.token().to_owned(),
-            Err(_) => {
-                let cookie = parts
-                    .extract::<TypedHeader<headers::Cookie>>()
-                    .await
-                    .map_err(|_| AuthError::MissingToken)?;
-                cookie
-                    .get("agamaToken")
-                    .ok_or(AuthError::MissingToken)?
-                    .to_owned()
-            }
+            Ok(TypedHeader(headers::Authorization(bearer))) => Some(bearer.token().to_owned()),
+            Err(_) => parts
+                .extract::<TypedHeader<headers::Cookie>>()
+                .await
+                .ok()
+                .and_then(|cookie| cookie.get("agamaToken").map(str::to_owned)),
         };
 
-        let token = AuthToken::new(&token);
-        Ok(token.claims(&state.config.jwt_secret)?)
+        if let Some(token) = token {
+            return state.validate_token(&AuthToken::new(&token));
+        }
+
+        if let Some(certificate) = parts.extensions.get::<ClientCertificate>() {
+            return state.certificate_claims(certificate);
+        }
+
+        match parts.extensions.get::<PeerCredentials>() {
+            Some(peer) => state.peer_claims(peer),
+            None => Err(AuthError::MissingToken),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_credential_id, now, RevokedTokens};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_revoked_tokens() {
+        let revoked = RevokedTokens::default();
+        let exp = now() + 60;
+        revoked.revoke("expired", 0);
+        revoked.revoke("valid", exp);
+        assert!(revoked.is_revoked("valid"));
+        assert!(!revoked.is_revoked("expired"));
+        assert!(!revoked.is_revoked("unknown"));
+    }
+
+    #[test]
+    fn test_revoked_tokens_persistence() {
+        let tmp_dir = TempDir::with_prefix("revoked").unwrap();
+        let path = tmp_dir.path().join("revoked-tokens.json");
+        let revoked = RevokedTokens::open(&path).unwrap();
+        revoked.revoke("valid", now() + 60);
+        revoked.revoke("certificate:00ff", 0);
+
+        let revoked = RevokedTokens::open(&path).unwrap();
+        assert!(revoked.is_revoked("valid"));
+        assert!(revoked.is_revoked("certificate:00ff"));
+        assert!(!revoked.is_revoked("unknown"));
+    }
+
+    #[test]
+    fn test_revoked_credentials() {
+        let revoked = RevokedTokens::default();
+        revoked.revoke("certificate:00ff", 0);
+        revoked.revoke_unknown("peer:3d7ac3e1-8a8a-4a3c-9f4e-2ab2f5f7b8c1");
+        // expired tokens are forgotten when revoking another one
+        revoked.revoke("valid", now() + 60);
+        assert!(revoked.is_revoked("certificate:00ff"));
+        assert!(revoked.is_revoked("peer:3d7ac3e1-8a8a-4a3c-9f4e-2ab2f5f7b8c1"));
+        assert!(is_credential_id("certificate:00ff"));
+        assert!(!is_credential_id("3d7ac3e1-8a8a-4a3c-9f4e-2ab2f5f7b8c1"));
     }
 }
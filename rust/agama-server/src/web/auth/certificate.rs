@@ -0,0 +1,83 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! TLS client certificates authentication.
+//!
+//! When the web server is started with a client CA (`--client-ca`), the clients can present a
+//! certificate during the TLS handshake. If it is signed by that CA, the request is authenticated
+//! even if it does not include a token.
+
+use agama_lib::auth::ClientId;
+use openssl::{hash::MessageDigest, nid::Nid, x509::X509Ref};
+use uuid::Uuid;
+
+/// Verified client certificate of the connection.
+///
+/// It is added as an extension to the requests.
+#[derive(Clone, Debug)]
+pub struct ClientCertificate {
+    /// Certificate common name (or the full subject if there is no common name).
+    pub subject: String,
+    /// SHA-256 fingerprint as a lowercase hexadecimal string.
+    pub fingerprint: String,
+    /// Client ID derived from the fingerprint, so it is stable across requests.
+    pub client_id: ClientId,
+}
+
+impl ClientCertificate {
+    /// Builds the client certificate from an X509 certificate.
+    ///
+    /// * `cert`: verified certificate.
+    pub fn from_x509(cert: &X509Ref) -> Result<Self, openssl::error::ErrorStack> {
+        let digest = cert.digest(MessageDigest::sha256())?;
+        let fingerprint = digest.iter().map(|b| format!("{b:02x}")).collect();
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+
+        let subject_name = cert.subject_name();
+        let subject = subject_name
+            .entries_by_nid(Nid::COMMONNAME)
+            .next()
+            .and_then(|e| e.data().as_utf8().ok())
+            .map(|cn| cn.to_string())
+            .unwrap_or_else(|| {
+                subject_name
+                    .entries()
+                    .filter_map(|e| e.data().as_utf8().ok())
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            });
+
+        Ok(Self {
+            subject,
+            fingerprint,
+            client_id: ClientId::new_from_uuid(Uuid::from_bytes(bytes)),
+        })
+    }
+
+    /// Token identifier for the certificate.
+    ///
+    /// It allows revoking the access of a given certificate. The revocation does not expire and it
+    /// also applies to the tokens obtained with the certificate.
+    pub fn token_id(&self) -> String {
+        format!("certificate:{}", self.fingerprint)
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Public key authentication.
+//!
+//! The client asks for a challenge, signs it with an SSH key (using `ssh-keygen -Y sign -n
+//! agama-auth`) and sends back the signature and the public key. The key must be listed in one of
+//! the authorized keys files (by default, `/root/.ssh/authorized_keys`, which is where
+//! `root.sshPublicKey` ends up, and `/etc/agama.d/authorized_keys`).
+//!
+//! The signature is checked with `ssh-keygen -Y verify`, so any key type supported by OpenSSH
+//! (including FIDO keys) can be used.
+//!
+//! The `from="..."` option of the authorized keys files is honored. The lines with other
+//! options that restrict the use of the key (e.g., `command` or `expiry-time`) are skipped, as
+//! they cannot be enforced. The options that only affect SSH sessions (e.g., `no-pty`) are
+//! ignored.
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::Write,
+    net::IpAddr,
+    path::PathBuf,
+    process::{Command, Stdio},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rand::distr::{Alphanumeric, SampleString};
+
+use super::AuthError;
+
+/// Namespace of the signatures (see the `-n` option of `ssh-keygen`).
+pub const SIGNATURE_NAMESPACE: &str = "agama-auth";
+/// Default authorized keys files.
+pub const DEFAULT_AUTHORIZED_KEYS: [&str; 2] =
+    ["/root/.ssh/authorized_keys", "/etc/agama.d/authorized_keys"];
+const CHALLENGE_SIZE: usize = 32;
+const CHALLENGE_LIFETIME: Duration = Duration::from_secs(60);
+/// Options of the authorized keys files which do not restrict authenticating with the key.
+const SESSION_OPTIONS: [&str; 14] = [
+    "agent-forwarding",
+    "environment",
+    "no-agent-forwarding",
+    "no-port-forwarding",
+    "no-pty",
+    "no-user-rc",
+    "no-x11-forwarding",
+    "permitlisten",
+    "permitopen",
+    "port-forwarding",
+    "pty",
+    "restrict",
+    "user-rc",
+    "x11-forwarding",
+];
+
+/// SSH public key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey {
+    /// Key type (e.g., "ssh-ed25519").
+    pub key_type: String,
+    /// Base64 encoded key.
+    pub data: String,
+}
+
+impl FromStr for PublicKey {
+    type Err = AuthError;
+
+    /// Parses a key in the OpenSSH format ("<type> <base64> [comment]").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let (Some(key_type), Some(data)) = (fields.next(), fields.next()) else {
+            return Err(AuthError::InvalidPublicKey(s.to_string()));
+        };
+        if !is_key_type(key_type) {
+            return Err(AuthError::InvalidPublicKey(s.to_string()));
+        }
+
+        Ok(Self {
+            key_type: key_type.to_string(),
+            data: data.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.key_type, self.data)
+    }
+}
+
+fn is_key_type(field: &str) -> bool {
+    ["ssh-", "ecdsa-", "sk-"]
+        .iter()
+        .any(|prefix| field.starts_with(prefix))
+}
+
+/// Entry of an authorized keys file.
+#[derive(Debug)]
+struct AuthorizedKey {
+    key: PublicKey,
+    /// Patterns of the addresses the key can be used from (`from="..."` option).
+    from: Option<Vec<String>>,
+}
+
+impl AuthorizedKey {
+    /// Parses a line of an authorized keys file ("[options] <type> <base64> [comment]").
+    ///
+    /// It returns `None` if the line is not valid or it contains unsupported restrictions.
+    fn parse(line: &str) -> Option<Self> {
+        let first = line.split_whitespace().next()?;
+        if is_key_type(first) {
+            return Some(Self {
+                key: line.parse().ok()?,
+                from: None,
+            });
+        }
+
+        let (options, key) = split_options(line);
+        let mut from = None;
+        for option in split_unquoted(options, ',') {
+            let (name, value) = option.split_once('=').unwrap_or((option, ""));
+            let name = name.to_lowercase();
+            if name == "from" {
+                let patterns = value.trim_matches('"').split(',').map(str::to_string);
+                from = Some(patterns.collect());
+            } else if !SESSION_OPTIONS.contains(&name.as_str()) {
+                tracing::warn!("Skipping an authorized key with the unsupported option {name}");
+                return None;
+            }
+        }
+
+        Some(Self {
+            key: key.parse().ok()?,
+            from,
+        })
+    }
+
+    /// Whether the key can be used from the given address.
+    ///
+    /// * `source`: client address (`None` if unknown).
+    fn allows_source(&self, source: Option<IpAddr>) -> bool {
+        let Some(patterns) = &self.from else {
+            return true;
+        };
+        let Some(source) = source else {
+            return false;
+        };
+
+        let mut allowed = false;
+        for pattern in patterns {
+            match pattern.strip_prefix('!') {
+                Some(negated) if address_matches(negated, source) => return false,
+                Some(_) => {}
+                None => allowed = allowed || address_matches(pattern, source),
+            }
+        }
+        allowed
+    }
+}
+
+/// Splits an authorized keys line into the options and the key.
+fn split_options(line: &str) -> (&str, &str) {
+    let mut quoted = false;
+    for (index, char) in line.char_indices() {
+        match char {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                return (&line[..index], line[index..].trim_start());
+            }
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+/// Splits a string by the given separator, ignoring the quoted ones.
+fn split_unquoted(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, char) in s.char_indices() {
+        if char == '"' {
+            quoted = !quoted;
+        } else if char == separator && !quoted {
+            parts.push(&s[start..index]);
+            start = index + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Whether the address matches a pattern of the `from` option.
+///
+/// The pattern can be an address with wildcards (`*` and `?`) or a network in the CIDR
+/// notation. Host names are not resolved, so they never match.
+fn address_matches(pattern: &str, address: IpAddr) -> bool {
+    let Some((network, prefix)) = pattern.split_once('/') else {
+        return wildcard_matches(pattern.as_bytes(), address.to_string().as_bytes());
+    };
+    let (Ok(network), Ok(prefix)) = (network.parse::<IpAddr>(), prefix.parse::<u32>()) else {
+        return false;
+    };
+    match (network, address) {
+        (IpAddr::V4(network), IpAddr::V4(address)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(network) & mask == u32::from(address) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(address)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(network) & mask == u128::from(address) & mask
+        }
+        _ => false,
+    }
+}
+
+fn wildcard_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            wildcard_matches(&pattern[1..], text)
+                || (!text.is_empty() && wildcard_matches(pattern, &text[1..]))
+        }
+        (Some(b'?'), Some(_)) => wildcard_matches(&pattern[1..], &text[1..]),
+        (Some(p), Some(t)) if p == t => wildcard_matches(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+/// Keys allowed to authenticate.
+#[derive(Debug, Default)]
+pub struct AuthorizedKeys(Vec<AuthorizedKey>);
+
+impl AuthorizedKeys {
+    /// Reads the keys from the given files. Missing files are ignored.
+    ///
+    /// * `paths`: authorized keys files.
+    pub fn read(paths: &[PathBuf]) -> Self {
+        let keys = paths
+            .iter()
+            .filter_map(|p| fs::read_to_string(p).ok())
+            .flat_map(|content| Self::parse(&content).0)
+            .collect();
+        Self(keys)
+    }
+
+    /// Parses the content of an authorized keys file.
+    ///
+    /// * `content`: file content.
+    pub fn parse(content: &str) -> Self {
+        let keys = content
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(AuthorizedKey::parse)
+            .collect();
+        Self(keys)
+    }
+
+    /// Whether the given key is authorized to authenticate from the given address.
+    ///
+    /// * `key`: key to check.
+    /// * `source`: client address (`None` if unknown).
+    pub fn allows(&self, key: &PublicKey, source: Option<IpAddr>) -> bool {
+        self.0
+            .iter()
+            .any(|k| &k.key == key && k.allows_source(source))
+    }
+}
+
+/// Pending challenges.
+///
+/// Each challenge can be used only once and it expires after one minute.
+#[derive(Clone, Default)]
+pub struct Challenges(Arc<Mutex<HashMap<String, Instant>>>);
+
+impl Challenges {
+    /// Generates a new challenge.
+    pub fn generate(&self) -> String {
+        let challenge = Alphanumeric.sample_string(&mut rand::rng(), CHALLENGE_SIZE);
+        let now = Instant::now();
+        let mut challenges = self.0.lock().unwrap();
+        challenges.retain(|_, expires| *expires > now);
+        challenges.insert(challenge.clone(), now + CHALLENGE_LIFETIME);
+        challenge
+    }
+
+    /// Consumes a challenge, returning whether it was valid.
+    ///
+    /// * `challenge`: challenge to consume.
+    pub fn take(&self, challenge: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .remove(challenge)
+            .is_some_and(|expires| expires > Instant::now())
+    }
+}
+
+/// Checks that the signature of the message was made with the given key.
+///
+/// * `key`: public key.
+/// * `message`: signed message.
+/// * `signature`: signature in the SSH format ("-----BEGIN SSH SIGNATURE-----...").
+pub fn verify_signature(key: &PublicKey, message: &[u8], signature: &str) -> Result<(), AuthError> {
+    let workdir = tempfile::tempdir().map_err(AuthError::KeyVerification)?;
+    let allowed_signers = workdir.path().join("allowed_signers");
+    fs::write(&allowed_signers, format!("agama {key}\n")).map_err(AuthError::KeyVerification)?;
+    let signature_file = workdir.path().join("signature");
+    fs::write(&signature_file, signature).map_err(AuthError::KeyVerification)?;
+
+    let mut child = Command::new("ssh-keygen")
+        .args([
+            "-Y",
+            "verify",
+            "-I",
+            "agama",
+            "-n",
+            SIGNATURE_NAMESPACE,
+            "-f",
+        ])
+        .arg(&allowed_signers)
+        .arg("-s")
+        .arg(&signature_file)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(AuthError::KeyVerification)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(message)
+            .map_err(AuthError::KeyVerification)?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(AuthError::KeyVerification)?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(AuthError::InvalidSignature(message));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthorizedKeys, Challenges, PublicKey};
+
+    const KEY: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl";
+
+    #[test]
+    fn test_parse_authorized_keys() {
+        let content = format!(
+            "# lab keys\n\
+             ssh-ed25519 {KEY} jane@lab\n\
+             no-pty,no-port-forwarding ssh-rsa AAAAB3NzaC1yc2E john@lab\n\
+             command=\"/usr/bin/backup\" ssh-rsa AAAAB3NzaC1yc2F backup@lab\n\
+             invalid line\n"
+        );
+        let keys = AuthorizedKeys::parse(&content);
+
+        let key: PublicKey = format!("ssh-ed25519 {KEY}").parse().unwrap();
+        assert!(keys.allows(&key, None));
+        let key: PublicKey = "ssh-rsa AAAAB3NzaC1yc2E other".parse().unwrap();
+        assert!(keys.allows(&key, None));
+        // unsupported restrictions
+        let key: PublicKey = "ssh-rsa AAAAB3NzaC1yc2F".parse().unwrap();
+        assert!(!keys.allows(&key, None));
+        let key: PublicKey = "ssh-rsa AAAAB3NzaC1yc2G".parse().unwrap();
+        assert!(!keys.allows(&key, None));
+    }
+
+    #[test]
+    fn test_authorized_keys_from() {
+        let content = format!(
+            "from=\"10.0.0.0/8,!10.0.0.1,192.168.1.?,fd00::/8\",no-pty ssh-ed25519 {KEY} jane@lab\n"
+        );
+        let keys = AuthorizedKeys::parse(&content);
+        let key: PublicKey = format!("ssh-ed25519 {KEY}").parse().unwrap();
+
+        let allows = |source: &str| keys.allows(&key, Some(source.parse().unwrap()));
+        assert!(allows("10.1.2.3"));
+        assert!(allows("192.168.1.5"));
+        assert!(allows("fd12::1"));
+        assert!(!allows("10.0.0.1"));
+        assert!(!allows("192.168.1.50"));
+        assert!(!allows("172.16.0.1"));
+        assert!(!keys.allows(&key, None));
+    }
+
+    #[test]
+    fn test_challenges() {
+        let challenges = Challenges::default();
+        let challenge = challenges.generate();
+        assert!(challenges.take(&challenge));
+        assert!(!challenges.take(&challenge));
+        assert!(!challenges.take("unknown"));
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Unix socket peer credentials authentication.
+//!
+//! When the web server listens on a Unix domain socket (`--socket`), it reads the credentials of
+//! the connecting process (`SO_PEERCRED`). The requests coming from a process running as root are
+//! authenticated even if they do not include a token.
+
+use agama_lib::auth::ClientId;
+
+/// Credentials of the process connected through the Unix socket.
+///
+/// It is added as an extension to the requests.
+#[derive(Clone, Debug)]
+pub struct PeerCredentials {
+    /// User ID of the process.
+    pub uid: u32,
+    /// Process ID, if known.
+    pub pid: Option<i32>,
+    /// Client ID of the connection, so it is stable across requests.
+    pub client_id: ClientId,
+}
+
+impl PeerCredentials {
+    /// Builds the credentials for a new connection.
+    ///
+    /// * `uid`: user ID of the process.
+    /// * `pid`: process ID, if known.
+    pub fn new(uid: u32, pid: Option<i32>) -> Self {
+        Self {
+            uid,
+            pid,
+            client_id: ClientId::new(),
+        }
+    }
+
+    /// Whether the process runs as root.
+    pub fn is_root(&self) -> bool {
+        self.uid == 0
+    }
+
+    /// Token identifier for the connection.
+    ///
+    /// It allows revoking the access of a given connection.
+    pub fn token_id(&self) -> String {
+        format!("peer:{}", self.client_id)
+    }
+}
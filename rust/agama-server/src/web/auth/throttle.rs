@@ -0,0 +1,226 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Throttling of the failed login attempts.
+//!
+//! After a few failed attempts from the same address, the client must wait before trying again.
+//! The delay doubles on each failure and, after too many failures, the address is locked out for
+//! a while. A successful login resets the counter. The attempts in progress count as failures,
+//! so sending many requests in parallel does not bypass the delay.
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::AuthError;
+
+/// Failed attempts allowed before applying any delay.
+const FREE_ATTEMPTS: u32 = 3;
+/// Failed attempts that cause a lockout.
+const MAX_ATTEMPTS: u32 = 10;
+/// How long an address is locked out. It is also the time after which the failures are forgotten.
+const LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Copy, Debug)]
+struct FailedAttempts {
+    count: u32,
+    /// Attempts in progress. They count as failures until they finish.
+    pending: u32,
+    last: Instant,
+}
+
+impl FailedAttempts {
+    fn new(now: Instant) -> Self {
+        Self {
+            count: 0,
+            pending: 0,
+            last: now,
+        }
+    }
+
+    /// Time to wait since the last attempt before trying again.
+    fn delay(&self) -> Duration {
+        let count = self.count + self.pending;
+        if count < FREE_ATTEMPTS {
+            Duration::ZERO
+        } else if count >= MAX_ATTEMPTS {
+            LOCKOUT
+        } else {
+            Duration::from_secs(1 << (count - FREE_ATTEMPTS))
+        }
+    }
+
+    /// Remaining time until the next attempt is allowed.
+    fn remaining(&self, now: Instant) -> Duration {
+        self.delay().saturating_sub(now.duration_since(self.last))
+    }
+}
+
+/// Login attempts by source address.
+///
+/// The requests without a known source address (e.g., the ones received through a Unix socket)
+/// share the same counter.
+#[derive(Clone, Default)]
+pub struct LoginAttempts(Arc<Mutex<HashMap<Option<IpAddr>, FailedAttempts>>>);
+
+impl LoginAttempts {
+    /// Checks whether a login attempt from the given address is allowed and, if so, reserves it.
+    ///
+    /// The reserved attempt counts as a failure until it is resolved through
+    /// [LoginAttempt::succeeded], so parallel requests cannot bypass the throttling. Dropping it
+    /// without resolving it registers a failure.
+    ///
+    /// * `source`: source address.
+    pub fn check(&self, source: Option<IpAddr>) -> Result<LoginAttempt, AuthError> {
+        let now = Instant::now();
+        let mut attempts = self.0.lock().unwrap();
+        attempts.retain(|_, f| f.pending > 0 || now.duration_since(f.last) < LOCKOUT);
+        let failed = attempts.entry(source).or_insert(FailedAttempts::new(now));
+
+        let remaining = failed.remaining(now);
+        if !remaining.is_zero() {
+            return Err(AuthError::TooManyAttempts(remaining.as_secs().max(1)));
+        }
+
+        failed.pending += 1;
+        failed.last = now;
+        Ok(LoginAttempt {
+            attempts: self.clone(),
+            source,
+            resolved: false,
+        })
+    }
+
+    /// Resolves a pending attempt.
+    ///
+    /// * `source`: source address.
+    /// * `success`: whether the attempt succeeded.
+    fn resolve(&self, source: Option<IpAddr>, success: bool) {
+        let now = Instant::now();
+        let mut attempts = self.0.lock().unwrap();
+        let failed = attempts.entry(source).or_insert(FailedAttempts::new(now));
+        failed.pending = failed.pending.saturating_sub(1);
+        if success {
+            failed.count = 0;
+            if failed.pending == 0 {
+                attempts.remove(&source);
+            }
+            return;
+        }
+
+        failed.count += 1;
+        failed.last = now;
+        if failed.count == MAX_ATTEMPTS {
+            tracing::warn!("Too many failed login attempts from {:?}", source);
+        }
+    }
+}
+
+/// Login attempt in progress (see [LoginAttempts::check]).
+pub struct LoginAttempt {
+    attempts: LoginAttempts,
+    source: Option<IpAddr>,
+    resolved: bool,
+}
+
+impl LoginAttempt {
+    /// Registers the attempt as successful, forgetting the previous failures.
+    pub fn succeeded(mut self) {
+        self.resolved = true;
+        self.attempts.resolve(self.source, true);
+    }
+
+    /// Registers the attempt as failed.
+    pub fn failed(mut self) {
+        self.resolved = true;
+        self.attempts.resolve(self.source, false);
+    }
+}
+
+impl Drop for LoginAttempt {
+    fn drop(&mut self) {
+        if !self.resolved {
+            self.attempts.resolve(self.source, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FailedAttempts, LoginAttempts, LOCKOUT};
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn test_delay() {
+        let now = Instant::now();
+        let delays: Vec<_> = (1..=10)
+            .map(|count| {
+                FailedAttempts {
+                    count,
+                    pending: 0,
+                    last: now,
+                }
+                .delay()
+            })
+            .collect();
+        assert_eq!(delays[1], Duration::ZERO);
+        assert_eq!(delays[2], Duration::from_secs(1));
+        assert_eq!(delays[3], Duration::from_secs(2));
+        assert_eq!(delays[8], Duration::from_secs(64));
+        assert_eq!(delays[9], LOCKOUT);
+    }
+
+    #[test]
+    fn test_login_attempts() {
+        let attempts = LoginAttempts::default();
+        let source = Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)));
+        let other = Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 11)));
+
+        for _ in 0..2 {
+            attempts.check(source).unwrap().failed();
+        }
+        attempts.check(source).unwrap().succeeded();
+        for _ in 0..3 {
+            attempts.check(source).unwrap().failed();
+        }
+        assert!(attempts.check(source).is_err());
+        attempts.check(other).unwrap().succeeded();
+    }
+
+    #[test]
+    fn test_parallel_login_attempts() {
+        let attempts = LoginAttempts::default();
+        let source = Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)));
+
+        // the pending attempts count as failures
+        let pending: Vec<_> = (0..3).map(|_| attempts.check(source).unwrap()).collect();
+        assert!(attempts.check(source).is_err());
+
+        // dropping an unresolved attempt registers a failure
+        drop(pending);
+        assert!(attempts.check(source).is_err());
+    }
+}
@@ -30,6 +30,10 @@
 //! All the settings are merged into a single configuration. The values in the latter locations
 //! take precedence.
 
+use std::{path::PathBuf, time::Duration};
+
+use super::auth::keys::DEFAULT_AUTHORIZED_KEYS;
+use agama_lib::auth::DEFAULT_TOKEN_LIFETIME;
 use config::{Config, ConfigError, File};
 use rand::distr::{Alphanumeric, SampleString};
 use serde::Deserialize;
@@ -39,6 +43,20 @@ use serde::Deserialize;
 pub struct ServiceConfig {
     /// Key to sign the JSON Web Tokens.
     pub jwt_secret: String,
+    /// Lifetime, in seconds, of the tokens obtained through the login process.
+    #[serde(default = "default_token_lifetime")]
+    pub token_lifetime: u64,
+    /// Files containing the SSH keys which are allowed to authenticate.
+    #[serde(default = "default_authorized_keys")]
+    pub authorized_keys: Vec<PathBuf>,
+}
+
+fn default_token_lifetime() -> u64 {
+    DEFAULT_TOKEN_LIFETIME.as_secs()
+}
+
+fn default_authorized_keys() -> Vec<PathBuf> {
+    DEFAULT_AUTHORIZED_KEYS.iter().map(PathBuf::from).collect()
 }
 
 impl ServiceConfig {
@@ -54,12 +72,19 @@ impl ServiceConfig {
             .build()?;
         config.try_deserialize()
     }
+
+    /// Lifetime of the tokens obtained through the login process.
+    pub fn token_lifetime(&self) -> Duration {
+        Duration::from_secs(self.token_lifetime)
+    }
 }
 
 impl Default for ServiceConfig {
     fn default() -> Self {
         Self {
             jwt_secret: "".to_string(),
+            token_lifetime: default_token_lifetime(),
+            authorized_keys: default_authorized_keys(),
         }
     }
 }
@@ -20,6 +20,8 @@
 
 use utoipa::openapi::{Components, Info, InfoBuilder, OpenApi, OpenApiBuilder, Paths};
 
+mod hardware;
+pub use hardware::HardwareApiDocBuilder;
 mod hostname;
 pub use hostname::HostnameApiDocBuilder;
 mod network;
@@ -46,6 +48,36 @@ mod scripts;
 pub use scripts::ScriptsApiDocBuilder;
 pub mod common;
 
+/// Builders of the specifications of all the services, along with the name of each service.
+pub const API_DOC_BUILDERS: [(&str, &dyn ApiDocBuilder); 13] = [
+    ("bootloader", &BootloaderApiDocBuilder),
+    ("hardware", &HardwareApiDocBuilder),
+    ("hostname", &HostnameApiDocBuilder),
+    ("l10n", &L10nApiDocBuilder),
+    ("manager", &ManagerApiDocBuilder),
+    ("misc", &MiscApiDocBuilder),
+    ("network", &NetworkApiDocBuilder),
+    ("profile", &ProfileApiDocBuilder),
+    ("questions", &QuestionsApiDocBuilder),
+    ("scripts", &ScriptsApiDocBuilder),
+    ("software", &SoftwareApiDocBuilder),
+    ("storage", &StorageApiDocBuilder),
+    ("users", &UsersApiDocBuilder),
+];
+
+/// Builds the specification of the whole HTTP API, merging the ones of all the services.
+pub fn full_api() -> OpenApi {
+    let info = InfoBuilder::new()
+        .title("Agama HTTP API")
+        .version("0.1.0")
+        .build();
+    let mut api = OpenApiBuilder::new().info(info).build();
+    for (_, builder) in API_DOC_BUILDERS {
+        api.merge(builder.build());
+    }
+    api
+}
+
 pub trait ApiDocBuilder {
     fn title(&self) -> String {
         "Agama HTTP API".to_string()
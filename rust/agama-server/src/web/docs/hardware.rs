@@ -0,0 +1,51 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+use utoipa::openapi::{Components, ComponentsBuilder, Paths, PathsBuilder};
+
+use super::ApiDocBuilder;
+
+pub struct HardwareApiDocBuilder;
+
+impl ApiDocBuilder for HardwareApiDocBuilder {
+    fn title(&self) -> String {
+        "Hardware HTTP API".to_string()
+    }
+
+    fn paths(&self) -> Paths {
+        PathsBuilder::new()
+            .path_from::<crate::hardware::web::__path_get_hardware>()
+            .path_from::<crate::hardware::web::__path_get_lshw>()
+            .build()
+    }
+
+    fn components(&self) -> Components {
+        ComponentsBuilder::new()
+            .schema_from::<agama_lib::hardware::model::HardwareInfo>()
+            .schema_from::<agama_lib::hardware::model::CpuInfo>()
+            .schema_from::<agama_lib::hardware::model::MemoryInfo>()
+            .schema_from::<agama_lib::hardware::model::DiskInfo>()
+            .schema_from::<agama_lib::hardware::model::DiskFileSystem>()
+            .schema_from::<agama_lib::hardware::model::NicInfo>()
+            .schema_from::<agama_lib::hardware::model::FirmwareType>()
+            .schema_from::<agama_lib::hardware::model::DmiInfo>()
+            .build()
+    }
+}
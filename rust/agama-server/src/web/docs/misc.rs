@@ -32,12 +32,23 @@ impl ApiDocBuilder for MiscApiDocBuilder {
     fn paths(&self) -> Paths {
         PathsBuilder::new()
             .path_from::<crate::web::http::__path_ping>()
+            .path_from::<crate::web::http::__path_openapi>()
+            .path_from::<crate::web::http::__path_create_token>()
+            .path_from::<crate::web::http::__path_revoke_token>()
+            .path_from::<crate::web::http::__path_refresh>()
+            .path_from::<crate::web::http::__path_challenge>()
+            .path_from::<crate::web::http::__path_login_with_key>()
             .build()
     }
 
     fn components(&self) -> Components {
         ComponentsBuilder::new()
             .schema_from::<crate::web::http::PingResponse>()
+            .schema_from::<crate::web::http::ChallengeResponse>()
+            .schema_from::<crate::web::http::KeyLoginRequest>()
+            .schema_from::<agama_lib::auth::CreateTokenRequest>()
+            .schema_from::<agama_lib::auth::CreateTokenResponse>()
+            .schema_from::<agama_lib::auth::TokenScope>()
             .build()
     }
 }
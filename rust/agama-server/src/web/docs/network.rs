@@ -50,13 +50,26 @@ impl ApiDocBuilder for NetworkApiDocBuilder {
 
     fn components(&self) -> Components {
         ComponentsBuilder::new()
+            .schema_from::<agama_lib::network::connectivity::ConnectivityCheck>()
+            .schema_from::<agama_lib::network::connectivity::ConnectivityCheckKind>()
+            .schema_from::<agama_lib::network::connectivity::ConnectivityChecks>()
+            .schema_from::<agama_lib::network::connectivity::ConnectivityReport>()
             .schema_from::<agama_lib::network::settings::BondSettings>()
             .schema_from::<agama_lib::network::settings::BridgeSettings>()
+            .schema_from::<agama_lib::network::settings::Dhcp4ClientSettings>()
+            .schema_from::<agama_lib::network::settings::Dhcp6ClientSettings>()
+            .schema_from::<agama_lib::network::settings::DummySettings>()
             .schema_from::<agama_lib::network::settings::IEEE8021XSettings>()
+            .schema_from::<agama_lib::network::settings::InfinibandSettings>()
             .schema_from::<agama_lib::network::settings::MatchSettings>()
             .schema_from::<agama_lib::network::settings::NetworkConnection>()
             .schema_from::<agama_lib::network::settings::NetworkSettings>()
             .schema_from::<agama_lib::network::settings::NetworkSettings>()
+            .schema_from::<agama_lib::network::settings::OvsBridgeSettings>()
+            .schema_from::<agama_lib::network::settings::OvsInterfaceSettings>()
+            .schema_from::<agama_lib::network::settings::OvsPortSettings>()
+            .schema_from::<agama_lib::network::settings::OvsSettings>()
+            .schema_from::<agama_lib::network::settings::TunSettings>()
             .schema_from::<agama_lib::network::settings::VlanSettings>()
             .schema_from::<agama_lib::network::settings::WirelessSettings>()
             .schema_from::<agama_lib::network::types::BondMode>()
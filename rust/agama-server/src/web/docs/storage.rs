@@ -36,10 +36,18 @@ impl ApiDocBuilder for StorageApiDocBuilder {
         PathsBuilder::new()
             .path_from::<crate::storage::web::__path_actions>()
             .path_from::<crate::storage::web::__path_devices_dirty>()
+            .path_from::<crate::storage::web::__path_get_config>()
+            .path_from::<crate::storage::web::__path_get_config_model>()
             .path_from::<crate::storage::web::__path_get_proposal_settings>()
             .path_from::<crate::storage::web::__path_probe>()
             .path_from::<crate::storage::web::__path_product_params>()
+            .path_from::<crate::storage::web::__path_reactivate>()
+            .path_from::<crate::storage::web::__path_reprobe>()
+            .path_from::<crate::storage::web::__path_reset_config>()
+            .path_from::<crate::storage::web::__path_set_config>()
+            .path_from::<crate::storage::web::__path_set_config_model>()
             .path_from::<crate::storage::web::__path_set_proposal_settings>()
+            .path_from::<crate::storage::web::__path_solve_config_model>()
             .path_from::<crate::storage::web::__path_staging_devices>()
             .path_from::<crate::storage::web::__path_system_devices>()
             .path_from::<crate::storage::web::__path_available_drives>()
@@ -116,6 +124,7 @@ impl ApiDocBuilder for StorageApiDocBuilder {
             .schema_from::<agama_lib::storage::model::VolumeOutline>()
             .schema_from::<agama_lib::storage::model::VolumeTarget>()
             .schema_from::<agama_lib::storage::model::dasd::DASDDevice>()
+            .schema_from::<agama_lib::storage::StorageSettings>()
             .schema_from::<agama_lib::storage::settings::dasd::DASDConfig>()
             .schema_from::<agama_lib::storage::settings::dasd::DASDDeviceConfig>()
             .schema_from::<agama_lib::storage::settings::dasd::DASDDeviceState>()
@@ -18,8 +18,337 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use agama_lib::http::Event;
-use tokio::sync::broadcast::{Receiver, Sender};
+//! Events channel and history.
+//!
+//! The services send their events through an [EventsSender]. The [EventHistory] reads them,
+//! assigns them a sequence number and keeps the latest ones, so the clients can resume their
+//! subscriptions after reconnecting.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use agama_lib::{event, http::Event};
+use tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender};
 
 pub type EventsSender = Sender<Event>;
 pub type EventsReceiver = Receiver<Event>;
+
+/// Default number of events kept in the history.
+pub const DEFAULT_HISTORY_SIZE: usize = 1024;
+/// Capacity of the channel used to send the sequenced events to the subscribers.
+///
+/// A subscriber lagging behind can resume from the history, so it does not need to be as large
+/// as the history.
+const SUBSCRIBERS_CHANNEL_SIZE: usize = 128;
+
+/// Events which a subscriber should process.
+pub struct EventSubscription {
+    /// Already recorded events to replay before processing the new ones. It might start with an
+    /// [agama_lib::http::EventPayload::EventsGap] event if some events are missing.
+    pub replay: Vec<Event>,
+    /// Channel to receive the new events.
+    pub receiver: EventsReceiver,
+    /// Sequence number of the last recorded event when subscribing.
+    pub last_seq: u64,
+}
+
+struct HistoryState {
+    next_seq: u64,
+    events: VecDeque<Event>,
+    capacity: usize,
+    sender: EventsSender,
+}
+
+/// Bounded history of events.
+///
+/// Each recorded event gets a monotonically increasing sequence number (starting at 1). Only the
+/// latest events are kept.
+#[derive(Clone)]
+pub struct EventHistory(Arc<Mutex<HistoryState>>);
+
+impl EventHistory {
+    /// Creates an empty history.
+    ///
+    /// * `capacity`: maximum number of events to keep.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(SUBSCRIBERS_CHANNEL_SIZE);
+        let state = HistoryState {
+            next_seq: 1,
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            sender,
+        };
+        Self(Arc::new(Mutex::new(state)))
+    }
+
+    /// Creates a history which records the events sent through the given channel.
+    ///
+    /// It spawns a task which runs until the channel is closed.
+    ///
+    /// * `events`: channel the services send their events to.
+    pub fn start(events: &EventsSender) -> Self {
+        let history = Self::new(DEFAULT_HISTORY_SIZE);
+        let mut receiver = events.subscribe();
+        let recorder = history.clone();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        recorder.push(event);
+                    }
+                    Err(RecvError::Lagged(count)) => {
+                        tracing::warn!("The events history missed {count} events");
+                        recorder.skip(count);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        history
+    }
+
+    /// Records an event, assigning it the next sequence number, and sends it to the subscribers.
+    ///
+    /// It returns the recorded event.
+    ///
+    /// * `event`: event to record.
+    pub fn push(&self, mut event: Event) -> Event {
+        let mut state = self.0.lock().unwrap();
+        event.seq = Some(state.next_seq);
+        state.next_seq += 1;
+        if state.events.len() == state.capacity {
+            state.events.pop_front();
+        }
+        state.events.push_back(event.clone());
+        _ = state.sender.send(event.clone());
+        event
+    }
+
+    /// Skips the sequence numbers of the events which could not be recorded and records an
+    /// [agama_lib::http::EventPayload::EventsGap] event, so the subscribers know that they missed
+    /// some events.
+    ///
+    /// * `count`: number of missed events.
+    pub fn skip(&self, count: u64) {
+        let since = {
+            let mut state = self.0.lock().unwrap();
+            let since = state.next_seq - 1;
+            state.next_seq += count;
+            since
+        };
+        self.push(event!(EventsGap {
+            since,
+            oldest: since + count + 1
+        }));
+    }
+
+    /// Subscribes to the events.
+    ///
+    /// * `since`: sequence number of the last event known by the subscriber. The later events
+    ///   are replayed. If it is `None`, only the new events are received.
+    pub fn subscribe(&self, since: Option<u64>) -> EventSubscription {
+        // holding the lock while subscribing guarantees that no event is lost or duplicated
+        // between the replayed and the received ones
+        let state = self.0.lock().unwrap();
+        let last_seq = state.next_seq - 1;
+        let receiver = state.sender.subscribe();
+
+        let Some(since) = since else {
+            return EventSubscription {
+                replay: vec![],
+                receiver,
+                last_seq,
+            };
+        };
+
+        let oldest = state
+            .events
+            .front()
+            .and_then(|e| e.seq)
+            .unwrap_or(state.next_seq);
+        let mut replay = vec![];
+        // a sequence number from the future means that the server was restarted
+        if since + 1 < oldest || since > last_seq {
+            replay.push(event!(EventsGap { since, oldest }));
+        }
+
+        let since = if since > last_seq { 0 } else { since };
+        replay.extend(
+            state
+                .events
+                .iter()
+                .filter(|e| e.seq.is_some_and(|seq| seq > since))
+                .cloned(),
+        );
+
+        EventSubscription {
+            replay,
+            receiver,
+            last_seq,
+        }
+    }
+}
+
+/// Subscription which resumes from the history when the subscriber lags behind.
+///
+/// It returns the replayed events first and, if the channel drops some events because the
+/// subscriber is too slow, it subscribes again from the last received event.
+pub struct ResumableSubscription {
+    history: EventHistory,
+    receiver: EventsReceiver,
+    pending: VecDeque<Event>,
+    last_seq: u64,
+}
+
+impl ResumableSubscription {
+    /// Subscribes to the events of the given history.
+    ///
+    /// * `history`: events history.
+    /// * `since`: sequence number of the last event known by the subscriber (see
+    ///   [EventHistory::subscribe]).
+    pub fn new(history: EventHistory, since: Option<u64>) -> Self {
+        let subscription = history.subscribe(since);
+        Self {
+            history,
+            receiver: subscription.receiver,
+            pending: subscription.replay.into(),
+            last_seq: subscription.last_seq,
+        }
+    }
+
+    /// Returns the next event, or `None` if the channel is closed.
+    ///
+    /// It is cancel safe, so it can be used in a `tokio::select!` without losing events.
+    pub async fn recv(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+
+            match self.receiver.recv().await {
+                Ok(event) => {
+                    self.last_seq = event.seq.unwrap_or(self.last_seq);
+                    return Some(event);
+                }
+                Err(RecvError::Lagged(count)) => {
+                    tracing::info!("Subscriber lagged behind {count} events, resuming");
+                    let subscription = self.history.subscribe(Some(self.last_seq));
+                    self.receiver = subscription.receiver;
+                    self.pending = subscription.replay.into();
+                    self.last_seq = subscription.last_seq;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventHistory, ResumableSubscription, SUBSCRIBERS_CHANNEL_SIZE};
+    use agama_lib::{
+        event,
+        http::{Event, EventPayload},
+    };
+
+    fn seqs(events: &[Event]) -> Vec<Option<u64>> {
+        events.iter().map(|e| e.seq).collect()
+    }
+
+    #[test]
+    fn test_replay_events() {
+        let history = EventHistory::new(3);
+        for _ in 0..2 {
+            history.push(event!(QuestionsChanged));
+        }
+
+        let subscription = history.subscribe(None);
+        assert!(subscription.replay.is_empty());
+        assert_eq!(subscription.last_seq, 2);
+
+        let subscription = history.subscribe(Some(1));
+        assert_eq!(seqs(&subscription.replay), vec![Some(2)]);
+
+        let subscription = history.subscribe(Some(0));
+        assert_eq!(seqs(&subscription.replay), vec![Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_gap_event() {
+        let history = EventHistory::new(3);
+        for _ in 0..5 {
+            history.push(event!(QuestionsChanged));
+        }
+
+        let subscription = history.subscribe(Some(1));
+        let first = &subscription.replay[0];
+        assert!(matches!(
+            first.payload,
+            EventPayload::EventsGap {
+                since: 1,
+                oldest: 3
+            }
+        ));
+        assert_eq!(
+            seqs(&subscription.replay[1..]),
+            vec![Some(3), Some(4), Some(5)]
+        );
+
+        // unknown sequence number (e.g., the server was restarted)
+        let subscription = history.subscribe(Some(10));
+        assert!(matches!(
+            subscription.replay[0].payload,
+            EventPayload::EventsGap { since: 10, .. }
+        ));
+        assert_eq!(subscription.replay.len(), 4);
+    }
+
+    #[test]
+    fn test_skip_events() {
+        let history = EventHistory::new(5);
+        for _ in 0..2 {
+            history.push(event!(QuestionsChanged));
+        }
+        history.skip(3);
+        history.push(event!(QuestionsChanged));
+
+        let subscription = history.subscribe(Some(1));
+        assert_eq!(seqs(&subscription.replay), vec![Some(2), Some(6), Some(7)]);
+        assert!(matches!(
+            subscription.replay[1].payload,
+            EventPayload::EventsGap {
+                since: 2,
+                oldest: 6
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_receive_new_events() {
+        let history = EventHistory::new(3);
+        let mut subscription = history.subscribe(None);
+        history.push(event!(QuestionsChanged));
+        let event = subscription.receiver.recv().await.unwrap();
+        assert_eq!(event.seq, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_resume_lagged_subscription() {
+        let history = EventHistory::new(SUBSCRIBERS_CHANNEL_SIZE * 2);
+        history.push(event!(QuestionsChanged));
+        let mut subscription = ResumableSubscription::new(history.clone(), Some(0));
+        let count = SUBSCRIBERS_CHANNEL_SIZE + 10;
+        for _ in 0..count {
+            history.push(event!(QuestionsChanged));
+        }
+
+        let mut received = vec![];
+        for _ in 0..=count {
+            received.push(subscription.recv().await.unwrap());
+        }
+        let expected: Vec<_> = (1..=count as u64 + 1).map(Some).collect();
+        assert_eq!(seqs(&received), expected);
+    }
+}
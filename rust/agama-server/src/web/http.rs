@@ -20,17 +20,28 @@
 
 //! Implements the basic handlers for the HTTP-based API (login, logout, ping, etc.).
 
-use super::{auth::AuthError, state::ServiceState};
-use agama_lib::auth::{AuthToken, TokenClaims};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use super::{
+    audit::{AuditEvent, LoginMethod},
+    auth::{is_credential_id, keys, AuthError, AuthorizedKeys, LoginAttempt, PublicKey},
+    state::ServiceState,
+};
+use agama_lib::auth::{
+    AuthToken, CreateTokenRequest, CreateTokenResponse, TokenClaims, TokenScope, MAX_TOKEN_LIFETIME,
+};
 use axum::{
-    extract::{Query, State},
+    extract::{ConnectInfo, Path, Query, State},
     http::{header, HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     Json,
 };
 use pam::Client;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{openapi::OpenApi, ToSchema};
 
 #[derive(Serialize, ToSchema)]
 pub struct PingResponse {
@@ -52,6 +63,19 @@ pub async fn ping() -> Json<PingResponse> {
     })
 }
 
+/// Returns the OpenAPI specification of the whole HTTP API.
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    context_path = "/api",
+    responses(
+        (status = 200, description = "OpenAPI specification", content_type = "application/json")
+    )
+)]
+pub async fn openapi() -> Json<OpenApi> {
+    Json(super::docs::full_api())
+}
+
 #[derive(Serialize, utoipa::ToSchema)]
 pub struct AuthResponse {
     /// Bearer token to use on subsequent calls
@@ -68,20 +92,91 @@ pub struct LoginRequest {
     path = "/auth",
     context_path = "/api",
     responses(
-        (status = 200, description = "The user has been successfully authenticated.", body = AuthResponse)
+        (status = 200, description = "The user has been successfully authenticated.", body = AuthResponse),
+        (status = 429, description = "Too many failed attempts from the same address.")
     )
 )]
 pub async fn login(
     State(state): State<ServiceState>,
+    source: Option<ConnectInfo<SocketAddr>>,
     Json(login): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, AuthError> {
+    let source = source_ip(source);
+    let attempt = check_login_attempts(&state, source, LoginMethod::Password)?;
+    let result = authenticate_root(login.password);
+    finish_login(&state, attempt, source, LoginMethod::Password, result)
+}
+
+/// Checks the root password using PAM.
+fn authenticate_root(password: String) -> Result<(), AuthError> {
     let mut pam_client = Client::with_password("agama")?;
     pam_client
         .conversation_mut()
-        .set_credentials("root", login.password);
+        .set_credentials("root", password);
     pam_client.authenticate()?;
+    Ok(())
+}
+
+/// Returns the source IP address of the request, if known.
+fn source_ip(source: Option<ConnectInfo<SocketAddr>>) -> Option<IpAddr> {
+    source.map(|ConnectInfo(addr)| addr.ip().to_canonical())
+}
+
+/// Checks whether a new login attempt is allowed, recording the refusal in the audit log.
+fn check_login_attempts(
+    state: &ServiceState,
+    source: Option<IpAddr>,
+    method: LoginMethod,
+) -> Result<LoginAttempt, AuthError> {
+    state.login_attempts.check(source).inspect_err(|error| {
+        state.audit.record(AuditEvent::Login {
+            method,
+            source,
+            success: false,
+            client: None,
+            error: Some(error.to_string()),
+        })
+    })
+}
+
+/// Registers the result of a login attempt and, if it succeeded, issues a new token.
+fn finish_login(
+    state: &ServiceState,
+    attempt: LoginAttempt,
+    source: Option<IpAddr>,
+    method: LoginMethod,
+    result: Result<(), AuthError>,
+) -> Result<impl IntoResponse, AuthError> {
+    if let Err(error) = result {
+        attempt.failed();
+        state.audit.record(AuditEvent::Login {
+            method,
+            source,
+            success: false,
+            client: None,
+            error: Some(error.to_string()),
+        });
+        return Err(error);
+    }
 
-    let token = AuthToken::generate(&state.config.jwt_secret)?;
+    attempt.succeeded();
+    let claims = TokenClaims::new(TokenScope::all(), state.config.token_lifetime());
+    state.audit.record(AuditEvent::Login {
+        method,
+        source,
+        success: true,
+        client: Some(claims.client_id.clone()),
+        error: None,
+    });
+    issue_session_token(state, &claims)
+}
+
+/// Issues a full access token and returns it both in the body and as a cookie.
+fn issue_session_token(
+    state: &ServiceState,
+    claims: &TokenClaims,
+) -> Result<impl IntoResponse, AuthError> {
+    let token = AuthToken::from_claims(claims, &state.config.jwt_secret)?;
     let content = Json(AuthResponse {
         token: token.to_string(),
     });
@@ -96,6 +191,84 @@ pub async fn login(
     Ok((headers, content))
 }
 
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ChallengeResponse {
+    /// Random string to sign with the SSH key
+    challenge: String,
+}
+
+#[utoipa::path(post,
+    path = "/auth/challenge",
+    context_path = "/api",
+    responses(
+        (status = 200, description = "Challenge to sign for the key-based authentication.", body = ChallengeResponse)
+    )
+)]
+pub async fn challenge(State(state): State<ServiceState>) -> Json<ChallengeResponse> {
+    Json(ChallengeResponse {
+        challenge: state.challenges.generate(),
+    })
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyLoginRequest {
+    /// Public key in the OpenSSH format (e.g., "ssh-ed25519 AAAA...")
+    pub public_key: String,
+    /// Challenge obtained from /api/auth/challenge
+    pub challenge: String,
+    /// Armored SSH signature of the challenge (namespace "agama-auth")
+    pub signature: String,
+}
+
+#[utoipa::path(post,
+    path = "/auth/key",
+    context_path = "/api",
+    responses(
+        (status = 200, description = "The user has been successfully authenticated.", body = AuthResponse),
+        (status = 401, description = "The key is not authorized or the signature is not valid."),
+        (status = 429, description = "Too many failed attempts from the same address.")
+    )
+)]
+pub async fn login_with_key(
+    State(state): State<ServiceState>,
+    source: Option<ConnectInfo<SocketAddr>>,
+    Json(login): Json<KeyLoginRequest>,
+) -> Result<impl IntoResponse, AuthError> {
+    let source = source_ip(source);
+    let attempt = check_login_attempts(&state, source, LoginMethod::Key)?;
+    let result = authenticate_key(&state, login, source).await;
+    finish_login(&state, attempt, source, LoginMethod::Key, result)
+}
+
+/// Checks the challenge signature and whether the key is authorized.
+async fn authenticate_key(
+    state: &ServiceState,
+    login: KeyLoginRequest,
+    source: Option<IpAddr>,
+) -> Result<(), AuthError> {
+    if !state.challenges.take(&login.challenge) {
+        return Err(AuthError::InvalidChallenge);
+    }
+
+    let key: PublicKey = login.public_key.parse()?;
+    let authorized_keys = AuthorizedKeys::read(&state.config.authorized_keys);
+    if !authorized_keys.allows(&key, source) {
+        return Err(AuthError::UnauthorizedKey);
+    }
+
+    let KeyLoginRequest {
+        challenge,
+        signature,
+        ..
+    } = login;
+    tokio::task::spawn_blocking(move || {
+        keys::verify_signature(&key, challenge.as_bytes(), &signature)
+    })
+    .await
+    .map_err(|e| AuthError::KeyVerification(std::io::Error::other(e)))?
+}
+
 #[derive(Clone, Deserialize, utoipa::ToSchema)]
 pub struct LoginFromQueryParams {
     /// Token to use for authentication.
@@ -114,7 +287,7 @@ pub async fn login_from_query(
     let mut headers = HeaderMap::new();
 
     let token = AuthToken::new(&params.token);
-    if token.claims(&state.config.jwt_secret).is_ok() {
+    if state.validate_token(&token).is_ok() {
         let cookie = auth_cookie_from_token(&token);
         headers.insert(
             header::SET_COOKIE,
@@ -143,9 +316,18 @@ pub async fn login_from_query(
 }
 
 #[utoipa::path(delete, path = "/api/auth", responses(
-    (status = 204, description = "The user has been logged out.")
+    (status = 204, description = "The user has been logged out and the token revoked.")
 ))]
-pub async fn logout(_claims: TokenClaims) -> Result<impl IntoResponse, AuthError> {
+pub async fn logout(
+    State(state): State<ServiceState>,
+    claims: TokenClaims,
+) -> Result<impl IntoResponse, AuthError> {
+    state.revoked_tokens.revoke_lineage(&claims);
+    state.audit.record(AuditEvent::Logout {
+        client: claims.client_id,
+        token: claims.jti,
+    });
+
     let mut headers = HeaderMap::new();
     let cookie = "agamaToken=deleted; HttpOnly; Expires=Thu, 01 Jan 1970 00:00:00 GMT".to_string();
     headers.insert(
@@ -164,6 +346,117 @@ pub async fn session(_claims: TokenClaims) -> Result<(), AuthError> {
     Ok(())
 }
 
+/// Refreshes the current token.
+///
+/// The new token keeps the client ID, the scopes and the lifetime of the current one. The
+/// current token is not revoked, so other requests using it are not affected. However, both
+/// tokens share the same lineage: revoking the original token (or logging out) revokes the
+/// refreshed ones too.
+#[utoipa::path(post, path = "/api/auth/refresh",
+    responses(
+        (status = 200, description = "The token has been refreshed.", body = AuthResponse),
+        (status = 401, description = "The current token is not valid.")
+    )
+)]
+pub async fn refresh(
+    State(state): State<ServiceState>,
+    claims: TokenClaims,
+) -> Result<impl IntoResponse, AuthError> {
+    let lifetime = claims
+        .lifetime()
+        .unwrap_or(state.config.token_lifetime())
+        .min(MAX_TOKEN_LIFETIME);
+    let new_claims = claims.refreshed(lifetime);
+    state.audit.record(AuditEvent::TokenRefreshed {
+        client: claims.client_id,
+        token: claims.jti,
+        new_token: new_claims.jti.clone(),
+        lineage: new_claims.origin.clone(),
+    });
+    issue_session_token(&state, &new_claims)
+}
+
+/// Creates a new token.
+///
+/// The new token cannot include scopes which are not part of the current one, and it must include
+/// at least one scope. If the current token comes from a client certificate or a Unix socket
+/// connection, the new one belongs to its lineage, so it does not survive revoking the
+/// certificate or the connection.
+#[utoipa::path(post, path = "/api/auth/tokens",
+    request_body = CreateTokenRequest,
+    responses(
+        (status = 200, description = "The token has been created.", body = CreateTokenResponse),
+        (status = 400, description = "The token would not include any scope."),
+        (status = 403, description = "The current token does not include the requested scopes.")
+    )
+)]
+pub async fn create_token(
+    State(state): State<ServiceState>,
+    claims: TokenClaims,
+    Json(request): Json<CreateTokenRequest>,
+) -> Result<Json<CreateTokenResponse>, AuthError> {
+    let scopes = request.scopes.unwrap_or_else(|| claims.scopes.clone());
+    if scopes.is_empty() {
+        return Err(AuthError::EmptyScopes);
+    }
+    if let Some(missing) = scopes.iter().find(|s| !claims.allows(**s)) {
+        return Err(AuthError::Forbidden(*missing));
+    }
+
+    let lifetime = request
+        .ttl
+        .map(Duration::from_secs)
+        .unwrap_or(state.config.token_lifetime())
+        .min(MAX_TOKEN_LIFETIME);
+    let mut new_claims = TokenClaims::new(scopes, lifetime);
+    if is_credential_id(claims.lineage()) {
+        new_claims.origin = claims.lineage().to_string();
+    }
+    let token = AuthToken::from_claims(&new_claims, &state.config.jwt_secret)?;
+    state.audit.record(AuditEvent::TokenCreated {
+        client: claims.client_id,
+        token: new_claims.jti.clone(),
+    });
+    Ok(Json(CreateTokenResponse {
+        id: new_claims.jti,
+        token: token.to_string(),
+        scopes: new_claims.scopes,
+        expires_at: new_claims.exp,
+    }))
+}
+
+/// Revokes a token.
+///
+/// Any token can revoke itself, which revokes the tokens of the same lineage too (see
+/// [TokenClaims::lineage]). Revoking other tokens requires a token including all the scopes.
+/// Revoking the first token of a lineage also revokes the tokens refreshed from it.
+#[utoipa::path(delete, path = "/api/auth/tokens/{id}",
+    params(("id" = String, Path, description = "Token identifier.")),
+    responses(
+        (status = 204, description = "The token has been revoked."),
+        (status = 403, description = "The current token cannot revoke other tokens.")
+    )
+)]
+pub async fn revoke_token(
+    State(state): State<ServiceState>,
+    claims: TokenClaims,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AuthError> {
+    if claims.jti == id {
+        state.revoked_tokens.revoke_lineage(&claims);
+    } else if let Some(missing) = TokenScope::all().into_iter().find(|s| !claims.allows(*s)) {
+        return Err(AuthError::Forbidden(missing));
+    } else {
+        state.revoked_tokens.revoke_unknown(&id);
+    }
+
+    state.audit.record(AuditEvent::TokenRevoked {
+        client: claims.client_id,
+        token: id,
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Creates the cookie containing the authentication token.
 ///
 /// It is a session token (no expiration date) so it should be gone
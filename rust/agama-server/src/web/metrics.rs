@@ -0,0 +1,624 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the metrics endpoint (`/api/metrics`).
+//!
+//! It exposes the installer state in the [OpenMetrics](https://openmetrics.io/) text format, so it
+//! can be scraped by Prometheus. Most of the values are collected from the events channel. The
+//! installation phase, the status and the progress of the services are read from D-Bus at startup,
+//! so they are known before any change happens. The HTTP requests are recorded by the
+//! [tower_http::trace::TraceLayer] and the number of pending questions is read from D-Bus when the
+//! metrics are requested.
+//!
+//! The downloaded bytes only include the files retrieved by the web server itself (e.g., the
+//! profiles, the scripts or the files to deploy). The packages are downloaded by the software
+//! service and they are not counted.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use agama_lib::{
+    error::ServiceError,
+    http::{Event, EventPayload},
+    manager::InstallationPhase,
+    progress::Progress,
+    proxies::{Manager1Proxy, ProgressProxy, ServiceStatusProxy},
+    utils::Transfer,
+};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::{state::ServiceState, EventsSender};
+
+/// Content type of the OpenMetrics text format.
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Upper bounds (in seconds) of the HTTP requests latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+const PHASES: [(InstallationPhase, &str); 4] = [
+    (InstallationPhase::Startup, "startup"),
+    (InstallationPhase::Config, "config"),
+    (InstallationPhase::Install, "install"),
+    (InstallationPhase::Finish, "finish"),
+];
+
+/// D-Bus services (name and path) whose status and progress are read at startup.
+const SERVICES: [(&str, &str); 3] = [
+    (
+        "org.opensuse.Agama.Manager1",
+        "/org/opensuse/Agama/Manager1",
+    ),
+    (
+        "org.opensuse.Agama.Software1",
+        "/org/opensuse/Agama/Software1",
+    ),
+    (
+        "org.opensuse.Agama.Storage1",
+        "/org/opensuse/Agama/Storage1",
+    ),
+];
+
+/// Transport used by a client to receive the events.
+#[derive(Clone, Copy, Debug)]
+pub enum EventsTransport {
+    WebSocket,
+    Sse,
+}
+
+impl EventsTransport {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::WebSocket => "websocket",
+            Self::Sse => "sse",
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProgressMetrics {
+    current_step: u32,
+    max_steps: u32,
+    finished: bool,
+}
+
+impl From<&Progress> for ProgressMetrics {
+    fn from(progress: &Progress) -> Self {
+        Self {
+            current_step: progress.current_step,
+            max_steps: progress.max_steps,
+            finished: progress.finished,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RequestsMetrics {
+    count: u64,
+    sum: f64,
+    buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+impl RequestsMetrics {
+    fn observe(&mut self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        self.count += 1;
+        self.sum += seconds;
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct MetricsState {
+    phase: Option<InstallationPhase>,
+    busy: BTreeMap<String, bool>,
+    progress: BTreeMap<String, ProgressMetrics>,
+    issues: BTreeMap<String, usize>,
+    responses: BTreeMap<u16, u64>,
+    latency: RequestsMetrics,
+}
+
+impl MetricsState {
+    fn update(&mut self, event: &Event) {
+        match &event.payload {
+            EventPayload::InstallationPhaseChanged { phase } => self.phase = Some(*phase),
+            EventPayload::ServiceStatusChanged { service, status } => {
+                self.busy.insert(service.clone(), *status == 1);
+            }
+            EventPayload::ProgressChanged { path, progress } => {
+                self.progress.insert(path.clone(), progress.into());
+            }
+            EventPayload::IssuesChanged { path, issues } => {
+                self.issues.insert(path.clone(), issues.len());
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds the values read at startup, keeping the ones received through the events as they are
+    /// more recent.
+    ///
+    /// * `initial`: values read at startup.
+    fn merge(&mut self, initial: MetricsState) {
+        if self.phase.is_none() {
+            self.phase = initial.phase;
+        }
+        for (service, busy) in initial.busy {
+            self.busy.entry(service).or_insert(busy);
+        }
+        for (path, progress) in initial.progress {
+            self.progress.entry(path).or_insert(progress);
+        }
+    }
+}
+
+/// Installer metrics.
+///
+/// It is cheap to clone, as all the clones share the same values.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    state: Arc<Mutex<MetricsState>>,
+    websocket_clients: Arc<AtomicUsize>,
+    sse_clients: Arc<AtomicUsize>,
+    dbus: Option<zbus::Connection>,
+}
+
+impl Metrics {
+    /// Creates a metrics collector which reads the pending questions from D-Bus.
+    ///
+    /// * `dbus`: D-Bus connection.
+    pub fn with_dbus(dbus: zbus::Connection) -> Self {
+        Self {
+            dbus: Some(dbus),
+            ..Default::default()
+        }
+    }
+
+    /// Starts collecting the metrics from the events sent through the given channel.
+    ///
+    /// It spawns a task which runs until the channel is closed. If there is a D-Bus connection,
+    /// it also reads the initial state of the services.
+    ///
+    /// * `events`: channel the services send their events to.
+    pub fn start(&self, events: &EventsSender) {
+        // subscribe before reading the initial state, so no change is missed
+        let mut receiver = events.subscribe();
+        if let Some(dbus) = self.dbus.clone() {
+            let state = Arc::clone(&self.state);
+            tokio::spawn(async move {
+                let initial = Self::read_initial_state(&dbus).await;
+                state.lock().unwrap().merge(initial);
+            });
+        }
+
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => state.lock().unwrap().update(&event),
+                    Err(RecvError::Lagged(count)) => {
+                        tracing::warn!("The metrics collector missed {count} events");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    // Reads the installation phase and the status and progress of the services from D-Bus.
+    async fn read_initial_state(dbus: &zbus::Connection) -> MetricsState {
+        let mut initial = MetricsState::default();
+        match Self::read_phase(dbus).await {
+            Ok(phase) => initial.phase = Some(phase),
+            Err(error) => tracing::warn!("Could not read the installation phase: {error}"),
+        }
+
+        for (service, path) in SERVICES {
+            match Self::read_status(dbus, service, path).await {
+                Ok(status) => {
+                    initial.busy.insert(service.to_string(), status == 1);
+                }
+                Err(error) => tracing::warn!("Could not read the status of {service}: {error}"),
+            }
+            match Self::read_progress(dbus, service, path).await {
+                Ok(progress) => {
+                    initial
+                        .progress
+                        .insert(path.to_string(), (&progress).into());
+                }
+                Err(error) => tracing::warn!("Could not read the progress of {service}: {error}"),
+            }
+        }
+        initial
+    }
+
+    async fn read_phase(dbus: &zbus::Connection) -> Result<InstallationPhase, ServiceError> {
+        let proxy = Manager1Proxy::new(dbus).await?;
+        InstallationPhase::try_from(proxy.current_installation_phase().await?)
+    }
+
+    async fn read_status(dbus: &zbus::Connection, service: &str, path: &str) -> zbus::Result<u32> {
+        let proxy = ServiceStatusProxy::builder(dbus)
+            .destination(service)?
+            .path(path)?
+            .build()
+            .await?;
+        proxy.current().await
+    }
+
+    async fn read_progress(
+        dbus: &zbus::Connection,
+        service: &str,
+        path: &str,
+    ) -> zbus::Result<Progress> {
+        let proxy = ProgressProxy::builder(dbus)
+            .destination(service)?
+            .path(path)?
+            .build()
+            .await?;
+        Progress::from_proxy(&proxy).await
+    }
+
+    /// Records an HTTP response.
+    ///
+    /// * `status`: response status.
+    /// * `latency`: time it took to produce the response.
+    pub fn record_response(&self, status: StatusCode, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        *state.responses.entry(status.as_u16()).or_default() += 1;
+        state.latency.observe(latency);
+    }
+
+    /// Registers a client receiving the events. It is unregistered when the returned guard is
+    /// dropped.
+    ///
+    /// * `transport`: transport used by the client.
+    pub fn client_connected(&self, transport: EventsTransport) -> EventsClientGuard {
+        let counter = Arc::clone(self.clients(transport));
+        counter.fetch_add(1, Ordering::Relaxed);
+        EventsClientGuard(counter)
+    }
+
+    fn clients(&self, transport: EventsTransport) -> &Arc<AtomicUsize> {
+        match transport {
+            EventsTransport::WebSocket => &self.websocket_clients,
+            EventsTransport::Sse => &self.sse_clients,
+        }
+    }
+
+    async fn pending_questions(&self) -> Option<usize> {
+        let dbus = self.dbus.as_ref()?;
+        match crate::questions::web::pending_questions(dbus).await {
+            Ok(count) => Some(count),
+            Err(error) => {
+                tracing::warn!("Could not read the pending questions: {error}");
+                None
+            }
+        }
+    }
+
+    /// Renders the metrics in the OpenMetrics text format.
+    pub async fn render(&self) -> String {
+        let questions = self.pending_questions().await;
+        let state = self.state.lock().unwrap();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "agama_installation_phase",
+            "stateset",
+            "Installation phase.",
+        );
+        if let Some(phase) = state.phase {
+            for (candidate, name) in PHASES {
+                let value = u8::from(candidate == phase);
+                sample(
+                    &mut out,
+                    "agama_installation_phase",
+                    &[("agama_installation_phase", name)],
+                    value,
+                );
+            }
+        }
+
+        header(
+            &mut out,
+            "agama_service_busy",
+            "gauge",
+            "Whether the service is busy.",
+        );
+        for (service, busy) in &state.busy {
+            sample(
+                &mut out,
+                "agama_service_busy",
+                &[("service", service)],
+                u8::from(*busy),
+            );
+        }
+
+        header(
+            &mut out,
+            "agama_progress_current_step",
+            "gauge",
+            "Current progress step.",
+        );
+        for (path, progress) in &state.progress {
+            sample(
+                &mut out,
+                "agama_progress_current_step",
+                &[("path", path)],
+                progress.current_step,
+            );
+        }
+        header(
+            &mut out,
+            "agama_progress_steps",
+            "gauge",
+            "Number of progress steps.",
+        );
+        for (path, progress) in &state.progress {
+            sample(
+                &mut out,
+                "agama_progress_steps",
+                &[("path", path)],
+                progress.max_steps,
+            );
+        }
+        header(
+            &mut out,
+            "agama_progress_finished",
+            "gauge",
+            "Whether the progress is finished.",
+        );
+        for (path, progress) in &state.progress {
+            sample(
+                &mut out,
+                "agama_progress_finished",
+                &[("path", path)],
+                u8::from(progress.finished),
+            );
+        }
+
+        header(&mut out, "agama_issues", "gauge", "Number of open issues.");
+        for (path, count) in &state.issues {
+            sample(&mut out, "agama_issues", &[("path", path)], count);
+        }
+
+        header(
+            &mut out,
+            "agama_pending_questions",
+            "gauge",
+            "Number of pending questions.",
+        );
+        if let Some(count) = questions {
+            sample(&mut out, "agama_pending_questions", &[], count);
+        }
+
+        header(
+            &mut out,
+            "agama_downloaded_bytes",
+            "counter",
+            "Bytes downloaded by the web server.",
+        );
+        sample(
+            &mut out,
+            "agama_downloaded_bytes_total",
+            &[],
+            Transfer::downloaded_bytes(),
+        );
+
+        header(
+            &mut out,
+            "agama_http_responses",
+            "counter",
+            "HTTP responses by status code.",
+        );
+        for (code, count) in &state.responses {
+            sample(
+                &mut out,
+                "agama_http_responses_total",
+                &[("code", &code.to_string())],
+                count,
+            );
+        }
+
+        header(
+            &mut out,
+            "agama_http_request_duration_seconds",
+            "histogram",
+            "HTTP requests latency.",
+        );
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(state.latency.buckets) {
+            sample(
+                &mut out,
+                "agama_http_request_duration_seconds_bucket",
+                &[("le", &bound.to_string())],
+                count,
+            );
+        }
+        sample(
+            &mut out,
+            "agama_http_request_duration_seconds_bucket",
+            &[("le", "+Inf")],
+            state.latency.count,
+        );
+        sample(
+            &mut out,
+            "agama_http_request_duration_seconds_sum",
+            &[],
+            state.latency.sum,
+        );
+        sample(
+            &mut out,
+            "agama_http_request_duration_seconds_count",
+            &[],
+            state.latency.count,
+        );
+
+        header(
+            &mut out,
+            "agama_event_clients",
+            "gauge",
+            "Number of clients receiving the events.",
+        );
+        for transport in [EventsTransport::WebSocket, EventsTransport::Sse] {
+            let count = self.clients(transport).load(Ordering::Relaxed);
+            sample(
+                &mut out,
+                "agama_event_clients",
+                &[("transport", transport.label())],
+                count,
+            );
+        }
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+/// Keeps a client registered in the metrics while it is alive.
+pub struct EventsClientGuard(Arc<AtomicUsize>);
+
+impl Drop for EventsClientGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    _ = writeln!(out, "# TYPE {name} {kind}");
+    _ = writeln!(out, "# HELP {name} {help}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<_> = labels
+            .iter()
+            .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+            .collect();
+        _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    _ = writeln!(out, " {value}");
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Returns the installer metrics in the OpenMetrics text format.
+pub async fn metrics_handler(State(state): State<ServiceState>) -> impl IntoResponse {
+    let body = state.metrics.render().await;
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use agama_lib::{event, manager::InstallationPhase, progress::Progress};
+    use axum::http::StatusCode;
+
+    use super::{EventsTransport, Metrics, MetricsState};
+
+    #[tokio::test]
+    async fn test_render_metrics() {
+        let metrics = Metrics::default();
+        {
+            let mut state = metrics.state.lock().unwrap();
+            state.update(&event!(InstallationPhaseChanged {
+                phase: InstallationPhase::Config
+            }));
+            state.update(&event!(ProgressChanged {
+                path: "/org/opensuse/Agama/Software1".to_string(),
+                progress: Progress {
+                    current_step: 2,
+                    max_steps: 5,
+                    current_title: "Reading \"repositories\"".to_string(),
+                    finished: false,
+                },
+            }));
+        }
+        metrics.record_response(StatusCode::OK, Duration::from_millis(20));
+        let guard = metrics.client_connected(EventsTransport::WebSocket);
+
+        let text = metrics.render().await;
+        assert!(text.contains("agama_installation_phase{agama_installation_phase=\"config\"} 1\n"));
+        assert!(text.contains("agama_installation_phase{agama_installation_phase=\"install\"} 0\n"));
+        assert!(text
+            .contains("agama_progress_current_step{path=\"/org/opensuse/Agama/Software1\"} 2\n"));
+        assert!(text.contains("agama_http_responses_total{code=\"200\"} 1\n"));
+        assert!(text.contains("agama_http_request_duration_seconds_bucket{le=\"0.01\"} 0\n"));
+        assert!(text.contains("agama_http_request_duration_seconds_bucket{le=\"0.025\"} 1\n"));
+        assert!(text.contains("agama_event_clients{transport=\"websocket\"} 1\n"));
+        assert!(text.contains("agama_downloaded_bytes_total "));
+        assert!(text.ends_with("# EOF\n"));
+
+        drop(guard);
+        let text = metrics.render().await;
+        assert!(text.contains("agama_event_clients{transport=\"websocket\"} 0\n"));
+    }
+
+    #[test]
+    fn test_merge_initial_state() {
+        let mut state = MetricsState::default();
+        state.update(&event!(ServiceStatusChanged {
+            service: "org.opensuse.Agama.Software1".to_string(),
+            status: 1,
+        }));
+
+        let mut initial = MetricsState {
+            phase: Some(InstallationPhase::Config),
+            ..Default::default()
+        };
+        initial
+            .busy
+            .insert("org.opensuse.Agama.Software1".to_string(), false);
+        initial
+            .busy
+            .insert("org.opensuse.Agama.Storage1".to_string(), false);
+        state.merge(initial);
+
+        assert_eq!(state.phase, Some(InstallationPhase::Config));
+        // the values from the events are more recent
+        assert_eq!(state.busy.get("org.opensuse.Agama.Software1"), Some(&true));
+        assert_eq!(state.busy.get("org.opensuse.Agama.Storage1"), Some(&false));
+    }
+}
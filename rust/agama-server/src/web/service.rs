@@ -18,17 +18,24 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use super::http::{login, login_from_query, logout, session};
-use super::{config::ServiceConfig, state::ServiceState, EventsSender};
-use agama_lib::auth::TokenClaims;
+use super::auth::{AuthError, RevokedTokens};
+use super::http::{
+    challenge, create_token, login, login_from_query, login_with_key, logout, refresh,
+    revoke_token, session,
+};
+use super::{
+    audit::AuditEvent, config::ServiceConfig, metrics::Metrics, state::ServiceState, AuditLog,
+    EventHistory, EventsSender,
+};
+use agama_lib::auth::{TokenClaims, TokenScope};
 use axum::http::HeaderValue;
 use axum::middleware::Next;
 use axum::{
     body::Body,
-    extract::Request,
+    extract::{OriginalUri, Request, State},
     middleware,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use hyper::header::CACHE_CONTROL;
@@ -50,6 +57,9 @@ use tracing::Span;
 ///
 /// * A static assets directory (`public_dir`).
 /// * A websocket at the `/ws` path.
+/// * A Server-Sent Events stream at the `/events` path.
+/// * The installer metrics at the `/metrics` path.
+/// * The OpenAPI specification at the `/openapi.json` path.
 /// * An authentication endpoint at `/auth`.
 /// * A 'ping' endpoint at '/ping'.
 /// * A number of authenticated services that are added using the `add_service` function.
@@ -58,6 +68,9 @@ pub struct MainServiceBuilder {
     events: EventsSender,
     api_router: Router<ServiceState>,
     public_dir: PathBuf,
+    audit: AuditLog,
+    metrics: Metrics,
+    revoked_tokens: RevokedTokens,
 }
 
 impl MainServiceBuilder {
@@ -69,7 +82,11 @@ impl MainServiceBuilder {
     where
         P: AsRef<Path>,
     {
-        let api_router = Router::new().route("/ws", get(super::ws::ws_handler));
+        let api_router = Router::new()
+            .route("/ws", get(super::ws::ws_handler))
+            .route("/events", get(super::sse::events_handler))
+            .route("/metrics", get(super::metrics::metrics_handler))
+            .route("/openapi.json", get(super::http::openapi));
         let config = ServiceConfig::default();
 
         Self {
@@ -77,6 +94,9 @@ impl MainServiceBuilder {
             api_router,
             config,
             public_dir: PathBuf::from(public_dir.as_ref()),
+            audit: AuditLog::default(),
+            metrics: Metrics::default(),
+            revoked_tokens: RevokedTokens::default(),
         }
     }
 
@@ -84,6 +104,30 @@ impl MainServiceBuilder {
         Self { config, ..self }
     }
 
+    /// Sets the audit log. By default, the events are not recorded.
+    ///
+    /// * `audit`: audit log.
+    pub fn with_audit_log(self, audit: AuditLog) -> Self {
+        Self { audit, ..self }
+    }
+
+    /// Sets the list of revoked tokens. By default, it is kept only in memory.
+    ///
+    /// * `revoked_tokens`: list of revoked tokens.
+    pub fn with_revoked_tokens(self, revoked_tokens: RevokedTokens) -> Self {
+        Self {
+            revoked_tokens,
+            ..self
+        }
+    }
+
+    /// Sets the metrics collector. By default, the pending questions are not reported.
+    ///
+    /// * `metrics`: metrics collector.
+    pub fn with_metrics(self, metrics: Metrics) -> Self {
+        Self { metrics, ..self }
+    }
+
     /// Add an authenticated service.
     ///
     /// * `path`: Path to mount the service under `/api`.
@@ -101,10 +145,18 @@ impl MainServiceBuilder {
     }
 
     pub fn build(self) -> Router {
+        self.metrics.start(&self.events);
+        let metrics = self.metrics.clone();
         let state = ServiceState {
             config: self.config,
+            history: EventHistory::start(&self.events),
             events: self.events,
             public_dir: self.public_dir.clone(),
+            revoked_tokens: self.revoked_tokens,
+            challenges: Default::default(),
+            login_attempts: Default::default(),
+            audit: self.audit,
+            metrics: self.metrics,
         };
 
         let api_router = self
@@ -114,7 +166,12 @@ impl MainServiceBuilder {
                 auth_middleware,
             ))
             .route("/ping", get(super::http::ping))
-            .route("/auth", post(login).get(session).delete(logout));
+            .route("/auth", post(login).get(session).delete(logout))
+            .route("/auth/challenge", post(challenge))
+            .route("/auth/key", post(login_with_key))
+            .route("/auth/refresh", post(refresh))
+            .route("/auth/tokens", post(create_token))
+            .route("/auth/tokens/:id", delete(revoke_token));
 
         tracing::info!("Serving static files from {}", self.public_dir.display());
         let serve = ServeDir::new(self.public_dir).precompressed_gzip();
@@ -134,7 +191,8 @@ impl MainServiceBuilder {
                         )
                     })
                     .on_response(
-                        |response: &Response<Body>, latency: Duration, span: &Span| {
+                        move |response: &Response<Body>, latency: Duration, span: &Span| {
+                            metrics.record_response(response.status(), latency);
                             tracing::info!(
                                 "response for {}: {} {:?}",
                                 span.id().unwrap_or(Id::from_u64(1)).into_u64(),
@@ -156,9 +214,36 @@ impl MainServiceBuilder {
 // Authentication middleware.
 //
 // 1. Extracts the claims of the authentication token.
-// 2. Adds the client ID as a extension to the request.
-async fn auth_middleware(claims: TokenClaims, mut request: Request, next: Next) -> Response {
-    request.extensions_mut().insert(Arc::new(claims.client_id));
+// 2. Checks whether the token includes the scope required by the request.
+// 3. Adds the client ID as a extension to the request.
+// 4. Records the token use and the requests changing the system in the audit log.
+async fn auth_middleware(
+    State(state): State<ServiceState>,
+    claims: TokenClaims,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = match request.extensions().get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => uri.path().to_string(),
+        None => request.uri().path().to_string(),
+    };
+    let method = request.method().to_string();
+    let required = TokenScope::required_for(&method, &path);
+    if !claims.allows(required) {
+        return AuthError::Forbidden(required).into_response();
+    }
+
+    state.audit.token_used(&claims);
+    let client_id = claims.client_id;
+    request.extensions_mut().insert(Arc::new(client_id.clone()));
     let response = next.run(request).await;
+    if required != TokenScope::ReadOnly {
+        state.audit.record(AuditEvent::Request {
+            client: client_id,
+            method,
+            path,
+            status: response.status().as_u16(),
+        });
+    }
     response
 }
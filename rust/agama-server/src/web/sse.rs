@@ -0,0 +1,172 @@
+// Copyright (c) [2025] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the Server-Sent Events (SSE) endpoint.
+//!
+//! It is an alternative to the WebSocket for clients which cannot use it (e.g., because of a
+//! proxy). It sends the same events, using the sequence number as the event ID, so the clients
+//! can resume the stream with the standard `Last-Event-ID` header. As it is a one-way channel,
+//! the events filter is given as query parameters (e.g.,
+//! `/api/events?types=QuestionsChanged,IssuesChanged`).
+
+use std::{convert::Infallible, sync::Arc};
+
+use super::{
+    event::{EventHistory, ResumableSubscription},
+    metrics::{EventsClientGuard, EventsTransport},
+    state::ServiceState,
+};
+use agama_lib::{
+    auth::ClientId,
+    http::{Event, EventFilter},
+};
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{self, KeepAlive, Sse},
+    Extension,
+};
+use futures_util::{stream, Stream};
+use serde::Deserialize;
+
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+#[derive(Debug, Deserialize)]
+pub struct EventsParams {
+    /// Sequence number of the last event received by the client. The `Last-Event-ID` header
+    /// takes precedence.
+    since: Option<u64>,
+    /// Comma-separated list of event types.
+    types: Option<String>,
+    /// Comma-separated list of service paths.
+    services: Option<String>,
+}
+
+impl EventsParams {
+    fn filter(&self) -> EventFilter {
+        EventFilter {
+            types: split_list(&self.types),
+            services: split_list(&self.services),
+            ..Default::default()
+        }
+    }
+}
+
+fn split_list(list: &Option<String>) -> Vec<String> {
+    list.iter()
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|i| !i.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// State of an events stream.
+struct EventsStream {
+    first: Option<Event>,
+    subscription: ResumableSubscription,
+    filter: EventFilter,
+    _client: EventsClientGuard,
+}
+
+impl EventsStream {
+    fn new(
+        history: EventHistory,
+        since: Option<u64>,
+        filter: EventFilter,
+        first: Event,
+        client: EventsClientGuard,
+    ) -> Self {
+        Self {
+            first: Some(first),
+            subscription: ResumableSubscription::new(history, since),
+            filter,
+            _client: client,
+        }
+    }
+
+    /// Returns the next event matching the filter, or `None` if the channel is closed.
+    async fn next(&mut self) -> Option<Event> {
+        if let Some(first) = self.first.take() {
+            if self.filter.matches(&first) {
+                return Some(first);
+            }
+        }
+
+        loop {
+            let event = self.subscription.recv().await?;
+            if self.filter.matches(&event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// Converts an event into a SSE event, using the sequence number as ID.
+fn to_sse_event(event: &Event) -> sse::Event {
+    let sse_event = sse::Event::default()
+        .json_data(event)
+        .unwrap_or_else(|e| sse::Event::default().comment(format!("serialization error: {e}")));
+    match event.seq {
+        Some(seq) => sse_event.id(seq.to_string()),
+        None => sse_event,
+    }
+}
+
+pub async fn events_handler(
+    State(state): State<ServiceState>,
+    Extension(client_id): Extension<Arc<ClientId>>,
+    Query(params): Query<EventsParams>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+    let since = headers
+        .get(LAST_EVENT_ID)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .or(params.since);
+
+    let conn_event = agama_lib::event!(ClientConnected, client_id.as_ref());
+    let client = state.metrics.client_connected(EventsTransport::Sse);
+    let events = EventsStream::new(state.history, since, params.filter(), conn_event, client);
+    let stream = stream::unfold(events, |mut events| async move {
+        let event = events.next().await?;
+        Some((Ok(to_sse_event(&event)), events))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_list, EventsParams};
+
+    #[test]
+    fn test_filter_from_params() {
+        let params = EventsParams {
+            since: None,
+            types: Some("QuestionsChanged, IssuesChanged,".to_string()),
+            services: None,
+        };
+        let filter = params.filter();
+        assert_eq!(filter.types, vec!["QuestionsChanged", "IssuesChanged"]);
+        assert!(filter.services.is_empty());
+        assert!(split_list(&None).is_empty());
+    }
+}
@@ -20,15 +20,29 @@
 
 //! Implements the web service state.
 
-use super::{config::ServiceConfig, EventsSender};
+use super::{
+    audit::AuditLog,
+    auth::{Challenges, LoginAttempts, RevokedTokens},
+    config::ServiceConfig,
+    metrics::Metrics,
+    EventHistory, EventsSender,
+};
 use std::path::PathBuf;
 
 /// Web service state.
 ///
-/// It holds the service configuration, the current D-Bus connection and a channel to send events.
+/// It holds the service configuration, the current D-Bus connection, a channel to send events, the
+/// events history, the authentication related data (revoked tokens, pending challenges and failed
+/// login attempts), the audit log and the metrics.
 #[derive(Clone)]
 pub struct ServiceState {
     pub config: ServiceConfig,
     pub events: EventsSender,
+    pub history: EventHistory,
     pub public_dir: PathBuf,
+    pub revoked_tokens: RevokedTokens,
+    pub challenges: Challenges,
+    pub login_attempts: LoginAttempts,
+    pub audit: AuditLog,
+    pub metrics: Metrics,
 }
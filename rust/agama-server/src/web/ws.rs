@@ -19,47 +19,112 @@
 // find current contact information at www.suse.com.
 
 //! Implements the websocket handling.
+//!
+//! The clients can resume a subscription by passing the sequence number of the last event they
+//! received (e.g., `/api/ws?since=42`). See [EventHistory] for further details.
+//!
+//! Additionally, they can send a [WebSocketCommand::Subscribe] message to receive only the events
+//! they are interested in. The events are filtered before serializing them.
 
 use std::sync::Arc;
 
-use super::{state::ServiceState, EventsSender};
-use agama_lib::auth::ClientId;
+use super::{
+    event::{EventHistory, ResumableSubscription},
+    metrics::EventsTransport,
+    state::ServiceState,
+};
+use agama_lib::{
+    auth::ClientId,
+    http::{Event, EventFilter, WebSocketCommand},
+};
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        Query, State, WebSocketUpgrade,
     },
     response::IntoResponse,
     Extension,
 };
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct WebSocketParams {
+    /// Sequence number of the last event received by the client.
+    since: Option<u64>,
+}
 
 pub async fn ws_handler(
     State(state): State<ServiceState>,
     Extension(client_id): Extension<Arc<ClientId>>,
+    Query(params): Query<WebSocketParams>,
     ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state.events, client_id))
+    ws.on_upgrade(move |socket| async move {
+        let _client = state.metrics.client_connected(EventsTransport::WebSocket);
+        handle_socket(socket, state.history, client_id, params.since).await
+    })
 }
 
-async fn handle_socket(mut socket: WebSocket, events: EventsSender, client_id: Arc<ClientId>) {
-    let mut rx = events.subscribe();
-
+async fn handle_socket(
+    mut socket: WebSocket,
+    history: EventHistory,
+    client_id: Arc<ClientId>,
+    since: Option<u64>,
+) {
     let conn_event = agama_lib::event!(ClientConnected, client_id.as_ref());
-    if let Ok(json) = serde_json::to_string(&conn_event) {
-        _ = socket.send(Message::Text(json)).await;
+    if !send_event(&mut socket, &conn_event).await {
+        return;
     }
 
-    while let Ok(msg) = rx.recv().await {
-        match serde_json::to_string(&msg) {
-            Ok(json) => {
-                if let Err(e) = socket.send(Message::Text(json)).await {
-                    tracing::info!("ws: client disconnected: {e}");
-                    return;
+    let mut filter = EventFilter::default();
+    let mut subscription = ResumableSubscription::new(history, since);
+    loop {
+        tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => handle_command(&text, &mut filter),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            event = subscription.recv() => match event {
+                Some(event) => {
+                    if filter.matches(&event) && !send_event(&mut socket, &event).await {
+                        return;
+                    }
                 }
+                None => return,
             }
-            Err(e) => {
-                tracing::error!("ws: error serializing message: {e}")
+        }
+    }
+}
+
+/// Handles a command sent by the client.
+///
+/// * `text`: message content.
+/// * `filter`: current events filter.
+fn handle_command(text: &str, filter: &mut EventFilter) {
+    match serde_json::from_str::<WebSocketCommand>(text) {
+        Ok(WebSocketCommand::Subscribe(new_filter)) => {
+            tracing::info!("ws: subscribing to {:?}", new_filter);
+            *filter = new_filter;
+        }
+        Err(e) => tracing::warn!("ws: unknown command {text}: {e}"),
+    }
+}
+
+/// Sends an event through the socket.
+///
+/// It returns false if the client is disconnected.
+async fn send_event(socket: &mut WebSocket, event: &Event) -> bool {
+    match serde_json::to_string(event) {
+        Ok(json) => {
+            if let Err(e) = socket.send(Message::Text(json)).await {
+                tracing::info!("ws: client disconnected: {e}");
+                return false;
             }
         }
+        Err(e) => {
+            tracing::error!("ws: error serializing message: {e}")
+        }
     }
+    true
 }
@@ -21,15 +21,18 @@
 pub mod common;
 
 use agama_lib::error::ServiceError;
-use agama_lib::network::settings::{BondSettings, BridgeSettings, NetworkConnection};
-use agama_lib::network::types::{DeviceType, SSID};
+use agama_lib::http::{Event, EventPayload};
+use agama_lib::network::settings::{
+    BondSettings, BridgeSettings, NetworkConnection, OvsBridgeSettings, OvsPortSettings,
+    OvsSettings,
+};
+use agama_lib::network::types::{ConnectionState, DeviceType, SSID};
 use agama_lib::network::{
-    model::{self, AccessPoint, GeneralState, NetworkState, StateConfig},
-    Adapter, NetworkAdapterError,
+    model::{self, AccessPoint, GeneralState, NetworkChange, NetworkState},
+    MemoryAdapter, Scenario,
 };
 use agama_server::network::web::network_service;
 
-use async_trait::async_trait;
 use axum::http::header;
 use axum::{
     body::Body,
@@ -38,7 +41,7 @@ use axum::{
 };
 use common::body_to_string;
 use serde_json::to_string;
-use std::error::Error;
+use std::{error::Error, time::Duration};
 use tokio::{sync::broadcast, test};
 use tower::ServiceExt;
 
@@ -55,23 +58,38 @@ async fn build_state() -> NetworkState {
 }
 
 async fn build_service(state: NetworkState) -> Result<Router, ServiceError> {
-    let adapter = NetworkTestAdapter(state);
+    let adapter = MemoryAdapter::new(state);
     let (tx, _rx) = broadcast::channel(16);
     network_service(adapter, tx).await
 }
 
-#[derive(Default)]
-pub struct NetworkTestAdapter(NetworkState);
+async fn build_scenario_service(
+    scenario: &str,
+) -> Result<(Router, broadcast::Receiver<Event>), Box<dyn Error>> {
+    let adapter = MemoryAdapter::from_scenario(Scenario::from_json(scenario)?)?;
+    let (tx, rx) = broadcast::channel(16);
+    let service = network_service(adapter, tx).await?;
+    Ok((service, rx))
+}
 
-#[async_trait]
-impl Adapter for NetworkTestAdapter {
-    async fn read(&self, _: StateConfig) -> Result<NetworkState, NetworkAdapterError> {
-        Ok(self.0.clone())
+async fn next_network_change(events: &mut broadcast::Receiver<Event>) -> NetworkChange {
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("No network change was received")
+            .unwrap();
+        if let EventPayload::NetworkChange { change } = event.payload {
+            return change;
+        }
     }
+}
 
-    async fn write(&self, _network: &NetworkState) -> Result<(), NetworkAdapterError> {
-        unimplemented!("Not used in tests");
-    }
+fn post(uri: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method(Method::POST)
+        .body(Body::empty())
+        .unwrap()
 }
 
 #[test]
@@ -283,3 +301,275 @@ async fn test_add_bridge_connection() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+async fn test_add_ovs_connections() -> Result<(), Box<dyn Error>> {
+    let state = build_state().await;
+    let network_service = build_service(state.clone()).await?;
+
+    let port = NetworkConnection {
+        id: "ovs-port-eth0".to_string(),
+        ovs: Some(OvsSettings::Port(OvsPortSettings {
+            tag: Some(10),
+            ports: vec!["eth0".to_string()],
+        })),
+        ..Default::default()
+    };
+    let bridge = NetworkConnection {
+        id: "ovs-br0".to_string(),
+        interface: Some("ovs-br0".to_string()),
+        ovs: Some(OvsSettings::Bridge(OvsBridgeSettings {
+            stp_enable: Some(true),
+            ports: vec!["ovs-port-eth0".to_string()],
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+
+    for conn in [port, bridge] {
+        let request = Request::builder()
+            .uri("/connections")
+            .header("Content-Type", "application/json")
+            .method(Method::POST)
+            .body(serde_json::to_string(&conn)?)
+            .unwrap();
+        let response = network_service.clone().oneshot(request).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let request = Request::builder()
+        .uri("/connections")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    let conns: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+    let bridge = conns.iter().find(|c| c["id"] == "ovs-br0").unwrap();
+    assert_eq!(
+        bridge["ovs"],
+        serde_json::json!({ "bridge": { "stpEnable": true, "ports": ["ovs-port-eth0"] } })
+    );
+    let port = conns.iter().find(|c| c["id"] == "ovs-port-eth0").unwrap();
+    assert_eq!(
+        port["ovs"],
+        serde_json::json!({ "port": { "tag": 10, "ports": ["eth0"] } })
+    );
+
+    Ok(())
+}
+
+#[test]
+async fn test_add_infiniband_and_tun_connections() -> Result<(), Box<dyn Error>> {
+    let state = build_state().await;
+    let network_service = build_service(state.clone()).await?;
+
+    let ib0 = serde_json::json!({
+        "id": "ib0",
+        "infiniband": { "transportMode": "connected", "pKey": 32769, "parent": "ib0" }
+    });
+    let tap0 = serde_json::json!({ "id": "tap0", "tun": { "mode": "tap", "owner": "1000" } });
+    let dummy0 = serde_json::json!({ "id": "dummy0", "dummy": {} });
+
+    for conn in [&ib0, &tap0, &dummy0] {
+        let request = Request::builder()
+            .uri("/connections")
+            .header("Content-Type", "application/json")
+            .method(Method::POST)
+            .body(conn.to_string())
+            .unwrap();
+        let response = network_service.clone().oneshot(request).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let request = Request::builder()
+        .uri("/connections")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    let body = body_to_string(response.into_body()).await;
+    let conns: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+    let find = |id: &str| conns.iter().find(|c| c["id"] == id).unwrap().clone();
+    assert_eq!(find("ib0")["infiniband"], ib0["infiniband"]);
+    assert_eq!(find("tap0")["tun"], tap0["tun"]);
+    assert_eq!(find("dummy0")["dummy"], dummy0["dummy"]);
+
+    let invalid = serde_json::json!({ "id": "ib1", "infiniband": { "transportMode": "fast" } });
+    let request = Request::builder()
+        .uri("/connections")
+        .header("Content-Type", "application/json")
+        .method(Method::POST)
+        .body(invalid.to_string())
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[test]
+async fn test_simulated_devices() -> Result<(), Box<dyn Error>> {
+    let (network_service, mut events) = build_scenario_service(
+        r#"{
+            "devices": [{ "name": "eth0", "type": "ethernet", "state": "disconnected" }],
+            "steps": [
+                { "addDevice": { "name": "wlan0", "type": "wireless", "state": "disconnected" } },
+                { "removeDevice": "eth0" }
+            ]
+        }"#,
+    )
+    .await?;
+
+    let change = next_network_change(&mut events).await;
+    assert!(matches!(change, NetworkChange::DeviceAdded(device) if device.name == "wlan0"));
+    let change = next_network_change(&mut events).await;
+    assert!(matches!(change, NetworkChange::DeviceRemoved(name) if name == "eth0"));
+
+    let request = Request::builder()
+        .uri("/devices")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+    let response = network_service.oneshot(request).await?;
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(r#""name":"wlan0""#));
+    assert!(!body.contains(r#""name":"eth0""#));
+    Ok(())
+}
+
+#[test]
+async fn test_simulated_wifi_scans() -> Result<(), Box<dyn Error>> {
+    let (network_service, _events) = build_scenario_service(
+        r#"{
+            "scans": [[{ "ssid": "AgamaNetwork" }], [{ "ssid": "AgamaNetwork2" }]]
+        }"#,
+    )
+    .await?;
+
+    for ssid in ["AgamaNetwork", "AgamaNetwork2", "AgamaNetwork2"] {
+        let request = Request::builder()
+            .uri("/wifi")
+            .method(Method::GET)
+            .body(Body::empty())
+            .unwrap();
+        let response = network_service.clone().oneshot(request).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_to_string(response.into_body()).await;
+        let networks: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(networks[0]["ssid"], ssid);
+    }
+    Ok(())
+}
+
+#[test]
+async fn test_simulated_apply() -> Result<(), Box<dyn Error>> {
+    let (network_service, mut events) = build_scenario_service(
+        r#"{
+            "devices": [{ "name": "eth0", "type": "ethernet", "state": "disconnected" }],
+            "connections": [{ "id": "eth0", "interface": "eth0", "method4": "auto" }],
+            "applyErrors": ["Device not ready"],
+            "onApply": [
+                { "connectionState": { "id": "eth0", "state": "activating" } },
+                { "wait": 10 },
+                { "connectionState": { "id": "eth0", "state": "activated" } }
+            ]
+        }"#,
+    )
+    .await?;
+
+    let response = network_service
+        .clone()
+        .oneshot(post("/system/apply"))
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = network_service.oneshot(post("/system/apply")).await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    for expected in [ConnectionState::Activating, ConnectionState::Activated] {
+        let change = next_network_change(&mut events).await;
+        assert!(matches!(
+            change,
+            NetworkChange::ConnectionStateChanged { id, state } if id == "eth0" && state == expected
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+async fn test_apply_with_failed_connectivity_checks() -> Result<(), Box<dyn Error>> {
+    let (network_service, mut events) = build_scenario_service(
+        r#"{ "connections": [{ "id": "eth0", "interface": "eth0", "method4": "auto" }] }"#,
+    )
+    .await?;
+
+    let eth1 = NetworkConnection {
+        id: "eth1".to_string(),
+        interface: Some("eth1".to_string()),
+        ..Default::default()
+    };
+    let request = Request::builder()
+        .uri("/connections")
+        .header("Content-Type", "application/json")
+        .method(Method::POST)
+        .body(serde_json::to_string(&eth1)?)
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // nothing listens on the port once the listener is dropped
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+    drop(listener);
+
+    let checks = serde_json::json!({ "http": [format!("http://{address}/")], "timeout": 1 });
+    let request = Request::builder()
+        .uri("/system/apply")
+        .header("Content-Type", "application/json")
+        .method(Method::POST)
+        .body(checks.to_string())
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = body_to_string(response.into_body()).await;
+    let error: serde_json::Value = serde_json::from_str(&body)?;
+    let report = &error["report"];
+    assert_eq!(report["success"], false);
+    assert_eq!(report["rolledBack"], true);
+    assert_eq!(report["checks"][0]["kind"], "http");
+
+    let change = next_network_change(&mut events).await;
+    assert!(matches!(change, NetworkChange::ConnectivityChecked(report) if report.rolled_back));
+
+    let request = Request::builder()
+        .uri("/connections")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+    let response = network_service.oneshot(request).await?;
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(r#""id":"eth0""#));
+    assert!(!body.contains(r#""id":"eth1""#));
+    Ok(())
+}
+
+#[test]
+async fn test_apply_with_too_long_timeout() -> Result<(), Box<dyn Error>> {
+    let (network_service, _events) = build_scenario_service(r#"{ "connections": [] }"#).await?;
+
+    let checks = serde_json::json!({ "gateway": true, "timeout": u64::MAX });
+    let request = Request::builder()
+        .uri("/system/apply")
+        .header("Content-Type", "application/json")
+        .method(Method::POST)
+        .body(checks.to_string())
+        .unwrap();
+    let response = network_service.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains("cannot exceed"));
+    Ok(())
+}
@@ -20,16 +20,17 @@
 
 pub mod common;
 
-use agama_lib::auth::AuthToken;
-use agama_server::web::{MainServiceBuilder, ServiceConfig};
+use agama_lib::auth::{AuthToken, ClientId, TokenClaims, TokenScope, DEFAULT_TOKEN_LIFETIME};
+use agama_server::web::{ClientCertificate, MainServiceBuilder, PeerCredentials, ServiceConfig};
 use axum::{
     body::Body,
     http::{Method, Request, StatusCode},
     response::Response,
     routing::get,
+    Router,
 };
 use common::body_to_string;
-use std::{error::Error, path::PathBuf};
+use std::{collections::BTreeSet, error::Error, path::PathBuf};
 use tokio::{sync::broadcast::channel, test};
 use tower::ServiceExt;
 
@@ -66,6 +67,7 @@ async fn protected() -> String {
 async fn access_protected_route(token: &str, jwt_secret: &str) -> Response {
     let config = ServiceConfig {
         jwt_secret: jwt_secret.to_string(),
+        ..Default::default()
     };
     let (tx, _) = channel(16);
     let web_service = MainServiceBuilder::new(tx, public_dir())
@@ -103,3 +105,377 @@ async fn test_access_protected_route_failed() -> Result<(), Box<dyn Error>> {
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     Ok(())
 }
+
+fn protected_service(jwt_secret: &str) -> Router {
+    let config = ServiceConfig {
+        jwt_secret: jwt_secret.to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    MainServiceBuilder::new(tx, public_dir())
+        .add_service("/protected", get(protected).put(protected))
+        .with_config(config)
+        .build()
+}
+
+fn request(method: Method, uri: &str, token: &AuthToken) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[test]
+async fn test_access_with_scoped_token() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let claims = TokenClaims::new(
+        BTreeSet::from([TokenScope::ReadOnly]),
+        DEFAULT_TOKEN_LIFETIME,
+    );
+    let token = AuthToken::from_claims(&claims, "nots3cr3t")?;
+
+    let response = service
+        .clone()
+        .oneshot(request(Method::GET, "/api/protected", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = service
+        .oneshot(request(Method::PUT, "/api/protected", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    Ok(())
+}
+
+#[test]
+async fn test_scopes_imply_read_only() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let claims = TokenClaims::new(
+        BTreeSet::from([TokenScope::Install]),
+        DEFAULT_TOKEN_LIFETIME,
+    );
+    let token = AuthToken::from_claims(&claims, "nots3cr3t")?;
+
+    let response = service
+        .clone()
+        .oneshot(request(Method::GET, "/api/protected", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = service
+        .oneshot(request(Method::PUT, "/api/protected", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    Ok(())
+}
+
+#[test]
+async fn test_create_token_without_scopes() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let master = AuthToken::generate("nots3cr3t")?;
+    let request = Request::builder()
+        .uri("/api/auth/tokens")
+        .method(Method::POST)
+        .header("Authorization", format!("Bearer {}", master))
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{ "scopes": [] }"#))
+        .unwrap();
+
+    let response = service.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[test]
+async fn test_access_with_peer_credentials() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+
+    for (uid, status) in [(0, StatusCode::OK), (1000, StatusCode::UNAUTHORIZED)] {
+        let mut request = Request::builder()
+            .uri("/api/protected")
+            .method(Method::PUT)
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(PeerCredentials::new(uid, Some(1234)));
+
+        let response = service.clone().oneshot(request).await?;
+        assert_eq!(response.status(), status);
+    }
+    Ok(())
+}
+
+#[test]
+async fn test_revoke_token() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let claims = TokenClaims::new(
+        BTreeSet::from([TokenScope::ReadOnly]),
+        DEFAULT_TOKEN_LIFETIME,
+    );
+    let token = AuthToken::from_claims(&claims, "nots3cr3t")?;
+    let master = AuthToken::generate("nots3cr3t")?;
+
+    // a read-only token cannot revoke other tokens
+    let uri = format!("/api/auth/tokens/{}", TokenClaims::default().jti);
+    let response = service
+        .clone()
+        .oneshot(request(Method::DELETE, &uri, &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let uri = format!("/api/auth/tokens/{}", &claims.jti);
+    let response = service
+        .clone()
+        .oneshot(request(Method::DELETE, &uri, &master))
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = service
+        .oneshot(request(Method::GET, "/api/protected", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[test]
+async fn test_reject_token_without_id() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let claims = TokenClaims {
+        jti: "".to_string(),
+        ..Default::default()
+    };
+    let token = AuthToken::from_claims(&claims, "nots3cr3t")?;
+
+    let response = service
+        .oneshot(request(Method::GET, "/api/protected", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[test]
+async fn test_refresh_token() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let lifetime = std::time::Duration::from_secs(3600);
+    let claims = TokenClaims::new(BTreeSet::from([TokenScope::ReadOnly]), lifetime);
+    let token = AuthToken::from_claims(&claims, "nots3cr3t")?;
+
+    let response = service
+        .oneshot(request(Method::POST, "/api/auth/refresh", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = body_to_string(response.into_body()).await;
+    let body: serde_json::Value = serde_json::from_str(&body)?;
+    let refreshed = AuthToken::new(body["token"].as_str().unwrap());
+    let new_claims = refreshed.claims("nots3cr3t")?;
+    assert_ne!(new_claims.jti, claims.jti);
+    assert_eq!(new_claims.client_id, claims.client_id);
+    assert_eq!(new_claims.scopes, claims.scopes);
+    assert_eq!(new_claims.lifetime(), Some(lifetime));
+    Ok(())
+}
+
+#[test]
+async fn test_revoke_refreshed_token() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let claims = TokenClaims::new(TokenScope::all(), DEFAULT_TOKEN_LIFETIME);
+    let token = AuthToken::from_claims(&claims, "nots3cr3t")?;
+
+    let response = service
+        .clone()
+        .oneshot(request(Method::POST, "/api/auth/refresh", &token))
+        .await?;
+    let body = body_to_string(response.into_body()).await;
+    let body: serde_json::Value = serde_json::from_str(&body)?;
+    let refreshed = AuthToken::new(body["token"].as_str().unwrap());
+    assert_eq!(refreshed.claims("nots3cr3t")?.origin, claims.jti);
+
+    // refreshing the refreshed token keeps the lineage
+    let response = service
+        .clone()
+        .oneshot(request(Method::POST, "/api/auth/refresh", &refreshed))
+        .await?;
+    let body = body_to_string(response.into_body()).await;
+    let body: serde_json::Value = serde_json::from_str(&body)?;
+    let refreshed = AuthToken::new(body["token"].as_str().unwrap());
+    assert_eq!(refreshed.claims("nots3cr3t")?.origin, claims.jti);
+
+    let response = service
+        .clone()
+        .oneshot(request(Method::GET, "/api/protected", &refreshed))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let uri = format!("/api/auth/tokens/{}", &claims.jti);
+    let response = service
+        .clone()
+        .oneshot(request(Method::DELETE, &uri, &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = service
+        .oneshot(request(Method::GET, "/api/protected", &refreshed))
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[test]
+async fn test_revoke_certificate() -> Result<(), Box<dyn Error>> {
+    let service = protected_service("nots3cr3t");
+    let certificate = ClientCertificate {
+        subject: "agama".to_string(),
+        fingerprint: "00ff".to_string(),
+        client_id: ClientId::new(),
+    };
+    let certificate_request = |method: Method, uri: &str, body: Body| {
+        let mut request = Request::builder()
+            .uri(uri)
+            .method(method)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .unwrap();
+        request.extensions_mut().insert(certificate.clone());
+        request
+    };
+
+    // tokens minted by the certificate client
+    let mut tokens = vec![];
+    for (uri, body) in [("/api/auth/refresh", ""), ("/api/auth/tokens", "{}")] {
+        let response = service
+            .clone()
+            .oneshot(certificate_request(Method::POST, uri, Body::from(body)))
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_to_string(response.into_body()).await;
+        let body: serde_json::Value = serde_json::from_str(&body)?;
+        let token = AuthToken::new(body["token"].as_str().unwrap());
+        assert_eq!(token.claims("nots3cr3t")?.origin, certificate.token_id());
+        tokens.push(token);
+    }
+
+    let master = AuthToken::generate("nots3cr3t")?;
+    let uri = format!("/api/auth/tokens/{}", certificate.token_id());
+    let response = service
+        .clone()
+        .oneshot(request(Method::DELETE, &uri, &master))
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = service
+        .clone()
+        .oneshot(certificate_request(
+            Method::GET,
+            "/api/protected",
+            Body::empty(),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    for token in tokens {
+        let response = service
+            .clone()
+            .oneshot(request(Method::GET, "/api/protected", &token))
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    Ok(())
+}
+
+#[test]
+async fn test_events_stream() -> Result<(), Box<dyn Error>> {
+    use http_body_util::BodyExt;
+
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let service = MainServiceBuilder::new(tx.clone(), public_dir())
+        .with_config(config)
+        .build();
+    let token = AuthToken::generate("nots3cr3t")?;
+
+    let response = service
+        .oneshot(request(Method::GET, "/api/events", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    let mut body = response.into_body();
+    let frame = body.frame().await.unwrap()?.into_data().unwrap();
+    let text = String::from_utf8(frame.to_vec())?;
+    assert!(text.contains("\"type\":\"ClientConnected\""));
+
+    tx.send(agama_lib::event!(QuestionsChanged))?;
+    let frame = body.frame().await.unwrap()?.into_data().unwrap();
+    let text = String::from_utf8(frame.to_vec())?;
+    assert!(text.contains("\"type\":\"QuestionsChanged\""));
+    assert!(text.contains("id: 1"));
+    Ok(())
+}
+
+#[test]
+async fn test_metrics() -> Result<(), Box<dyn Error>> {
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let service = MainServiceBuilder::new(tx.clone(), public_dir())
+        .with_config(config)
+        .build();
+    let token = AuthToken::generate("nots3cr3t")?;
+
+    tx.send(agama_lib::event!(InstallationPhaseChanged {
+        phase: agama_lib::manager::InstallationPhase::Install
+    }))?;
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+    let response = service
+        .oneshot(request(Method::GET, "/api/metrics", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .headers()
+        .get("content-type")
+        .unwrap()
+        .to_str()?
+        .starts_with("application/openmetrics-text"));
+
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains("agama_installation_phase{agama_installation_phase=\"install\"} 1"));
+    assert!(body.ends_with("# EOF\n"));
+    Ok(())
+}
+
+#[test]
+async fn test_openapi_spec() -> Result<(), Box<dyn Error>> {
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let service = MainServiceBuilder::new(tx, public_dir())
+        .with_config(config)
+        .build();
+    let token = AuthToken::generate("nots3cr3t")?;
+
+    let response = service
+        .oneshot(request(Method::GET, "/api/openapi.json", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = body_to_string(response.into_body()).await;
+    let spec: serde_json::Value = serde_json::from_str(&body)?;
+    assert!(spec.pointer("/paths/~1api~1ping/get").is_some());
+    assert!(spec.pointer("/paths/~1api~1software~1config/put").is_some());
+    Ok(())
+}
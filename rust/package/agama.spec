@@ -41,10 +41,11 @@ Requires:       dbus-1-common
 BuildRequires:  dbus-1-daemon
 BuildRequires:  clang-devel
 BuildRequires:  pkgconfig(pam)
-# required by autoinstallation
-BuildRequires:  jsonnet
-Requires:       jsonnet
+# required by the profiles importing hw.libsonnet
 Requires:       lshw
+# required for verifying the signatures of the downloaded files
+Requires:       gpg2
+Requires:       minisign
 # required by the password checking
 BuildRequires:  libpwquality-tools
 Requires:       libpwquality-tools
@@ -3,12 +3,9 @@ use std::{env, path::PathBuf};
 mod tasks {
     use std::{fs::File, io::Write, path::Path};
 
-    use agama_cli::Cli;
-    use agama_server::web::docs::{
-        ApiDocBuilder, HostnameApiDocBuilder, L10nApiDocBuilder, ManagerApiDocBuilder,
-        MiscApiDocBuilder, NetworkApiDocBuilder, ProfileApiDocBuilder, QuestionsApiDocBuilder,
-        ScriptsApiDocBuilder, SoftwareApiDocBuilder, StorageApiDocBuilder, UsersApiDocBuilder,
-    };
+    use agama_cli::{complete_api_paths, Cli};
+    use agama_lib::api_spec::ApiSpec;
+    use agama_server::web::docs::{full_api, ApiDocBuilder, API_DOC_BUILDERS};
     use clap::CommandFactory;
     use clap_complete::aot;
     use clap_markdown::MarkdownOptions;
@@ -16,10 +13,13 @@ mod tasks {
     use crate::create_output_dir;
 
     /// Generate auto-completion snippets for common shells.
+    ///
+    /// The paths of the HTTP API are offered for the `agama api` subcommands.
     pub fn generate_completions() -> std::io::Result<()> {
         let out_dir = create_output_dir("shell")?;
 
-        let mut cmd = Cli::command();
+        let spec = serde_json::to_value(full_api())?;
+        let mut cmd = complete_api_paths(Cli::command(), &ApiSpec::new(spec));
         clap_complete::generate_to(aot::Bash, &mut cmd, "agama", &out_dir)?;
         clap_complete::generate_to(aot::Fish, &mut cmd, "agama", &out_dir)?;
         clap_complete::generate_to(aot::Zsh, &mut cmd, "agama", &out_dir)?;
@@ -64,17 +64,9 @@ mod tasks {
     pub fn generate_openapi() -> std::io::Result<()> {
         let out_dir = create_output_dir("openapi")?;
 
-        write_openapi(HostnameApiDocBuilder {}, out_dir.join("hostname.json"))?;
-        write_openapi(L10nApiDocBuilder {}, out_dir.join("l10n.json"))?;
-        write_openapi(ManagerApiDocBuilder {}, out_dir.join("manager.json"))?;
-        write_openapi(MiscApiDocBuilder {}, out_dir.join("misc.json"))?;
-        write_openapi(NetworkApiDocBuilder {}, out_dir.join("network.json"))?;
-        write_openapi(ProfileApiDocBuilder {}, out_dir.join("profile.json"))?;
-        write_openapi(QuestionsApiDocBuilder {}, out_dir.join("questions.json"))?;
-        write_openapi(ScriptsApiDocBuilder {}, out_dir.join("scripts.json"))?;
-        write_openapi(SoftwareApiDocBuilder {}, out_dir.join("software.json"))?;
-        write_openapi(StorageApiDocBuilder {}, out_dir.join("storage.json"))?;
-        write_openapi(UsersApiDocBuilder {}, out_dir.join("users.json"))?;
+        for (name, builder) in API_DOC_BUILDERS {
+            write_openapi(builder, out_dir.join(format!("{name}.json")))?;
+        }
         println!(
             "Generate the OpenAPI specification at {}.",
             out_dir.display()
@@ -82,10 +74,7 @@ mod tasks {
         Ok(())
     }
 
-    fn write_openapi<T, P: AsRef<Path>>(builder: T, path: P) -> std::io::Result<()>
-    where
-        T: ApiDocBuilder,
-    {
+    fn write_openapi<P: AsRef<Path>>(builder: &dyn ApiDocBuilder, path: P) -> std::io::Result<()> {
         let openapi = builder.build().to_pretty_json()?;
         let mut file = File::create(path)?;
         file.write_all(openapi.as_bytes())?;
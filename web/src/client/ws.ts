@@ -76,6 +76,9 @@ class WSClient implements WSClientIface {
 
   reconnectAttempts: number;
 
+  /** Sequence number of the last received event, used to resume after reconnecting. */
+  lastSeq?: number;
+
   timeout: ReturnType<typeof setTimeout>;
 
   /**
@@ -112,7 +115,9 @@ class WSClient implements WSClientIface {
   }
 
   buildClient() {
-    const client = new WebSocket(this.url);
+    const url = new URL(this.url);
+    if (this.lastSeq !== undefined) url.searchParams.set("since", this.lastSeq.toString());
+    const client = new WebSocket(url.toString());
     client.onopen = () => {
       console.log("Websocket connected");
       this.reconnectAttempts = 0;
@@ -215,6 +220,7 @@ class WSClient implements WSClientIface {
    */
   dispatchEvent(event: MessageEvent) {
     const eventObject = JSON.parse(event.data);
+    if (eventObject.seq !== undefined) this.lastSeq = eventObject.seq;
     this.handlers.events.forEach((f) => f(eventObject));
   }
 
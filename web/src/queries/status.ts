@@ -87,6 +87,10 @@ const useInstallerStatusChanges = () => {
             }
           }
           break;
+        case "EventsGap":
+          // some events were lost (e.g., after a long disconnection), so reload everything
+          queryClient.invalidateQueries();
+          break;
       }
     });
   });